@@ -0,0 +1,66 @@
+//! A dedicated server that hosts co-op games without opening a window.
+//!
+//! Run with `cargo run --bin aegir_server -- --port 7777`.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use aegir_lib::game_state::GameState;
+use aegir_lib::net::{Server, DEFAULT_PORT};
+use aegir_lib::simulation::TICK_RATE;
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+
+fn main() -> ExitCode {
+    let port = match std::env::args()
+        .skip_while(|argument| argument != "--port")
+        .nth(1)
+    {
+        Some(port) => match port.parse() {
+            Ok(port) => port,
+            Err(error) => {
+                eprintln!("--port should be a number from 0 to 65535, not `{port}`: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => DEFAULT_PORT,
+    };
+
+    let server = match Server::bind(port) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("Could not listen on port {port}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut app = App::new();
+    // Update once for every simulation tick
+    app.add_plugins(
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f32(
+            1. / TICK_RATE,
+        ))),
+    )
+    .add_plugins((
+        LogPlugin::default(),
+        aegir_lib::simulation::ron_asset::asset_plugin(),
+    ))
+    .add_plugins(aegir_lib::HeadlessPlugins)
+    .insert_resource(server)
+    .add_systems(Startup, start_playing);
+
+    aegir_lib::debug::crash_report::run(app);
+
+    ExitCode::SUCCESS
+}
+
+/// Skips the menus, since the server has no one to show them to.
+fn start_playing(server: Res<Server>, mut next_state: ResMut<NextState<GameState>>) {
+    match server.local_address() {
+        Ok(address) => info!("Listening on {address}"),
+        Err(error) => warn!("Listening on an unknown address: {error}"),
+    }
+
+    next_state.set(GameState::Playing);
+}
@@ -1,25 +1,60 @@
 //! Everything needed to run the main game logic
+//!
+//! Run with `--bench` to run the benchmark instead, adding `--headless` to leave out the window.
 
+use aegir_lib::benchmark::{BenchmarkConfig, BenchmarkPlugin};
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 
-/// Set the game state to align systems with their respective runtimes
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-enum GameState {
-    #[default]
-    Menu,
-    Playing,
+fn main() {
+    let benchmark = BenchmarkConfig::from_args(std::env::args());
+
+    let mut app = App::new();
+    if benchmark.enabled && benchmark.headless {
+        add_headless_plugins(&mut app);
+    } else {
+        add_game_plugins(&mut app);
+    }
+    app.insert_resource(benchmark).add_plugins(BenchmarkPlugin);
+
+    aegir_lib::debug::crash_report::run(app);
 }
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: String::from("Bevy 3D Template"),
+/// Adds everything needed to play the game in a window.
+fn add_game_plugins(app: &mut App) {
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Bevy 3D Template"),
+                    ..default()
+                }),
                 ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(aegir_lib::player::PlayerPlugin)
-        .add_plugins(aegir_lib::graphics::GraphicsPlugin)
-        .run();
+            })
+            .set(aegir_lib::simulation::ron_asset::asset_plugin()),
+    )
+    .add_plugins(aegir_lib::game_state::GameStatePlugin)
+    .add_plugins(aegir_lib::menus::MenusPlugin)
+    .add_plugins(aegir_lib::net::NetPlugin)
+    .insert_resource(aegir_lib::replay::ReplayConfig::from_args(std::env::args()))
+    .add_plugins(aegir_lib::replay::ReplayPlugin)
+    .add_plugins(aegir_lib::player::PlayerPlugin)
+    .add_plugins(aegir_lib::simulation::SimulationPlugin)
+    .add_plugins(aegir_lib::stats::StatsPlugin)
+    .add_plugins(aegir_lib::graphics::GraphicsPlugin)
+    .add_plugins(aegir_lib::accessibility::AccessibilityPlugin)
+    .add_plugins(aegir_lib::hud::HudPlugin)
+    .add_plugins(aegir_lib::sound::SoundPlugin)
+    .add_plugins(aegir_lib::debug::DebugPlugin);
+}
+
+/// Adds only the simulation, which runs as fast as it can without a window, like the dedicated
+/// server.
+fn add_headless_plugins(app: &mut App) {
+    app.add_plugins(MinimalPlugins)
+        .add_plugins((
+            LogPlugin::default(),
+            aegir_lib::simulation::ron_asset::asset_plugin(),
+        ))
+        .add_plugins(aegir_lib::HeadlessPlugins);
 }
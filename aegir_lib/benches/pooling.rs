@@ -0,0 +1,105 @@
+//! Compares spawning and despawning short-lived entities with recycling them through an
+//! [`EntityPool`], under a barrage of hundreds of shots a second.
+//!
+//! Run with `cargo bench -p aegir_lib --bench pooling`; each iteration is one frame of fire.
+
+use aegir_lib::pooling::EntityPool;
+use bevy::ecs::schedule::IntoSystemConfigs;
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// How many shots are fired each frame.
+const SHOTS_PER_FRAME: usize = 50;
+
+/// How many frames each shot lasts.
+const LIFETIME_FRAMES: u32 = 60;
+
+/// How many frames are run before measuring, so that the number of shots in flight is steady.
+const WARM_UP_FRAMES: u32 = 2 * LIFETIME_FRAMES;
+
+/// A stand-in for a projectile or particle.
+#[derive(Component, Debug)]
+struct Shot {
+    /// How many more frames it lasts.
+    frames_left: u32,
+}
+
+/// Everything a shot is spawned with.
+fn shot() -> (SpatialBundle, Shot) {
+    (
+        SpatialBundle::default(),
+        Shot {
+            frames_left: LIFETIME_FRAMES,
+        },
+    )
+}
+
+/// Spawns a new entity for each shot.
+fn fire_spawned(mut commands: Commands) {
+    for _ in 0..SHOTS_PER_FRAME {
+        commands.spawn(shot());
+    }
+}
+
+/// Despawns shots once they run out.
+fn age_despawned(mut commands: Commands, mut query: Query<(Entity, &mut Shot)>) {
+    for (entity, mut shot) in query.iter_mut() {
+        shot.frames_left -= 1;
+        if shot.frames_left == 0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Draws each shot from the pool.
+fn fire_pooled(mut commands: Commands, mut pool: ResMut<EntityPool<Shot>>) {
+    for _ in 0..SHOTS_PER_FRAME {
+        pool.spawn(&mut commands, shot());
+    }
+}
+
+/// Returns shots to the pool once they run out.
+fn age_pooled(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Shot>>,
+    mut query: Query<(Entity, &mut Shot)>,
+) {
+    for (entity, mut shot) in query.iter_mut() {
+        shot.frames_left -= 1;
+        if shot.frames_left == 0 {
+            pool.release(&mut commands, entity);
+        }
+    }
+}
+
+/// A world that has been under steady fire from `systems` for a while.
+fn world_under_fire<M>(systems: impl IntoSystemConfigs<M>) -> (World, Schedule) {
+    let mut world = World::new();
+    world.init_resource::<EntityPool<Shot>>();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(systems);
+
+    for _ in 0..WARM_UP_FRAMES {
+        schedule.run(&mut world);
+    }
+    (world, schedule)
+}
+
+/// Measures one frame of heavy fire with and without pooling.
+fn heavy_fire(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("heavy fire frame");
+
+    group.bench_function("spawn and despawn", |bencher| {
+        let (mut world, mut schedule) = world_under_fire((fire_spawned, age_despawned).chain());
+        bencher.iter(|| schedule.run(&mut world));
+    });
+    group.bench_function("pooled", |bencher| {
+        let (mut world, mut schedule) = world_under_fire((fire_pooled, age_pooled).chain());
+        bencher.iter(|| schedule.run(&mut world));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, heavy_fire);
+criterion_main!(benches);
@@ -0,0 +1,122 @@
+//! Options that make the game easier to see, hear and play.
+//!
+//! The [`HudTheme`] holds the colors that tell friend from foe across the HUD, with palettes that
+//! stay distinct for colorblind players. [`AccessibilitySettings`] scale the whole interface and
+//! show an [`AudioCaption`] on screen for each warning sound. Whether each action is held or
+//! toggled is chosen in its [`InputMap`](crate::player::input::InputMap).
+
+use bevy::prelude::*;
+
+use crate::player::targeting::Disposition;
+
+/// The interface scales the settings cycle through.
+pub const UI_SCALES: [f64; 5] = [0.75, 1., 1.25, 1.5, 2.];
+
+/// Adds accessibility options.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilitySettings>()
+            .init_resource::<HudTheme>()
+            .add_event::<AudioCaption>()
+            .add_systems(Update, scale_ui);
+    }
+}
+
+/// How the interface is presented, for players to suit their eyes and ears.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AccessibilitySettings {
+    /// How large the interface is drawn, as a multiple of its usual size.
+    pub ui_scale: f64,
+    /// Are warning sounds captioned on screen?
+    pub captions: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            ui_scale: 1.,
+            captions: false,
+        }
+    }
+}
+
+/// A set of HUD colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Palette {
+    /// Blue friends, yellow neutrals and red enemies.
+    #[default]
+    Standard,
+    /// Blue friends, white neutrals and orange enemies, for players who cannot tell red from green
+    /// because they lack green-sensitive cones.
+    Deuteranopia,
+    /// Sky blue friends, white neutrals and yellow enemies, for players who cannot tell red from
+    /// green because they lack red-sensitive cones, and so see red as dark.
+    Protanopia,
+    /// Teal friends, white neutrals and red enemies, for players who cannot tell blue from yellow.
+    Tritanopia,
+}
+
+impl Palette {
+    /// Every palette, in the order the settings cycle through them.
+    pub const ALL: [Palette; 4] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+    ];
+
+    /// The name shown for the palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deuteranopia",
+            Palette::Protanopia => "Protanopia",
+            Palette::Tritanopia => "Tritanopia",
+        }
+    }
+}
+
+/// The colors that the HUD, radar and cockpit displays mark things in.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HudTheme {
+    /// The palette the colors are drawn from.
+    pub palette: Palette,
+}
+
+impl HudTheme {
+    /// The color contacts are marked in, identifying friend from foe.
+    pub fn disposition(&self, disposition: Disposition) -> Color {
+        match (self.palette, disposition) {
+            (Palette::Standard, Disposition::Friendly) => Color::rgb(0.3, 0.6, 1.),
+            (Palette::Standard, Disposition::Neutral) => Color::rgb(1., 0.9, 0.3),
+            (Palette::Standard, Disposition::Hostile) => Color::rgb(1., 0.25, 0.2),
+            (Palette::Deuteranopia, Disposition::Friendly) => Color::rgb(0.2, 0.55, 1.),
+            (Palette::Protanopia, Disposition::Friendly) => Color::rgb(0.35, 0.75, 1.),
+            (Palette::Tritanopia, Disposition::Friendly) => Color::rgb(0.2, 0.85, 0.8),
+            (_, Disposition::Neutral) => Color::rgb(0.9, 0.9, 0.9),
+            (Palette::Deuteranopia, Disposition::Hostile) => Color::rgb(1., 0.55, 0.),
+            (Palette::Protanopia, Disposition::Hostile) => Color::rgb(1., 0.85, 0.1),
+            (Palette::Tritanopia, Disposition::Hostile) => Color::rgb(1., 0.2, 0.35),
+        }
+    }
+
+    /// The color of warnings, such as missile locks and captions for alarms.
+    pub fn warning(&self) -> Color {
+        self.disposition(Disposition::Hostile)
+    }
+}
+
+/// A warning sound has played, for the HUD to caption when captions are on.
+///
+/// Looping sounds send their caption every frame for as long as they play.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioCaption(pub &'static str);
+
+/// Scales the whole interface by the chosen amount.
+fn scale_ui(settings: Res<AccessibilitySettings>, mut ui_scale: ResMut<UiScale>) {
+    if settings.is_changed() && ui_scale.scale != settings.ui_scale {
+        ui_scale.scale = settings.ui_scale;
+    }
+}
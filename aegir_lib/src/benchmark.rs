@@ -0,0 +1,469 @@
+//! A deterministic stress test for catching performance regressions, run with `--bench`.
+//!
+//! The benchmark skips the menus and fills the sector with [`STRESS_ASTEROIDS`] asteroids and
+//! [`STRESS_SHIPS`] AI ships from two hostile factions, who fight until the benchmark ends, all
+//! laid out from [`BENCHMARK_SEED`]. Every frame advances time by exactly one tick, so the same
+//! ticks are simulated however quickly or slowly the frames run.
+//!
+//! After [`WARMUP_FRAMES`], the wall time of each frame and of the AI, flight, spatial, health
+//! and effects systems is recorded as a Bevy [`Diagnostic`]. Once the requested number of frames
+//! have been measured, their statistics are written as CSV and the app exits. Run with
+//! `--headless` as well to leave out the window and rendering, as continuous integration would.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::app::AppExit;
+use bevy::asset::LoadState;
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticId, Diagnostics, DiagnosticsPlugin, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::{HashMap, Instant};
+
+use crate::game_state::GameState;
+use crate::graphics::VfxSet;
+use crate::player::ship::PlayerShip;
+use crate::simulation::ai::{spawn_ai_ship, AiPilot, AiSet, Squadrons};
+use crate::simulation::asteroids::AsteroidField;
+use crate::simulation::factions::Faction;
+use crate::simulation::flight::FlightSet;
+use crate::simulation::health::{Health, HealthSet};
+use crate::simulation::random::RngStream;
+use crate::simulation::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use crate::simulation::spatial::SpatialSet;
+use crate::simulation::weapons::WeaponLibrary;
+use crate::simulation::{WorldSeed, TICK_RATE};
+
+/// The [`WorldSeed`] the stress scene is laid out from.
+pub const BENCHMARK_SEED: u64 = 0xAE61_BE7C;
+
+/// How many frames are measured unless `--frames` says otherwise.
+pub const DEFAULT_FRAMES: u32 = 1800;
+
+/// How many frames run before measuring starts, while caches and pools fill.
+pub const WARMUP_FRAMES: u32 = 120;
+
+/// How many asteroids are scattered through the stress scene.
+pub const STRESS_ASTEROIDS: usize = 5000;
+
+/// How many AI ships fight in the stress scene, split evenly between two factions.
+pub const STRESS_SHIPS: u32 = 50;
+
+/// How far the asteroids are scattered from the middle of the stress scene, in meters.
+const ASTEROID_FIELD_RADIUS: f32 = 4000.;
+
+/// How far apart the two fleets start, in meters, close enough to see each other.
+const FLEET_SEPARATION: f32 = 600.;
+
+/// How far apart ships in a fleet start, in meters.
+const SHIP_SPACING: f32 = 30.;
+
+/// How many ships stand side by side in each row of a fleet.
+const SHIPS_PER_ROW: u32 = 5;
+
+/// How much damage the ships of the stress scene can take, so that none are destroyed and the
+/// fighting never lets up.
+const STRESS_SHIP_HEALTH: f32 = 1e9;
+
+/// The wall time of each frame, in milliseconds.
+pub const FRAME_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d01);
+
+/// The wall time of the AI systems each tick, in milliseconds.
+pub const AI_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d02);
+
+/// The wall time of the flight systems each tick, in milliseconds.
+pub const FLIGHT_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d03);
+
+/// The wall time of the spatial index systems each tick, in milliseconds.
+pub const SPATIAL_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d04);
+
+/// The wall time of the health systems each tick, in milliseconds.
+pub const HEALTH_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d05);
+
+/// The wall time of the visual effects systems each frame, in milliseconds.
+pub const VFX_TIME: DiagnosticId =
+    DiagnosticId::from_u128(0x5d1c_07a2_4e3b_4f1d_9a68_2c0e_b1f4_7d06);
+
+/// Every diagnostic the benchmark records, in the order they are reported.
+pub const BENCHMARK_DIAGNOSTICS: [DiagnosticId; 6] = [
+    FRAME_TIME,
+    AI_TIME,
+    FLIGHT_TIME,
+    SPATIAL_TIME,
+    HEALTH_TIME,
+    VFX_TIME,
+];
+
+/// Runs the stress scene and reports how long it took, when asked to by the [`BenchmarkConfig`].
+///
+/// The config must be inserted before this plugin is added, as nothing is added without it.
+pub struct BenchmarkPlugin;
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BenchmarkConfig>()
+            .init_resource::<BenchmarkPhase>()
+            .init_resource::<SpanTimers>();
+
+        let config = app.world.resource::<BenchmarkConfig>().clone();
+        if !config.enabled {
+            return;
+        }
+        if !app.is_plugin_added::<DiagnosticsPlugin>() {
+            app.add_plugins(DiagnosticsPlugin);
+        }
+
+        // Measurements of each tick and frame are kept until the report is written
+        let history = config.frames as usize * 2;
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1. / TICK_RATE,
+        )))
+        .register_diagnostic(Diagnostic::new(FRAME_TIME, "frame", history).with_suffix("ms"))
+        .add_systems(Startup, start_benchmark)
+        .add_systems(
+            Update,
+            spawn_stress_scene.run_if(
+                in_state(GameState::Playing).and_then(resource_equals(BenchmarkPhase::Loading)),
+            ),
+        )
+        .add_systems(First, measure_frame.run_if(measuring))
+        .add_systems(Last, advance_benchmark);
+
+        time_set(app, FixedUpdate, AiSet, AI_TIME, "ai", history);
+        time_set(app, FixedUpdate, FlightSet, FLIGHT_TIME, "flight", history);
+        time_set(
+            app,
+            FixedUpdate,
+            SpatialSet,
+            SPATIAL_TIME,
+            "spatial",
+            history,
+        );
+        time_set(app, FixedUpdate, HealthSet, HEALTH_TIME, "health", history);
+        // Effects are only drawn with a window
+        if !config.headless {
+            time_set(app, Update, VfxSet, VFX_TIME, "vfx", history);
+        }
+    }
+}
+
+/// Whether to run the benchmark, and how.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+    /// Run the stress scene instead of showing the menus.
+    pub enabled: bool,
+    /// Run without a window or rendering.
+    pub headless: bool,
+    /// How many frames to measure, after warming up.
+    pub frames: u32,
+    /// Write the report to this file, rather than printing it.
+    pub output: Option<PathBuf>,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        BenchmarkConfig {
+            enabled: false,
+            headless: false,
+            frames: DEFAULT_FRAMES,
+            output: None,
+        }
+    }
+}
+
+impl BenchmarkConfig {
+    /// Reads `--bench`, `--headless`, `--frames <count>` and `--bench-output <path>` from the
+    /// command line `arguments`.
+    pub fn from_args(arguments: impl IntoIterator<Item = String>) -> Self {
+        let mut config = BenchmarkConfig::default();
+        let mut arguments = arguments.into_iter();
+
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--bench" => config.enabled = true,
+                "--headless" => config.headless = true,
+                "--frames" => {
+                    if let Some(frames) = arguments.next().and_then(|frames| frames.parse().ok()) {
+                        config.frames = frames;
+                    }
+                }
+                "--bench-output" => config.output = arguments.next().map(PathBuf::from),
+                _ => (),
+            }
+        }
+
+        config
+    }
+}
+
+/// How far through the benchmark the app is.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum BenchmarkPhase {
+    /// Waiting for play to begin and for ships and weapons to load.
+    #[default]
+    Loading,
+    /// Running without measuring, with this many frames left to go.
+    WarmingUp(u32),
+    /// Measuring, with this many frames measured so far.
+    Measuring(u32),
+    /// The report has been written.
+    Finished,
+}
+
+/// When each timed set started its latest run, and when the latest frame started.
+#[derive(Resource, Debug, Default)]
+struct SpanTimers(HashMap<DiagnosticId, Instant>);
+
+impl SpanTimers {
+    /// Starts timing the span measured by `id`.
+    fn start(&mut self, id: DiagnosticId) {
+        self.0.insert(id, Instant::now());
+    }
+
+    /// Stops timing the span measured by `id`, returning how long it took in milliseconds.
+    fn stop(&mut self, id: DiagnosticId) -> Option<f64> {
+        self.0
+            .remove(&id)
+            .map(|start| start.elapsed().as_secs_f64() * 1000.)
+    }
+}
+
+/// The statistics of one diagnostic's measurements, in its own units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingStats {
+    /// How many measurements were taken.
+    pub samples: usize,
+    /// The average measurement.
+    pub mean: f64,
+    /// The smallest measurement.
+    pub min: f64,
+    /// The middle measurement.
+    pub median: f64,
+    /// The measurement that 95% of the others fall below.
+    pub p95: f64,
+    /// The largest measurement.
+    pub max: f64,
+}
+
+impl TimingStats {
+    /// The header line of a CSV report, naming each column of [`TimingStats::csv_row`].
+    pub const CSV_HEADER: &'static str = "name,samples,mean_ms,min_ms,median_ms,p95_ms,max_ms";
+
+    /// Summarizes `measurements`, or returns `None` if there are none.
+    pub fn from_measurements(measurements: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut sorted: Vec<f64> = measurements.into_iter().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let percentile = |fraction: f64| {
+            let rank = (fraction * (sorted.len() - 1) as f64).round() as usize;
+            sorted[rank]
+        };
+        Some(TimingStats {
+            samples: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            min: sorted[0],
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    /// A line of a CSV report for the diagnostic called `name`.
+    pub fn csv_row(&self, name: &str) -> String {
+        format!(
+            "{name},{},{:.4},{:.4},{:.4},{:.4},{:.4}",
+            self.samples, self.mean, self.min, self.median, self.p95, self.max
+        )
+    }
+}
+
+/// Records the wall time of `set` each time it runs in `schedule` as the diagnostic `id`.
+///
+/// The time is measured from just before the set's first system to just after its last, so
+/// systems running alongside it in parallel can add to it.
+fn time_set(
+    app: &mut App,
+    schedule: impl ScheduleLabel + Clone,
+    set: impl SystemSet + Clone,
+    id: DiagnosticId,
+    name: &'static str,
+    history: usize,
+) {
+    let start = move |mut timers: ResMut<SpanTimers>| timers.start(id);
+    let stop = move |mut timers: ResMut<SpanTimers>, mut diagnostics: Diagnostics| {
+        if let Some(elapsed) = timers.stop(id) {
+            diagnostics.add_measurement(id, || elapsed);
+        }
+    };
+
+    app.register_diagnostic(Diagnostic::new(id, name, history).with_suffix("ms"))
+        .add_systems(
+            schedule.clone(),
+            start.before(set.clone()).run_if(measuring),
+        )
+        .add_systems(schedule, stop.after(set).run_if(measuring));
+}
+
+/// Is the benchmark measuring this frame?
+fn measuring(phase: Res<BenchmarkPhase>) -> bool {
+    matches!(*phase, BenchmarkPhase::Measuring(_))
+}
+
+/// Seeds the world with the [`BENCHMARK_SEED`] and skips the menus.
+fn start_benchmark(
+    mut world_seed: ResMut<WorldSeed>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    info!("Starting the benchmark");
+    *world_seed = WorldSeed(BENCHMARK_SEED);
+    next_state.set(GameState::Playing);
+}
+
+/// Fills the sector with asteroids and two fleets of AI ships once ships and weapons have loaded.
+///
+/// The fleets face each other across the middle of the sector, well within sight. Any player
+/// ship is made as tough as theirs, so that play does not end early.
+#[allow(clippy::too_many_arguments)]
+fn spawn_stress_scene(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    world_seed: Res<WorldSeed>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+    mut squadrons: ResMut<Squadrons>,
+    mut phase: ResMut<BenchmarkPhase>,
+    player_query: Query<Entity, With<PlayerShip>>,
+) {
+    let handles = ship_library
+        .ships()
+        .iter()
+        .map(|handle| handle.id())
+        .chain(weapon_library.weapons().iter().map(|handle| handle.id()));
+    if asset_server.get_group_load_state(handles) == LoadState::Loading {
+        return;
+    }
+
+    let field = AsteroidField {
+        count: STRESS_ASTEROIDS,
+        radius: ASTEROID_FIELD_RADIUS,
+        clearing: FLEET_SEPARATION,
+        ..default()
+    };
+    field.spawn(&mut commands, &mut world_seed.rng(RngStream::Benchmark));
+
+    let class = ship_library.ships().first();
+    let definition = class
+        .and_then(|handle| ship_definitions.get(handle))
+        .cloned()
+        .unwrap_or_default();
+    let weapon = weapon_library.weapons().first();
+
+    for (faction, side) in [(Faction::Aegir, -1.), (Faction::Pirate, 1.)] {
+        let squadron = squadrons.allocate();
+        let fleet_ships = STRESS_SHIPS / 2;
+        for index in 0..fleet_ships {
+            let row = (index / SHIPS_PER_ROW) as f32;
+            let column = (index % SHIPS_PER_ROW) as f32 - (SHIPS_PER_ROW - 1) as f32 / 2.;
+            let position = Vec3::new(
+                column * SHIP_SPACING,
+                0.,
+                side * (FLEET_SEPARATION / 2. + row * SHIP_SPACING),
+            );
+            let transform = Transform::from_translation(position)
+                .looking_at(Vec3::new(position.x, 0., 0.), Vec3::Y);
+
+            let ship = spawn_ai_ship(
+                &mut commands,
+                transform,
+                &definition,
+                weapon,
+                faction,
+                squadron,
+                AiPilot::default(),
+            );
+            commands
+                .entity(ship)
+                .insert(Health::new(STRESS_SHIP_HEALTH));
+            if let Some(class) = class {
+                commands.entity(ship).insert(ShipClass(class.clone()));
+            }
+        }
+    }
+    for player in player_query.iter() {
+        commands
+            .entity(player)
+            .insert(Health::new(STRESS_SHIP_HEALTH));
+    }
+
+    info!("Spawned {STRESS_ASTEROIDS} asteroids and {STRESS_SHIPS} ships, warming up");
+    *phase = BenchmarkPhase::WarmingUp(WARMUP_FRAMES);
+}
+
+/// Records how long the previous frame took, from the start of one frame to the next.
+fn measure_frame(mut timers: ResMut<SpanTimers>, mut diagnostics: Diagnostics) {
+    // The first frame measured has no previous frame to time
+    if let Some(elapsed) = timers.stop(FRAME_TIME) {
+        diagnostics.add_measurement(FRAME_TIME, || elapsed);
+    }
+    timers.start(FRAME_TIME);
+}
+
+/// Counts frames through the warm-up and measurement, then writes the report and exits.
+fn advance_benchmark(
+    config: Res<BenchmarkConfig>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut phase: ResMut<BenchmarkPhase>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    *phase = match *phase {
+        BenchmarkPhase::WarmingUp(0) => BenchmarkPhase::Measuring(0),
+        BenchmarkPhase::WarmingUp(frames) => BenchmarkPhase::WarmingUp(frames - 1),
+        BenchmarkPhase::Measuring(frames) if frames + 1 < config.frames => {
+            BenchmarkPhase::Measuring(frames + 1)
+        }
+        BenchmarkPhase::Measuring(_) => {
+            write_report(&config, &diagnostics);
+            app_exit.send(AppExit);
+            BenchmarkPhase::Finished
+        }
+        phase => phase,
+    };
+}
+
+/// Writes the statistics of every benchmark diagnostic as CSV, to the configured file or to
+/// standard output.
+fn write_report(config: &BenchmarkConfig, diagnostics: &DiagnosticsStore) {
+    let mut lines = vec![TimingStats::CSV_HEADER.to_string()];
+    for id in BENCHMARK_DIAGNOSTICS {
+        let Some(diagnostic) = diagnostics.get(id) else {
+            continue;
+        };
+        if let Some(stats) = TimingStats::from_measurements(diagnostic.values().copied()) {
+            lines.push(stats.csv_row(&diagnostic.name));
+        }
+    }
+    let report = lines.join("\n") + "\n";
+
+    match &config.output {
+        Some(path) => match std::fs::write(path, &report) {
+            Ok(()) => info!("Wrote the benchmark report to {}", path.display()),
+            Err(error) => {
+                error!("Could not write {}: {error}", path.display());
+                print!("{report}");
+            }
+        },
+        None => print!("{report}"),
+    }
+}
@@ -0,0 +1,292 @@
+//! An in-game console, toggled with `~`, that runs commands registered by other modules.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::player::input::KeyboardFocus;
+
+/// How many lines of output the console keeps.
+const MAX_LOG_LINES: usize = 12;
+
+/// Runs a console command with the words typed after its name, returning what to print.
+pub type ConsoleCommandFn = fn(&[&str], &mut World) -> Result<String, String>;
+
+/// Console logic
+pub(super) struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_console_command("help", "help", help)
+            .add_systems(Startup, spawn_console)
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    type_in_console,
+                    run_console_commands,
+                    update_console_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Lets plugins register their own console commands.
+pub trait ConsoleAppExt {
+    /// Registers a command that runs `run` when a line starting with `name` is entered.
+    ///
+    /// Names may contain spaces, like `spawn asteroid`; the longest matching name wins.
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name, ConsoleCommand { usage, run });
+        self
+    }
+}
+
+/// A command that can be run from the console.
+#[derive(Debug, Clone, Copy)]
+struct ConsoleCommand {
+    /// How to call the command, shown by `help`.
+    usage: &'static str,
+    /// What the command does.
+    run: ConsoleCommandFn,
+}
+
+/// Every registered console command, by name.
+#[derive(Resource, Debug, Default)]
+pub struct ConsoleCommands(HashMap<&'static str, ConsoleCommand>);
+
+impl ConsoleCommands {
+    /// Finds the command with the longest name that `words` starts with, and the words after it.
+    fn find<'a, 'w>(&'a self, words: &'w [&'w str]) -> Option<(&'a ConsoleCommand, &'w [&'w str])> {
+        (1..=words.len()).rev().find_map(|length| {
+            let name = words[..length].join(" ");
+            self.0
+                .get(name.as_str())
+                .map(|command| (command, &words[length..]))
+        })
+    }
+}
+
+/// The state of the console.
+#[derive(Resource, Debug, Default)]
+pub struct Console {
+    /// Is the console open?
+    open: bool,
+    /// The line being typed.
+    input: String,
+    /// Lines that have been entered but not run yet.
+    pending: Vec<String>,
+    /// Recent commands and their output, oldest first.
+    log: VecDeque<String>,
+}
+
+impl Console {
+    /// Is the console open?
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Adds a line of output.
+    pub fn log(&mut self, line: impl Into<String>) {
+        self.log.push_back(line.into());
+        while self.log.len() > MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+}
+
+/// Marks the root node of the console.
+#[derive(Component, Debug)]
+struct ConsoleRoot;
+
+/// Marks the console's output text.
+#[derive(Component, Debug)]
+struct ConsoleLogText;
+
+/// Marks the line being typed into the console.
+#[derive(Component, Debug)]
+struct ConsoleInputText;
+
+/// Spawns the hidden console across the top of the screen.
+fn spawn_console(mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: 16.,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.),
+                    left: Val::Px(0.),
+                    width: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+            ConsoleRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                ConsoleLogText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        color: Color::YELLOW,
+                        ..text_style
+                    },
+                ),
+                ConsoleInputText,
+            ));
+        });
+}
+
+/// Opens and closes the console with `~`, taking the keyboard away from the game while open.
+fn toggle_console(
+    keyboard: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut focus: ResMut<KeyboardFocus>,
+    mut query: Query<&mut Style, With<ConsoleRoot>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Grave) {
+        return;
+    }
+
+    console.open = !console.open;
+    *focus = if console.open {
+        KeyboardFocus::Text
+    } else {
+        KeyboardFocus::Game
+    };
+
+    for mut style in query.iter_mut() {
+        style.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+/// Edits the line being typed, queuing it to run when `Enter` is pressed.
+fn type_in_console(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.iter() {
+        if !event.char.is_control() && !matches!(event.char, '`' | '~') {
+            console.input.push(event.char);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if keyboard.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.pending.push(line);
+        }
+    }
+}
+
+/// Runs every queued command line with full access to the world.
+fn run_console_commands(world: &mut World) {
+    let lines = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    if lines.is_empty() {
+        return;
+    }
+
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        // Copy the command out, since it may want to read the registry itself
+        let found = world
+            .resource::<ConsoleCommands>()
+            .find(&words)
+            .map(|(&command, arguments)| (command, arguments));
+
+        let output = match found {
+            Some((command, arguments)) => {
+                (command.run)(arguments, world).unwrap_or_else(|error| format!("error: {error}"))
+            }
+            None => format!("unknown command `{}`, try `help`", words[0]),
+        };
+
+        let mut console = world.resource_mut::<Console>();
+        console.log(format!("> {line}"));
+        if !output.is_empty() {
+            console.log(output);
+        }
+    }
+}
+
+/// Shows the console's output and the line being typed.
+fn update_console_text(
+    console: Res<Console>,
+    mut log_query: Query<&mut Text, (With<ConsoleLogText>, Without<ConsoleInputText>)>,
+    mut input_query: Query<&mut Text, With<ConsoleInputText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut text in log_query.iter_mut() {
+        text.sections[0].value = console.log.iter().cloned().collect::<Vec<_>>().join("\n");
+    }
+
+    for mut text in input_query.iter_mut() {
+        text.sections[0].value = format!("> {}_", console.input);
+    }
+}
+
+/// Lists every registered command.
+fn help(_arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let mut usages: Vec<&str> = world
+        .resource::<ConsoleCommands>()
+        .0
+        .values()
+        .map(|command| command.usage)
+        .collect();
+    usages.sort_unstable();
+
+    Ok(usages.join("\n"))
+}
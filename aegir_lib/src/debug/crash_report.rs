@@ -0,0 +1,201 @@
+//! Crash reports, written when the game panics, and bug reports, written when the player asks.
+//!
+//! [`run`] installs a panic hook before running the app. Since the hook cannot reach into the
+//! world, the state worth reporting (the world seed, the sector, the player's ship and the
+//! settings) is copied into a [`CrashDiagnostics`] snapshot every frame, which the hook reads
+//! when it fires. Pressing [`REPORT_BUG_KEY`] writes the same report without crashing.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::accessibility::{AccessibilitySettings, HudTheme};
+use crate::game_state::GameState;
+use crate::graphics::post::GraphicsSettings;
+use crate::player::input::MouseSettings;
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+use crate::simulation::sector::{CurrentSector, SectorDefinition};
+use crate::simulation::time_control::TimeScale;
+use crate::simulation::WorldSeed;
+
+/// The folder crash and bug reports are written to, relative to the working directory.
+pub const REPORT_FOLDER: &str = "crash_reports";
+
+/// The key that writes a bug report.
+pub const REPORT_BUG_KEY: KeyCode = KeyCode::F8;
+
+/// Runs `app` with a panic hook that writes a crash report before the game goes down, and the
+/// [`REPORT_BUG_KEY`] to write one on demand.
+pub fn run(mut app: App) {
+    let diagnostics = CrashDiagnostics::default();
+    let snapshot = Arc::clone(&diagnostics.0);
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        // The panic may have happened while the snapshot was being recorded, so do not wait on it
+        let report = match snapshot.try_lock() {
+            Ok(snapshot) => snapshot.report(&info.to_string()),
+            Err(_) => DiagnosticsSnapshot::default().report(&info.to_string()),
+        };
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        match write_report("crash", &format!("{report}\nBacktrace:\n{backtrace}\n")) {
+            Ok(path) => eprintln!("Wrote a crash report to {}", path.display()),
+            Err(error) => eprintln!("Could not write a crash report: {error}"),
+        }
+    }));
+
+    app.insert_resource(diagnostics)
+        .add_plugins(CrashReportPlugin)
+        .run();
+}
+
+/// Crash report logic
+pub(super) struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrashDiagnostics>()
+            .add_systems(Update, report_bug)
+            .add_systems(Last, record_diagnostics);
+    }
+}
+
+/// The latest [`DiagnosticsSnapshot`], shared with the panic hook.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CrashDiagnostics(Arc<Mutex<DiagnosticsSnapshot>>);
+
+/// The state of the game worth knowing when it goes wrong.
+#[derive(Debug, Clone, Default)]
+struct DiagnosticsSnapshot {
+    /// What the game was doing.
+    game_state: Option<GameState>,
+    /// The seed the world was generated from.
+    world_seed: Option<WorldSeed>,
+    /// The name of the sector the player was in.
+    sector: Option<String>,
+    /// Where the player's ship was, and which way it was facing.
+    player_transform: Option<Transform>,
+    /// How fast the player's ship was moving.
+    player_velocity: Option<Vec3>,
+    /// Each of the player's settings, as they would be printed for debugging.
+    settings: Vec<String>,
+}
+
+impl DiagnosticsSnapshot {
+    /// A report of the snapshot, headed by what went wrong.
+    fn report(&self, headline: &str) -> String {
+        let mut report = format!("Aegir {}\n{headline}\n\n", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(report, "Game state: {:?}", self.game_state);
+        let _ = writeln!(
+            report,
+            "World seed: {:?}",
+            self.world_seed.map(|seed| seed.0)
+        );
+        let _ = writeln!(report, "Sector: {:?}", self.sector);
+        let _ = writeln!(report, "Player transform: {:?}", self.player_transform);
+        let _ = writeln!(report, "Player velocity: {:?}", self.player_velocity);
+        let _ = writeln!(report, "\nSettings:");
+        for setting in &self.settings {
+            let _ = writeln!(report, "{setting}");
+        }
+        report
+    }
+}
+
+/// Copies the state worth reporting into the [`CrashDiagnostics`] at the end of every frame.
+///
+/// Settings only change in menus, so they are only copied again when one of them has changed.
+#[allow(clippy::too_many_arguments)]
+fn record_diagnostics(
+    diagnostics: Res<CrashDiagnostics>,
+    game_state: Res<State<GameState>>,
+    world_seed: Res<WorldSeed>,
+    current_sector: Option<Res<CurrentSector>>,
+    sector_definitions: Option<Res<Assets<SectorDefinition>>>,
+    player_query: Query<(&Transform, Option<&Velocity>), With<PlayerShip>>,
+    settings: (
+        Option<Res<MouseSettings>>,
+        Option<Res<GraphicsSettings>>,
+        Option<Res<AccessibilitySettings>>,
+        Option<Res<HudTheme>>,
+        Option<Res<TimeScale>>,
+    ),
+) {
+    let Ok(mut snapshot) = diagnostics.0.lock() else {
+        return;
+    };
+
+    snapshot.game_state = Some(*game_state.get());
+    snapshot.world_seed = Some(*world_seed);
+    snapshot.sector = current_sector
+        .as_ref()
+        .and_then(|current| current.sector())
+        .and_then(|handle| sector_definitions.as_ref()?.get(handle))
+        .map(|definition| definition.name.clone());
+    let player = player_query.get_single().ok();
+    snapshot.player_transform = player.map(|(transform, _)| *transform);
+    snapshot.player_velocity = player.and_then(|(_, velocity)| velocity.map(|velocity| velocity.0));
+
+    let (mouse, graphics, accessibility, theme, time_scale) = settings;
+    let changed = mouse.as_ref().is_some_and(|mouse| mouse.is_changed())
+        || graphics
+            .as_ref()
+            .is_some_and(|graphics| graphics.is_changed())
+        || accessibility
+            .as_ref()
+            .is_some_and(|accessibility| accessibility.is_changed())
+        || theme.as_ref().is_some_and(|theme| theme.is_changed())
+        || time_scale
+            .as_ref()
+            .is_some_and(|time_scale| time_scale.is_changed());
+    if changed || snapshot.settings.is_empty() {
+        snapshot.settings = [
+            mouse.map(|mouse| format!("{:?}", *mouse)),
+            graphics.map(|graphics| format!("{:?}", *graphics)),
+            accessibility.map(|accessibility| format!("{:?}", *accessibility)),
+            theme.map(|theme| format!("{:?}", *theme)),
+            time_scale.map(|time_scale| format!("{:?}", *time_scale)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+    }
+}
+
+/// Writes a bug report when the player presses [`REPORT_BUG_KEY`].
+fn report_bug(keyboard: Option<Res<Input<KeyCode>>>, diagnostics: Res<CrashDiagnostics>) {
+    if !keyboard.is_some_and(|keyboard| keyboard.just_pressed(REPORT_BUG_KEY)) {
+        return;
+    }
+    let Ok(snapshot) = diagnostics.0.lock() else {
+        return;
+    };
+
+    let report = snapshot.report("Reported by the player");
+    match write_report("bug", &report) {
+        Ok(path) => info!("Wrote a bug report to {}", path.display()),
+        Err(error) => warn!("Could not write a bug report: {error}"),
+    }
+}
+
+/// Writes `report` to a file in the [`REPORT_FOLDER`] named after its `kind` and the time,
+/// returning where it was written.
+fn write_report(kind: &str, report: &str) -> std::io::Result<PathBuf> {
+    let folder = Path::new(REPORT_FOLDER);
+    std::fs::create_dir_all(folder)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = folder.join(format!("{kind}-{timestamp}.txt"));
+    std::fs::write(&path, report)?;
+
+    Ok(path)
+}
@@ -0,0 +1,21 @@
+//! Developer tools: a command console and a diagnostics overlay, both toggled at runtime, and
+//! crash and bug reports.
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::prelude::{App, Plugin};
+
+pub mod console;
+pub mod crash_report;
+mod overlay;
+
+/// Adds the debug console and overlay.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+
+        app.add_plugins((console::ConsolePlugin, overlay::OverlayPlugin));
+    }
+}
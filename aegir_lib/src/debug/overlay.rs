@@ -0,0 +1,109 @@
+//! A corner overlay of performance and player diagnostics, toggled with `F3` or the console.
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::player::camera::CameraMode;
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+
+use super::console::Console;
+
+/// Overlay logic
+pub(super) struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_overlay)
+            .add_systems(Update, (toggle_overlay, update_overlay).chain());
+    }
+}
+
+/// Marks the overlay text.
+#[derive(Component, Debug, Default)]
+struct DebugOverlay {
+    /// Has the overlay been opened with `F3`?
+    pinned: bool,
+}
+
+/// Spawns the hidden overlay in the top-right corner of the screen.
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.),
+                right: Val::Px(8.),
+                ..default()
+            },
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::LIME_GREEN,
+                    ..default()
+                },
+            )
+        },
+        DebugOverlay::default(),
+    ));
+}
+
+/// Shows the overlay while pinned with `F3` or while the console is open.
+fn toggle_overlay(
+    keyboard: Res<Input<KeyCode>>,
+    console: Res<Console>,
+    mut query: Query<(&mut DebugOverlay, &mut Style)>,
+) {
+    for (mut overlay, mut style) in query.iter_mut() {
+        if keyboard.just_pressed(KeyCode::F3) {
+            overlay.pinned = !overlay.pinned;
+        }
+
+        style.display = if overlay.pinned || console.is_open() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+/// Refreshes the diagnostics shown by the overlay.
+fn update_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    camera_mode: Res<CameraMode>,
+    entities: Query<()>,
+    player_query: Query<(&Transform, &Velocity), With<PlayerShip>>,
+    mut query: Query<(&mut Text, &Style), With<DebugOverlay>>,
+) {
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let frame_time = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        format!("FPS: {fps:.0} ({frame_time:.2} ms)"),
+        format!("Entities: {}", entities.iter().count()),
+        format!("Camera: {:?}", *camera_mode),
+    ];
+
+    if let Ok((transform, velocity)) = player_query.get_single() {
+        let position = transform.translation;
+        lines.push(format!(
+            "Position: {:.1} {:.1} {:.1}",
+            position.x, position.y, position.z
+        ));
+        lines.push(format!("Speed: {:.1} m/s", velocity.0.length()));
+    }
+
+    for (mut text, style) in query.iter_mut() {
+        if style.display != Display::None {
+            text.sections[0].value = lines.join("\n");
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! The high-level states that the game moves between.
+
+use bevy::prelude::*;
+
+/// Tracks which [`GameState`] the game is in.
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .add_systems(OnExit(GameState::Playing), despawn_game_entities);
+    }
+}
+
+/// Set the game state to align systems with their respective runtimes
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum GameState {
+    /// The main menu is open and no world has been spawned.
+    #[default]
+    Menu,
+    /// Waiting for a multiplayer host to accept us.
+    Connecting,
+    /// The player is flying.
+    Playing,
+    /// The flight is over, and its statistics are being shown.
+    Debrief,
+}
+
+/// Marks entities that belong to a game in progress, which are despawned when it ends.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct InGame;
+
+/// Despawns every [`InGame`] entity.
+fn despawn_game_entities(mut commands: Commands, query: Query<Entity, With<InGame>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,454 @@
+//! Meshes for asteroids, the debris and ore pickups that come off them, and the mining laser's
+//! beam.
+//!
+//! Asteroids are drawn with less detail the further they are from the camera, as set by the
+//! [`AsteroidDetail`] in the [`GraphicsSettings`]. The furthest are not drawn one by one at all,
+//! but merged into a batch for each block of space, which is culled as a whole when out of view.
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, MeshVertexAttribute, VertexAttributeValues};
+use bevy::render::primitives::Aabb;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::debug::console::ConsoleAppExt;
+use crate::player::camera::ChaseCamera;
+use crate::simulation::asteroids::Asteroid;
+use crate::simulation::mining::{Debris, MiningLaser};
+use crate::simulation::pickups::Pickup;
+
+use super::post::{AsteroidDetail, GraphicsSettings};
+
+/// The color of mining laser beams.
+const BEAM_COLOR: Color = Color::rgb(1., 0.5, 0.1);
+
+/// The width, height and depth of the block of space each batch of distant asteroids covers, in
+/// meters.
+const BATCH_CELL_SIZE: f32 = 1000.;
+
+/// How far the camera must move before asteroids are sorted into detail levels again, in meters.
+const LOD_UPDATE_DISTANCE: f32 = 20.;
+
+/// Asteroid rendering logic
+pub(super) struct AsteroidGraphicsPlugin;
+
+impl Plugin for AsteroidGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsteroidAssets>()
+            .init_resource::<AsteroidBatches>()
+            .add_console_command(
+                "asteroid detail",
+                "asteroid detail <full detail distance> <batch distance> [max full detail]",
+                asteroid_detail_command,
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        dress_asteroids,
+                        update_asteroid_lod,
+                        rebuild_asteroid_batches,
+                    )
+                        .chain(),
+                    dress_debris,
+                    dress_pickups,
+                    draw_mining_beams,
+                ),
+            );
+    }
+}
+
+/// Handles to the meshes and materials shared by every asteroid.
+#[derive(Resource, Debug)]
+struct AsteroidAssets {
+    /// A sphere of radius one, scaled by each asteroid's transform.
+    rock: Handle<Mesh>,
+    /// A coarser sphere of radius one, for asteroids some way off.
+    rough_rock: Handle<Mesh>,
+    /// A coarser sphere still, copied into batches for each distant asteroid.
+    batched_rock: MeshTemplate,
+    /// A small chunk of rock.
+    chunk: Handle<Mesh>,
+    /// The material shared by asteroids and debris.
+    material: Handle<StandardMaterial>,
+    /// A canister of ore.
+    canister: Handle<Mesh>,
+    /// The glowing material of ore canisters, so they stand out against the rocks.
+    canister_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for AsteroidAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let rock = meshes.add(
+            shape::UVSphere {
+                radius: 1.,
+                sectors: 24,
+                stacks: 16,
+            }
+            .into(),
+        );
+        let rough_rock = meshes.add(
+            shape::UVSphere {
+                radius: 1.,
+                sectors: 12,
+                stacks: 8,
+            }
+            .into(),
+        );
+        let batched_rock = MeshTemplate::new(&Mesh::from(shape::UVSphere {
+            radius: 1.,
+            sectors: 6,
+            stacks: 4,
+        }));
+        let chunk = meshes.add(Mesh::from(shape::Box::new(0.4, 0.3, 0.5)));
+        let canister = meshes.add(Mesh::from(shape::Cube { size: 0.7 }));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.45, 0.4, 0.35),
+            perceptual_roughness: 1.,
+            ..default()
+        });
+        let canister_material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.9, 0.75, 0.3),
+            emissive: Color::rgb(0.6, 0.45, 0.1),
+            ..default()
+        });
+
+        AsteroidAssets {
+            rock,
+            rough_rock,
+            batched_rock,
+            chunk,
+            material,
+            canister,
+            canister_material,
+        }
+    }
+}
+
+/// The vertices and triangles of a mesh, to copy into batches.
+#[derive(Debug, Clone, Default)]
+struct MeshTemplate {
+    /// Where each vertex is.
+    positions: Vec<Vec3>,
+    /// Which way each vertex faces.
+    normals: Vec<Vec3>,
+    /// The vertices of each triangle, three at a time.
+    indices: Vec<u32>,
+}
+
+impl MeshTemplate {
+    /// Copies the positions, normals and triangles of `mesh`.
+    fn new(mesh: &Mesh) -> Self {
+        let vectors = |attribute: MeshVertexAttribute| match mesh.attribute(attribute) {
+            Some(VertexAttributeValues::Float32x3(values)) => {
+                values.iter().copied().map(Vec3::from).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        MeshTemplate {
+            positions: vectors(Mesh::ATTRIBUTE_POSITION),
+            normals: vectors(Mesh::ATTRIBUTE_NORMAL),
+            indices: mesh.indices().map_or_else(Vec::new, |indices| {
+                indices.iter().map(|index| index as u32).collect()
+            }),
+        }
+    }
+
+    /// A single mesh holding a copy of the template placed by each of `transforms`.
+    fn batch<'a>(&self, transforms: impl Iterator<Item = &'a Transform>) -> Mesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+        for transform in transforms {
+            let first = positions.len() as u32;
+            positions.extend(
+                self.positions
+                    .iter()
+                    .map(|&position| transform.transform_point(position).to_array()),
+            );
+            normals.extend(
+                self.normals
+                    .iter()
+                    .map(|&normal| (transform.rotation * normal).to_array()),
+            );
+            indices.extend(self.indices.iter().map(|&index| first + index));
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// How much detail an asteroid is drawn with.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum AsteroidLod {
+    /// Drawn in full.
+    Full,
+    /// Drawn with a coarser mesh.
+    Rough,
+    /// Not drawn itself, but merged into the batch for its block of space.
+    Batched,
+}
+
+/// The batches that distant asteroids are merged into, one for each block of space.
+#[derive(Resource, Debug, Default)]
+struct AsteroidBatches {
+    /// The entity drawing the batch for each block.
+    batches: HashMap<IVec3, Entity>,
+    /// The block each batched asteroid is in.
+    members: HashMap<Entity, IVec3>,
+    /// Blocks whose batches must be rebuilt, since asteroids have joined or left them.
+    dirty: HashSet<IVec3>,
+}
+
+impl AsteroidBatches {
+    /// The block of space containing `position`.
+    fn cell(position: Vec3) -> IVec3 {
+        (position / BATCH_CELL_SIZE).floor().as_ivec3()
+    }
+}
+
+/// Gives asteroids their material once they have spawned, and draws them in full until they are
+/// sorted by distance.
+fn dress_asteroids(
+    mut commands: Commands,
+    asteroid_assets: Res<AsteroidAssets>,
+    query: Query<Entity, Added<Asteroid>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert((
+            asteroid_assets.rock.clone(),
+            asteroid_assets.material.clone(),
+            AsteroidLod::Full,
+        ));
+    }
+}
+
+/// Sorts asteroids into detail levels by their distance from the camera, swapping their meshes
+/// and moving them in and out of batches.
+///
+/// This only runs once the camera has moved some way, the settings have changed or asteroids have
+/// spawned, since most asteroids stay put.
+#[allow(clippy::too_many_arguments)]
+fn update_asteroid_lod(
+    mut commands: Commands,
+    settings: Res<GraphicsSettings>,
+    asteroid_assets: Res<AsteroidAssets>,
+    mut batches: ResMut<AsteroidBatches>,
+    mut last_update: Local<Option<Vec3>>,
+    camera_query: Query<&GlobalTransform, With<ChaseCamera>>,
+    added: Query<(), Added<AsteroidLod>>,
+    mut asteroids: Query<(Entity, &Transform, &mut AsteroidLod)>,
+) {
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+    let camera = camera.translation();
+    let moved = last_update.map_or(true, |last| last.distance(camera) >= LOD_UPDATE_DISTANCE);
+    if !moved && !settings.is_changed() && added.is_empty() {
+        return;
+    }
+    *last_update = Some(camera);
+
+    let AsteroidDetail {
+        full_detail_distance,
+        batch_distance,
+        max_full_detail,
+    } = settings.asteroid_detail;
+    let mut nearby: Vec<(Entity, f32)> = asteroids
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation.distance(camera)))
+        .filter(|&(_, distance)| distance < full_detail_distance)
+        .collect();
+    if nearby.len() > max_full_detail {
+        nearby.select_nth_unstable_by(max_full_detail, |(_, a), (_, b)| a.total_cmp(b));
+        nearby.truncate(max_full_detail);
+    }
+    let full_detail: HashSet<Entity> = nearby.into_iter().map(|(entity, _)| entity).collect();
+
+    for (entity, transform, mut lod) in asteroids.iter_mut() {
+        let wanted = if full_detail.contains(&entity) {
+            AsteroidLod::Full
+        } else if transform.translation.distance(camera) < batch_distance {
+            AsteroidLod::Rough
+        } else {
+            AsteroidLod::Batched
+        };
+        if *lod == wanted {
+            continue;
+        }
+
+        match wanted {
+            AsteroidLod::Full => {
+                commands.entity(entity).insert(asteroid_assets.rock.clone());
+            }
+            AsteroidLod::Rough => {
+                commands
+                    .entity(entity)
+                    .insert(asteroid_assets.rough_rock.clone());
+            }
+            AsteroidLod::Batched => {
+                commands.entity(entity).remove::<Handle<Mesh>>();
+                let cell = AsteroidBatches::cell(transform.translation);
+                batches.members.insert(entity, cell);
+                batches.dirty.insert(cell);
+            }
+        }
+        if *lod == AsteroidLod::Batched {
+            if let Some(cell) = batches.members.remove(&entity) {
+                batches.dirty.insert(cell);
+            }
+        }
+        *lod = wanted;
+    }
+}
+
+/// Rebuilds the batch of each block of space that distant asteroids have joined or left,
+/// despawning batches left empty.
+fn rebuild_asteroid_batches(
+    mut commands: Commands,
+    asteroid_assets: Res<AsteroidAssets>,
+    mut batches: ResMut<AsteroidBatches>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut removed: RemovedComponents<Asteroid>,
+    asteroids: Query<&Transform, With<Asteroid>>,
+) {
+    for entity in removed.iter() {
+        if let Some(cell) = batches.members.remove(&entity) {
+            batches.dirty.insert(cell);
+        }
+    }
+    if batches.dirty.is_empty() {
+        return;
+    }
+
+    let dirty = std::mem::take(&mut batches.dirty);
+    let mut contents: HashMap<IVec3, Vec<&Transform>> = HashMap::default();
+    for (&entity, cell) in &batches.members {
+        if dirty.contains(cell) {
+            if let Ok(transform) = asteroids.get(entity) {
+                contents.entry(*cell).or_default().push(transform);
+            }
+        }
+    }
+
+    for cell in dirty {
+        // The batch may have been despawned along with everything else when play ended
+        let batch = batches
+            .batches
+            .get(&cell)
+            .copied()
+            .filter(|&batch| commands.get_entity(batch).is_some());
+        let Some(transforms) = contents.remove(&cell) else {
+            if let Some(batch) = batches.batches.remove(&cell) {
+                if let Some(entity_commands) = commands.get_entity(batch) {
+                    entity_commands.despawn_recursive();
+                }
+            }
+            continue;
+        };
+
+        let mesh = meshes.add(asteroid_assets.batched_rock.batch(transforms.into_iter()));
+        match batch {
+            // Bounds are only worked out for meshes without them, so drop the old batch's
+            Some(batch) => {
+                commands.entity(batch).insert(mesh).remove::<Aabb>();
+            }
+            None => {
+                let batch = commands
+                    .spawn((
+                        PbrBundle {
+                            mesh,
+                            material: asteroid_assets.material.clone(),
+                            ..default()
+                        },
+                        Name::new("Asteroid batch"),
+                    ))
+                    .id();
+                batches.batches.insert(cell, batch);
+            }
+        }
+    }
+}
+
+/// Gives debris its mesh once it has spawned.
+fn dress_debris(
+    mut commands: Commands,
+    asteroid_assets: Res<AsteroidAssets>,
+    query: Query<Entity, Added<Debris>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert((
+            asteroid_assets.chunk.clone(),
+            asteroid_assets.material.clone(),
+        ));
+    }
+}
+
+/// Gives ore pickups their mesh once they have spawned.
+fn dress_pickups(
+    mut commands: Commands,
+    asteroid_assets: Res<AsteroidAssets>,
+    query: Query<Entity, Added<Pickup>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert((
+            asteroid_assets.canister.clone(),
+            asteroid_assets.canister_material.clone(),
+        ));
+    }
+}
+
+/// Draws a line from each firing mining laser to wherever its beam ends.
+fn draw_mining_beams(mut gizmos: Gizmos, query: Query<(&GlobalTransform, &MiningLaser)>) {
+    for (transform, laser) in query.iter() {
+        if let Some(beam_end) = laser.beam_end {
+            gizmos.line(transform.translation(), beam_end, BEAM_COLOR);
+        }
+    }
+}
+
+/// Console command that sets how far away asteroids are drawn in full and batched together, and
+/// how many can be drawn in full at once.
+fn asteroid_detail_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (full_detail_distance, batch_distance, max_full_detail) = match *arguments {
+        [full, batch] => (full, batch, None),
+        [full, batch, max] => (full, batch, Some(max)),
+        _ => return Err("expected two distances and an optional count".to_string()),
+    };
+    let distance = |distance: &str| {
+        distance
+            .parse()
+            .ok()
+            .filter(|&distance: &f32| distance >= 0.)
+            .ok_or_else(|| format!("`{distance}` is not a distance"))
+    };
+    let full_detail_distance = distance(full_detail_distance)?;
+    let batch_distance = distance(batch_distance)?;
+    let max_full_detail = max_full_detail
+        .map(|max| {
+            max.parse::<usize>()
+                .map_err(|_| format!("`{max}` is not a count"))
+        })
+        .transpose()?;
+
+    let mut settings = world.resource_mut::<GraphicsSettings>();
+    let detail = &mut settings.asteroid_detail;
+    detail.full_detail_distance = full_detail_distance;
+    detail.batch_distance = batch_distance;
+    if let Some(max_full_detail) = max_full_detail {
+        detail.max_full_detail = max_full_detail;
+    }
+
+    Ok(format!(
+        "up to {} asteroids in full within {} m, batched beyond {} m",
+        detail.max_full_detail, detail.full_detail_distance, detail.batch_distance
+    ))
+}
@@ -0,0 +1,575 @@
+//! The cockpit seen from the pilot's seat, with instruments showing the ship's velocity vector,
+//! a repeater of the radar and the state of its hull and shields.
+//!
+//! The cockpit is drawn on its own render layer by a second camera with a very near clipping
+//! plane, layered over the main camera, so the world never clips into it. Each instrument is a
+//! small 2D scene rendered to a texture by its own camera, and that texture is mapped onto one of
+//! the cockpit's screens.
+
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::accessibility::HudTheme;
+use crate::hud::radar::RadarSettings;
+use crate::player::camera::{CameraMode, ChaseCamera, COCKPIT_EYE};
+use crate::player::photo_mode::PhotoMode;
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::{Disposition, Targetable};
+use crate::simulation::energy::Shield;
+use crate::simulation::flight::Velocity;
+use crate::simulation::health::Health;
+use crate::simulation::spatial::SpatialIndex;
+
+/// The render layer the cockpit is drawn on, which only the cockpit camera sees.
+const COCKPIT_LAYER: u8 = 1;
+
+/// The render layer the instruments' 2D scenes are drawn on, which only their cameras see.
+const INSTRUMENT_LAYER: u8 = 2;
+
+/// The width and height of each instrument's texture, in pixels.
+const INSTRUMENT_RESOLUTION: u32 = 256;
+
+/// How far apart the instruments' 2D scenes are laid out, so each camera only sees its own.
+const INSTRUMENT_SPACING: f32 = 1000.;
+
+/// The width and height of each screen in the cockpit, in meters.
+const SCREEN_SIZE: f32 = 0.2;
+
+/// The color behind each instrument's display.
+const SCREEN_BACKGROUND: Color = Color::rgb(0.01, 0.03, 0.05);
+
+/// The color instruments draw their markings in.
+const SCREEN_INK: Color = Color::rgb(0.3, 0.9, 1.);
+
+/// How far the velocity marker moves from the center when flying straight sideways, in pixels.
+const VELOCITY_MARKER_RANGE: f32 = 100.;
+
+/// How far the radar repeater's edge is from its center, in pixels.
+const REPEATER_RADIUS: f32 = 110.;
+
+/// The width and height of a contact's blip on the radar repeater, in pixels.
+const REPEATER_BLIP_SIZE: f32 = 8.;
+
+/// The width of a full gauge on the shield readout, in pixels.
+const GAUGE_WIDTH: f32 = 160.;
+
+/// The boxes the cockpit frame is built from, as their centers and sizes in meters, relative to
+/// the pilot's eyes.
+const FRAME_PIECES: [(Vec3, Vec3); 5] = [
+    // Dashboard
+    (Vec3::new(0., -0.32, -0.6), Vec3::new(1.2, 0.2, 0.45)),
+    // Canopy struts
+    (Vec3::new(-0.58, 0.05, -0.6), Vec3::new(0.05, 0.75, 0.05)),
+    (Vec3::new(0.58, 0.05, -0.6), Vec3::new(0.05, 0.75, 0.05)),
+    // Canopy bow
+    (Vec3::new(0., 0.4, -0.6), Vec3::new(1.2, 0.05, 0.05)),
+    // Side sills
+    (Vec3::new(0., -0.22, 0.1), Vec3::new(1.3, 0.05, 0.6)),
+];
+
+/// Cockpit rendering logic
+pub(super) struct CockpitGraphicsPlugin;
+
+impl Plugin for CockpitGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CockpitAssets>()
+            .add_systems(Startup, spawn_instruments)
+            .add_systems(
+                Update,
+                (
+                    attach_cockpit_camera,
+                    build_cockpit,
+                    switch_cockpit_cameras,
+                    (
+                        update_velocity_vector,
+                        update_radar_repeater,
+                        update_shield_readout,
+                    )
+                        .run_if(resource_equals(CameraMode::Cockpit)),
+                ),
+            );
+    }
+}
+
+/// One of the displays on the cockpit's dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Instrument {
+    /// Where the ship is heading relative to where it is pointing, and how fast.
+    VelocityVector,
+    /// The contacts shown on the radar, seen from above.
+    RadarRepeater,
+    /// How much hull and shield remain.
+    ShieldReadout,
+}
+
+impl Instrument {
+    /// Every instrument, from left to right across the dashboard.
+    const ALL: [Instrument; 3] = [
+        Instrument::ShieldReadout,
+        Instrument::VelocityVector,
+        Instrument::RadarRepeater,
+    ];
+
+    /// Where the instrument's 2D scene is laid out.
+    fn origin(self) -> Vec2 {
+        let index = Self::ALL
+            .iter()
+            .position(|&instrument| instrument == self)
+            .unwrap_or_default();
+        Vec2::new(index as f32 * INSTRUMENT_SPACING, -INSTRUMENT_SPACING)
+    }
+
+    /// Where the instrument's screen sits on the dashboard, relative to the pilot's eyes.
+    fn screen_transform(self) -> Transform {
+        let x = match self {
+            Instrument::ShieldReadout => -0.3,
+            Instrument::VelocityVector => 0.,
+            Instrument::RadarRepeater => 0.3,
+        };
+        // Tilt the screens back to face up towards the pilot
+        Transform::from_xyz(x, -0.2, -0.4).with_rotation(Quat::from_rotation_x(-0.4))
+    }
+}
+
+/// Handles to the meshes, materials and textures the cockpit is drawn with.
+#[derive(Resource, Debug)]
+struct CockpitAssets {
+    /// A unit cube, scaled into each piece of the frame.
+    frame: Handle<Mesh>,
+    /// The material of the frame.
+    frame_material: Handle<StandardMaterial>,
+    /// The quad each instrument is shown on.
+    screen: Handle<Mesh>,
+    /// The texture each instrument is rendered to, and the material showing it on its screen.
+    instruments: Vec<(Instrument, Handle<Image>, Handle<StandardMaterial>)>,
+}
+
+impl FromWorld for CockpitAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let frame = meshes.add(Mesh::from(shape::Cube { size: 1. }));
+        let screen = meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(SCREEN_SIZE))));
+
+        let size = Extent3d {
+            width: INSTRUMENT_RESOLUTION,
+            height: INSTRUMENT_RESOLUTION,
+            depth_or_array_layers: 1,
+        };
+        let images: Vec<Handle<Image>> = {
+            let mut images = world.resource_mut::<Assets<Image>>();
+            Instrument::ALL
+                .iter()
+                .map(|_| {
+                    let mut image = Image {
+                        texture_descriptor: TextureDescriptor {
+                            label: None,
+                            size,
+                            dimension: TextureDimension::D2,
+                            format: TextureFormat::Bgra8UnormSrgb,
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            usage: TextureUsages::TEXTURE_BINDING
+                                | TextureUsages::COPY_DST
+                                | TextureUsages::RENDER_ATTACHMENT,
+                            view_formats: &[],
+                        },
+                        ..default()
+                    };
+                    image.resize(size);
+                    images.add(image)
+                })
+                .collect()
+        };
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        // The cockpit is unlit, so that it reads the same whatever the sector's lighting
+        let frame_material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.08, 0.09, 0.1),
+            unlit: true,
+            ..default()
+        });
+        let instruments = Instrument::ALL
+            .into_iter()
+            .zip(images)
+            .map(|(instrument, image)| {
+                let material = materials.add(StandardMaterial {
+                    base_color_texture: Some(image.clone()),
+                    unlit: true,
+                    ..default()
+                });
+                (instrument, image, material)
+            })
+            .collect();
+
+        CockpitAssets {
+            frame,
+            frame_material,
+            screen,
+            instruments,
+        }
+    }
+}
+
+/// Marks the camera that draws the cockpit over the main camera's view.
+#[derive(Component, Debug)]
+struct CockpitCamera;
+
+/// Marks the cameras that render each instrument to its texture.
+#[derive(Component, Debug)]
+struct InstrumentCamera;
+
+/// Marks the velocity vector's marker, which shows where the ship is heading.
+#[derive(Component, Debug)]
+struct VelocityMarker;
+
+/// Marks the velocity vector's speed readout.
+#[derive(Component, Debug)]
+struct SpeedReadout;
+
+/// Marks the radar repeater's 2D scene, which its blips are children of.
+#[derive(Component, Debug)]
+struct RadarRepeater;
+
+/// A blip on the radar repeater, reused for whichever contact needs it each frame.
+#[derive(Component, Debug)]
+struct RepeaterBlip;
+
+/// A bar on the shield readout.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum Gauge {
+    /// How much of the hull remains.
+    Hull,
+    /// How much shield remains.
+    Shields,
+}
+
+/// A plain rectangle drawn in an instrument.
+fn instrument_sprite(color: Color, size: Vec2, position: Vec2) -> (SpriteBundle, RenderLayers) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.)),
+            ..default()
+        },
+        RenderLayers::layer(INSTRUMENT_LAYER),
+    )
+}
+
+/// A line of text drawn in an instrument.
+fn instrument_text(text: &str, font_size: f32, position: Vec2) -> (Text2dBundle, RenderLayers) {
+    (
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size,
+                    color: SCREEN_INK,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(position.extend(1.)),
+            ..default()
+        },
+        RenderLayers::layer(INSTRUMENT_LAYER),
+    )
+}
+
+/// Spawns each instrument's 2D scene, and the camera that renders it to its texture.
+///
+/// The cameras stay inactive until the player sits in the cockpit.
+fn spawn_instruments(mut commands: Commands, cockpit_assets: Res<CockpitAssets>) {
+    for (instrument, image, _) in &cockpit_assets.instruments {
+        let origin = instrument.origin();
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(image.clone()),
+                    // Render the instruments before the cockpit that shows them
+                    order: -1,
+                    is_active: false,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::Custom(SCREEN_BACKGROUND),
+                },
+                transform: Transform::from_xyz(origin.x, origin.y, 999.9),
+                ..default()
+            },
+            RenderLayers::layer(INSTRUMENT_LAYER),
+            UiCameraConfig { show_ui: false },
+            InstrumentCamera,
+        ));
+
+        let mut scene = commands.spawn(SpatialBundle::from_transform(Transform::from_translation(
+            origin.extend(0.),
+        )));
+        match instrument {
+            Instrument::VelocityVector => {
+                scene.with_children(|parent| {
+                    let dim = SCREEN_INK.with_a(0.3);
+                    parent.spawn(instrument_sprite(dim, Vec2::new(200., 2.), Vec2::ZERO));
+                    parent.spawn(instrument_sprite(dim, Vec2::new(2., 200.), Vec2::ZERO));
+                    parent.spawn((
+                        instrument_sprite(SCREEN_INK, Vec2::splat(12.), Vec2::ZERO),
+                        VelocityMarker,
+                    ));
+                    parent.spawn((instrument_text("", 28., Vec2::new(0., -110.)), SpeedReadout));
+                });
+            }
+            Instrument::RadarRepeater => {
+                scene.insert(RadarRepeater).with_children(|parent| {
+                    let dim = SCREEN_INK.with_a(0.3);
+                    let diameter = REPEATER_RADIUS * 2.;
+                    parent.spawn(instrument_sprite(dim, Vec2::new(diameter, 1.), Vec2::ZERO));
+                    parent.spawn(instrument_sprite(dim, Vec2::new(1., diameter), Vec2::ZERO));
+                    parent.spawn(instrument_sprite(Color::WHITE, Vec2::splat(6.), Vec2::ZERO));
+                });
+            }
+            Instrument::ShieldReadout => {
+                scene.with_children(|parent| {
+                    for (gauge, label, y) in
+                        [(Gauge::Hull, "HULL", 40.), (Gauge::Shields, "SHLD", -40.)]
+                    {
+                        let left = -GAUGE_WIDTH / 2.;
+                        parent.spawn(instrument_text(label, 24., Vec2::new(0., y + 28.)));
+                        parent.spawn(instrument_sprite(
+                            SCREEN_INK.with_a(0.15),
+                            Vec2::new(GAUGE_WIDTH, 20.),
+                            Vec2::new(0., y),
+                        ));
+                        let mut fill = instrument_sprite(
+                            SCREEN_INK,
+                            Vec2::new(GAUGE_WIDTH, 20.),
+                            Vec2::new(left, y),
+                        );
+                        fill.0.sprite.anchor = Anchor::CenterLeft;
+                        parent.spawn((fill, gauge));
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Gives each main camera a cockpit camera, which follows it around as its child.
+fn attach_cockpit_camera(mut commands: Commands, query: Query<Entity, Added<ChaseCamera>>) {
+    for camera in query.iter() {
+        commands.entity(camera).with_children(|parent| {
+            parent.spawn((
+                Camera3dBundle {
+                    camera: Camera {
+                        // Draw over the main camera
+                        order: 1,
+                        is_active: false,
+                        ..default()
+                    },
+                    camera_3d: Camera3d {
+                        clear_color: ClearColorConfig::None,
+                        ..default()
+                    },
+                    projection: Projection::Perspective(PerspectiveProjection {
+                        near: 0.01,
+                        far: 10.,
+                        ..default()
+                    }),
+                    ..default()
+                },
+                RenderLayers::layer(COCKPIT_LAYER),
+                UiCameraConfig { show_ui: false },
+                CockpitCamera,
+            ));
+        });
+    }
+}
+
+/// Builds the cockpit inside the player's ship once it has spawned.
+fn build_cockpit(
+    mut commands: Commands,
+    cockpit_assets: Res<CockpitAssets>,
+    query: Query<Entity, Added<PlayerShip>>,
+) {
+    for ship in query.iter() {
+        commands.entity(ship).with_children(|parent| {
+            parent
+                .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                    COCKPIT_EYE,
+                )))
+                .with_children(|cockpit| {
+                    for (position, size) in FRAME_PIECES {
+                        cockpit.spawn((
+                            PbrBundle {
+                                mesh: cockpit_assets.frame.clone(),
+                                material: cockpit_assets.frame_material.clone(),
+                                transform: Transform::from_translation(position).with_scale(size),
+                                ..default()
+                            },
+                            RenderLayers::layer(COCKPIT_LAYER),
+                        ));
+                    }
+                    for (instrument, _, material) in &cockpit_assets.instruments {
+                        cockpit.spawn((
+                            PbrBundle {
+                                mesh: cockpit_assets.screen.clone(),
+                                material: material.clone(),
+                                transform: instrument.screen_transform(),
+                                ..default()
+                            },
+                            RenderLayers::layer(COCKPIT_LAYER),
+                        ));
+                    }
+                });
+        });
+    }
+}
+
+/// Turns the cockpit and instrument cameras on while the player sits in the cockpit, keeping the
+/// cockpit camera's field of view matched to the main camera's.
+fn switch_cockpit_cameras(
+    camera_mode: Res<CameraMode>,
+    photo_mode: Res<State<PhotoMode>>,
+    main_query: Query<&Projection, (With<ChaseCamera>, Without<CockpitCamera>)>,
+    mut cockpit_query: Query<
+        (&Parent, &mut Camera, &mut Projection),
+        (With<CockpitCamera>, Without<ChaseCamera>),
+    >,
+    mut instrument_query: Query<&mut Camera, (With<InstrumentCamera>, Without<CockpitCamera>)>,
+) {
+    // The orbiting photo camera would carry the cockpit away from the ship with it
+    let active = *camera_mode == CameraMode::Cockpit && *photo_mode.get() == PhotoMode::Off;
+
+    for (parent, mut camera, mut projection) in cockpit_query.iter_mut() {
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+        if let (Ok(Projection::Perspective(main)), Projection::Perspective(cockpit)) =
+            (main_query.get(parent.get()), &mut *projection)
+        {
+            if cockpit.fov != main.fov {
+                cockpit.fov = main.fov;
+            }
+        }
+    }
+    for mut camera in instrument_query.iter_mut() {
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+    }
+}
+
+/// Moves the velocity marker to show which way the player's ship is moving, relative to where it
+/// points, turning it red when the ship is moving backwards.
+fn update_velocity_vector(
+    ship_query: Query<(&Transform, &Velocity), With<PlayerShip>>,
+    mut marker_query: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        (With<VelocityMarker>, Without<PlayerShip>),
+    >,
+    mut readout_query: Query<&mut Text, With<SpeedReadout>>,
+) {
+    let Ok((ship, velocity)) = ship_query.get_single() else {
+        return;
+    };
+    let speed = velocity.0.length();
+    let local = (ship.rotation.inverse() * velocity.0).normalize_or_zero();
+
+    for (mut transform, mut sprite, mut visibility) in marker_query.iter_mut() {
+        *visibility = if speed > 0.5 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        transform.translation.x = local.x * VELOCITY_MARKER_RANGE;
+        transform.translation.y = local.y * VELOCITY_MARKER_RANGE;
+        // Forward is -Z, so moving backwards shows as positive Z
+        sprite.color = if local.z > 0. {
+            Color::rgb(1., 0.3, 0.2)
+        } else {
+            SCREEN_INK
+        };
+    }
+    for mut text in readout_query.iter_mut() {
+        text.sections[0].value = format!("{speed:.0} m/s");
+    }
+}
+
+/// Moves, spawns and hides blips on the radar repeater to match the contacts within radar range
+/// of the player, seen from above with the ship's nose pointing up.
+fn update_radar_repeater(
+    mut commands: Commands,
+    settings: Res<RadarSettings>,
+    theme: Res<HudTheme>,
+    index: Res<SpatialIndex>,
+    ship_query: Query<&Transform, With<PlayerShip>>,
+    contact_query: Query<(&GlobalTransform, Option<&Disposition>), With<Targetable>>,
+    repeater_query: Query<Entity, With<RadarRepeater>>,
+    mut blip_query: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        (With<RepeaterBlip>, Without<PlayerShip>),
+    >,
+) {
+    let (Ok(ship), Ok(repeater)) = (ship_query.get_single(), repeater_query.get_single()) else {
+        return;
+    };
+
+    let scale = REPEATER_RADIUS / settings.range;
+    let mut blips = blip_query.iter_mut();
+    for (contact, _) in index.within(ship.translation, settings.range) {
+        let Ok((transform, disposition)) = contact_query.get(contact) else {
+            continue;
+        };
+        let offset = transform.translation() - ship.translation;
+        if offset.length() > settings.range {
+            continue;
+        }
+
+        let local = ship.rotation.inverse() * offset * scale;
+        let position = Vec3::new(local.x, -local.z, 1.);
+        let color = theme.disposition(disposition.copied().unwrap_or_default());
+
+        match blips.next() {
+            Some((mut blip_transform, mut sprite, mut visibility)) => {
+                blip_transform.translation = position;
+                sprite.color = color;
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                let mut blip =
+                    instrument_sprite(color, Vec2::splat(REPEATER_BLIP_SIZE), Vec2::ZERO);
+                blip.0.transform.translation = position;
+                let blip = commands.spawn((blip, RepeaterBlip)).id();
+                commands.entity(repeater).add_child(blip);
+            }
+        }
+    }
+    for (_, _, mut visibility) in blips {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Sizes the shield readout's gauges to the player's remaining hull and shield.
+fn update_shield_readout(
+    ship_query: Query<(&Health, Option<&Shield>), With<PlayerShip>>,
+    mut gauge_query: Query<(&Gauge, &mut Sprite)>,
+) {
+    let Ok((health, shield)) = ship_query.get_single() else {
+        return;
+    };
+
+    for (gauge, mut sprite) in gauge_query.iter_mut() {
+        let fraction = match gauge {
+            Gauge::Hull => health.fraction(),
+            Gauge::Shields => shield.map_or(0., |shield| shield.fraction()),
+        };
+        sprite.custom_size = Some(Vec2::new(GAUGE_WIDTH * fraction, 20.));
+    }
+}
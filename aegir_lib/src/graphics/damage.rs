@@ -0,0 +1,170 @@
+//! Sparks and smoke streaming from critically damaged hulls.
+//!
+//! Particles are drawn from an [`EntityPool`], and returned to it once they have faded away.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::game_state::InGame;
+use crate::pooling::EntityPool;
+use crate::simulation::asteroids::Asteroid;
+use crate::simulation::geometry::Collider;
+use crate::simulation::health::Health;
+
+use super::VfxSet;
+
+/// How many sparks each critically damaged ship throws off each second.
+const SPARKS_PER_SECOND: f32 = 12.;
+
+/// How many puffs of smoke each critically damaged ship trails each second.
+const SMOKE_PER_SECOND: f32 = 6.;
+
+/// Hull damage rendering logic
+pub(super) struct DamageGraphicsPlugin;
+
+impl Plugin for DamageGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleAssets>()
+            .init_resource::<EntityPool<Particle>>()
+            .add_systems(
+                Update,
+                (emit_damage_particles, update_particles).in_set(VfxSet),
+            );
+    }
+}
+
+/// Handles to the mesh and materials shared by every particle.
+#[derive(Resource, Debug)]
+struct ParticleAssets {
+    /// A small sphere, scaled by each particle.
+    mesh: Handle<Mesh>,
+    /// Bright, unlit orange.
+    spark: Handle<StandardMaterial>,
+    /// Translucent grey.
+    smoke: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(
+            shape::UVSphere {
+                radius: 1.,
+                sectors: 6,
+                stacks: 4,
+            }
+            .into(),
+        );
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let spark = materials.add(StandardMaterial {
+            base_color: Color::rgb(1., 0.6, 0.2),
+            emissive: Color::rgb_linear(4., 2., 0.5),
+            unlit: true,
+            ..default()
+        });
+        let smoke = materials.add(StandardMaterial {
+            base_color: Color::rgba(0.3, 0.3, 0.3, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+
+        ParticleAssets { mesh, spark, smoke }
+    }
+}
+
+/// A purely visual speck that drifts, grows or shrinks, and vanishes.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct Particle {
+    /// How far it moves each second.
+    velocity: Vec3,
+    /// How much its scale changes each second.
+    growth: f32,
+    /// How long it has left, in seconds.
+    lifetime: f32,
+}
+
+/// Throws sparks and smoke from the hulls of critically damaged ships, but not from asteroids.
+fn emit_damage_particles(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Particle>>,
+    time: Res<Time>,
+    particle_assets: Res<ParticleAssets>,
+    query: Query<(&GlobalTransform, &Health, Option<&Collider>), Without<Asteroid>>,
+) {
+    let mut rng = rand::thread_rng();
+    let delta_time = time.delta_seconds();
+
+    for (transform, health, collider) in query.iter() {
+        if !health.is_critical() {
+            continue;
+        }
+        let radius = collider.map_or(1., |collider| collider.radius);
+
+        for (rate, material, speed, size, growth, lifetime) in [
+            (
+                SPARKS_PER_SECOND,
+                &particle_assets.spark,
+                6.,
+                0.06,
+                -0.1,
+                0.5,
+            ),
+            (SMOKE_PER_SECOND, &particle_assets.smoke, 1., 0.3, 0.8, 2.),
+        ] {
+            // At most one of each per frame, which averages out to the rate at any sensible frame
+            // rate
+            if !rng.gen_bool(f64::from((rate * delta_time).min(1.))) {
+                continue;
+            }
+
+            let direction = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize_or_zero();
+            let position = transform.translation() + direction * radius * 0.6;
+
+            pool.spawn(
+                &mut commands,
+                (
+                    PbrBundle {
+                        mesh: particle_assets.mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(position)
+                            .with_scale(Vec3::splat(size)),
+                        ..default()
+                    },
+                    Particle {
+                        velocity: direction * speed,
+                        growth,
+                        lifetime,
+                    },
+                    InGame,
+                ),
+            );
+        }
+    }
+}
+
+/// Moves, scales and eventually returns particles to the pool.
+fn update_particles(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Particle>>,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Particle)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut transform, mut particle) in query.iter_mut() {
+        particle.lifetime -= delta_time;
+        if particle.lifetime <= 0. {
+            pool.release(&mut commands, entity);
+            continue;
+        }
+
+        transform.translation += particle.velocity * delta_time;
+        transform.scale =
+            (transform.scale + Vec3::splat(particle.growth * delta_time)).max(Vec3::ZERO);
+    }
+}
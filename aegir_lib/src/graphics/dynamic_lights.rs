@@ -0,0 +1,218 @@
+//! Point lights that come and go: flashes from explosions and muzzles, and the glow of engines.
+//!
+//! Flashes are drawn from an [`EntityPool`] and fade out on their own. Every light that moves or
+//! changes, flashes and engine lights alike, is a [`DynamicLight`], and only the
+//! [`GraphicsSettings::max_dynamic_lights`] of them that shine brightest on the camera are drawn.
+
+use bevy::prelude::*;
+
+use crate::game_state::InGame;
+use crate::player::camera::ChaseCamera;
+use crate::pooling::EntityPool;
+use crate::simulation::health::Destroyed;
+use crate::simulation::weapons::{WeaponDefinition, WeaponFired};
+
+use super::post::GraphicsSettings;
+use super::VfxSet;
+
+/// How bright an explosion's flash is at its peak, in lumens.
+const EXPLOSION_INTENSITY: f32 = 2_000_000.;
+
+/// How far an explosion's flash reaches, in meters.
+const EXPLOSION_RANGE: f32 = 250.;
+
+/// How long an explosion's flash takes to fade, in seconds.
+const EXPLOSION_LIFETIME: f32 = 0.6;
+
+/// The color of an explosion's flash.
+const EXPLOSION_COLOR: Color = Color::rgb(1., 0.6, 0.25);
+
+/// How bright a muzzle flash is at its peak, in lumens.
+const MUZZLE_FLASH_INTENSITY: f32 = 40_000.;
+
+/// How far a muzzle flash reaches, in meters.
+const MUZZLE_FLASH_RANGE: f32 = 30.;
+
+/// How long a muzzle flash takes to fade, in seconds.
+const MUZZLE_FLASH_LIFETIME: f32 = 0.08;
+
+/// Dynamic lighting logic
+pub(super) struct DynamicLightingPlugin;
+
+impl Plugin for DynamicLightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityPool<Flash>>().add_systems(
+            Update,
+            (
+                flash_explosions,
+                flash_muzzles,
+                fade_flashes,
+                enforce_light_budget,
+            )
+                .chain()
+                .in_set(VfxSet),
+        );
+    }
+}
+
+/// A point light that counts towards the light budget, hidden when there are brighter ones.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub(super) struct DynamicLight;
+
+/// A short-lived point light that fades out from its peak intensity.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct Flash {
+    /// How bright the flash starts, in lumens.
+    peak_intensity: f32,
+    /// How long the flash lasts in all, in seconds.
+    lifetime: f32,
+    /// How long the flash has lasted so far, in seconds.
+    age: f32,
+}
+
+/// Lights a flash at `position`, reusing a faded one if there is one.
+fn spawn_flash(
+    commands: &mut Commands,
+    pool: &mut EntityPool<Flash>,
+    position: Vec3,
+    color: Color,
+    intensity: f32,
+    range: f32,
+    lifetime: f32,
+) {
+    pool.spawn(
+        commands,
+        (
+            PointLightBundle {
+                point_light: PointLight {
+                    color,
+                    intensity,
+                    range,
+                    ..default()
+                },
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Flash {
+                peak_intensity: intensity,
+                lifetime,
+                age: 0.,
+            },
+            DynamicLight,
+            InGame,
+        ),
+    );
+}
+
+/// Lights a flash wherever something is destroyed.
+fn flash_explosions(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Flash>>,
+    mut destroyed: EventReader<Destroyed>,
+) {
+    for event in destroyed.iter() {
+        spawn_flash(
+            &mut commands,
+            &mut pool,
+            event.position,
+            EXPLOSION_COLOR,
+            EXPLOSION_INTENSITY,
+            EXPLOSION_RANGE,
+            EXPLOSION_LIFETIME,
+        );
+    }
+}
+
+/// Lights a flash at the muzzle of each weapon fired, in the color of its shots.
+fn flash_muzzles(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Flash>>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut weapon_fired: EventReader<WeaponFired>,
+) {
+    for event in weapon_fired.iter() {
+        let color = definitions
+            .get(&event.weapon)
+            .and_then(|definition| {
+                definition
+                    .projectile
+                    .as_ref()
+                    .map(|projectile| projectile.color)
+                    .or(definition.beam.map(|beam| beam.color))
+            })
+            .map_or(Color::WHITE, |[red, green, blue]| {
+                Color::rgb_linear(red, green, blue)
+            });
+
+        spawn_flash(
+            &mut commands,
+            &mut pool,
+            event.muzzle,
+            color,
+            MUZZLE_FLASH_INTENSITY,
+            MUZZLE_FLASH_RANGE,
+            MUZZLE_FLASH_LIFETIME,
+        );
+    }
+}
+
+/// Dims each flash as it ages, returning it to the pool once it has gone out.
+fn fade_flashes(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Flash>>,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Flash, &mut PointLight)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut flash, mut light) in query.iter_mut() {
+        flash.age += delta_time;
+        let remaining = 1. - flash.age / flash.lifetime;
+        if remaining <= 0. {
+            light.intensity = 0.;
+            if let Some(mut entity_commands) = pool.release(&mut commands, entity) {
+                entity_commands.remove::<DynamicLight>();
+            }
+            continue;
+        }
+
+        light.intensity = flash.peak_intensity * remaining * remaining;
+    }
+}
+
+/// Shows only the dynamic lights that shine brightest on the camera, up to the budget in the
+/// [`GraphicsSettings`], and hides the rest.
+fn enforce_light_budget(
+    settings: Res<GraphicsSettings>,
+    camera_query: Query<&GlobalTransform, With<ChaseCamera>>,
+    mut light_query: Query<
+        (Entity, &PointLight, &GlobalTransform, &mut Visibility),
+        With<DynamicLight>,
+    >,
+) {
+    let camera = camera_query
+        .get_single()
+        .map_or(Vec3::ZERO, |transform| transform.translation());
+
+    let mut lights: Vec<(Entity, f32)> = light_query
+        .iter()
+        .filter(|(_, light, ..)| light.intensity > 0.)
+        .map(|(entity, light, transform, _)| {
+            let distance_squared = transform.translation().distance_squared(camera);
+            (entity, light.intensity / (1. + distance_squared))
+        })
+        .collect();
+    lights.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    lights.truncate(settings.max_dynamic_lights);
+
+    for (entity, _, _, mut visibility) in light_query.iter_mut() {
+        let shown = if lights.iter().any(|&(lit, _)| lit == entity) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Attachment points read from ship scenes, so that artists can place hardpoints and engines in
+//! their modelling tool rather than in code.
+//!
+//! Once a ship's scene has spawned, its nodes are searched by name:
+//!
+//! - `hardpoint_*` nodes, in name order, move the ship's hardpoints in slot order. Nodes beyond
+//!   the hardpoints in the ship's definition add new, empty hardpoints.
+//! - `thruster_*` nodes each add an [`EngineEmitter`] that glows with the ship's throttle, and
+//!   lights up the hull around it.
+
+use bevy::prelude::*;
+use bevy::scene::SceneInstance;
+
+use crate::simulation::flight::Throttle;
+use crate::simulation::weapons::Hardpoint;
+
+use super::dynamic_lights::DynamicLight;
+
+/// The prefix of scene nodes that mark hardpoints.
+const HARDPOINT_PREFIX: &str = "hardpoint";
+
+/// The prefix of scene nodes that mark engine exhausts.
+const THRUSTER_PREFIX: &str = "thruster";
+
+/// How brightly an engine glows at full throttle, as a multiple of its material's emissive color.
+const MAX_ENGINE_GLOW: f32 = 3.;
+
+/// How bright the light behind each engine is at full throttle, in lumens.
+const ENGINE_LIGHT_INTENSITY: f32 = 20_000.;
+
+/// How far the light behind each engine reaches, in meters.
+const ENGINE_LIGHT_RANGE: f32 = 15.;
+
+/// Scene fitting logic
+pub(super) struct FittingsPlugin;
+
+impl Plugin for FittingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EmitterAssets>()
+            .add_systems(Update, (attach_fittings, glow_engine_emitters));
+    }
+}
+
+/// Marks a ship's scene, whose named nodes have not been searched for fittings yet.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub(super) struct UnfittedShipScene;
+
+/// An engine exhaust, which glows brighter as its ship's throttle opens.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct EngineEmitter;
+
+/// The light cast by an engine exhaust, which brightens with its ship's throttle.
+#[derive(Component, Debug, Clone, Copy, Default)]
+struct EngineLight;
+
+/// Handles to the mesh and material shared by every engine emitter.
+#[derive(Resource, Debug)]
+struct EmitterAssets {
+    /// The glow behind each exhaust.
+    glow: Handle<Mesh>,
+    /// The material of the glow, which each emitter is given a copy of to brighten by itself.
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for EmitterAssets {
+    fn from_world(world: &mut World) -> Self {
+        let glow = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::UVSphere {
+                radius: 0.25,
+                sectors: 12,
+                stacks: 8,
+            }));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::rgb(0.4, 0.7, 1.),
+                emissive: Color::rgb(1.5, 2.5, 4.),
+                unlit: true,
+                ..default()
+            });
+
+        EmitterAssets { glow, material }
+    }
+}
+
+/// Where `node` sits relative to `ancestor`, found by combining the local transforms in between.
+fn transform_relative_to(
+    node: Entity,
+    ancestor: Entity,
+    parent_query: &Query<&Parent>,
+    transform_query: &Query<&Transform>,
+) -> Transform {
+    let mut relative = Transform::IDENTITY;
+    let mut current = node;
+    while current != ancestor {
+        let Ok(transform) = transform_query.get(current) else {
+            break;
+        };
+        relative = transform.mul_transform(relative);
+        let Ok(parent) = parent_query.get(current) else {
+            break;
+        };
+        current = parent.get();
+    }
+    relative
+}
+
+/// Moves hardpoints to, and adds engine emitters at, the named nodes of ship scenes that have
+/// finished spawning.
+#[allow(clippy::too_many_arguments)]
+fn attach_fittings(
+    mut commands: Commands,
+    scene_spawner: Res<SceneSpawner>,
+    emitter_assets: Res<EmitterAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    scene_query: Query<(Entity, &SceneInstance, &Parent), With<UnfittedShipScene>>,
+    children_query: Query<&Children>,
+    name_query: Query<&Name>,
+    parent_query: Query<&Parent>,
+    mut transform_queries: ParamSet<(Query<&Transform>, Query<(&Hardpoint, &mut Transform)>)>,
+) {
+    for (scene, instance, ship) in scene_query.iter() {
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        commands.entity(scene).remove::<UnfittedShipScene>();
+        let ship = ship.get();
+
+        let mut nodes: Vec<(&str, Entity)> = children_query
+            .iter_descendants(scene)
+            .filter_map(|node| Some((name_query.get(node).ok()?.as_str(), node)))
+            .collect();
+        nodes.sort();
+
+        let (hardpoint_placements, thruster_placements) = {
+            let transform_query = transform_queries.p0();
+            let placements = |prefix: &str| -> Vec<Transform> {
+                nodes
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(prefix))
+                    .map(|&(_, node)| {
+                        transform_relative_to(node, ship, &parent_query, &transform_query)
+                    })
+                    .collect()
+            };
+            (placements(HARDPOINT_PREFIX), placements(THRUSTER_PREFIX))
+        };
+
+        let mut hardpoint_query = transform_queries.p1();
+        for (slot, placement) in hardpoint_placements.into_iter().enumerate() {
+            let existing = children_query
+                .get(ship)
+                .into_iter()
+                .flatten()
+                .copied()
+                .find(|&child| {
+                    hardpoint_query
+                        .get(child)
+                        .is_ok_and(|(hardpoint, _)| hardpoint.slot == slot)
+                });
+
+            match existing.and_then(|child| hardpoint_query.get_mut(child).ok()) {
+                Some((_, mut transform)) => *transform = placement,
+                None => {
+                    commands.entity(ship).with_children(|parent| {
+                        parent
+                            .spawn((SpatialBundle::from_transform(placement), Hardpoint { slot }));
+                    });
+                }
+            }
+        }
+
+        for placement in thruster_placements {
+            let material = materials
+                .get(&emitter_assets.material)
+                .cloned()
+                .map_or_else(
+                    || emitter_assets.material.clone(),
+                    |material| materials.add(material),
+                );
+            commands.entity(ship).with_children(|parent| {
+                parent
+                    .spawn((
+                        PbrBundle {
+                            mesh: emitter_assets.glow.clone(),
+                            material,
+                            transform: placement,
+                            ..default()
+                        },
+                        EngineEmitter,
+                    ))
+                    .with_children(|emitter| {
+                        emitter.spawn((
+                            PointLightBundle {
+                                point_light: PointLight {
+                                    color: Color::rgb(0.4, 0.7, 1.),
+                                    intensity: 0.,
+                                    range: ENGINE_LIGHT_RANGE,
+                                    ..default()
+                                },
+                                // Just behind the exhaust, so that it lights the hull around it
+                                transform: Transform::from_xyz(0., 0., 0.5),
+                                ..default()
+                            },
+                            EngineLight,
+                            DynamicLight,
+                        ));
+                    });
+            });
+        }
+    }
+}
+
+/// Grows and brightens each engine emitter's glow, and the light it casts, with its ship's
+/// throttle.
+fn glow_engine_emitters(
+    emitter_assets: Res<EmitterAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ship_query: Query<&Throttle>,
+    mut emitter_query: Query<
+        (
+            &Parent,
+            &mut Transform,
+            &Handle<StandardMaterial>,
+            Option<&Children>,
+        ),
+        With<EngineEmitter>,
+    >,
+    mut light_query: Query<&mut PointLight, With<EngineLight>>,
+) {
+    let base_emissive = materials
+        .get(&emitter_assets.material)
+        .map_or(Color::BLACK, |material| material.emissive);
+
+    for (ship, mut transform, material, children) in emitter_query.iter_mut() {
+        let throttle = ship_query
+            .get(ship.get())
+            .map_or(0., |throttle| throttle.fraction());
+        transform.scale = Vec3::new(1., 1., 1. + 3. * throttle) * (0.3 + 0.7 * throttle);
+
+        let emissive = base_emissive * (0.3 + (MAX_ENGINE_GLOW - 0.3) * throttle);
+        // Looking a material up mutably marks it as changed, so only do so when it needs to change
+        let stale = material != &emitter_assets.material
+            && materials
+                .get(material)
+                .is_some_and(|material| material.emissive != emissive);
+        if stale {
+            if let Some(material) = materials.get_mut(material) {
+                material.emissive = emissive;
+            }
+        }
+        let intensity = ENGINE_LIGHT_INTENSITY * throttle;
+        for &child in children.into_iter().flatten() {
+            if let Ok(mut light) = light_query.get_mut(child) {
+                if light.intensity != intensity {
+                    light.intensity = intensity;
+                }
+            }
+        }
+    }
+}
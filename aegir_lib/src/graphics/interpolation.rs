@@ -0,0 +1,94 @@
+//! Smooths the motion of bodies that only move on fixed simulation ticks.
+//!
+//! The simulation advances in [`FixedUpdate`], which may run zero or several times in a frame.
+//! Drawing bodies exactly where the simulation left them makes motion judder whenever the
+//! frame rate and tick rate disagree, so between [`Update`] and transform propagation each body
+//! is instead drawn part of the way between its previous and current simulated positions.
+//! Its simulated [`Transform`] is restored at the start of the next frame.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::simulation::flight::{FlightSet, Velocity};
+
+/// Transform interpolation logic
+pub(super) struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, restore_simulated_transforms)
+            .add_systems(FixedUpdate, record_previous_transforms.before(FlightSet))
+            .add_systems(
+                PostUpdate,
+                (track_new_bodies, interpolate_transforms)
+                    .chain()
+                    .in_set(InterpolationSet)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Systems that move bodies from their simulated transforms to their interpolated ones.
+///
+/// Anything that should follow the smoothed motion of a body, like a camera, should run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterpolationSet;
+
+/// The simulated transforms that a body's drawn [`Transform`] is interpolated between.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct SimulatedTransform {
+    /// Where the body was at the start of the latest tick.
+    previous: Transform,
+    /// Where the body is according to the simulation.
+    current: Transform,
+}
+
+/// Starts interpolating every body that has just begun moving.
+///
+/// Pooled bodies begin moving again each time they are reused, which also stops them being drawn
+/// sliding over from wherever they were last used.
+fn track_new_bodies(mut commands: Commands, query: Query<(Entity, &Transform), Added<Velocity>>) {
+    for (entity, &transform) in query.iter() {
+        commands.entity(entity).insert(SimulatedTransform {
+            previous: transform,
+            current: transform,
+        });
+    }
+}
+
+/// Puts bodies back where the simulation left them, undoing last frame's interpolation.
+fn restore_simulated_transforms(mut query: Query<(&mut Transform, &SimulatedTransform)>) {
+    for (mut transform, simulated) in query.iter_mut() {
+        *transform = simulated.current;
+    }
+}
+
+/// Remembers where bodies were before each tick moves them.
+fn record_previous_transforms(mut query: Query<(&Transform, &mut SimulatedTransform)>) {
+    for (&transform, mut simulated) in query.iter_mut() {
+        simulated.previous = transform;
+    }
+}
+
+/// Draws bodies between their previous and current simulated transforms,
+/// according to how far the clock has run into the next tick.
+fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(&mut Transform, &mut SimulatedTransform)>,
+) {
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).min(1.);
+
+    for (mut transform, mut simulated) in query.iter_mut() {
+        // Capture anything that moved the body outside of the simulation this frame
+        simulated.current = *transform;
+
+        transform.translation = simulated
+            .previous
+            .translation
+            .lerp(simulated.current.translation, alpha);
+        transform.rotation = simulated
+            .previous
+            .rotation
+            .slerp(simulated.current.rotation, alpha);
+    }
+}
@@ -1,7 +1,12 @@
-//! Lights and lighting.
+//! Lights and lighting, and the sky behind them, set by each sector as it loads.
 // Modified from Leafwing *Emergence*
 
+use bevy::core_pipeline::Skybox;
 use bevy::prelude::*;
+use bevy::render::render_resource::{TextureViewDescriptor, TextureViewDimension};
+
+use crate::player::camera::ChaseCamera;
+use crate::simulation::sector::{CurrentSector, SectorDefinition, SectorLoaded, Sky};
 
 /// Handles all lighting logic
 pub(super) struct LightingPlugin;
@@ -12,14 +17,132 @@ impl Plugin for LightingPlugin {
             brightness: 1.,
             color: Color::WHITE,
         })
-        .add_systems(Startup, spawn_sun);
+        .init_resource::<SectorSky>()
+        .add_systems(Startup, spawn_sun)
+        .add_systems(Update, (light_sectors, apply_sky).chain());
     }
 }
 
+/// Marks the directional light that stands in for the sector's sun.
+#[derive(Component, Debug)]
+struct Sun;
+
+/// The cubemap the current sector's sky is drawn with, if it has one.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+struct SectorSky {
+    /// The cubemap, which may still be loading.
+    cubemap: Option<Handle<Image>>,
+    /// Has the cubemap been reinterpreted and given to the cameras?
+    applied: bool,
+}
+
 /// Spawns a directional light source to illuminate the scene
 fn spawn_sun(mut commands: Commands) {
-    commands.spawn(DirectionalLightBundle {
-        transform: Transform::from_xyz(30., 100., 30.).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+    commands.spawn((
+        DirectionalLightBundle {
+            transform: Transform::from_xyz(30., 100., 30.).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        Sun,
+    ));
+}
+
+/// Relights the scene and changes the sky to match each sector as it loads, and again whenever the
+/// current sector's definition changes.
+#[allow(clippy::too_many_arguments)]
+fn light_sectors(
+    mut loaded: EventReader<SectorLoaded>,
+    mut modified: EventReader<AssetEvent<SectorDefinition>>,
+    current: Res<CurrentSector>,
+    asset_server: Res<AssetServer>,
+    definitions: Res<Assets<SectorDefinition>>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sky: ResMut<SectorSky>,
+    mut sun_query: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+) {
+    let changed = modified
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } if current.sector() == Some(handle) => {
+                Some(handle.clone())
+            }
+            _ => None,
+        })
+        .last();
+    let Some(definition) = loaded
+        .iter()
+        .last()
+        .map(|event| event.sector.clone())
+        .or(changed)
+        .and_then(|sector| definitions.get(&sector))
+    else {
+        return;
+    };
+    let lighting = &definition.lighting;
+
+    *ambient_light = AmbientLight {
+        color: Color::rgb_from_array(lighting.ambient_color),
+        brightness: lighting.ambient_brightness,
+    };
+    for (mut transform, mut light) in sun_query.iter_mut() {
+        let direction = Vec3::from(lighting.sun_direction)
+            .try_normalize()
+            .unwrap_or(Vec3::NEG_Y);
+        *transform = Transform::IDENTITY.looking_to(direction, Vec3::Y);
+        light.color = Color::rgb_from_array(lighting.sun_color);
+        light.illuminance = lighting.sun_illuminance;
+    }
+
+    *sky = match &definition.sky {
+        Sky::Color(color) => {
+            clear_color.0 = Color::rgb_from_array(*color);
+            SectorSky::default()
+        }
+        Sky::Cubemap(path) => SectorSky {
+            cubemap: Some(asset_server.load(path)),
+            applied: false,
+        },
+    };
+}
+
+/// Gives the main camera the sector's cubemap once it has loaded, or takes it away if the sector
+/// has a flat sky.
+fn apply_sky(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut sky: ResMut<SectorSky>,
+    camera_query: Query<(Entity, Option<&Skybox>), With<ChaseCamera>>,
+) {
+    let Some(cubemap) = sky.cubemap.clone() else {
+        for (camera, skybox) in camera_query.iter() {
+            if skybox.is_some() {
+                commands.entity(camera).remove::<Skybox>();
+            }
+        }
+        return;
+    };
+
+    if !sky.applied {
+        let Some(image) = images.get_mut(&cubemap) else {
+            return;
+        };
+        // The faces are stacked vertically in a single image, so split them into the layers of a
+        // cube texture
+        if image.texture_descriptor.array_layer_count() == 1 {
+            image.reinterpret_stacked_2d_as_array(image.height() / image.width());
+            image.texture_view_descriptor = Some(TextureViewDescriptor {
+                dimension: Some(TextureViewDimension::Cube),
+                ..default()
+            });
+        }
+        sky.applied = true;
+    }
+
+    // Cameras can be spawned after the sky is applied, so keep checking for ones without it
+    for (camera, skybox) in camera_query.iter() {
+        if skybox.map_or(true, |skybox| skybox.0 != cubemap) {
+            commands.entity(camera).insert(Skybox(cubemap.clone()));
+        }
+    }
 }
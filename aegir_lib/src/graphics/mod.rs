@@ -1,9 +1,35 @@
 //! Logic for starting the graphics pipeline
-use bevy::prelude::{App, Plugin};
+use bevy::prelude::{App, Plugin, SystemSet};
 
+use self::asteroids::AsteroidGraphicsPlugin;
+use self::cockpit::CockpitGraphicsPlugin;
+use self::damage::DamageGraphicsPlugin;
+use self::dynamic_lights::DynamicLightingPlugin;
+use self::fittings::FittingsPlugin;
+use self::interpolation::InterpolationPlugin;
 use self::lighting::LightingPlugin;
+use self::planets::PlanetGraphicsPlugin;
+use self::post::PostProcessingPlugin;
+use self::ships::ShipGraphicsPlugin;
+use self::stations::StationGraphicsPlugin;
+use self::tractor::TractorGraphicsPlugin;
+use self::warp::WarpGraphicsPlugin;
+use self::weapons::WeaponGraphicsPlugin;
 
+mod asteroids;
+mod cockpit;
+mod damage;
+mod dynamic_lights;
+pub mod fittings;
+pub mod interpolation;
 mod lighting;
+mod planets;
+pub mod post;
+mod ships;
+mod stations;
+mod tractor;
+mod warp;
+mod weapons;
 
 /// Adds game logic for rendering the game world.
 ///
@@ -12,8 +38,29 @@ pub struct GraphicsPlugin;
 
 impl Plugin for GraphicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(LightingPlugin);
+        app.add_plugins((
+            AsteroidGraphicsPlugin,
+            CockpitGraphicsPlugin,
+            DamageGraphicsPlugin,
+            DynamicLightingPlugin,
+            FittingsPlugin,
+            InterpolationPlugin,
+            LightingPlugin,
+            PlanetGraphicsPlugin,
+            PostProcessingPlugin,
+            ShipGraphicsPlugin,
+            StationGraphicsPlugin,
+            TractorGraphicsPlugin,
+            WarpGraphicsPlugin,
+            WeaponGraphicsPlugin,
+        ));
     }
 }
 
+/// Systems that draw short-lived effects: damage particles, shots and beams, and flashes of light.
+///
+/// These run in `Update`, and are timed as a whole by the benchmark.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VfxSet;
+
 // fn render_terrain
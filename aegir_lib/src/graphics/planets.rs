@@ -0,0 +1,38 @@
+//! Meshes and materials for planets.
+
+use bevy::prelude::*;
+
+use crate::simulation::flight::GravityWell;
+use crate::simulation::planets::Planet;
+
+/// Planet rendering logic
+pub(super) struct PlanetGraphicsPlugin;
+
+impl Plugin for PlanetGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, dress_planets);
+    }
+}
+
+/// Gives planets a sphere as large as their surface once they have spawned.
+fn dress_planets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &GravityWell), Added<Planet>>,
+) {
+    for (entity, well) in query.iter() {
+        let mesh = meshes.add(Mesh::from(shape::UVSphere {
+            radius: well.surface_radius,
+            sectors: 64,
+            stacks: 32,
+        }));
+        let material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.45, 0.35, 0.3),
+            perceptual_roughness: 0.9,
+            ..default()
+        });
+
+        commands.entity(entity).insert((mesh, material));
+    }
+}
@@ -0,0 +1,255 @@
+//! The post-processing applied to the 3D cameras: HDR bloom, so that engines, shots and stars
+//! glow, exposure and tonemapping, and streaks of passing dust that grow with the ship's speed.
+
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::prelude::*;
+use bevy::render::view::ColorGrading;
+
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+
+/// The tonemapping operators the settings cycle through, with the names shown for them.
+pub const TONEMAPPERS: [(Tonemapping, &str); 5] = [
+    (Tonemapping::TonyMcMapface, "Tony McMapface"),
+    (Tonemapping::AcesFitted, "ACES"),
+    (Tonemapping::AgX, "AgX"),
+    (Tonemapping::BlenderFilmic, "Filmic"),
+    (Tonemapping::Reinhard, "Reinhard"),
+];
+
+/// The exposures the settings cycle through, in stops.
+pub const EXPOSURES: [f32; 7] = [-1.5, -1., -0.5, 0., 0.5, 1., 1.5];
+
+/// The asteroid detail levels the settings cycle through, with the names shown for them.
+pub const ASTEROID_DETAIL_LEVELS: [(AsteroidDetail, &str); 4] = [
+    (
+        AsteroidDetail {
+            full_detail_distance: 200.,
+            batch_distance: 1000.,
+            max_full_detail: 50,
+        },
+        "Low",
+    ),
+    (
+        AsteroidDetail {
+            full_detail_distance: 400.,
+            batch_distance: 2000.,
+            max_full_detail: 150,
+        },
+        "Medium",
+    ),
+    (
+        AsteroidDetail {
+            full_detail_distance: 800.,
+            batch_distance: 4000.,
+            max_full_detail: 300,
+        },
+        "High",
+    ),
+    (
+        AsteroidDetail {
+            full_detail_distance: 1600.,
+            batch_distance: 8000.,
+            max_full_detail: 600,
+        },
+        "Ultra",
+    ),
+];
+
+/// The budgets for dynamic lights the settings cycle through.
+pub const DYNAMIC_LIGHT_BUDGETS: [usize; 4] = [0, 8, 16, 32];
+
+/// How strongly bright things bloom.
+const BLOOM_INTENSITY: f32 = 0.2;
+
+/// How many dust motes streak past the camera.
+const STREAK_COUNT: usize = 150;
+
+/// The width, height and depth of the box of dust kept around the camera, in meters.
+const DUST_FIELD_SIZE: f32 = 120.;
+
+/// How fast the ship must be moving for dust to start streaking past, in meters per second.
+const STREAK_MIN_SPEED: f32 = 40.;
+
+/// The speed at which the streaks are at their strongest, in meters per second.
+const STREAK_FULL_SPEED: f32 = 160.;
+
+/// How long each streak is at full strength, in meters.
+const STREAK_LENGTH: f32 = 6.;
+
+/// The color of the streaks at full strength.
+const STREAK_COLOR: Color = Color::rgba(0.8, 0.85, 1., 0.5);
+
+/// Post-processing logic
+pub(super) struct PostProcessingPlugin;
+
+impl Plugin for PostProcessingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphicsSettings>()
+            .add_systems(Update, (apply_graphics_settings, draw_motion_streaks));
+    }
+}
+
+/// How the world is drawn, for players to suit their taste and their display.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GraphicsSettings {
+    /// Do bright things, such as engines and shots, glow?
+    pub bloom: bool,
+    /// How much brighter or darker the scene is drawn, in stops.
+    pub exposure: f32,
+    /// How the scene's high dynamic range is mapped onto the display.
+    pub tonemapping: Tonemapping,
+    /// Does dust streak past the camera at speed?
+    pub motion_streaks: bool,
+    /// How far away asteroids are drawn in full, and when they are batched together.
+    pub asteroid_detail: AsteroidDetail,
+    /// How many flashes and engine lights may shine at once; the dimmest are left out.
+    pub max_dynamic_lights: usize,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            bloom: true,
+            exposure: 0.,
+            tonemapping: Tonemapping::TonyMcMapface,
+            motion_streaks: true,
+            asteroid_detail: ASTEROID_DETAIL_LEVELS[1].0,
+            max_dynamic_lights: DYNAMIC_LIGHT_BUDGETS[2],
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// The name of the chosen tonemapping operator.
+    pub fn tonemapping_name(&self) -> &'static str {
+        TONEMAPPERS
+            .iter()
+            .find(|(tonemapping, _)| *tonemapping == self.tonemapping)
+            .map_or("Custom", |(_, name)| name)
+    }
+
+    /// The name of the chosen asteroid detail level.
+    pub fn asteroid_detail_name(&self) -> &'static str {
+        ASTEROID_DETAIL_LEVELS
+            .iter()
+            .find(|(detail, _)| *detail == self.asteroid_detail)
+            .map_or("Custom", |(_, name)| name)
+    }
+}
+
+/// How asteroids are drawn at a distance.
+///
+/// The nearest asteroids are drawn in full, those further away with fewer polygons, and those
+/// furthest away are merged into a few batches rather than drawn one by one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AsteroidDetail {
+    /// Asteroids closer to the camera than this are drawn in full, in meters.
+    pub full_detail_distance: f32,
+    /// Asteroids further from the camera than this are batched together, in meters.
+    pub batch_distance: f32,
+    /// The most asteroids drawn in full at once, nearest first.
+    pub max_full_detail: usize,
+}
+
+/// Draws every 3D camera in high dynamic range, with the chosen exposure and tonemapping, and
+/// blooms the main camera's view if bloom is on.
+///
+/// The cockpit camera draws over the main camera, so it is kept in the same range to share its
+/// image, but is not bloomed a second time.
+fn apply_graphics_settings(
+    mut commands: Commands,
+    settings: Res<GraphicsSettings>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Camera,
+            &mut Tonemapping,
+            &mut ColorGrading,
+            Option<&ChaseCamera>,
+            Option<&BloomSettings>,
+        ),
+        With<Camera3d>,
+    >,
+    added: Query<(), Added<Camera3d>>,
+) {
+    if !settings.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for (entity, mut camera, mut tonemapping, mut color_grading, chase, bloom_settings) in
+        query.iter_mut()
+    {
+        if !camera.hdr {
+            camera.hdr = true;
+        }
+        *tonemapping = settings.tonemapping;
+        color_grading.exposure = settings.exposure;
+
+        let bloom = settings.bloom && chase.is_some();
+        let bloomed = bloom_settings.is_some();
+        if bloom && !bloomed {
+            commands.entity(entity).insert(BloomSettings {
+                intensity: BLOOM_INTENSITY,
+                ..BloomSettings::NATURAL
+            });
+        } else if !bloom && bloomed {
+            commands.entity(entity).remove::<BloomSettings>();
+        }
+    }
+}
+
+/// Draws dust streaking past the camera while the player's ship is moving fast, growing longer and
+/// brighter with its speed.
+///
+/// The dust sits still in the world, in a box that wraps around the camera, so it rushes past in
+/// the opposite direction to the ship's motion.
+fn draw_motion_streaks(
+    mut gizmos: Gizmos,
+    settings: Res<GraphicsSettings>,
+    player_query: Query<&Velocity, With<PlayerShip>>,
+    camera_query: Query<&GlobalTransform, With<ChaseCamera>>,
+) {
+    if !settings.motion_streaks {
+        return;
+    }
+    let (Ok(velocity), Ok(camera_transform)) =
+        (player_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+    let speed = velocity.0.length();
+    let strength =
+        ((speed - STREAK_MIN_SPEED) / (STREAK_FULL_SPEED - STREAK_MIN_SPEED)).clamp(0., 1.);
+    if strength <= 0. {
+        return;
+    }
+
+    let camera = camera_transform.translation();
+    let trail = velocity.0 / speed * STREAK_LENGTH * strength;
+    let half_size = DUST_FIELD_SIZE / 2.;
+    for mote in 0..STREAK_COUNT {
+        // Scatter the motes with a low-discrepancy sequence, so that they fill the box evenly
+        // without needing to be stored
+        let mote = mote as f32;
+        let seed = Vec3::new(
+            (mote * 0.819_172_5).fract(),
+            (mote * 0.671_043_6).fract(),
+            (mote * 0.549_700_5).fract(),
+        ) * DUST_FIELD_SIZE;
+        let wrapped = seed - camera;
+        let offset = Vec3::new(
+            wrapped.x.rem_euclid(DUST_FIELD_SIZE),
+            wrapped.y.rem_euclid(DUST_FIELD_SIZE),
+            wrapped.z.rem_euclid(DUST_FIELD_SIZE),
+        ) - Vec3::splat(half_size);
+
+        // Fade motes out towards the edge of the box, so they do not pop in and out of view
+        let fade = (1. - offset.length() / half_size).max(0.);
+        let color = STREAK_COLOR.with_a(STREAK_COLOR.a() * strength * fade);
+        let position = camera + offset;
+        gizmos.line(position, position + trail, color);
+    }
+}
@@ -0,0 +1,172 @@
+//! Meshes and materials for ships.
+
+use bevy::prelude::*;
+
+use crate::net::replication::RemoteShip;
+use crate::player::ship::PlayerShip;
+use crate::simulation::ai::AiPilot;
+use crate::simulation::missions::TargetDrone;
+use crate::simulation::ships::{ShipClass, ShipDefinition};
+
+use super::fittings::UnfittedShipScene;
+
+/// Ship rendering logic
+pub(super) struct ShipGraphicsPlugin;
+
+impl Plugin for ShipGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShipAssets>().add_systems(
+            Update,
+            (
+                dress_player_ship,
+                dress_remote_ships,
+                dress_ai_ships,
+                dress_target_drones,
+            ),
+        );
+    }
+}
+
+/// Handles to the meshes and materials shared by every ship.
+#[derive(Resource, Debug)]
+struct ShipAssets {
+    /// The hull shared by every ship.
+    hull: Handle<Mesh>,
+    /// The material of the local player's ship.
+    player_material: Handle<StandardMaterial>,
+    /// The materials of remote players' ships, chosen by their peer id.
+    peer_materials: Vec<Handle<StandardMaterial>>,
+    /// The material of computer-controlled ships.
+    ai_material: Handle<StandardMaterial>,
+    /// The body of mission target drones.
+    drone: Handle<Mesh>,
+    /// The material of mission target drones.
+    drone_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ShipAssets {
+    fn from_world(world: &mut World) -> Self {
+        let hull = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Box::new(1.5, 0.5, 3.)));
+        let drone = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::UVSphere {
+                radius: 2.,
+                sectors: 8,
+                stacks: 6,
+            }));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let player_material = materials.add(Color::GRAY.into());
+        let peer_materials = [
+            Color::rgb(0.9, 0.3, 0.3),
+            Color::rgb(0.3, 0.9, 0.3),
+            Color::rgb(0.3, 0.3, 0.9),
+        ]
+        .into_iter()
+        .map(|color| materials.add(color.into()))
+        .collect();
+        let ai_material = materials.add(Color::rgb(0.45, 0.3, 0.3).into());
+        let drone_material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.8, 0.25, 0.2),
+            emissive: Color::rgb(0.3, 0.05, 0.02),
+            ..default()
+        });
+
+        ShipAssets {
+            hull,
+            player_material,
+            peer_materials,
+            ai_material,
+            drone,
+            drone_material,
+        }
+    }
+}
+
+/// Gives the player's ship its class's scene once it has spawned, or the plain hull if it has none.
+///
+/// Paths without a label load the file's first scene, so `models/kestrel.glb` is read as
+/// `models/kestrel.glb#Scene0`.
+fn dress_player_ship(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ship_assets: Res<ShipAssets>,
+    definitions: Res<Assets<ShipDefinition>>,
+    query: Query<(Entity, Option<&ShipClass>), Added<PlayerShip>>,
+) {
+    for (entity, class) in query.iter() {
+        let scene = class
+            .and_then(|class| definitions.get(&class.0))
+            .and_then(|definition| definition.mesh.as_deref());
+
+        match scene {
+            Some(path) => {
+                let path = if path.contains('#') {
+                    path.to_string()
+                } else {
+                    format!("{path}#Scene0")
+                };
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        SceneBundle {
+                            scene: asset_server.load(path),
+                            ..default()
+                        },
+                        UnfittedShipScene,
+                    ));
+                });
+            }
+            None => {
+                commands.entity(entity).insert((
+                    ship_assets.hull.clone(),
+                    ship_assets.player_material.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Gives remote players' ships their meshes once they have spawned, colored by player.
+fn dress_remote_ships(
+    mut commands: Commands,
+    ship_assets: Res<ShipAssets>,
+    query: Query<(Entity, &RemoteShip), Added<RemoteShip>>,
+) {
+    for (entity, remote_ship) in query.iter() {
+        let index = usize::from(remote_ship.peer.0) % ship_assets.peer_materials.len();
+
+        commands.entity(entity).insert((
+            ship_assets.hull.clone(),
+            ship_assets.peer_materials[index].clone(),
+        ));
+    }
+}
+
+/// Gives computer-controlled ships their meshes once they have spawned.
+fn dress_ai_ships(
+    mut commands: Commands,
+    ship_assets: Res<ShipAssets>,
+    query: Query<Entity, Added<AiPilot>>,
+) {
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .insert((ship_assets.hull.clone(), ship_assets.ai_material.clone()));
+    }
+}
+
+/// Gives mission target drones their meshes once they have spawned.
+fn dress_target_drones(
+    mut commands: Commands,
+    ship_assets: Res<ShipAssets>,
+    query: Query<Entity, Added<TargetDrone>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert((
+            ship_assets.drone.clone(),
+            ship_assets.drone_material.clone(),
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! Meshes and materials for stations.
+
+use bevy::prelude::*;
+
+use crate::simulation::stations::Station;
+
+/// Station rendering logic
+pub(super) struct StationGraphicsPlugin;
+
+impl Plugin for StationGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, dress_stations);
+    }
+}
+
+/// Gives stations a hub and a ring once they have spawned.
+fn dress_stations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<Entity, Added<Station>>,
+) {
+    for entity in query.iter() {
+        let hub = meshes.add(Mesh::from(shape::UVSphere {
+            radius: 20.,
+            ..default()
+        }));
+        let ring = meshes.add(Mesh::from(shape::Torus {
+            radius: 36.,
+            ring_radius: 4.,
+            ..default()
+        }));
+        let material = materials.add(StandardMaterial {
+            base_color: Color::rgb(0.6, 0.62, 0.7),
+            metallic: 0.6,
+            perceptual_roughness: 0.5,
+            ..default()
+        });
+
+        commands.entity(entity).insert((hub, material.clone()));
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: ring,
+                material,
+                ..default()
+            });
+        });
+    }
+}
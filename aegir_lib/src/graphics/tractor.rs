@@ -0,0 +1,32 @@
+//! The beam drawn between a tractor beam and the object it is holding.
+
+use bevy::prelude::*;
+
+use crate::simulation::tractor::TractorBeam;
+
+/// The color of tractor beams.
+const BEAM_COLOR: Color = Color::rgb(0.4, 0.7, 1.);
+
+/// Tractor beam rendering logic
+pub(super) struct TractorGraphicsPlugin;
+
+impl Plugin for TractorGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_tractor_beams);
+    }
+}
+
+/// Draws a line from each ship to the object its tractor beam is holding.
+fn draw_tractor_beams(
+    mut gizmos: Gizmos,
+    beams: Query<(&GlobalTransform, &TractorBeam)>,
+    objects: Query<&GlobalTransform>,
+) {
+    for (transform, beam) in beams.iter() {
+        let Some(held) = beam.held().and_then(|held| objects.get(held).ok()) else {
+            continue;
+        };
+
+        gizmos.line(transform.translation(), held.translation(), BEAM_COLOR);
+    }
+}
@@ -0,0 +1,176 @@
+//! The warp tunnel seen while the player's ship charges its jump drive and jumps: streaks of light
+//! rushing past the camera, which widens its field of view and shakes as the ship enters and
+//! leaves the tunnel.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::player::camera::{CameraShake, ChaseCamera};
+use crate::player::ship::PlayerShip;
+use crate::simulation::jump_drive::{JumpCompleted, JumpDrive, JumpStarted, JumpState};
+
+/// How many streaks make up the tunnel.
+const STREAK_COUNT: usize = 120;
+
+/// How long the tunnel is, in meters; streaks wrap around once they pass the camera.
+const TUNNEL_LENGTH: f32 = 200.;
+
+/// The radius of the tunnel, in meters.
+const TUNNEL_RADIUS: f32 = 12.;
+
+/// How fast streaks rush past at full intensity, in meters per second.
+const STREAK_SPEED: f32 = 400.;
+
+/// How long each streak is at full intensity, in meters.
+const STREAK_LENGTH: f32 = 30.;
+
+/// How far the field of view widens at full intensity, as a fraction of its usual width.
+const FOV_WIDENING: f32 = 0.4;
+
+/// How quickly the effect fades in and out, in intensity per second.
+const FADE_SPEED: f32 = 1.5;
+
+/// How long before the drive finishes charging the tunnel begins to form, in seconds.
+const FORMING_TIME: f32 = 2.;
+
+/// The color of the streaks.
+const STREAK_COLOR: Color = Color::rgb(0.6, 0.8, 1.);
+
+/// Warp tunnel rendering logic
+pub(super) struct WarpGraphicsPlugin;
+
+impl Plugin for WarpGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WarpTunnel>().add_systems(
+            Update,
+            (
+                fade_warp_tunnel,
+                shake_on_jump,
+                widen_field_of_view,
+                draw_warp_tunnel,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// How strongly the warp tunnel is shown.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+struct WarpTunnel {
+    /// How strongly the tunnel is shown, between `0.0` and `1.0`.
+    intensity: f32,
+    /// How far the streaks have travelled, in meters.
+    travelled: f32,
+    /// The camera's field of view before the tunnel widened it, while it is widened.
+    base_fov: Option<f32>,
+}
+
+/// Fades the tunnel in as the player's drive finishes charging and while they jump, and out
+/// again once they arrive.
+fn fade_warp_tunnel(
+    time: Res<Time>,
+    mut tunnel: ResMut<WarpTunnel>,
+    query: Query<&JumpDrive, With<PlayerShip>>,
+) {
+    let target = query.get_single().map_or(0., |drive| match drive.state() {
+        JumpState::Jumping { .. } => 1.,
+        JumpState::Charging { elapsed, .. } => {
+            let remaining = drive.charge_time - elapsed;
+            // Only hint at the tunnel while charging, so that the jump itself is the big moment
+            0.3 * (1. - remaining / FORMING_TIME).clamp(0., 1.)
+        }
+        JumpState::Idle => 0.,
+    });
+
+    let delta_time = time.delta_seconds();
+    let step = FADE_SPEED * delta_time;
+    tunnel.intensity += (target - tunnel.intensity).clamp(-step, step);
+    tunnel.travelled =
+        (tunnel.travelled + STREAK_SPEED * tunnel.intensity * delta_time) % TUNNEL_LENGTH;
+}
+
+/// Jolts the camera as the player's ship enters and leaves the tunnel.
+fn shake_on_jump(
+    mut started: EventReader<JumpStarted>,
+    mut completed: EventReader<JumpCompleted>,
+    ship_query: Query<Entity, With<PlayerShip>>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    let Ok(player) = ship_query.get_single() else {
+        started.clear();
+        completed.clear();
+        return;
+    };
+
+    let jolts = started.iter().filter(|event| event.ship == player).count()
+        + completed
+            .iter()
+            .filter(|event| event.ship == player)
+            .count();
+    if jolts == 0 {
+        return;
+    }
+    for mut shake in camera_query.iter_mut() {
+        shake.add_trauma(0.5);
+    }
+}
+
+/// Widens the camera's field of view while in the tunnel, restoring it afterwards.
+fn widen_field_of_view(
+    mut tunnel: ResMut<WarpTunnel>,
+    mut query: Query<&mut Projection, With<Camera3d>>,
+) {
+    for mut projection in query.iter_mut() {
+        let Projection::Perspective(perspective) = &mut *projection else {
+            continue;
+        };
+
+        if tunnel.intensity <= 0. {
+            if let Some(base_fov) = tunnel.base_fov.take() {
+                perspective.fov = base_fov;
+            }
+            continue;
+        }
+        let base_fov = *tunnel.base_fov.get_or_insert(perspective.fov);
+        perspective.fov = base_fov * (1. + FOV_WIDENING * tunnel.intensity);
+    }
+}
+
+/// Draws streaks of light rushing past the camera, down the length of the tunnel.
+fn draw_warp_tunnel(
+    mut gizmos: Gizmos,
+    tunnel: Res<WarpTunnel>,
+    camera_query: Query<&GlobalTransform, With<ChaseCamera>>,
+) {
+    if tunnel.intensity <= 0. {
+        return;
+    }
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    let color = STREAK_COLOR.with_a(tunnel.intensity);
+    let length = STREAK_LENGTH * tunnel.intensity;
+    for streak in 0..STREAK_COUNT {
+        // Spread the streaks evenly around the tunnel and stagger them along it, so that the
+        // pattern looks random without needing to be stored
+        let fraction = streak as f32 / STREAK_COUNT as f32;
+        let angle = fraction * TAU * 7.;
+        let radius = TUNNEL_RADIUS * (0.6 + 0.4 * (fraction * 13.).fract());
+        let depth = ((fraction * 31.).fract() * TUNNEL_LENGTH + tunnel.travelled) % TUNNEL_LENGTH;
+
+        // Streaks start far ahead of the camera and rush towards it
+        let start = Vec3::new(
+            radius * angle.cos(),
+            radius * angle.sin(),
+            depth - TUNNEL_LENGTH,
+        );
+        let end = start + Vec3::Z * length;
+        gizmos.line(
+            camera_transform.transform_point(start),
+            camera_transform.transform_point(end),
+            color,
+        );
+    }
+}
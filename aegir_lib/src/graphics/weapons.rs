@@ -0,0 +1,350 @@
+//! Meshes and materials for projectiles, beams, countermeasure decoys and deployables.
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::utils::HashMap;
+
+use crate::simulation::countermeasures::{CountermeasureKind, Decoy};
+use crate::simulation::deployables::{DeployableKind, Mine, Turret};
+use crate::simulation::weapons::{MountedWeapon, Projectile, WeaponDefinition};
+
+use super::VfxSet;
+
+/// How much longer than it is wide a projectile is drawn, to suggest its speed.
+const PROJECTILE_STRETCH: f32 = 6.;
+
+/// How many flat sides a beam is drawn with.
+const BEAM_SIDES: u32 = 12;
+
+/// How thick a beam is where it ends, as a fraction of how thick it is at the weapon.
+const BEAM_TAPER: f32 = 0.3;
+
+/// Weapon rendering logic
+pub(super) struct WeaponGraphicsPlugin;
+
+impl Plugin for WeaponGraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectileAssets>().add_systems(
+            Update,
+            (
+                dress_projectiles,
+                draw_beams,
+                dress_decoys,
+                dress_deployables,
+            )
+                .in_set(VfxSet),
+        );
+    }
+}
+
+/// Handles to the meshes shared by every projectile and beam, and a glowing material for each
+/// weapon.
+#[derive(Resource, Debug)]
+struct ProjectileAssets {
+    /// A sphere of radius one, scaled to each projectile's size.
+    mesh: Handle<Mesh>,
+    /// A tapered tube one meter long, scaled to each beam's size.
+    beam_mesh: Handle<Mesh>,
+    /// The material for each weapon's projectiles, created the first time it fires.
+    materials: HashMap<Handle<WeaponDefinition>, Handle<StandardMaterial>>,
+    /// The material for each kind of decoy, created the first time one is dropped.
+    decoy_materials: HashMap<CountermeasureKind, Handle<StandardMaterial>>,
+    /// The material for each kind of deployable, created the first time one is dropped.
+    deployable_materials: HashMap<DeployableKind, Handle<StandardMaterial>>,
+}
+
+impl ProjectileAssets {
+    /// The glowing material for `weapon`'s shots, in `color`, created the first time it is needed.
+    fn material(
+        &mut self,
+        weapon: &Handle<WeaponDefinition>,
+        [red, green, blue]: [f32; 3],
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry(weapon.clone())
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: Color::rgb_linear(red, green, blue),
+                    emissive: Color::rgb_linear(red, green, blue) * 4.,
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+impl FromWorld for ProjectileAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let mesh = meshes.add(
+            shape::UVSphere {
+                radius: 1.,
+                sectors: 8,
+                stacks: 6,
+            }
+            .into(),
+        );
+        let beam_mesh = meshes.add(beam_mesh());
+
+        ProjectileAssets {
+            mesh,
+            beam_mesh,
+            materials: HashMap::default(),
+            decoy_materials: HashMap::default(),
+            deployable_materials: HashMap::default(),
+        }
+    }
+}
+
+/// A tube of radius one at its base, narrowing by [`BEAM_TAPER`] as it runs one meter forwards.
+fn beam_mesh() -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for side in 0..=BEAM_SIDES {
+        let fraction = side as f32 / BEAM_SIDES as f32;
+        let (sin, cos) = (fraction * std::f32::consts::TAU).sin_cos();
+        let normal = Vec3::new(cos, sin, 1. - BEAM_TAPER).normalize();
+
+        positions.push([cos, sin, 0.]);
+        positions.push([cos * BEAM_TAPER, sin * BEAM_TAPER, -1.]);
+        normals.extend([normal.to_array(); 2]);
+        uvs.extend([[fraction, 0.], [fraction, 1.]]);
+    }
+    let indices = (0..BEAM_SIDES)
+        .flat_map(|side| {
+            let base = side * 2;
+            [base, base + 1, base + 2, base + 2, base + 1, base + 3]
+        })
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// The glowing mesh drawn for a projectile, kept when the projectile returns to its pool.
+#[derive(Component, Debug)]
+struct ProjectileModel;
+
+/// Gives projectiles a glowing mesh in their weapon's color once they have been fired.
+///
+/// Projectiles reused from the pool already have a mesh, which is recolored and resized instead.
+fn dress_projectiles(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &Projectile, Option<&Children>), Added<Projectile>>,
+    mut model_query: Query<(&mut Handle<StandardMaterial>, &mut Transform), With<ProjectileModel>>,
+) {
+    for (entity, projectile, children) in query.iter() {
+        let Some(projectile_definition) = definitions
+            .get(&projectile.weapon)
+            .and_then(|definition| definition.projectile.as_ref())
+        else {
+            continue;
+        };
+        let material = projectile_assets.material(
+            &projectile.weapon,
+            projectile_definition.color,
+            &mut materials,
+        );
+
+        // The projectile's own transform belongs to the simulation, so scale a child instead
+        let scale = Vec3::new(1., 1., PROJECTILE_STRETCH) * projectile.radius;
+        let mut models =
+            model_query.iter_many_mut(children.map_or(&[][..], |children| &**children));
+        if let Some((mut model_material, mut model_transform)) = models.fetch_next() {
+            *model_material = material;
+            model_transform.scale = scale;
+            continue;
+        }
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: projectile_assets.mesh.clone(),
+                    material,
+                    transform: Transform::from_scale(scale),
+                    ..default()
+                },
+                ProjectileModel,
+            ));
+        });
+    }
+}
+
+/// The glowing beam drawn from a beam weapon's hardpoint, hidden while the weapon is not firing.
+#[derive(Component, Debug)]
+struct BeamModel;
+
+/// Stretches a tapered, glowing mesh from each firing beam weapon to where its beam ends.
+fn draw_beams(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    hardpoints: Query<(Entity, &GlobalTransform, &MountedWeapon, Option<&Children>)>,
+    mut model_query: Query<(&mut Transform, &mut Visibility), With<BeamModel>>,
+) {
+    for (hardpoint, global_transform, weapon, children) in hardpoints.iter() {
+        let Some(beam) = definitions
+            .get(&weapon.definition)
+            .and_then(|definition| definition.beam)
+        else {
+            continue;
+        };
+
+        // The simulation places the end of the beam in world space, so bring it into the
+        // hardpoint's own space for the model
+        let beam_transform = weapon.beam_end().and_then(|end| {
+            let end = global_transform.affine().inverse().transform_point3(end);
+            Some(Transform {
+                rotation: Quat::from_rotation_arc(Vec3::NEG_Z, end.try_normalize()?),
+                scale: Vec3::new(beam.radius, beam.radius, end.length()),
+                ..default()
+            })
+        });
+
+        let mut models =
+            model_query.iter_many_mut(children.map_or(&[][..], |children| &**children));
+        if let Some((mut model_transform, mut visibility)) = models.fetch_next() {
+            match beam_transform {
+                Some(beam_transform) => {
+                    *model_transform = beam_transform;
+                    if *visibility != Visibility::Inherited {
+                        *visibility = Visibility::Inherited;
+                    }
+                }
+                None if *visibility != Visibility::Hidden => *visibility = Visibility::Hidden,
+                None => {}
+            }
+            continue;
+        }
+
+        let Some(beam_transform) = beam_transform else {
+            continue;
+        };
+        let material = projectile_assets.material(&weapon.definition, beam.color, &mut materials);
+        commands.entity(hardpoint).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: projectile_assets.beam_mesh.clone(),
+                    material,
+                    transform: beam_transform,
+                    ..default()
+                },
+                BeamModel,
+            ));
+        });
+    }
+}
+
+/// Gives decoys a glowing mesh once they have been dropped: bright flares, or a dull cloud of
+/// chaff.
+fn dress_decoys(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &Decoy), Added<Decoy>>,
+) {
+    for (entity, decoy) in query.iter() {
+        let material = projectile_assets
+            .decoy_materials
+            .entry(decoy.kind)
+            .or_insert_with(|| {
+                let (color, glow) = match decoy.kind {
+                    CountermeasureKind::Flares => (Color::rgb_linear(1., 0.6, 0.2), 8.),
+                    CountermeasureKind::Chaff => (Color::rgb_linear(0.6, 0.6, 0.65), 1.),
+                };
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    emissive: color * glow,
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .clone();
+
+        let scale = match decoy.kind {
+            CountermeasureKind::Flares => 0.6,
+            CountermeasureKind::Chaff => 2.5,
+        };
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: projectile_assets.mesh.clone(),
+                material,
+                transform: Transform::from_scale(Vec3::splat(scale)),
+                ..default()
+            });
+        });
+    }
+}
+
+/// Gives deployables a mesh once they have been dropped: a glowing red mine, or a dull turret pod
+/// with a barrel along its line of fire.
+fn dress_deployables(
+    mut commands: Commands,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, Option<&Mine>), Or<(Added<Mine>, Added<Turret>)>>,
+) {
+    for (entity, mine) in query.iter() {
+        let kind = if mine.is_some() {
+            DeployableKind::Mines
+        } else {
+            DeployableKind::Turrets
+        };
+        let material = projectile_assets
+            .deployable_materials
+            .entry(kind)
+            .or_insert_with(|| {
+                materials.add(match kind {
+                    DeployableKind::Mines => StandardMaterial {
+                        base_color: Color::rgb_linear(0.3, 0.05, 0.05),
+                        emissive: Color::rgb_linear(1., 0.1, 0.05) * 2.,
+                        ..default()
+                    },
+                    DeployableKind::Turrets => StandardMaterial {
+                        base_color: Color::rgb(0.45, 0.47, 0.5),
+                        metallic: 0.8,
+                        perceptual_roughness: 0.4,
+                        ..default()
+                    },
+                })
+            })
+            .clone();
+
+        let mesh = projectile_assets.mesh.clone();
+        commands.entity(entity).with_children(|parent| match kind {
+            DeployableKind::Mines => {
+                parent.spawn(PbrBundle {
+                    mesh,
+                    material,
+                    transform: Transform::from_scale(Vec3::splat(1.5)),
+                    ..default()
+                });
+            }
+            DeployableKind::Turrets => {
+                parent.spawn(PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_scale(Vec3::splat(2.)),
+                    ..default()
+                });
+                parent.spawn(PbrBundle {
+                    mesh,
+                    material,
+                    transform: Transform::from_xyz(0., 0., -2.).with_scale(Vec3::new(0.4, 0.4, 2.)),
+                    ..default()
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,95 @@
+//! Captions for warning sounds, for players who cannot hear them.
+
+use bevy::prelude::*;
+
+use crate::accessibility::{AccessibilitySettings, AudioCaption, HudTheme};
+use crate::game_state::{GameState, InGame};
+
+/// How long each caption stays on screen after its sound was last heard, in seconds.
+const CAPTION_LIFETIME: f32 = 2.;
+
+/// Caption HUD logic
+pub(super) struct CaptionsHudPlugin;
+
+impl Plugin for CaptionsHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_captions)
+            .add_systems(Update, update_captions);
+    }
+}
+
+/// Marks the text listing the captions of recent warning sounds.
+#[derive(Component, Debug)]
+struct CaptionText;
+
+/// Spawns the captions centered low on the screen.
+fn spawn_captions(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Percent(18.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.,
+                        ..default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                CaptionText,
+            ));
+        });
+}
+
+/// Shows the caption of each warning sound heard recently, one to a line, while captions are on.
+fn update_captions(
+    time: Res<Time>,
+    settings: Res<AccessibilitySettings>,
+    theme: Res<HudTheme>,
+    mut events: EventReader<AudioCaption>,
+    mut recent: Local<Vec<(&'static str, f32)>>,
+    mut text_query: Query<&mut Text, With<CaptionText>>,
+) {
+    let delta_time = time.delta_seconds();
+    recent.retain_mut(|(_, remaining)| {
+        *remaining -= delta_time;
+        *remaining > 0.
+    });
+    for &AudioCaption(caption) in events.iter() {
+        match recent.iter_mut().find(|(text, _)| *text == caption) {
+            Some((_, remaining)) => *remaining = CAPTION_LIFETIME,
+            None => recent.push((caption, CAPTION_LIFETIME)),
+        }
+    }
+
+    let captions = if settings.captions {
+        recent
+            .iter()
+            .map(|(caption, _)| *caption)
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        String::new()
+    };
+    for mut text in text_query.iter_mut() {
+        let section = &mut text.sections[0];
+        if section.value != captions {
+            section.value = captions.clone();
+        }
+        if section.style.color != theme.warning() {
+            section.style.color = theme.warning();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! A readout of the cargo in the player ship's hold, and the credits they have to trade with.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::economy::{Commodity, Trader};
+use crate::simulation::mining::Inventory;
+
+/// Cargo HUD logic
+pub(super) struct CargoHudPlugin;
+
+impl Plugin for CargoHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_cargo_readout)
+            .add_systems(Update, update_cargo_readout);
+    }
+}
+
+/// Marks the text listing the player's cargo.
+#[derive(Component, Debug)]
+struct CargoReadout;
+
+/// Spawns the cargo readout in the bottom-right corner of the screen.
+fn spawn_cargo_readout(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.),
+            bottom: Val::Px(20.),
+            ..default()
+        }),
+        CargoReadout,
+        InGame,
+    ));
+}
+
+/// Lists each commodity carried by the player, how full their hold is and their credits.
+fn update_cargo_readout(
+    inventory_query: Query<
+        (&Inventory, Option<&Trader>),
+        (With<PlayerShip>, Or<(Changed<Inventory>, Changed<Trader>)>),
+    >,
+    mut text_query: Query<&mut Text, With<CargoReadout>>,
+) {
+    let Ok((inventory, trader)) = inventory_query.get_single() else {
+        return;
+    };
+
+    let mut readout = format!("CARGO {:.0}/{:.0}", inventory.total(), inventory.capacity);
+    for commodity in Commodity::ALL {
+        let amount = inventory.amount(commodity);
+        if amount > 0. {
+            readout.push_str(&format!("\n{} {amount:.1}", commodity.name()));
+        }
+    }
+    if let Some(trader) = trader {
+        readout.push_str(&format!("\nCREDITS {:.0}", trader.credits()));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = readout.clone();
+    }
+}
@@ -0,0 +1,137 @@
+//! A flashing warning while a hostile missile is tracking the player, and a count of the
+//! countermeasures they have left to shake it with.
+
+use bevy::prelude::*;
+
+use crate::accessibility::HudTheme;
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::countermeasures::Countermeasures;
+use crate::simulation::weapons::{Projectile, Seeker};
+
+/// How many times the missile warning flashes each second.
+const FLASH_RATE: f32 = 3.;
+
+/// Countermeasure HUD logic
+pub(super) struct CountermeasuresHudPlugin;
+
+impl Plugin for CountermeasuresHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_countermeasures_hud)
+            .add_systems(
+                Update,
+                (flash_missile_warning, update_countermeasure_readout),
+            );
+    }
+}
+
+/// Marks the text warning that a missile is tracking the player.
+#[derive(Component, Debug)]
+struct MissileWarning;
+
+/// Marks the text counting the player's countermeasures.
+#[derive(Component, Debug)]
+struct CountermeasureReadout;
+
+/// Spawns the missile warning above the middle of the screen, and the countermeasure count to the
+/// right of the heat bars.
+fn spawn_countermeasures_hud(mut commands: Commands, theme: Res<HudTheme>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(30.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "MISSILE",
+                    TextStyle {
+                        font_size: 32.,
+                        color: theme.warning(),
+                        ..default()
+                    },
+                ),
+                MissileWarning,
+            ));
+        });
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(340.),
+            bottom: Val::Px(20.),
+            ..default()
+        }),
+        CountermeasureReadout,
+        InGame,
+    ));
+}
+
+/// Flashes the missile warning while a missile fired by someone else is tracking the player.
+fn flash_missile_warning(
+    time: Res<Time>,
+    theme: Res<HudTheme>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    missile_query: Query<(&Seeker, &Projectile)>,
+    mut warning_query: Query<(&mut Visibility, &mut Text), With<MissileWarning>>,
+) {
+    let locked = player_query.get_single().is_ok_and(|player| {
+        missile_query.iter().any(|(seeker, projectile)| {
+            seeker.target == Some(player) && projectile.source != player
+        })
+    });
+    let lit = locked && (time.elapsed_seconds() * FLASH_RATE).fract() < 0.5;
+
+    for (mut visibility, mut text) in warning_query.iter_mut() {
+        if theme.is_changed() {
+            text.sections[0].style.color = theme.warning();
+        }
+        let wanted = if lit {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+/// Shows how many countermeasures the player has left.
+fn update_countermeasure_readout(
+    countermeasures_query: Query<&Countermeasures, (With<PlayerShip>, Changed<Countermeasures>)>,
+    mut text_query: Query<&mut Text, With<CountermeasureReadout>>,
+) {
+    let Ok(countermeasures) = countermeasures_query.get_single() else {
+        return;
+    };
+
+    let readout = format!(
+        "{} {}/{}",
+        countermeasures.kind.name().to_uppercase(),
+        countermeasures.charges,
+        countermeasures.max_charges
+    );
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != readout {
+            text.sections[0].value = readout.clone();
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! A crosshair where the player's weapons converge, and a pip for each gimballed weapon showing
+//! where it is aimed.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::simulation::weapons::{MountedWeapon, WeaponDefinition, WeaponTrigger};
+
+/// The width and height of the crosshair, in pixels.
+const CROSSHAIR_SIZE: f32 = 20.;
+
+/// The width and height of each aim pip, in pixels.
+const PIP_SIZE: f32 = 8.;
+
+/// How far ahead the crosshair is drawn when none of the player's weapons converge, in meters.
+const DEFAULT_AIM_DISTANCE: f32 = 500.;
+
+/// The color of the crosshair and aim pips.
+const CROSSHAIR_COLOR: Color = Color::rgba(0.8, 1., 0.9, 0.8);
+
+/// Crosshair HUD logic
+pub(super) struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_crosshair)
+            .add_systems(
+                Update,
+                (add_aim_pips, update_crosshair, update_aim_pips).chain(),
+            );
+    }
+}
+
+/// Marks the crosshair drawn where the player's weapons converge.
+#[derive(Component, Debug)]
+struct Crosshair;
+
+/// Marks the pip drawn where a gimballed weapon on the player's ship is aimed.
+#[derive(Component, Debug)]
+struct AimPip {
+    /// The hardpoint whose weapon is shown.
+    hardpoint: Entity,
+}
+
+/// Spawns the crosshair, hidden until it has been placed.
+fn spawn_crosshair(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(CROSSHAIR_SIZE),
+                    height: Val::Px(CROSSHAIR_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Crosshair,
+            InGame,
+        ))
+        .with_children(|parent| {
+            for (width, height) in [(CROSSHAIR_SIZE, 2.), (2., CROSSHAIR_SIZE)] {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(width),
+                        height: Val::Px(height),
+                        ..default()
+                    },
+                    background_color: CROSSHAIR_COLOR.into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+/// Adds a hidden aim pip for each weapon mounted on the player's ship.
+fn add_aim_pips(
+    mut commands: Commands,
+    player_query: Query<(), With<PlayerShip>>,
+    hardpoint_query: Query<(Entity, &Parent), Added<MountedWeapon>>,
+) {
+    for (hardpoint, parent) in hardpoint_query.iter() {
+        if !player_query.contains(parent.get()) {
+            continue;
+        }
+
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(PIP_SIZE),
+                    height: Val::Px(PIP_SIZE),
+                    border: UiRect::all(Val::Px(1.)),
+                    ..default()
+                },
+                border_color: CROSSHAIR_COLOR.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            AimPip { hardpoint },
+            InGame,
+        ));
+    }
+}
+
+/// Places the crosshair ahead of the player's ship, at the average range its weapons converge.
+fn update_crosshair(
+    definitions: Res<Assets<WeaponDefinition>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    player_query: Query<(Entity, &GlobalTransform), With<PlayerShip>>,
+    hardpoint_query: Query<(&Parent, &MountedWeapon)>,
+    mut crosshair_query: Query<(&mut Style, &mut Visibility), With<Crosshair>>,
+) {
+    let Ok((mut style, mut visibility)) = crosshair_query.get_single_mut() else {
+        return;
+    };
+    let (Ok((player, transform)), Ok((camera, camera_transform))) =
+        (player_query.get_single(), camera_query.get_single())
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let ranges: Vec<f32> = hardpoint_query
+        .iter()
+        .filter(|(parent, _)| parent.get() == player)
+        .filter_map(|(_, weapon)| definitions.get(&weapon.definition)?.convergence)
+        .collect();
+    let range = if ranges.is_empty() {
+        DEFAULT_AIM_DISTANCE
+    } else {
+        ranges.iter().sum::<f32>() / ranges.len() as f32
+    };
+
+    let point = transform.translation() + transform.forward() * range;
+    let Some(position) = camera.world_to_viewport(camera_transform, point) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+    style.left = Val::Px(position.x - CROSSHAIR_SIZE / 2.);
+    style.top = Val::Px(position.y - CROSSHAIR_SIZE / 2.);
+}
+
+/// Places each gimballed weapon's pip where it is aimed: at the player's target if it is tracking
+/// one, or else out at its convergence range.
+///
+/// Fixed weapons have no pip, as they always fire at the crosshair. Pips whose hardpoint is gone
+/// are removed.
+fn update_aim_pips(
+    mut commands: Commands,
+    definitions: Res<Assets<WeaponDefinition>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    player_query: Query<&WeaponTrigger, With<PlayerShip>>,
+    hardpoint_query: Query<(&Parent, &GlobalTransform, &MountedWeapon)>,
+    mut pip_query: Query<(Entity, &AimPip, &mut Style, &mut Visibility)>,
+) {
+    let camera = camera_query.get_single().ok();
+
+    for (pip, aim_pip, mut style, mut visibility) in pip_query.iter_mut() {
+        let Ok((parent, transform, weapon)) = hardpoint_query.get(aim_pip.hardpoint) else {
+            commands.entity(pip).despawn_recursive();
+            continue;
+        };
+        let definition = definitions
+            .get(&weapon.definition)
+            .filter(|definition| definition.gimbal > 0.);
+        let (Some(definition), Some(direction), Some((camera, camera_transform))) =
+            (definition, weapon.direction(), camera)
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let muzzle = transform.translation();
+        let range = player_query
+            .get(parent.get())
+            .ok()
+            .and_then(|trigger| trigger.aim)
+            .map(|aim| aim.distance(muzzle))
+            .or(definition.convergence)
+            .unwrap_or(DEFAULT_AIM_DISTANCE);
+        let Some(position) = camera.world_to_viewport(camera_transform, muzzle + direction * range)
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        style.left = Val::Px(position.x - PIP_SIZE / 2.);
+        style.top = Val::Px(position.y - PIP_SIZE / 2.);
+    }
+}
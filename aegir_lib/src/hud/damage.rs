@@ -0,0 +1,96 @@
+//! A red vignette around the edges of the screen while the player's hull is critically damaged.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::health::{Health, CRITICAL_HEALTH};
+
+/// The width and height of the vignette texture, in pixels; it is stretched to fill the screen.
+const VIGNETTE_TEXTURE_SIZE: u32 = 128;
+
+/// How many times each second the vignette pulses.
+const PULSE_RATE: f32 = 1.5;
+
+/// Damage HUD logic
+pub(super) struct DamageHudPlugin;
+
+impl Plugin for DamageHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_vignette)
+            .add_systems(Update, pulse_vignette);
+    }
+}
+
+/// Marks the image that darkens the screen edges.
+#[derive(Component, Debug)]
+struct DamageVignette;
+
+/// Spawns the vignette over the whole screen, initially invisible.
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = VIGNETTE_TEXTURE_SIZE as f32;
+    let data = (0..VIGNETTE_TEXTURE_SIZE * VIGNETTE_TEXTURE_SIZE)
+        .flat_map(|index| {
+            let x = (index % VIGNETTE_TEXTURE_SIZE) as f32 / size * 2. - 1.;
+            let y = (index / VIGNETTE_TEXTURE_SIZE) as f32 / size * 2. - 1.;
+            // Clear in the middle, fading in towards the corners
+            let edge = ((x * x + y * y).sqrt() - 0.6).clamp(0., 0.8) / 0.8;
+            [255, 255, 255, (edge * edge * 255.) as u8]
+        })
+        .collect();
+
+    let texture = images.add(Image::new(
+        Extent3d {
+            width: VIGNETTE_TEXTURE_SIZE,
+            height: VIGNETTE_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            image: UiImage::new(texture),
+            background_color: Color::NONE.into(),
+            ..default()
+        },
+        DamageVignette,
+        InGame,
+    ));
+}
+
+/// Fades the vignette in as the player's health falls below [`CRITICAL_HEALTH`], pulsing it.
+fn pulse_vignette(
+    time: Res<Time>,
+    ship_query: Query<&Health, With<PlayerShip>>,
+    mut vignette_query: Query<&mut BackgroundColor, With<DamageVignette>>,
+) {
+    let severity = ship_query.get_single().map_or(0., |health| {
+        if health.is_critical() {
+            1. - health.fraction() / CRITICAL_HEALTH
+        } else {
+            0.
+        }
+    });
+
+    let alpha = if severity > 0. {
+        let pulse =
+            0.75 + 0.25 * (time.elapsed_seconds() * PULSE_RATE * std::f32::consts::TAU).sin();
+        (0.3 + 0.7 * severity) * pulse
+    } else {
+        0.
+    };
+
+    for mut color in vignette_query.iter_mut() {
+        color.0 = Color::rgba(0.8, 0., 0., alpha);
+    }
+}
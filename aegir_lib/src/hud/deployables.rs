@@ -0,0 +1,65 @@
+//! A count of the mines or turret pods the player has left to drop.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::deployables::Deployables;
+
+/// Deployables HUD logic
+pub(super) struct DeployablesHudPlugin;
+
+impl Plugin for DeployablesHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_deployables_readout)
+            .add_systems(Update, update_deployables_readout);
+    }
+}
+
+/// Marks the text counting the player's deployables.
+#[derive(Component, Debug)]
+struct DeployablesReadout;
+
+/// Spawns the deployables count above the countermeasure count.
+fn spawn_deployables_readout(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(340.),
+            bottom: Val::Px(40.),
+            ..default()
+        }),
+        DeployablesReadout,
+        InGame,
+    ));
+}
+
+/// Shows how many deployables the player has left.
+fn update_deployables_readout(
+    deployables_query: Query<&Deployables, (With<PlayerShip>, Changed<Deployables>)>,
+    mut text_query: Query<&mut Text, With<DeployablesReadout>>,
+) {
+    let Ok(deployables) = deployables_query.get_single() else {
+        return;
+    };
+
+    let readout = format!(
+        "{} {}/{}",
+        deployables.kind.name().to_uppercase(),
+        deployables.charges,
+        deployables.max_charges
+    );
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != readout {
+            text.sections[0].value = readout.clone();
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Readouts for the player ship's energy pool and power distribution.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::energy::{Energy, PowerDistribution, Subsystem};
+
+/// The color of the filled part of the energy bar.
+const ENERGY_COLOR: Color = Color::rgb(0.2, 0.8, 1.);
+
+/// Energy HUD logic
+pub(super) struct EnergyHudPlugin;
+
+impl Plugin for EnergyHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_energy_hud)
+            .add_systems(Update, (update_energy_bar, update_power_readout));
+    }
+}
+
+/// Marks the node whose width shows how full the player's energy pool is.
+#[derive(Component, Debug)]
+struct EnergyBarFill;
+
+/// Marks the text showing how the player's power is distributed.
+#[derive(Component, Debug)]
+struct PowerReadout;
+
+/// Spawns the energy bar and power readout in the bottom-left corner of the screen.
+fn spawn_energy_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.),
+                    bottom: Val::Px(20.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                PowerReadout,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(200.),
+                        height: Val::Px(12.),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: ENERGY_COLOR.into(),
+                            ..default()
+                        },
+                        EnergyBarFill,
+                    ));
+                });
+        });
+}
+
+/// Resizes the energy bar to match the player's [`Energy`].
+fn update_energy_bar(
+    energy_query: Query<&Energy, (With<PlayerShip>, Changed<Energy>)>,
+    mut bar_query: Query<&mut Style, With<EnergyBarFill>>,
+) {
+    let Ok(energy) = energy_query.get_single() else {
+        return;
+    };
+
+    for mut style in bar_query.iter_mut() {
+        style.width = Val::Percent(energy.fraction() * 100.);
+    }
+}
+
+/// Shows how many pips each subsystem has, Elite-style.
+fn update_power_readout(
+    power_query: Query<&PowerDistribution, (With<PlayerShip>, Changed<PowerDistribution>)>,
+    mut text_query: Query<&mut Text, With<PowerReadout>>,
+) {
+    let Ok(power) = power_query.get_single() else {
+        return;
+    };
+
+    let readout = Subsystem::ALL
+        .iter()
+        .map(|&subsystem| {
+            let label = match subsystem {
+                Subsystem::Engines => "ENG",
+                Subsystem::Weapons => "WEP",
+                Subsystem::Shields => "SHD",
+            };
+            format!("{label} {:.1}", f32::from(power.half_pips(subsystem)) / 2.)
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = readout.clone();
+    }
+}
@@ -0,0 +1,322 @@
+//! The galaxy map, where the player chooses the sector to jump to, and a readout of the jump
+//! drive's progress.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{ActionState, KeyboardFocus, MenuAction};
+use crate::player::ship::{JumpDestination, PlayerShip};
+use crate::simulation::jump_drive::{JumpDrive, JumpInterrupted, JumpInterruption, JumpState};
+use crate::simulation::sector::{CurrentSector, SectorDefinition, SectorLibrary};
+
+/// The key that opens and closes the galaxy map.
+const MAP_KEY: KeyCode = KeyCode::G;
+
+/// The width and height of the galaxy map, in pixels.
+const MAP_SIZE: Vec2 = Vec2::new(640., 420.);
+
+/// The color of sectors that are neither chosen nor being flown in.
+const SECTOR_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+
+/// The color of sectors under the cursor.
+const HOVERED_SECTOR_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+/// The color of the sector chosen as the destination.
+const DESTINATION_COLOR: Color = Color::rgb(0.2, 0.45, 0.8);
+
+/// The color of the sector being flown in.
+const CURRENT_SECTOR_COLOR: Color = Color::rgb(0.2, 0.5, 0.3);
+
+/// How long the readout says a jump was interrupted, in seconds.
+const INTERRUPTION_NOTICE_TIME: f32 = 2.;
+
+/// Galaxy map logic
+pub(super) struct GalaxyMapPlugin;
+
+impl Plugin for GalaxyMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GalaxyMap>()
+            .add_systems(OnEnter(GameState::Playing), spawn_jump_readout)
+            .add_systems(OnExit(GameState::Playing), close_galaxy_map)
+            .add_systems(
+                Update,
+                (
+                    toggle_galaxy_map.run_if(in_state(GameState::Playing)),
+                    choose_destination,
+                    color_sectors,
+                    update_jump_readout,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether the galaxy map is open.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GalaxyMap {
+    /// Is the map shown, with the keyboard taken from the game?
+    open: bool,
+}
+
+/// Marks the root of the galaxy map, which is despawned when it closes.
+#[derive(Component, Debug)]
+struct GalaxyMapRoot;
+
+/// A sector on the galaxy map, which becomes the destination when pressed.
+#[derive(Component, Debug, Clone)]
+struct SectorButton(Handle<SectorDefinition>);
+
+/// The text describing what the player's jump drive is doing.
+#[derive(Component, Debug, Clone, Copy, Default)]
+struct JumpReadout {
+    /// How much longer the readout says the last jump was interrupted, in seconds.
+    interruption_notice: f32,
+    /// Was the last jump interrupted by damage, rather than cancelled?
+    damaged: bool,
+}
+
+/// Spawns the jump readout at the top of the screen, blank until the drive is used.
+fn spawn_jump_readout(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Px(60.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.,
+                        color: Color::rgb(0.6, 0.8, 1.),
+                        ..default()
+                    },
+                ),
+                JumpReadout::default(),
+            ));
+        });
+}
+
+/// Opens and closes the galaxy map with `G`, taking the keyboard away from the game while open.
+/// [`MenuAction::Back`] also closes it.
+///
+/// The map is built afresh each time it opens, so it always lists every loaded sector.
+#[allow(clippy::too_many_arguments)]
+fn toggle_galaxy_map(
+    mut commands: Commands,
+    keyboard: Res<Input<KeyCode>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    library: Res<SectorLibrary>,
+    definitions: Res<Assets<SectorDefinition>>,
+    mut map: ResMut<GalaxyMap>,
+    mut focus: ResMut<KeyboardFocus>,
+    root_query: Query<Entity, With<GalaxyMapRoot>>,
+) {
+    let closing = map.open && menu_actions.just_pressed(MenuAction::Back);
+    if !keyboard.just_pressed(MAP_KEY) && !closing {
+        return;
+    }
+
+    if map.open {
+        map.open = false;
+        *focus = KeyboardFocus::Game;
+        for entity in root_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    // Leave the keyboard alone while something else, such as the console, has it
+    if *focus != KeyboardFocus::Game {
+        return;
+    }
+    map.open = true;
+    *focus = KeyboardFocus::Menu;
+
+    let text_style = TextStyle {
+        font_size: 18.,
+        color: Color::WHITE,
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            GalaxyMapRoot,
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "GALAXY MAP",
+                TextStyle {
+                    font_size: 28.,
+                    ..text_style.clone()
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(MAP_SIZE.x),
+                        height: Val::Px(MAP_SIZE.y),
+                        border: UiRect::all(Val::Px(1.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.02, 0.03, 0.08, 0.9).into(),
+                    border_color: Color::rgb(0.3, 0.35, 0.5).into(),
+                    ..default()
+                })
+                .with_children(|map| {
+                    for handle in library.sectors() {
+                        let Some(definition) = definitions.get(handle) else {
+                            continue;
+                        };
+                        let [x, y] = definition.map_position;
+                        map.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Percent(x * 100.),
+                                    top: Val::Percent(y * 100.),
+                                    padding: UiRect::all(Val::Px(6.)),
+                                    ..default()
+                                },
+                                background_color: SECTOR_COLOR.into(),
+                                ..default()
+                            },
+                            SectorButton(handle.clone()),
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                definition.name.clone(),
+                                text_style.clone(),
+                            ));
+                        });
+                    }
+                });
+            parent.spawn(TextBundle::from_section(
+                "Choose a destination, then press J to jump. Press G or Escape to close the map.",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Gives the keyboard back to the game if play ends with the map open.
+fn close_galaxy_map(mut map: ResMut<GalaxyMap>, mut focus: ResMut<KeyboardFocus>) {
+    if map.open {
+        map.open = false;
+        *focus = KeyboardFocus::Game;
+    }
+}
+
+/// Makes a sector the jump destination when it is pressed on the map.
+fn choose_destination(
+    query: Query<(&Interaction, &SectorButton), Changed<Interaction>>,
+    current: Res<CurrentSector>,
+    mut destination: ResMut<JumpDestination>,
+) {
+    for (interaction, SectorButton(sector)) in query.iter() {
+        if *interaction == Interaction::Pressed && current.sector() != Some(sector) {
+            destination.0 = Some(sector.clone());
+        }
+    }
+}
+
+/// Colors each sector on the map by whether it is being flown in, is the destination, or is under
+/// the cursor.
+fn color_sectors(
+    current: Res<CurrentSector>,
+    destination: Res<JumpDestination>,
+    mut query: Query<(&Interaction, &SectorButton, &mut BackgroundColor)>,
+) {
+    for (interaction, SectorButton(sector), mut color) in query.iter_mut() {
+        *color = if current.sector() == Some(sector) {
+            CURRENT_SECTOR_COLOR
+        } else if destination.0.as_ref() == Some(sector) {
+            DESTINATION_COLOR
+        } else if *interaction == Interaction::None {
+            SECTOR_COLOR
+        } else {
+            HOVERED_SECTOR_COLOR
+        }
+        .into();
+    }
+}
+
+/// Describes what the player's jump drive is doing, and says when a jump is interrupted.
+fn update_jump_readout(
+    time: Res<Time>,
+    mut interrupted: EventReader<JumpInterrupted>,
+    definitions: Res<Assets<SectorDefinition>>,
+    drive_query: Query<(Entity, &JumpDrive), With<PlayerShip>>,
+    mut readout_query: Query<(&mut Text, &mut JumpReadout)>,
+) {
+    let drive = drive_query.get_single().ok();
+    let interruption = interrupted
+        .iter()
+        .filter(|event| drive.is_some_and(|(player, _)| event.ship == player))
+        .last()
+        .copied();
+
+    for (mut text, mut readout) in readout_query.iter_mut() {
+        if let Some(event) = interruption {
+            readout.interruption_notice = INTERRUPTION_NOTICE_TIME;
+            readout.damaged = event.reason == JumpInterruption::Damaged;
+        }
+        readout.interruption_notice = (readout.interruption_notice - time.delta_seconds()).max(0.);
+
+        let sector_name = |sector: &Handle<SectorDefinition>| {
+            definitions
+                .get(sector)
+                .map_or("unknown space", |definition| definition.name.as_str())
+                .to_string()
+        };
+        let value = match drive.map(|(_, drive)| (drive, drive.state())) {
+            Some((
+                drive,
+                JumpState::Charging {
+                    destination,
+                    elapsed,
+                },
+            )) => format!(
+                "JUMP TO {} IN {:.1}s",
+                sector_name(destination).to_uppercase(),
+                (drive.charge_time - elapsed).max(0.),
+            ),
+            Some((_, JumpState::Jumping { destination, .. })) => {
+                format!("JUMPING TO {}", sector_name(destination).to_uppercase())
+            }
+            _ if readout.interruption_notice > 0. && readout.damaged => {
+                "JUMP INTERRUPTED BY DAMAGE".to_string()
+            }
+            _ if readout.interruption_notice > 0. => "JUMP CANCELLED".to_string(),
+            _ => String::new(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! A warning shown while the player's ship is being pulled by a gravity well.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::GravityWell;
+
+/// Gravity HUD logic
+pub(super) struct GravityHudPlugin;
+
+impl Plugin for GravityHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_gravity_indicator)
+            .add_systems(Update, update_gravity_indicator);
+    }
+}
+
+/// Marks the text showing the pull of the gravity wells around the player.
+#[derive(Component, Debug)]
+struct GravityIndicator;
+
+/// Spawns the gravity indicator near the top of the screen, blank until the player enters a well.
+fn spawn_gravity_indicator(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Px(90.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.,
+                        color: Color::rgb(1., 0.7, 0.3),
+                        ..default()
+                    },
+                ),
+                GravityIndicator,
+            ));
+        });
+}
+
+/// Shows how hard the gravity wells around the player are pulling on their ship.
+fn update_gravity_indicator(
+    ship_query: Query<&Transform, With<PlayerShip>>,
+    well_query: Query<(&Transform, &GravityWell)>,
+    mut text_query: Query<&mut Text, With<GravityIndicator>>,
+) {
+    let pull = ship_query.get_single().map_or(Vec3::ZERO, |ship| {
+        well_query
+            .iter()
+            .map(|(transform, well)| well.acceleration(ship.translation - transform.translation))
+            .sum()
+    });
+
+    let value = if pull == Vec3::ZERO {
+        String::new()
+    } else {
+        format!("GRAVITY WELL {:.1} m/s²", pull.length())
+    };
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Feedback for the player's hits: numbers that float up from where shots land, and a marker that
+//! flashes around the middle of the screen, which changes when a hit is a kill.
+
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::{GameState, InGame};
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::simulation::health::{Damaged, Destroyed};
+
+/// How long damage numbers float before they vanish, in seconds.
+const NUMBER_LIFETIME: f32 = 1.2;
+
+/// How fast damage numbers rise, in meters per second.
+const NUMBER_RISE_SPEED: f32 = 4.;
+
+/// How long the hit marker flashes for after a hit, in seconds.
+const HIT_FLASH_TIME: f32 = 0.2;
+
+/// How long the hit marker flashes for after a kill, in seconds.
+const KILL_FLASH_TIME: f32 = 0.6;
+
+/// The width and height of the hit marker, in pixels.
+const HIT_MARKER_SIZE: f32 = 20.;
+
+/// The width and height of the hit marker after a kill, in pixels.
+const KILL_MARKER_SIZE: f32 = 32.;
+
+/// The color of damage numbers and of the hit marker after a hit.
+const HIT_COLOR: Color = Color::rgb(1., 0.95, 0.8);
+
+/// The color of the hit marker after a kill.
+const KILL_COLOR: Color = Color::rgb(1., 0.3, 0.2);
+
+/// Hit feedback logic
+pub(super) struct HitFeedbackPlugin;
+
+impl Plugin for HitFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitFeedbackSettings>()
+            .add_console_command(
+                "hit feedback",
+                "hit feedback <numbers|markers> <on|off>",
+                hit_feedback_command,
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_hit_marker)
+            .add_systems(
+                Update,
+                (spawn_damage_numbers, float_damage_numbers, flash_hit_marker),
+            );
+    }
+}
+
+/// Which kinds of hit feedback are shown, for players who prefer a clean screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitFeedbackSettings {
+    /// Are damage numbers shown where the player's shots land?
+    pub damage_numbers: bool,
+    /// Does the hit marker flash when the player's shots land?
+    pub hit_markers: bool,
+}
+
+impl Default for HitFeedbackSettings {
+    fn default() -> Self {
+        HitFeedbackSettings {
+            damage_numbers: true,
+            hit_markers: true,
+        }
+    }
+}
+
+/// A number showing how much damage a shot dealt, floating up from where it landed.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct DamageNumber {
+    /// Where the number is, in world space.
+    position: Vec3,
+    /// How long the number has been shown, in seconds.
+    age: f32,
+}
+
+/// The marker that flashes around the middle of the screen when the player's shots land.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct HitMarker {
+    /// How long the marker has left to flash, in seconds.
+    remaining: f32,
+    /// Is the marker confirming a kill, rather than a hit?
+    kill: bool,
+}
+
+impl HitMarker {
+    /// How long the current flash lasts in total, in seconds.
+    fn duration(&self) -> f32 {
+        if self.kill {
+            KILL_FLASH_TIME
+        } else {
+            HIT_FLASH_TIME
+        }
+    }
+}
+
+/// Spawns the hit marker, hidden until the player lands a shot.
+fn spawn_hit_marker(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(HIT_MARKER_SIZE),
+                        height: Val::Px(HIT_MARKER_SIZE),
+                        border: UiRect::all(Val::Px(2.)),
+                        ..default()
+                    },
+                    border_color: Color::NONE.into(),
+                    ..default()
+                },
+                HitMarker {
+                    remaining: 0.,
+                    kill: false,
+                },
+            ));
+        });
+}
+
+/// Spawns a damage number at each of the player's hits, and flashes the hit marker.
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    settings: Res<HitFeedbackSettings>,
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventReader<Destroyed>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    mut marker_query: Query<&mut HitMarker>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        damaged.clear();
+        destroyed.clear();
+        return;
+    };
+    let by_player =
+        |source: Option<Entity>, target: Entity| source == Some(player) && target != player;
+
+    let mut hit = false;
+    for event in damaged.iter() {
+        if !by_player(event.source, event.target) {
+            continue;
+        }
+        hit = true;
+
+        if settings.damage_numbers {
+            commands.spawn((
+                TextBundle::from_section(
+                    format!("{:.0}", event.amount.ceil()),
+                    TextStyle {
+                        font_size: 18.,
+                        color: HIT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                }),
+                DamageNumber {
+                    position: event.position,
+                    age: 0.,
+                },
+                InGame,
+            ));
+        }
+    }
+    let killed = destroyed
+        .iter()
+        .any(|event| by_player(event.killer, event.entity));
+
+    if !settings.hit_markers || !(hit || killed) {
+        return;
+    }
+    for mut marker in marker_query.iter_mut() {
+        // A hit does not cut short the flash of a kill
+        if killed || !marker.kill || marker.remaining <= 0. {
+            marker.kill = killed;
+            marker.remaining = marker.duration();
+        }
+    }
+}
+
+/// Raises, fades and eventually removes damage numbers, keeping each over its point in the world.
+fn float_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    mut query: Query<(
+        Entity,
+        &mut DamageNumber,
+        &mut Style,
+        &mut Text,
+        &mut Visibility,
+    )>,
+) {
+    let camera = camera_query.get_single().ok();
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut number, mut style, mut text, mut visibility) in query.iter_mut() {
+        number.age += delta_time;
+        if number.age >= NUMBER_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        number.position += Vec3::Y * NUMBER_RISE_SPEED * delta_time;
+
+        let Some(position) = camera.and_then(|(camera, camera_transform)| {
+            camera.world_to_viewport(camera_transform, number.position)
+        }) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        style.left = Val::Px(position.x);
+        style.top = Val::Px(position.y);
+
+        let fade = 1. - number.age / NUMBER_LIFETIME;
+        text.sections[0].style.color = HIT_COLOR.with_a(fade);
+    }
+}
+
+/// Fades the hit marker out over the course of its flash, growing it for kills.
+fn flash_hit_marker(
+    time: Res<Time>,
+    mut query: Query<(&mut HitMarker, &mut Style, &mut BorderColor)>,
+) {
+    for (mut marker, mut style, mut border) in query.iter_mut() {
+        marker.remaining = (marker.remaining - time.delta_seconds()).max(0.);
+
+        let (size, color) = if marker.kill {
+            (KILL_MARKER_SIZE, KILL_COLOR)
+        } else {
+            (HIT_MARKER_SIZE, HIT_COLOR)
+        };
+        if style.width != Val::Px(size) {
+            style.width = Val::Px(size);
+            style.height = Val::Px(size);
+        }
+        border.0 = color.with_a(marker.remaining / marker.duration());
+    }
+}
+
+/// Console command that shows or hides damage numbers or hit markers.
+fn hit_feedback_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let [kind, state] = *arguments else {
+        return Err("expected `numbers` or `markers`, then `on` or `off`".to_string());
+    };
+    let enabled = match state {
+        "on" => true,
+        "off" => false,
+        _ => return Err(format!("`{state}` is not `on` or `off`")),
+    };
+
+    let mut settings = world.resource_mut::<HitFeedbackSettings>();
+    match kind {
+        "numbers" => settings.damage_numbers = enabled,
+        "markers" => settings.hit_markers = enabled,
+        _ => return Err(format!("`{kind}` is not `numbers` or `markers`")),
+    }
+
+    Ok(format!("{kind} {state}"))
+}
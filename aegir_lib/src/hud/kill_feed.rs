@@ -0,0 +1,129 @@
+//! A feed listing recent kills, which fade away after a few seconds.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::stats::KillReported;
+
+/// How many kills the feed lists at once.
+const MAX_ENTRIES: usize = 5;
+
+/// How long each kill stays listed, in seconds.
+const ENTRY_LIFETIME: f32 = 6.;
+
+/// How long listed kills take to fade out at the end of their lifetime, in seconds.
+const FADE_TIME: f32 = 1.;
+
+/// Kill feed logic
+pub(super) struct KillFeedPlugin;
+
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_kill_feed)
+            .add_systems(Update, (add_kills, age_kills).chain());
+    }
+}
+
+/// Marks the node that kills are listed within.
+#[derive(Component, Debug)]
+struct KillFeed;
+
+/// A listed kill.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct KillFeedEntry {
+    /// How long the kill has been listed, in seconds.
+    age: f32,
+    /// The color the kill is listed in before it fades.
+    color: Color,
+}
+
+/// Spawns the kill feed on the right of the screen.
+fn spawn_kill_feed(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(35.),
+                right: Val::Px(20.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(4.),
+                ..default()
+            },
+            ..default()
+        },
+        KillFeed,
+        InGame,
+    ));
+}
+
+/// Lists each new kill at the bottom of the feed, dropping the oldest when it is full.
+fn add_kills(
+    mut commands: Commands,
+    mut kills: EventReader<KillReported>,
+    feed_query: Query<(Entity, Option<&Children>), With<KillFeed>>,
+) {
+    let Ok((feed, children)) = feed_query.get_single() else {
+        kills.clear();
+        return;
+    };
+
+    let existing: &[Entity] = children.map(|children| &children[..]).unwrap_or_default();
+    let mut listed = existing.len();
+    let mut evicted = 0;
+    for kill in kills.iter() {
+        if listed >= MAX_ENTRIES {
+            if let Some(&oldest) = existing.get(evicted) {
+                commands.entity(oldest).despawn_recursive();
+                evicted += 1;
+                listed -= 1;
+            }
+        }
+        listed += 1;
+
+        let color = if kill.of_player {
+            Color::rgb(1., 0.35, 0.3)
+        } else if kill.by_player {
+            Color::rgb(0.4, 1., 0.6)
+        } else {
+            Color::WHITE
+        };
+        let line = match &kill.killer {
+            Some(killer) => format!("{killer} destroyed {}", kill.victim),
+            None => format!("{} was destroyed", kill.victim),
+        };
+
+        let entry = commands
+            .spawn((
+                TextBundle::from_section(
+                    line,
+                    TextStyle {
+                        font_size: 16.,
+                        color,
+                        ..default()
+                    },
+                ),
+                KillFeedEntry { age: 0., color },
+            ))
+            .id();
+        commands.entity(feed).add_child(entry);
+    }
+}
+
+/// Fades out and then removes kills that have been listed for long enough.
+fn age_kills(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut KillFeedEntry, &mut Text)>,
+) {
+    for (entity, mut entry, mut text) in query.iter_mut() {
+        entry.age += time.delta_seconds();
+        if entry.age >= ENTRY_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let fade = ((ENTRY_LIFETIME - entry.age) / FADE_TIME).min(1.);
+        text.sections[0].style.color = entry.color.with_a(fade);
+    }
+}
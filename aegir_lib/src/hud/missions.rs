@@ -0,0 +1,289 @@
+//! The active mission's objectives, prompts for the objectives under way, and a banner when it
+//! ends.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{FlightAction, InputMap};
+use crate::simulation::missions::{
+    ActiveMission, Goal, MissionDefinition, MissionStatus, ObjectiveProgress, ObjectiveState,
+};
+
+/// The font size of each objective.
+const OBJECTIVE_FONT_SIZE: f32 = 16.;
+
+/// Mission HUD logic
+pub(super) struct MissionHudPlugin;
+
+impl Plugin for MissionHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_mission_hud)
+            .add_systems(
+                Update,
+                (
+                    update_objective_list,
+                    update_objective_prompt,
+                    update_mission_banner,
+                ),
+            );
+    }
+}
+
+/// Marks the text listing the active mission's objectives.
+#[derive(Component, Debug)]
+struct ObjectiveList;
+
+/// Marks the text giving the instructions for the objectives under way.
+#[derive(Component, Debug)]
+struct ObjectivePrompt;
+
+/// Marks the text announcing that the active mission is complete or has failed.
+#[derive(Component, Debug)]
+struct MissionBanner;
+
+/// Spawns the objective list in the top-right corner, the prompt below the middle of the screen and
+/// the banner across the top.
+fn spawn_mission_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::default().with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.),
+            right: Val::Px(20.),
+            ..default()
+        }),
+        ObjectiveList,
+        InGame,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(25.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 48.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                MissionBanner,
+            ));
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(70.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.,
+                        color: Color::rgb(0.6, 0.8, 1.),
+                        ..default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                ObjectivePrompt,
+            ));
+        });
+}
+
+/// The color an objective is listed in.
+fn state_color(state: ObjectiveState) -> Color {
+    match state {
+        ObjectiveState::Locked => Color::rgba(1., 1., 1., 0.35),
+        ObjectiveState::Active => Color::WHITE,
+        ObjectiveState::Complete => Color::rgb(0.4, 1., 0.6),
+        ObjectiveState::Failed => Color::rgb(1., 0.35, 0.3),
+    }
+}
+
+/// Describes an objective and how far through it the player is.
+fn describe_objective(description: &str, goal: &Goal, progress: &ObjectiveProgress) -> String {
+    let mark = match progress.state {
+        ObjectiveState::Locked => "-",
+        ObjectiveState::Active => ">",
+        ObjectiveState::Complete => "+",
+        ObjectiveState::Failed => "x",
+    };
+    let detail = match (goal, progress.state) {
+        (Goal::Destroy { count, .. }, ObjectiveState::Active) => {
+            format!(" ({}/{count})", progress.destroyed)
+        }
+        (Goal::Survive { seconds }, ObjectiveState::Active) => {
+            format!(" ({:.0}s)", (seconds - progress.elapsed).max(0.).ceil())
+        }
+        (Goal::Perform { seconds, .. }, ObjectiveState::Active) if *seconds > 0. => {
+            format!(" ({:.0}%)", (progress.performed / seconds).min(1.) * 100.)
+        }
+        _ => String::new(),
+    };
+
+    format!("{mark} {description}{detail}\n")
+}
+
+/// Lists each of the active mission's objectives, colored by how far through it the player is.
+fn update_objective_list(
+    mission: Option<Res<ActiveMission>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    mut text_query: Query<&mut Text, With<ObjectiveList>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    let Some((mission, definition)) = mission
+        .as_ref()
+        .and_then(|mission| Some((mission, definitions.get(&mission.definition)?)))
+    else {
+        text.sections.clear();
+        return;
+    };
+    if !mission.is_changed() && !text.sections.is_empty() {
+        return;
+    }
+
+    let heading = TextSection::new(
+        format!("{}\n", definition.name.to_uppercase()),
+        TextStyle {
+            font_size: OBJECTIVE_FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    );
+    let objectives =
+        definition
+            .objectives
+            .iter()
+            .zip(mission.objectives())
+            .map(|(objective, progress)| {
+                let mut line =
+                    describe_objective(&objective.description, &objective.goal, progress);
+                if let (Some(limit), ObjectiveState::Active) =
+                    (objective.time_limit, progress.state)
+                {
+                    line.insert_str(
+                        line.len() - 1,
+                        &format!(" [{:.0}s left]", (limit - progress.elapsed).max(0.).ceil()),
+                    );
+                }
+                TextSection::new(
+                    line,
+                    TextStyle {
+                        font_size: OBJECTIVE_FONT_SIZE,
+                        color: state_color(progress.state),
+                        ..default()
+                    },
+                )
+            });
+
+    text.sections = std::iter::once(heading).chain(objectives).collect();
+}
+
+/// Replaces each `{Action}` in `prompt` with the inputs bound to that [`FlightAction`], leaving
+/// anything that does not name an action as it is.
+fn fill_in_bindings(prompt: &str, input_map: &InputMap<FlightAction>) -> String {
+    let mut filled = String::with_capacity(prompt.len());
+    let mut rest = prompt;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        let placeholder = &rest[start..=start + length];
+        match ron::from_str::<FlightAction>(&placeholder[1..placeholder.len() - 1]) {
+            Ok(action) => {
+                let labels: Vec<String> = input_map
+                    .bindings(action)
+                    .iter()
+                    .map(|input| input.label())
+                    .collect();
+                if labels.is_empty() {
+                    filled.push_str("(unbound)");
+                } else {
+                    filled.push_str(&labels.join(" or "));
+                }
+            }
+            Err(_) => filled.push_str(placeholder),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+/// Shows the prompts of the active mission's objectives that are under way, with the inputs the
+/// player has bound filled in.
+fn update_objective_prompt(
+    mission: Option<Res<ActiveMission>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    input_map: Res<InputMap<FlightAction>>,
+    mut text_query: Query<&mut Text, With<ObjectivePrompt>>,
+) {
+    let prompt = mission
+        .as_ref()
+        .filter(|mission| mission.status() == MissionStatus::InProgress)
+        .and_then(|mission| {
+            let definition = definitions.get(&mission.definition)?;
+            let prompts: Vec<String> = definition
+                .objectives
+                .iter()
+                .zip(mission.objectives())
+                .filter(|(_, progress)| progress.state == ObjectiveState::Active)
+                .filter_map(|(objective, _)| objective.prompt.as_deref())
+                .map(|prompt| fill_in_bindings(prompt, &input_map))
+                .collect();
+            Some(prompts.join("\n"))
+        })
+        .unwrap_or_default();
+
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != prompt {
+            text.sections[0].value = prompt.clone();
+        }
+    }
+}
+
+/// Announces when the active mission is complete or has failed.
+fn update_mission_banner(
+    mission: Option<Res<ActiveMission>>,
+    mut text_query: Query<&mut Text, With<MissionBanner>>,
+) {
+    let (banner, color) = match mission.map(|mission| mission.status()) {
+        Some(MissionStatus::Complete) => ("MISSION COMPLETE", Color::rgb(0.4, 1., 0.6)),
+        Some(MissionStatus::Failed) => ("MISSION FAILED", Color::rgb(1., 0.35, 0.3)),
+        Some(MissionStatus::InProgress) | None => ("", Color::WHITE),
+    };
+
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != banner {
+            text.sections[0].value = banner.to_string();
+            text.sections[0].style.color = color;
+        }
+    }
+}
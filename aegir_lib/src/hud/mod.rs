@@ -0,0 +1,105 @@
+//! The heads-up display drawn over the game world.
+use bevy::prelude::{App, Camera, GlobalTransform, Plugin, Vec2, Vec3};
+
+mod captions;
+mod cargo;
+mod countermeasures;
+mod crosshair;
+mod damage;
+mod deployables;
+mod energy;
+mod galaxy_map;
+mod gravity;
+pub mod hit_feedback;
+mod kill_feed;
+mod missions;
+mod navigation;
+pub mod radar;
+mod sector_map;
+mod slow_motion;
+mod targeting;
+mod trade;
+mod velocity;
+mod weapons;
+mod wingmen;
+
+/// Adds the player's heads-up display.
+///
+/// Like [`GraphicsPlugin`](crate::graphics::GraphicsPlugin), this only displays the simulation.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        // Tuples of plugins can only hold fifteen, so they are added in two
+        app.add_plugins((
+            captions::CaptionsHudPlugin,
+            cargo::CargoHudPlugin,
+            countermeasures::CountermeasuresHudPlugin,
+            crosshair::CrosshairPlugin,
+            damage::DamageHudPlugin,
+            deployables::DeployablesHudPlugin,
+            energy::EnergyHudPlugin,
+            galaxy_map::GalaxyMapPlugin,
+            gravity::GravityHudPlugin,
+            hit_feedback::HitFeedbackPlugin,
+            kill_feed::KillFeedPlugin,
+        ))
+        .add_plugins((
+            missions::MissionHudPlugin,
+            navigation::NavigationHudPlugin,
+            radar::RadarPlugin,
+            sector_map::SectorMapPlugin,
+            slow_motion::SlowMotionHudPlugin,
+            targeting::TargetingHudPlugin,
+            trade::TradeHudPlugin,
+            velocity::VelocityHudPlugin,
+            weapons::WeaponHudPlugin,
+            wingmen::WingmenHudPlugin,
+        ));
+    }
+}
+
+/// Where to draw a marker for `point` on screen, in logical pixels.
+///
+/// Points off-screen, including those behind the camera, are pinned `margin` pixels inside the
+/// edge of the screen nearest to them, and come with an arrow pointing their way. Returns `None`
+/// if the camera's viewport is not known yet.
+pub(crate) fn project_marker(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    point: Vec3,
+    margin: f32,
+) -> Option<(Vec2, Option<&'static str>)> {
+    let size = camera.logical_viewport_size()?;
+
+    let view = camera_transform.compute_transform().rotation.inverse()
+        * (point - camera_transform.translation());
+    let on_screen = camera
+        .world_to_ndc(camera_transform, point)
+        .filter(|ndc| view.z < 0. && ndc.x.abs() <= 1. && ndc.y.abs() <= 1.);
+
+    let center = size / 2.;
+    Some(match on_screen {
+        Some(ndc) => (center + Vec2::new(ndc.x, -ndc.y) * center, None),
+        None => {
+            // Screen space has Y pointing down; points behind the camera still point the right way
+            let direction = Vec2::new(view.x, -view.y)
+                .try_normalize()
+                .unwrap_or(Vec2::Y);
+            let bounds = center - margin;
+            let scale = (bounds.x / direction.x.abs()).min(bounds.y / direction.y.abs());
+            let arrow = if direction.x.abs() > direction.y.abs() {
+                if direction.x > 0. {
+                    ">"
+                } else {
+                    "<"
+                }
+            } else if direction.y > 0. {
+                "v"
+            } else {
+                "^"
+            };
+            (center + direction * scale, Some(arrow))
+        }
+    })
+}
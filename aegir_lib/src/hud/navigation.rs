@@ -0,0 +1,151 @@
+//! A marker pointing at the player's selected waypoint, and the autopilot status.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::simulation::navigation::{Autopilot, Waypoint};
+use crate::simulation::stations::{DockingComputer, Station};
+
+use super::project_marker;
+
+/// The color of the waypoint marker.
+const MARKER_COLOR: Color = Color::rgb(0.4, 1., 0.6);
+
+/// How far from the edge of the screen the marker is kept while the waypoint is off-screen.
+const SCREEN_MARGIN: f32 = 40.;
+
+/// Roughly half the size of the marker, used to center it on the waypoint.
+const MARKER_HALF_SIZE: Vec2 = Vec2::new(8., 10.);
+
+/// Navigation HUD logic
+pub(super) struct NavigationHudPlugin;
+
+impl Plugin for NavigationHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_navigation_hud)
+            .add_systems(Update, (update_waypoint_marker, update_navigation_readout));
+    }
+}
+
+/// Marks the text that sits over the selected waypoint, or points to it from the screen edge.
+#[derive(Component, Debug)]
+struct WaypointMarker;
+
+/// Marks the text showing the selected waypoint, its distance and the autopilot status.
+#[derive(Component, Debug)]
+struct NavigationReadout;
+
+/// Spawns the waypoint marker and, at the top of the screen, the navigation readout.
+fn spawn_navigation_hud(mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: 16.,
+        color: MARKER_COLOR,
+        ..default()
+    };
+
+    commands.spawn((
+        TextBundle::from_section("", text_style.clone()).with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        }),
+        WaypointMarker,
+        InGame,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section("", text_style).with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.),
+            left: Val::Px(20.),
+            ..default()
+        }),
+        NavigationReadout,
+        InGame,
+    ));
+}
+
+/// Formats a distance in meters, switching to kilometers once it is far enough.
+fn format_distance(meters: f32) -> String {
+    if meters < 1000. {
+        format!("{meters:.0} m")
+    } else {
+        format!("{:.1} km", meters / 1000.)
+    }
+}
+
+/// Places the marker over the selected waypoint, or at the screen edge nearest to it with an arrow.
+fn update_waypoint_marker(
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    player_query: Query<&Autopilot, With<PlayerShip>>,
+    waypoint_query: Query<&GlobalTransform, With<Waypoint>>,
+    mut marker_query: Query<(&mut Text, &mut Style, &mut Visibility), With<WaypointMarker>>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = marker_query.get_single_mut() else {
+        return;
+    };
+    let destination = player_query
+        .get_single()
+        .ok()
+        .and_then(Autopilot::waypoint)
+        .and_then(|waypoint| waypoint_query.get(waypoint).ok());
+    let (Some(destination), Ok((camera, camera_transform))) =
+        (destination, camera_query.get_single())
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let Some((position, arrow)) = project_marker(
+        camera,
+        camera_transform,
+        destination.translation(),
+        SCREEN_MARGIN,
+    ) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let corner = position - MARKER_HALF_SIZE;
+    style.left = Val::Px(corner.x);
+    style.top = Val::Px(corner.y);
+    text.sections[0].value = arrow.unwrap_or("+").to_string();
+}
+
+/// Shows the selected waypoint's name and distance, and whether the autopilot is flying, or the
+/// station the player is docked with.
+fn update_navigation_readout(
+    player_query: Query<(&Transform, &Autopilot, Option<&DockingComputer>), With<PlayerShip>>,
+    waypoint_query: Query<(&Waypoint, &GlobalTransform)>,
+    station_query: Query<&Station>,
+    mut text_query: Query<&mut Text, With<NavigationReadout>>,
+) {
+    let readout = match player_query.get_single() {
+        Ok((_, _, Some(computer))) if computer.docked().is_some() => computer
+            .docked()
+            .and_then(|station| station_query.get(station).ok())
+            .map(|station| format!("DOCKED  {}  B TO TRADE", station.name))
+            .unwrap_or_default(),
+        Ok((transform, autopilot, _)) => autopilot
+            .waypoint()
+            .and_then(|waypoint| waypoint_query.get(waypoint).ok())
+            .map(|(waypoint, destination)| {
+                let distance = destination.translation().distance(transform.translation);
+                let status = if autopilot.is_engaged() {
+                    "  AUTOPILOT"
+                } else {
+                    ""
+                };
+                format!("{}  {}{status}", waypoint.name, format_distance(distance))
+            })
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != readout {
+            text.sections[0].value = readout.clone();
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! A radar that shows nearby contacts around the player's ship, Elite-style.
+//!
+//! Contacts are projected onto the ship's horizontal plane, with a stalk rising or falling to a
+//! blip to show how far above or below the plane they are.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::utils::HashMap;
+
+use crate::accessibility::HudTheme;
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::{Disposition, Targetable};
+use crate::simulation::spatial::SpatialIndex;
+
+/// The width and height of the radar on screen, in pixels.
+const RADAR_SIZE: f32 = 180.;
+
+/// The width and height of the texture the range rings are drawn into, in pixels.
+const RING_TEXTURE_SIZE: u32 = 256;
+
+/// The width and height of a contact's blip, in pixels.
+const BLIP_SIZE: f32 = 5.;
+
+/// Radar logic
+pub(super) struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RadarSettings>()
+            .add_console_command("radar", "radar <range> [rings]", radar_command)
+            .add_systems(OnEnter(GameState::Playing), spawn_radar)
+            .add_systems(Update, (draw_range_rings, update_blips));
+    }
+}
+
+/// How far the radar reaches and how it is marked.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct RadarSettings {
+    /// Contacts further than this from the player are not shown, in meters.
+    pub range: f32,
+    /// How many evenly spaced range rings are drawn, including the outer edge.
+    pub rings: u32,
+}
+
+impl Default for RadarSettings {
+    fn default() -> Self {
+        RadarSettings {
+            range: 2000.,
+            rings: 3,
+        }
+    }
+}
+
+/// Marks the image that the range rings are drawn into.
+#[derive(Component, Debug)]
+struct RadarRings;
+
+/// Marks the node that blips are placed within.
+#[derive(Component, Debug)]
+struct RadarScope;
+
+/// One of the two nodes that show a contact on the radar.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct RadarBlip {
+    /// The entity being shown.
+    contact: Entity,
+    /// Which part of the contact's marking this is.
+    part: BlipPart,
+}
+
+/// The parts of a contact's marking on the radar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BlipPart {
+    /// The line from the plane to the contact's height.
+    Stalk,
+    /// The square at the contact's height.
+    Dot,
+}
+
+/// Spawns the radar at the bottom of the screen.
+fn spawn_radar(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let rings = images.add(Image::new_fill(
+        Extent3d {
+            width: RING_TEXTURE_SIZE,
+            height: RING_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.),
+                    left: Val::Percent(50.),
+                    margin: UiRect::left(Val::Px(-RADAR_SIZE / 2.)),
+                    width: Val::Px(RADAR_SIZE),
+                    height: Val::Px(RADAR_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+            RadarScope,
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    image: UiImage::new(rings),
+                    ..default()
+                },
+                RadarRings,
+            ));
+        });
+}
+
+/// Redraws the range rings whenever the radar is spawned or its settings change.
+fn draw_range_rings(
+    settings: Res<RadarSettings>,
+    mut images: ResMut<Assets<Image>>,
+    query: Query<Ref<UiImage>, With<RadarRings>>,
+) {
+    for image in query.iter() {
+        if !settings.is_changed() && !image.is_added() {
+            continue;
+        }
+        let Some(image) = images.get_mut(&image.texture) else {
+            continue;
+        };
+
+        let size = RING_TEXTURE_SIZE as f32;
+        let center = size / 2.;
+        let ring_spacing = center / settings.rings.max(1) as f32;
+
+        for (index, pixel) in image.data.chunks_exact_mut(4).enumerate() {
+            let x = (index as u32 % RING_TEXTURE_SIZE) as f32 + 0.5 - center;
+            let y = (index as u32 / RING_TEXTURE_SIZE) as f32 + 0.5 - center;
+            let radius = (x * x + y * y).sqrt();
+
+            let rgba: [u8; 4] = if radius > center {
+                [0, 0, 0, 0]
+            } else if (radius % ring_spacing).min(ring_spacing - radius % ring_spacing) < 1.
+                || x.abs() < 0.5 && y < 0.
+            {
+                // A ring, or the line marking straight ahead
+                [120, 200, 255, 160]
+            } else {
+                [0, 20, 40, 110]
+            };
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+}
+
+/// Moves, spawns and despawns blips to match the contacts within range of the player.
+fn update_blips(
+    mut commands: Commands,
+    settings: Res<RadarSettings>,
+    theme: Res<HudTheme>,
+    player_query: Query<&Transform, With<PlayerShip>>,
+    index: Res<SpatialIndex>,
+    contact_query: Query<(&GlobalTransform, Option<&Disposition>), With<Targetable>>,
+    scope_query: Query<Entity, With<RadarScope>>,
+    mut blip_query: Query<(Entity, &RadarBlip, &mut Style, &mut BackgroundColor)>,
+) {
+    let (Ok(player), Ok(scope)) = (player_query.get_single(), scope_query.get_single()) else {
+        return;
+    };
+
+    let scale = RADAR_SIZE / 2. / settings.range;
+    let mut layouts = HashMap::new();
+    for (contact, _) in index.within(player.translation, settings.range) {
+        let Ok((transform, disposition)) = contact_query.get(contact) else {
+            continue;
+        };
+        let offset = transform.translation() - player.translation;
+        if offset.length() > settings.range {
+            continue;
+        }
+
+        let local = player.rotation.inverse() * offset * scale;
+        // On screen, forward (-Z) is up and above the plane (+Y) is also up
+        let plane = Vec2::new(local.x, local.z) + RADAR_SIZE / 2.;
+        let height = local.y;
+        let color = theme.disposition(disposition.copied().unwrap_or_default());
+
+        let stalk = Rect::from_corners(
+            Vec2::new(plane.x, plane.y),
+            Vec2::new(plane.x + 1., plane.y - height),
+        );
+        let dot =
+            Rect::from_center_size(Vec2::new(plane.x, plane.y - height), Vec2::splat(BLIP_SIZE));
+
+        layouts.insert(
+            RadarBlip {
+                contact,
+                part: BlipPart::Stalk,
+            },
+            (stalk, color.with_a(0.6)),
+        );
+        layouts.insert(
+            RadarBlip {
+                contact,
+                part: BlipPart::Dot,
+            },
+            (dot, color),
+        );
+    }
+
+    let place = |style: &mut Style, rect: Rect| {
+        style.position_type = PositionType::Absolute;
+        style.left = Val::Px(rect.min.x);
+        style.top = Val::Px(rect.min.y);
+        style.width = Val::Px(rect.width());
+        style.height = Val::Px(rect.height());
+    };
+
+    for (entity, blip, mut style, mut background) in blip_query.iter_mut() {
+        match layouts.remove(blip) {
+            Some((rect, color)) => {
+                place(&mut style, rect);
+                background.0 = color;
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    for (blip, (rect, color)) in layouts {
+        let mut style = Style::default();
+        place(&mut style, rect);
+
+        let entity = commands
+            .spawn((
+                NodeBundle {
+                    style,
+                    background_color: color.into(),
+                    ..default()
+                },
+                blip,
+            ))
+            .id();
+        commands.entity(scope).add_child(entity);
+    }
+}
+
+/// Console command that changes how far the radar reaches and how many rings it draws.
+fn radar_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (range, rings) = match *arguments {
+        [range] => (range, None),
+        [range, rings] => (range, Some(rings)),
+        _ => return Err("expected a range and an optional ring count".to_string()),
+    };
+    let range: f32 = range
+        .parse()
+        .ok()
+        .filter(|&range: &f32| range > 0.)
+        .ok_or_else(|| format!("`{range}` is not a positive distance"))?;
+    let rings = rings
+        .map(|rings| {
+            rings
+                .parse::<u32>()
+                .map_err(|_| format!("`{rings}` is not a ring count"))
+        })
+        .transpose()?;
+
+    let mut settings = world.resource_mut::<RadarSettings>();
+    settings.range = range;
+    if let Some(rings) = rings {
+        settings.rings = rings;
+    }
+
+    Ok(format!(
+        "radar range {} m with {} rings",
+        settings.range, settings.rings
+    ))
+}
@@ -0,0 +1,602 @@
+//! A full-screen map of the current sector, seen from above, where the player can set a waypoint.
+//!
+//! The map marks the sector's stations and waypoints, the edge of its asteroid field, any hostile
+//! ships within [`RadarSettings::range`] and the player's own ship. Dragging pans the map, the
+//! mouse wheel zooms it, and clicking sets a waypoint there for the autopilot.
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy::window::PrimaryWindow;
+
+use crate::accessibility::HudTheme;
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{
+    ActionState, FlightAction, InputKind, InputMap, InputSet, KeyboardFocus, MenuAction,
+    PIXELS_PER_LINE,
+};
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::{Disposition, Targetable};
+use crate::simulation::navigation::{Autopilot, Waypoint, WaypointBundle};
+use crate::simulation::sector::{CurrentSector, InSector, SectorDefinition};
+use crate::simulation::spatial::SpatialIndex;
+use crate::simulation::stations::Station;
+
+use super::radar::RadarSettings;
+
+/// How many meters each pixel of the map covers when it opens.
+const DEFAULT_SCALE: f32 = 5.;
+
+/// The closest the map zooms in, in meters per pixel.
+const MIN_SCALE: f32 = 0.5;
+
+/// The furthest the map zooms out, in meters per pixel.
+const MAX_SCALE: f32 = 100.;
+
+/// How much each line scrolled on the mouse wheel zooms the map.
+const ZOOM_PER_SCROLL_LINE: f32 = 1.2;
+
+/// How far the cursor may move between pressing and releasing the mouse button for it to count
+/// as a click rather than a drag, in pixels.
+const CLICK_TOLERANCE: f32 = 4.;
+
+/// How many dots mark the edge of the asteroid field.
+const BOUNDARY_DOTS: usize = 96;
+
+/// The color of the dots marking the edge of the asteroid field.
+const BOUNDARY_COLOR: Color = Color::rgba(0.6, 0.5, 0.4, 0.6);
+
+/// The color of stations.
+const STATION_COLOR: Color = Color::rgb(0.7, 0.7, 0.8);
+
+/// The color of waypoints that the autopilot is not flying to.
+const WAYPOINT_COLOR: Color = Color::rgb(0.2, 0.6, 0.4);
+
+/// The color of the waypoint the autopilot is flying to.
+const SELECTED_WAYPOINT_COLOR: Color = Color::rgb(0.4, 1., 0.6);
+
+/// The color of the player's ship.
+const PLAYER_COLOR: Color = Color::WHITE;
+
+/// How far ahead of the player's marker the tick showing their heading is drawn, in pixels.
+const HEADING_LENGTH: f32 = 12.;
+
+/// The name given to waypoints set on the map.
+const MAP_WAYPOINT_NAME: &str = "Map waypoint";
+
+/// Sector map logic
+pub(super) struct SectorMapPlugin;
+
+impl Plugin for SectorMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SectorMap>()
+            .add_systems(OnExit(GameState::Playing), close_sector_map)
+            .add_systems(FixedUpdate, request_sector_map.in_set(InputSet::Apply))
+            .add_systems(
+                Update,
+                (
+                    toggle_sector_map.run_if(in_state(GameState::Playing)),
+                    pan_and_zoom,
+                    update_map_markers,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether the sector map is open, and which part of the sector it shows.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+struct SectorMap {
+    /// Is the map shown, with the keyboard taken from the game?
+    open: bool,
+    /// Has the player asked to open the map since it was last opened?
+    requested: bool,
+    /// The point in the middle of the map, as world X and Z.
+    center: Vec2,
+    /// How many meters each pixel covers.
+    scale: f32,
+    /// Where the cursor was when the mouse button was pressed over the map, if it is still held,
+    /// and whether it has since moved far enough to be a drag.
+    drag: Option<(Vec2, bool)>,
+}
+
+impl Default for SectorMap {
+    fn default() -> Self {
+        SectorMap {
+            open: false,
+            requested: false,
+            center: Vec2::ZERO,
+            scale: DEFAULT_SCALE,
+            drag: None,
+        }
+    }
+}
+
+impl SectorMap {
+    /// Where `position` is drawn on a window of `size`, in logical pixels.
+    ///
+    /// The map looks down on the sector with forward (-Z) up.
+    fn to_screen(&self, position: Vec3, size: Vec2) -> Vec2 {
+        (Vec2::new(position.x, position.z) - self.center) / self.scale + size / 2.
+    }
+
+    /// The world X and Z drawn at `point` on a window of `size`.
+    fn to_world(&self, point: Vec2, size: Vec2) -> Vec2 {
+        (point - size / 2.) * self.scale + self.center
+    }
+}
+
+/// Marks the root of the sector map, which is despawned when it closes.
+#[derive(Component, Debug)]
+struct SectorMapRoot;
+
+/// Marks the node that markers are placed within.
+#[derive(Component, Debug)]
+struct MapPanel;
+
+/// Marks the text describing the map's scale.
+#[derive(Component, Debug)]
+struct MapScaleText;
+
+/// Marks the waypoint set on the map, which is replaced when another is set.
+#[derive(Component, Debug)]
+struct MapWaypoint;
+
+/// Something shown on the sector map.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MapMarker {
+    /// A station.
+    Station(Entity),
+    /// A waypoint.
+    Waypoint(Entity),
+    /// A hostile ship within radar range.
+    Hostile(Entity),
+    /// The player's ship.
+    Player,
+    /// The tick ahead of the player's ship showing which way it is heading.
+    Heading,
+    /// One of the dots marking the edge of the asteroid field.
+    Boundary(usize),
+}
+
+/// How a marker is drawn.
+#[derive(Debug, Clone)]
+struct MarkerLayout {
+    /// Where its middle is, in logical pixels.
+    position: Vec2,
+    /// Its width and height, in pixels.
+    size: f32,
+    /// Its color.
+    color: Color,
+    /// The name written beside it, if any.
+    label: Option<String>,
+}
+
+/// Asks for the sector map to open when the player presses [`FlightAction::SectorMap`].
+fn request_sector_map(action_state: Res<ActionState<FlightAction>>, mut map: ResMut<SectorMap>) {
+    if action_state.just_pressed(FlightAction::SectorMap) {
+        map.requested = true;
+    }
+}
+
+/// Opens the sector map on request, centered on the player's ship and taking the keyboard away
+/// from the game, and closes it again when the player presses [`FlightAction::SectorMap`]'s
+/// inputs or [`MenuAction::Back`].
+#[allow(clippy::too_many_arguments)]
+fn toggle_sector_map(
+    mut commands: Commands,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    input_map: Res<InputMap<FlightAction>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut map: ResMut<SectorMap>,
+    mut focus: ResMut<KeyboardFocus>,
+    player_query: Query<&Transform, With<PlayerShip>>,
+    root_query: Query<Entity, With<SectorMapRoot>>,
+) {
+    if map.open {
+        // Flight actions are not triggered while the map has the keyboard, so look for their inputs
+        let toggled =
+            input_map
+                .bindings(FlightAction::SectorMap)
+                .iter()
+                .any(|&input| match input {
+                    InputKind::Keyboard(key) => keyboard.just_pressed(key),
+                    InputKind::Mouse(button) => mouse_buttons.just_pressed(button),
+                    InputKind::GamepadButton(button) => gamepad_buttons
+                        .get_just_pressed()
+                        .any(|pressed| pressed.button_type == button),
+                    InputKind::GamepadChord(modifier, button) => {
+                        gamepad_buttons.get_just_pressed().any(|pressed| {
+                            pressed.button_type == button
+                                && gamepad_buttons
+                                    .pressed(GamepadButton::new(pressed.gamepad, modifier))
+                        })
+                    }
+                    // Sticks are for flying, and are never bound to the map
+                    InputKind::GamepadAxis(..) => false,
+                });
+        if toggled || menu_actions.just_pressed(MenuAction::Back) {
+            map.open = false;
+            map.drag = None;
+            *focus = KeyboardFocus::Game;
+            for entity in root_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        return;
+    }
+    if !std::mem::take(&mut map.requested) || *focus != KeyboardFocus::Game {
+        return;
+    }
+    map.open = true;
+    *focus = KeyboardFocus::Menu;
+    if let Ok(player) = player_query.get_single() {
+        map.center = Vec2::new(player.translation.x, player.translation.z);
+    }
+
+    let close_keys: Vec<String> = input_map
+        .bindings(FlightAction::SectorMap)
+        .iter()
+        .map(|input| input.label())
+        .chain(["Escape".to_string()])
+        .collect();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                background_color: Color::rgba(0.01, 0.02, 0.05, 0.92).into(),
+                ..default()
+            },
+            SectorMapRoot,
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MapPanel,
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    "SECTOR MAP",
+                    TextStyle {
+                        font_size: 28.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.),
+                    left: Val::Px(20.),
+                    ..default()
+                }),
+            );
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(56.),
+                    left: Val::Px(20.),
+                    ..default()
+                }),
+                MapScaleText,
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "Drag to pan, scroll to zoom and click to set a waypoint. \
+                         Press {} to close the map.",
+                        close_keys.join(" or ")
+                    ),
+                    TextStyle {
+                        font_size: 16.,
+                        color: Color::GRAY,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.),
+                    left: Val::Px(20.),
+                    ..default()
+                }),
+            );
+        });
+}
+
+/// Gives the keyboard back to the game if play ends with the map open.
+fn close_sector_map(mut map: ResMut<SectorMap>, mut focus: ResMut<KeyboardFocus>) {
+    if map.open {
+        map.open = false;
+        map.drag = None;
+        *focus = KeyboardFocus::Game;
+    }
+    map.requested = false;
+}
+
+/// Pans the open map while the mouse is dragged across it and zooms it around the cursor as the
+/// wheel is scrolled, and sets a waypoint where it is clicked.
+#[allow(clippy::too_many_arguments)]
+fn pan_and_zoom(
+    mut commands: Commands,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut map: ResMut<SectorMap>,
+    mut last_cursor: Local<Option<Vec2>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut player_query: Query<(&Transform, &mut Autopilot), With<PlayerShip>>,
+    map_waypoint_query: Query<Entity, With<MapWaypoint>>,
+) {
+    let scrolled = mouse_wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum::<f32>();
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let cursor = window.cursor_position();
+    let previous_cursor = std::mem::replace(&mut *last_cursor, cursor);
+    if !map.open {
+        return;
+    }
+    let size = Vec2::new(window.width(), window.height());
+
+    if let Some(cursor) = cursor {
+        // Zoom around the cursor, so the point under it stays put
+        if scrolled != 0. {
+            let anchor = map.to_world(cursor, size);
+            map.scale =
+                (map.scale * ZOOM_PER_SCROLL_LINE.powf(-scrolled)).clamp(MIN_SCALE, MAX_SCALE);
+            let drift = map.to_world(cursor, size) - anchor;
+            map.center -= drift;
+        }
+
+        if mouse_buttons.just_pressed(MouseButton::Left) {
+            map.drag = Some((cursor, false));
+        }
+        if let Some((start, dragging)) = map.drag {
+            let dragging = dragging || start.distance(cursor) > CLICK_TOLERANCE;
+            if dragging {
+                let moved = cursor - previous_cursor.unwrap_or(cursor);
+                map.center -= moved * map.scale;
+            }
+            map.drag = Some((start, dragging));
+        }
+    }
+
+    if !mouse_buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some((start, dragging)) = map.drag.take() else {
+        return;
+    };
+    let Ok((player, mut autopilot)) = player_query.get_single_mut() else {
+        return;
+    };
+    if dragging {
+        return;
+    }
+
+    for entity in map_waypoint_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    // The map is flat, so the waypoint is set at the ship's own height
+    let point = map.to_world(start, size);
+    let waypoint = commands
+        .spawn((
+            WaypointBundle::new(
+                MAP_WAYPOINT_NAME,
+                Vec3::new(point.x, player.translation.y, point.y),
+            ),
+            MapWaypoint,
+            InSector,
+        ))
+        .id();
+    autopilot.select(Some(waypoint));
+}
+
+/// Moves, spawns and despawns the open map's markers to match the sector, and describes its scale.
+#[allow(clippy::too_many_arguments)]
+fn update_map_markers(
+    mut commands: Commands,
+    map: Res<SectorMap>,
+    theme: Res<HudTheme>,
+    radar: Res<RadarSettings>,
+    index: Res<SpatialIndex>,
+    current_sector: Res<CurrentSector>,
+    definitions: Res<Assets<SectorDefinition>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<(&Transform, Option<&Autopilot>), With<PlayerShip>>,
+    station_query: Query<(Entity, &Transform, &Station)>,
+    waypoint_query: Query<(Entity, &Transform, &Waypoint)>,
+    contact_query: Query<(&Transform, &Disposition), With<Targetable>>,
+    panel_query: Query<Entity, With<MapPanel>>,
+    mut marker_query: Query<(Entity, &MapMarker, &mut Style, &mut BackgroundColor)>,
+    mut scale_query: Query<&mut Text, With<MapScaleText>>,
+) {
+    let (Ok(window), Ok(panel)) = (window_query.get_single(), panel_query.get_single()) else {
+        return;
+    };
+    if !map.open {
+        return;
+    }
+    let size = Vec2::new(window.width(), window.height());
+
+    for mut text in scale_query.iter_mut() {
+        let value = format!("{:.0} m across", size.x * map.scale);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+
+    let mut layouts = HashMap::new();
+    let mut place = |marker, position, size, color, label: Option<&str>| {
+        layouts.insert(
+            marker,
+            MarkerLayout {
+                position,
+                size,
+                color,
+                label: label.map(str::to_string),
+            },
+        );
+    };
+
+    if let Some(field) = current_sector
+        .sector()
+        .and_then(|sector| definitions.get(sector))
+        .map(|definition| &definition.asteroid_field)
+        .filter(|field| field.count > 0)
+    {
+        let center = Vec3::from(field.center);
+        for dot in 0..BOUNDARY_DOTS {
+            let angle = dot as f32 / BOUNDARY_DOTS as f32 * std::f32::consts::TAU;
+            let point = center + Vec3::new(angle.cos(), 0., angle.sin()) * field.radius;
+            let position = map.to_screen(point, size);
+            place(MapMarker::Boundary(dot), position, 2., BOUNDARY_COLOR, None);
+        }
+    }
+
+    for (station, transform, Station { name, .. }) in station_query.iter() {
+        let position = map.to_screen(transform.translation, size);
+        place(
+            MapMarker::Station(station),
+            position,
+            10.,
+            STATION_COLOR,
+            Some(name.as_str()),
+        );
+    }
+
+    let player = player_query.get_single().ok();
+    let selected = player.and_then(|(_, autopilot)| autopilot?.waypoint());
+    for (waypoint, transform, Waypoint { name, .. }) in waypoint_query.iter() {
+        let color = if selected == Some(waypoint) {
+            SELECTED_WAYPOINT_COLOR
+        } else {
+            WAYPOINT_COLOR
+        };
+        let position = map.to_screen(transform.translation, size);
+        place(
+            MapMarker::Waypoint(waypoint),
+            position,
+            8.,
+            color,
+            Some(name.as_str()),
+        );
+    }
+
+    if let Some((player, _)) = player {
+        for (contact, _) in index.within(player.translation, radar.range) {
+            let Ok((transform, &Disposition::Hostile)) = contact_query.get(contact) else {
+                continue;
+            };
+            let position = map.to_screen(transform.translation, size);
+            place(
+                MapMarker::Hostile(contact),
+                position,
+                6.,
+                theme.disposition(Disposition::Hostile),
+                None,
+            );
+        }
+
+        let position = map.to_screen(player.translation, size);
+        let forward = player.forward();
+        let heading = Vec2::new(forward.x, forward.z).normalize_or_zero() * HEADING_LENGTH;
+        place(MapMarker::Player, position, 8., PLAYER_COLOR, Some("You"));
+        place(
+            MapMarker::Heading,
+            position + heading,
+            3.,
+            PLAYER_COLOR,
+            None,
+        );
+    }
+
+    // Markers are placed by percentage, so they land in the right place however the UI is scaled
+    let place_style = |style: &mut Style, layout: &MarkerLayout| {
+        let half = layout.size / 2.;
+        style.position_type = PositionType::Absolute;
+        style.left = Val::Percent(layout.position.x / size.x * 100.);
+        style.top = Val::Percent(layout.position.y / size.y * 100.);
+        style.margin = UiRect {
+            left: Val::Px(-half),
+            top: Val::Px(-half),
+            ..default()
+        };
+        style.width = Val::Px(layout.size);
+        style.height = Val::Px(layout.size);
+    };
+
+    for (entity, marker, mut style, mut background) in marker_query.iter_mut() {
+        match layouts.remove(marker) {
+            Some(layout) => {
+                place_style(&mut style, &layout);
+                background.0 = layout.color;
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    for (marker, layout) in layouts {
+        let mut style = Style::default();
+        place_style(&mut style, &layout);
+
+        let mut entity = commands.spawn((
+            NodeBundle {
+                style,
+                background_color: layout.color.into(),
+                ..default()
+            },
+            marker,
+        ));
+        if let Some(label) = layout.label {
+            entity.with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(
+                        label,
+                        TextStyle {
+                            font_size: 14.,
+                            color: layout.color,
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(layout.size + 4.),
+                        top: Val::Px(-4.),
+                        ..default()
+                    }),
+                );
+            });
+        }
+        let entity = entity.id();
+        commands.entity(panel).add_child(entity);
+    }
+}
@@ -0,0 +1,82 @@
+//! A readout of the player's slow motion: how long it has left while engaged, and how long until it
+//! can be used again.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::time_control::{SlowMotion, TimeScale};
+
+/// The color of the readout while slow motion is ready or engaged.
+const READY_COLOR: Color = Color::rgb(0.5, 0.85, 1.);
+
+/// The color of the readout while slow motion is cooling down.
+const COOLING_COLOR: Color = Color::GRAY;
+
+/// Slow motion HUD logic
+pub(super) struct SlowMotionHudPlugin;
+
+impl Plugin for SlowMotionHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_slow_motion_readout)
+            .add_systems(Update, update_slow_motion_readout);
+    }
+}
+
+/// Marks the text showing the state of the player's slow motion.
+#[derive(Component, Debug)]
+struct SlowMotionReadout;
+
+/// Spawns the slow motion readout above the countermeasure count.
+fn spawn_slow_motion_readout(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: READY_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(340.),
+            bottom: Val::Px(44.),
+            ..default()
+        }),
+        SlowMotionReadout,
+        InGame,
+    ));
+}
+
+/// Shows how much of the player's slow motion is left while engaged, and otherwise whether it is
+/// ready or how long until it is. Also notes when time has been paused from the console.
+fn update_slow_motion_readout(
+    time_scale: Res<TimeScale>,
+    player_query: Query<&SlowMotion, With<PlayerShip>>,
+    mut text_query: Query<&mut Text, With<SlowMotionReadout>>,
+) {
+    let (readout, color) = match player_query.get_single() {
+        _ if time_scale.is_paused() => ("PAUSED".to_string(), READY_COLOR),
+        Ok(slow_motion) if slow_motion.is_engaged() => (
+            format!("SLOW-MO {:.0}%", slow_motion.remaining_fraction() * 100.),
+            READY_COLOR,
+        ),
+        Ok(slow_motion) if slow_motion.cooldown() > 0. => (
+            format!("SLOW-MO {:.0}s", slow_motion.cooldown().ceil()),
+            COOLING_COLOR,
+        ),
+        Ok(_) => ("SLOW-MO READY".to_string(), READY_COLOR),
+        Err(_) => (String::new(), READY_COLOR),
+    };
+
+    for mut text in text_query.iter_mut() {
+        let section = &mut text.sections[0];
+        if section.value != readout {
+            section.value = readout.clone();
+        }
+        if section.style.color != color {
+            section.style.color = color;
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! A bracket around the player's current target, colored by whether it is friend or foe.
+
+use bevy::prelude::*;
+
+use crate::accessibility::HudTheme;
+use crate::game_state::{GameState, InGame};
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::{CurrentTarget, Disposition};
+use crate::simulation::factions::Faction;
+
+/// The width and height of the bracket, in pixels.
+const BRACKET_SIZE: f32 = 36.;
+
+/// Targeting HUD logic
+pub(super) struct TargetingHudPlugin;
+
+impl Plugin for TargetingHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_target_bracket)
+            .add_systems(Update, update_target_bracket);
+    }
+}
+
+/// Marks the bracket drawn around the current target.
+#[derive(Component, Debug)]
+struct TargetBracket;
+
+/// Marks the text under the bracket naming the target's faction and distance.
+#[derive(Component, Debug)]
+struct TargetLabel;
+
+/// Spawns the bracket, hidden until there is a target on screen.
+fn spawn_target_bracket(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(BRACKET_SIZE),
+                    height: Val::Px(BRACKET_SIZE),
+                    border: UiRect::all(Val::Px(2.)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TargetBracket,
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(BRACKET_SIZE + 2.),
+                    ..default()
+                }),
+                TargetLabel,
+            ));
+        });
+}
+
+/// Places the bracket around the current target and colors it by the target's disposition.
+fn update_target_bracket(
+    current_target: Res<CurrentTarget>,
+    theme: Res<HudTheme>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    player_query: Query<&GlobalTransform, With<PlayerShip>>,
+    target_query: Query<(&GlobalTransform, Option<&Disposition>, Option<&Faction>)>,
+    mut bracket_query: Query<(&mut Style, &mut BorderColor, &mut Visibility), With<TargetBracket>>,
+    mut label_query: Query<&mut Text, With<TargetLabel>>,
+) {
+    let Ok((mut style, mut border, mut visibility)) = bracket_query.get_single_mut() else {
+        return;
+    };
+    let target = current_target
+        .entity()
+        .and_then(|target| target_query.get(target).ok());
+    let (Some((transform, disposition, faction)), Ok((camera, camera_transform))) =
+        (target, camera_query.get_single())
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let Some(position) = camera.world_to_viewport(camera_transform, transform.translation()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let color = theme.disposition(disposition.copied().unwrap_or_default());
+    style.left = Val::Px(position.x - BRACKET_SIZE / 2.);
+    style.top = Val::Px(position.y - BRACKET_SIZE / 2.);
+    border.0 = color;
+
+    let distance = player_query
+        .get_single()
+        .map(|player| player.translation().distance(transform.translation()))
+        .unwrap_or_default();
+    let label = format!(
+        "{} {distance:.0} m",
+        faction.map_or("Unknown", |faction| faction.name())
+    );
+    for mut text in label_query.iter_mut() {
+        text.sections[0].value = label.clone();
+        text.sections[0].style.color = color;
+    }
+}
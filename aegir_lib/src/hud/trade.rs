@@ -0,0 +1,307 @@
+//! The trade screen, where the player buys and sells commodities at the station they are docked
+//! with.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{ActionState, KeyboardFocus, MenuAction};
+use crate::player::ship::PlayerShip;
+use crate::simulation::economy::{Commodity, Market, TradeKind, TradeOrder, Trader};
+use crate::simulation::factions::{Faction, Reputation, HOSTILE_STANDING};
+use crate::simulation::mining::Inventory;
+use crate::simulation::stations::{DockingComputer, Station};
+
+/// The key that opens and closes the trade screen while docked.
+const TRADE_KEY: KeyCode = KeyCode::B;
+
+/// How many units each press of a buy or sell button trades.
+const TRADE_LOT: f32 = 5.;
+
+/// The color of buttons that are not under the cursor.
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+
+/// The color of buttons under the cursor.
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+/// The color of buttons being pressed.
+const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.2, 0.45, 0.8);
+
+/// Trade screen logic
+pub(super) struct TradeHudPlugin;
+
+impl Plugin for TradeHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TradeScreen>()
+            .add_systems(OnExit(GameState::Playing), close_trade_screen)
+            .add_systems(
+                Update,
+                (
+                    toggle_trade_screen.run_if(in_state(GameState::Playing)),
+                    close_trade_screen_on_launch,
+                    place_orders,
+                    color_trade_buttons,
+                    update_listing_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether the trade screen is open.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TradeScreen {
+    /// Is the screen shown, with the keyboard taken from the game?
+    open: bool,
+}
+
+/// Marks the root of the trade screen, which is despawned when it closes.
+#[derive(Component, Debug)]
+struct TradeScreenRoot;
+
+/// Marks the text showing the station's prices for a commodity, and how much the player holds.
+#[derive(Component, Debug, Clone, Copy)]
+struct ListingText(Commodity);
+
+/// A button that asks to buy or sell a lot of a commodity when pressed.
+#[derive(Component, Debug, Clone, Copy)]
+struct TradeButton {
+    /// Whether the button buys or sells.
+    kind: TradeKind,
+    /// What the button trades.
+    commodity: Commodity,
+}
+
+/// Opens and closes the trade screen with `B` while docked, taking the keyboard away from the game
+/// while open. [`MenuAction::Back`] also closes it.
+///
+/// The screen is built afresh each time it opens, listing whatever the station trades.
+#[allow(clippy::too_many_arguments)]
+fn toggle_trade_screen(
+    mut commands: Commands,
+    keyboard: Res<Input<KeyCode>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut screen: ResMut<TradeScreen>,
+    mut focus: ResMut<KeyboardFocus>,
+    player_query: Query<&DockingComputer, With<PlayerShip>>,
+    station_query: Query<(&Station, &Market)>,
+    root_query: Query<Entity, With<TradeScreenRoot>>,
+) {
+    let closing = screen.open && menu_actions.just_pressed(MenuAction::Back);
+    if !keyboard.just_pressed(TRADE_KEY) && !closing {
+        return;
+    }
+
+    if screen.open {
+        screen.open = false;
+        *focus = KeyboardFocus::Game;
+        for entity in root_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    // Leave the keyboard alone while something else, such as the console, has it
+    if *focus != KeyboardFocus::Game {
+        return;
+    }
+    let Some((station, market)) = player_query
+        .get_single()
+        .ok()
+        .and_then(DockingComputer::docked)
+        .and_then(|station| station_query.get(station).ok())
+    else {
+        return;
+    };
+    screen.open = true;
+    *focus = KeyboardFocus::Menu;
+
+    let text_style = TextStyle {
+        font_size: 18.,
+        color: Color::WHITE,
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            TradeScreenRoot,
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("{} MARKET", station.name.to_uppercase()),
+                TextStyle {
+                    font_size: 28.,
+                    ..text_style.clone()
+                },
+            ));
+            for listing in market.listings() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(640.),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            TextBundle::from_section("", text_style.clone()).with_style(Style {
+                                flex_grow: 1.,
+                                ..default()
+                            }),
+                            ListingText(listing.commodity),
+                        ));
+                        for (kind, label) in [(TradeKind::Buy, "BUY"), (TradeKind::Sell, "SELL")] {
+                            row.spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+                                        ..default()
+                                    },
+                                    background_color: BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                                TradeButton {
+                                    kind,
+                                    commodity: listing.commodity,
+                                },
+                            ))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section(
+                                    format!("{label} {TRADE_LOT:.0}"),
+                                    text_style.clone(),
+                                ));
+                            });
+                        }
+                    });
+            }
+            parent.spawn(TextBundle::from_section(
+                "Prices are what the station charges, then pays. Press B or Escape to close.",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Gives the keyboard back to the game if play ends with the trade screen open.
+fn close_trade_screen(mut screen: ResMut<TradeScreen>, mut focus: ResMut<KeyboardFocus>) {
+    if screen.open {
+        screen.open = false;
+        *focus = KeyboardFocus::Game;
+    }
+}
+
+/// Closes the trade screen if the player is no longer docked.
+fn close_trade_screen_on_launch(
+    mut commands: Commands,
+    mut screen: ResMut<TradeScreen>,
+    mut focus: ResMut<KeyboardFocus>,
+    player_query: Query<&DockingComputer, With<PlayerShip>>,
+    root_query: Query<Entity, With<TradeScreenRoot>>,
+) {
+    let docked = player_query
+        .get_single()
+        .is_ok_and(|computer| computer.docked().is_some());
+    if !screen.open || docked {
+        return;
+    }
+
+    screen.open = false;
+    *focus = KeyboardFocus::Game;
+    for entity in root_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Asks for a lot of a commodity to be bought or sold when its button is pressed.
+fn place_orders(
+    button_query: Query<(&Interaction, &TradeButton), Changed<Interaction>>,
+    mut player_query: Query<&mut Trader, With<PlayerShip>>,
+) {
+    let Ok(mut trader) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for (interaction, button) in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            trader.orders.push(TradeOrder {
+                kind: button.kind,
+                commodity: button.commodity,
+                amount: TRADE_LOT,
+            });
+        }
+    }
+}
+
+/// Colors each buy and sell button by whether it is under the cursor or being pressed.
+fn color_trade_buttons(
+    mut query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (With<TradeButton>, Changed<Interaction>),
+    >,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON_COLOR,
+            Interaction::Hovered => HOVERED_BUTTON_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+/// Shows what the docked station is charging and paying for each commodity, given its faction's
+/// standing with the player's, and how much of each the player holds.
+fn update_listing_text(
+    reputation: Res<Reputation>,
+    player_query: Query<(&DockingComputer, &Inventory, Option<&Faction>), With<PlayerShip>>,
+    station_query: Query<(&Market, &Faction)>,
+    mut text_query: Query<(&mut Text, &ListingText)>,
+) {
+    let Ok((computer, inventory, faction)) = player_query.get_single() else {
+        return;
+    };
+    let Some((market, &station_faction)) = computer
+        .docked()
+        .and_then(|station| station_query.get(station).ok())
+    else {
+        return;
+    };
+    let standing = faction.map_or(0., |&faction| reputation.standing(faction, station_faction));
+
+    for (mut text, &ListingText(commodity)) in text_query.iter_mut() {
+        let held = inventory.amount(commodity);
+        let value = match market.listing(commodity) {
+            Some(_) if standing <= HOSTILE_STANDING => {
+                format!("{}  REFUSED  held {held:.1}", commodity.name())
+            }
+            Some(listing) => format!(
+                "{}  {:.1} / {:.1} cr  held {held:.1}",
+                commodity.name(),
+                listing.buy_price(standing),
+                listing.sell_price(standing),
+            ),
+            None => String::new(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
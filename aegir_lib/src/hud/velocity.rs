@@ -0,0 +1,203 @@
+//! Prograde and retrograde markers showing which way the player's ship is drifting, and how fast it
+//! is closing on its target.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::camera::ChaseCamera;
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::CurrentTarget;
+use crate::simulation::flight::Velocity;
+
+use super::project_marker;
+
+/// The color of the prograde marker.
+const PROGRADE_COLOR: Color = Color::rgb(0.9, 0.9, 0.4);
+
+/// The color of the retrograde marker.
+const RETROGRADE_COLOR: Color = Color::rgb(1., 0.6, 0.3);
+
+/// How fast the ship must be moving before its velocity is marked, in meters per second.
+const MIN_SPEED: f32 = 0.5;
+
+/// How far from the camera the velocity is projected from, in meters.
+///
+/// This only needs to be far enough that the camera's offset from the ship is negligible.
+const PROJECTION_DISTANCE: f32 = 10_000.;
+
+/// How far from the edge of the screen the markers are kept while off-screen.
+const SCREEN_MARGIN: f32 = 60.;
+
+/// Roughly half the size of a marker, used to center it on its direction.
+const MARKER_HALF_SIZE: Vec2 = Vec2::new(14., 10.);
+
+/// Velocity HUD logic
+pub(super) struct VelocityHudPlugin;
+
+impl Plugin for VelocityHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_velocity_hud)
+            .add_systems(Update, (update_velocity_markers, update_closing_speed));
+    }
+}
+
+/// A marker over the direction the player's ship is moving in, or the opposite direction.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum VelocityMarker {
+    /// Marks the direction of travel.
+    Prograde,
+    /// Marks the direction opposite to travel, to point the nose at when braking.
+    Retrograde,
+}
+
+impl VelocityMarker {
+    /// The symbol drawn for the marker.
+    fn symbol(self) -> &'static str {
+        match self {
+            VelocityMarker::Prograde => "(+)",
+            VelocityMarker::Retrograde => "(x)",
+        }
+    }
+}
+
+/// Marks the text showing how fast the player is closing on their target.
+#[derive(Component, Debug)]
+struct ClosingSpeedReadout;
+
+/// Spawns the velocity markers, hidden until the ship moves, and the closing speed readout below
+/// the middle of the screen.
+fn spawn_velocity_hud(mut commands: Commands) {
+    for (marker, color) in [
+        (VelocityMarker::Prograde, PROGRADE_COLOR),
+        (VelocityMarker::Retrograde, RETROGRADE_COLOR),
+    ] {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    marker.symbol(),
+                    TextStyle {
+                        font_size: 16.,
+                        color,
+                        ..default()
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            marker,
+            InGame,
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(58.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                ClosingSpeedReadout,
+            ));
+        });
+}
+
+/// Places the prograde and retrograde markers over the directions the player's ship is moving to
+/// and from, or at the screen edge nearest to them with an arrow.
+fn update_velocity_markers(
+    camera_query: Query<(&Camera, &GlobalTransform), With<ChaseCamera>>,
+    player_query: Query<&Velocity, With<PlayerShip>>,
+    mut marker_query: Query<(&VelocityMarker, &mut Text, &mut Style, &mut Visibility)>,
+) {
+    let direction = player_query
+        .get_single()
+        .ok()
+        .and_then(|velocity| (velocity.0.length() >= MIN_SPEED).then_some(velocity.0))
+        .and_then(Vec3::try_normalize);
+    let camera = camera_query.get_single().ok();
+
+    for (&marker, mut text, mut style, mut visibility) in marker_query.iter_mut() {
+        let (Some(direction), Some((camera, camera_transform))) = (direction, camera) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let direction = match marker {
+            VelocityMarker::Prograde => direction,
+            VelocityMarker::Retrograde => -direction,
+        };
+        let point = camera_transform.translation() + direction * PROJECTION_DISTANCE;
+        let Some((position, arrow)) =
+            project_marker(camera, camera_transform, point, SCREEN_MARGIN)
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let corner = position - MARKER_HALF_SIZE;
+        style.left = Val::Px(corner.x);
+        style.top = Val::Px(corner.y);
+        let label = match arrow {
+            Some(arrow) => format!("{arrow}{}", marker.symbol()),
+            None => marker.symbol().to_string(),
+        };
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
+/// Shows how fast the player's ship is closing on its current target, or opening the distance,
+/// from the velocity of each along the line between them.
+fn update_closing_speed(
+    current_target: Res<CurrentTarget>,
+    player_query: Query<(&GlobalTransform, &Velocity), With<PlayerShip>>,
+    target_query: Query<(&GlobalTransform, Option<&Velocity>)>,
+    mut text_query: Query<&mut Text, With<ClosingSpeedReadout>>,
+) {
+    let target = current_target
+        .entity()
+        .and_then(|target| target_query.get(target).ok());
+    let readout = match (player_query.get_single(), target) {
+        (Ok((player_transform, player_velocity)), Some((target_transform, target_velocity))) => {
+            let line_of_sight = (target_transform.translation() - player_transform.translation())
+                .normalize_or_zero();
+            // Targets without a velocity, such as stations, stay put
+            let relative =
+                target_velocity.map_or(Vec3::ZERO, |velocity| velocity.0) - player_velocity.0;
+            let closing = -relative.dot(line_of_sight);
+            if closing >= 0. {
+                format!("CLOSING {closing:.0} m/s")
+            } else {
+                format!("OPENING {:.0} m/s", -closing)
+            }
+        }
+        _ => String::new(),
+    };
+
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != readout {
+            text.sections[0].value = readout.clone();
+        }
+    }
+}
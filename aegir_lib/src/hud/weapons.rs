@@ -0,0 +1,135 @@
+//! Heat bars for each of the player ship's weapons.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::ship::PlayerShip;
+use crate::simulation::weapons::{Hardpoint, Heat};
+
+/// The color of a heat bar for a cool weapon.
+const COOL_COLOR: Color = Color::rgb(1., 0.85, 0.3);
+
+/// The color of a heat bar for a weapon about to overheat.
+const HOT_COLOR: Color = Color::rgb(1., 0.2, 0.1);
+
+/// Weapon HUD logic
+pub(super) struct WeaponHudPlugin;
+
+impl Plugin for WeaponHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_heat_panel)
+            .add_systems(Update, (add_heat_bars, update_heat_bars).chain());
+    }
+}
+
+/// Marks the node that holds every heat bar.
+#[derive(Component, Debug)]
+struct HeatPanel;
+
+/// Marks the node whose width shows how hot the weapon on a hardpoint is.
+#[derive(Component, Debug)]
+struct HeatBarFill {
+    /// The hardpoint whose weapon is shown.
+    hardpoint: Entity,
+}
+
+/// Spawns the empty panel for heat bars, to the right of the energy bar.
+fn spawn_heat_panel(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(240.),
+                bottom: Val::Px(20.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                ..default()
+            },
+            ..default()
+        },
+        HeatPanel,
+        InGame,
+    ));
+}
+
+/// Adds a heat bar for each weapon mounted on the player's ship.
+fn add_heat_bars(
+    mut commands: Commands,
+    panel_query: Query<Entity, With<HeatPanel>>,
+    player_query: Query<(), With<PlayerShip>>,
+    hardpoint_query: Query<(Entity, &Parent, &Hardpoint), Added<Heat>>,
+) {
+    let Ok(panel) = panel_query.get_single() else {
+        return;
+    };
+
+    let mut hardpoints: Vec<_> = hardpoint_query
+        .iter()
+        .filter(|(_, parent, _)| player_query.contains(parent.get()))
+        .collect();
+    hardpoints.sort_by_key(|(_, _, hardpoint)| hardpoint.slot);
+
+    commands.entity(panel).with_children(|parent| {
+        for (entity, _, _) in hardpoints {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(80.),
+                        height: Val::Px(6.),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: COOL_COLOR.into(),
+                            ..default()
+                        },
+                        HeatBarFill { hardpoint: entity },
+                    ));
+                });
+        }
+    });
+}
+
+/// Resizes and recolors each heat bar, flashing it while its weapon is locked by overheating.
+fn update_heat_bars(
+    time: Res<Time>,
+    heat_query: Query<&Heat>,
+    mut bar_query: Query<(&HeatBarFill, &mut Style, &mut BackgroundColor)>,
+) {
+    let flash = (time.elapsed_seconds() * 8.).sin() > 0.;
+
+    for (fill, mut style, mut color) in bar_query.iter_mut() {
+        let Ok(heat) = heat_query.get(fill.hardpoint) else {
+            continue;
+        };
+
+        let fraction = heat.fraction();
+        style.width = Val::Percent(fraction * 100.);
+        color.0 = if heat.is_overheated() {
+            if flash {
+                HOT_COLOR
+            } else {
+                Color::WHITE
+            }
+        } else {
+            lerp_color(COOL_COLOR, HOT_COLOR, fraction)
+        };
+    }
+}
+
+/// Blends between two colors, component by component.
+fn lerp_color(from: Color, to: Color, amount: f32) -> Color {
+    let [r, g, b, a] = Vec4::from(from.as_rgba_f32())
+        .lerp(Vec4::from(to.as_rgba_f32()), amount)
+        .to_array();
+    Color::rgba(r, g, b, a)
+}
@@ -0,0 +1,134 @@
+//! The wing command menu, listing the orders the player can give their wingmen, and a reminder of
+//! the last order given.
+
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{FlightAction, InputMap};
+use crate::player::ship::{PlayerShip, WingCommandMenu};
+use crate::simulation::wingmen::{WingCommander, Wingman};
+
+/// The size of the readout's text.
+const FONT_SIZE: f32 = 16.;
+
+/// The color of the menu's heading and the last order given.
+const HEADING_COLOR: Color = Color::rgb(0.3, 0.6, 1.);
+
+/// The color of the orders listed in the menu.
+const ORDER_COLOR: Color = Color::WHITE;
+
+/// The orders listed in the menu, with the actions that give them.
+const ORDERS: [(FlightAction, &str); 4] = [
+    (FlightAction::OrderAttack, "Attack my target"),
+    (FlightAction::OrderFormUp, "Form up"),
+    (FlightAction::OrderEngage, "Break and engage"),
+    (FlightAction::OrderDefend, "Defend me"),
+];
+
+/// Wing command HUD logic
+pub(super) struct WingmenHudPlugin;
+
+impl Plugin for WingmenHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_wing_readout)
+            .add_systems(Update, update_wing_readout);
+    }
+}
+
+/// Marks the text showing the wing command menu, or the last order given.
+#[derive(Component, Debug)]
+struct WingReadout;
+
+/// Spawns the wing readout on the left of the screen.
+fn spawn_wing_readout(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: FONT_SIZE,
+                color: HEADING_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.),
+            top: Val::Percent(45.),
+            ..default()
+        }),
+        WingReadout,
+        InGame,
+    ));
+}
+
+/// Lists the orders and the keys that give them while the wing command menu is open, and
+/// otherwise shows the last order given, as long as the player has any wingmen.
+fn update_wing_readout(
+    menu: Res<WingCommandMenu>,
+    input_map: Res<InputMap<FlightAction>>,
+    player_query: Query<(Entity, &WingCommander), With<PlayerShip>>,
+    wingmen_query: Query<&Wingman>,
+    mut text_query: Query<&mut Text, With<WingReadout>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    let (leader, commander) = match player_query.get_single() {
+        Ok((leader, commander)) => (Some(leader), Some(commander)),
+        Err(_) => (None, None),
+    };
+    let wingmen = wingmen_query
+        .iter()
+        .filter(|wingman| Some(wingman.leader) == leader)
+        .count();
+
+    let sections: Vec<(String, Color)> = match commander {
+        Some(_) if menu.open => {
+            let heading = if wingmen == 0 {
+                "WING COMMANDS (NO WINGMEN)\n".to_string()
+            } else {
+                "WING COMMANDS\n".to_string()
+            };
+            std::iter::once((heading, HEADING_COLOR))
+                .chain(ORDERS.iter().map(|&(action, name)| {
+                    let key = input_map
+                        .bindings(action)
+                        .first()
+                        .map_or("-".to_string(), |input| input.label());
+                    (format!("{key}  {name}\n"), ORDER_COLOR)
+                }))
+                .collect()
+        }
+        Some(commander) if wingmen > 0 => vec![(
+            format!(
+                "WING ({wingmen}): {}",
+                commander.order().name().to_uppercase()
+            ),
+            HEADING_COLOR,
+        )],
+        _ => Vec::new(),
+    };
+
+    let unchanged = text.sections.len() == sections.len()
+        && text
+            .sections
+            .iter()
+            .zip(&sections)
+            .all(|(section, (value, _))| section.value == *value);
+    if unchanged {
+        return;
+    }
+    text.sections = sections
+        .into_iter()
+        .map(|(value, color)| {
+            TextSection::new(
+                value,
+                TextStyle {
+                    font_size: FONT_SIZE,
+                    color,
+                    ..default()
+                },
+            )
+        })
+        .collect();
+}
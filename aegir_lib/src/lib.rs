@@ -6,5 +6,34 @@
 // Often exceeded by queries
 #![allow(clippy::type_complexity)]
 
+pub mod accessibility;
+pub mod benchmark;
+pub mod debug;
+pub mod game_state;
 pub mod graphics;
+pub mod hud;
+pub mod menus;
+pub mod net;
 pub mod player;
+pub mod pooling;
+pub mod replay;
+pub mod simulation;
+pub mod sound;
+pub mod stats;
+
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+
+/// The game logic that runs without a window or a player at the keyboard, as on a dedicated
+/// server or a headless benchmark.
+///
+/// Bevy's own plugins, such as `MinimalPlugins` and an `AssetPlugin`, must be added alongside.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(game_state::GameStatePlugin)
+            .add(net::NetPlugin)
+            .add(simulation::SimulationPlugin)
+    }
+}
@@ -0,0 +1,198 @@
+//! The summary shown when a flight ends, with the session's statistics beside the lifetime totals.
+
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::player::input::{ActionState, InputContext, MenuAction};
+use crate::stats::{LifetimeStats, SessionStats};
+
+/// The color of the button that returns to the main menu.
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+
+/// Debrief logic
+pub(super) struct DebriefPlugin;
+
+impl Plugin for DebriefPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Debrief), spawn_debrief)
+            .add_systems(OnExit(GameState::Debrief), despawn_debrief)
+            .add_systems(Update, end_flight.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, return_to_menu.run_if(in_state(GameState::Debrief)));
+    }
+}
+
+/// Marks the root node of the debrief, so it can be cleaned up.
+#[derive(Component, Debug)]
+struct DebriefRoot;
+
+/// Marks the button that returns to the main menu.
+#[derive(Component, Debug)]
+struct ReturnButton;
+
+/// Formats a duration in seconds as hours, minutes and seconds.
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The rows of the debrief, as a label and a value for the session and for every session.
+fn rows(session: &SessionStats, lifetime: &LifetimeStats) -> Vec<(&'static str, String, String)> {
+    let totals = &lifetime.totals;
+    vec![
+        ("Kills", session.kills.to_string(), totals.kills.to_string()),
+        (
+            "Deaths",
+            session.deaths.to_string(),
+            totals.deaths.to_string(),
+        ),
+        (
+            "Damage dealt",
+            format!("{:.0}", session.damage_dealt),
+            format!("{:.0}", totals.damage_dealt),
+        ),
+        (
+            "Accuracy",
+            format!("{:.0}%", session.accuracy() * 100.),
+            format!("{:.0}%", totals.accuracy() * 100.),
+        ),
+        (
+            "Ore mined",
+            format!("{:.1}", session.ore_mined),
+            format!("{:.1}", totals.ore_mined),
+        ),
+        (
+            "Flight time",
+            format_duration(session.flight_time),
+            format_duration(totals.flight_time),
+        ),
+    ]
+}
+
+/// Spawns the debrief.
+fn spawn_debrief(mut commands: Commands, session: Res<SessionStats>, lifetime: Res<LifetimeStats>) {
+    let text_style = TextStyle {
+        font_size: 20.,
+        color: Color::WHITE,
+        ..default()
+    };
+    let column = |width: f32| Style {
+        width: Val::Px(width),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                ..default()
+            },
+            DebriefRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "DEBRIEF",
+                TextStyle {
+                    font_size: 48.,
+                    ..text_style.clone()
+                },
+            ));
+
+            let heading = (
+                "",
+                "This flight".to_string(),
+                format!("All {} flights", lifetime.sessions),
+            );
+            for (label, this_session, all_sessions) in
+                std::iter::once(heading).chain(rows(&session, &lifetime))
+            {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(16.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for (value, width) in [
+                            (label.to_string(), 160.),
+                            (this_session, 140.),
+                            (all_sessions, 140.),
+                        ] {
+                            row.spawn(
+                                TextBundle::from_section(value, text_style.clone())
+                                    .with_style(column(width)),
+                            );
+                        }
+                    });
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(240.),
+                            padding: UiRect::all(Val::Px(8.)),
+                            margin: UiRect::top(Val::Px(16.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    ReturnButton,
+                ))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        "Return to menu",
+                        text_style.clone(),
+                    ));
+                });
+        });
+}
+
+/// Removes the debrief.
+fn despawn_debrief(mut commands: Commands, query: Query<Entity, With<DebriefRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Ends the flight when the player presses `Escape` while flying or docked, rather than in a menu.
+fn end_flight(
+    keyboard: Res<Input<KeyCode>>,
+    context: Res<InputContext>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let in_play = matches!(*context, InputContext::Flight | InputContext::Docked);
+    if in_play && keyboard.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Debrief);
+    }
+}
+
+/// Returns to the main menu when the button or [`MenuAction::Select`] is pressed.
+fn return_to_menu(
+    query: Query<&Interaction, (Changed<Interaction>, With<ReturnButton>)>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let pressed = query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if pressed || menu_actions.just_pressed(MenuAction::Select) {
+        next_state.set(GameState::Menu);
+    }
+}
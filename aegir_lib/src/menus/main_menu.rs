@@ -0,0 +1,786 @@
+//! The menu shown when the game starts, used to fly solo or to host or join a co-op game, to
+//! choose the mission, the ship the player flies and the weapons, countermeasures and deployables
+//! fitted to it, and to change settings.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::accessibility::{AccessibilitySettings, HudTheme, Palette, UI_SCALES};
+use crate::game_state::GameState;
+use crate::graphics::post::{
+    GraphicsSettings, ASTEROID_DETAIL_LEVELS, DYNAMIC_LIGHT_BUDGETS, EXPOSURES, TONEMAPPERS,
+};
+use crate::hud::hit_feedback::HitFeedbackSettings;
+use crate::net::{Client, ConnectionMessage, NetConfig, Server};
+use crate::player::input::{BindingProfiles, FlightAction, InputMap, MouseSettings};
+use crate::player::loadout::Loadout;
+use crate::simulation::missions::{MissionDefinition, MissionLibrary, MissionSelection};
+use crate::simulation::ships::{ShipDefinition, ShipLibrary};
+use crate::simulation::weapons::{WeaponDefinition, WeaponLibrary};
+
+/// The color of menu buttons that are not being interacted with.
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+
+/// The color of menu buttons under the cursor.
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+/// The mouse sensitivities the settings cycle through.
+const MOUSE_SENSITIVITIES: [f32; 7] = [0.25, 0.5, 0.75, 1., 1.5, 2., 3.];
+
+/// The mouse acceleration used when it is switched on in the settings.
+const MOUSE_ACCELERATION: f32 = 1.;
+
+/// The actions the settings can switch between being held and toggled, with the names shown for
+/// them.
+const TOGGLEABLE_ACTIONS: [(FlightAction, &str); 4] = [
+    (FlightAction::Thrust, "Afterburner"),
+    (FlightAction::FireWeapons, "Fire weapons"),
+    (FlightAction::Mine, "Mining laser"),
+    (FlightAction::Activate, "Tractor beam"),
+];
+
+/// The most hardpoints the loadout can list; ships with more cannot fit weapons to the rest.
+const MAX_LISTED_HARDPOINTS: usize = 6;
+
+/// Main menu logic
+pub(super) struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Menu), spawn_main_menu)
+            .add_systems(OnExit(GameState::Menu), despawn_main_menu)
+            .add_systems(
+                Update,
+                (
+                    highlight_buttons,
+                    press_buttons,
+                    edit_server_address,
+                    show_connection_message,
+                    label_loadout,
+                    label_mission,
+                    label_settings,
+                )
+                    .run_if(in_state(GameState::Menu)),
+            );
+    }
+}
+
+/// Marks the root node of the main menu, so it can be cleaned up.
+#[derive(Component, Debug)]
+struct MainMenuRoot;
+
+/// What each main menu button does.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuButton {
+    /// Start a single-player game.
+    FlySolo,
+    /// Start a game that other players can join.
+    Host,
+    /// Join the game hosted at the address in [`NetConfig`].
+    Join,
+    /// Choose the next ship in the [`ShipLibrary`].
+    CycleShip,
+    /// Fit the next weapon to the hardpoint in this slot of the [`Loadout`].
+    CycleWeapon(usize),
+    /// Carry the next kind of countermeasure.
+    CycleCountermeasures,
+    /// Carry the next kind of deployable.
+    CycleDeployables,
+    /// Choose the next mission in the [`MissionLibrary`], or free flight.
+    CycleMission,
+    /// Show or hide damage numbers.
+    ToggleDamageNumbers,
+    /// Show or hide the hit marker.
+    ToggleHitMarkers,
+    /// Choose the next of the [`MOUSE_SENSITIVITIES`].
+    CycleMouseSensitivity,
+    /// Switch mouse acceleration on or off.
+    ToggleMouseAcceleration,
+    /// Invert the mouse's pitch, or put it back.
+    ToggleInvertY,
+    /// Switch bloom on or off.
+    ToggleBloom,
+    /// Choose the next of the [`EXPOSURES`].
+    CycleExposure,
+    /// Choose the next of the [`TONEMAPPERS`].
+    CycleTonemapping,
+    /// Switch motion streaks on or off.
+    ToggleMotionStreaks,
+    /// Choose the next of the [`ASTEROID_DETAIL_LEVELS`].
+    CycleAsteroidDetail,
+    /// Choose the next of the [`DYNAMIC_LIGHT_BUDGETS`].
+    CycleDynamicLights,
+    /// Choose the next of the [`UI_SCALES`].
+    CycleUiScale,
+    /// Choose the next HUD [`Palette`].
+    CyclePalette,
+    /// Switch captions for warning sounds on or off.
+    ToggleCaptions,
+    /// Switch this action between being held and toggled.
+    ToggleHoldMode(FlightAction),
+    /// Choose the next of the [`BindingProfiles`].
+    CycleBindingProfile,
+}
+
+/// Marks the text showing the address that will be joined.
+#[derive(Component, Debug)]
+struct ServerAddressText;
+
+/// Marks the text showing part of the [`Loadout`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadoutLabel {
+    /// The chosen ship.
+    Ship,
+    /// The weapon fitted to the hardpoint in this slot.
+    Weapon(usize),
+    /// The countermeasures carried.
+    Countermeasures,
+    /// The deployables carried.
+    Deployables,
+}
+
+/// Marks the text showing a setting.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsLabel {
+    /// Whether damage numbers are shown.
+    DamageNumbers,
+    /// Whether the hit marker is shown.
+    HitMarkers,
+    /// How sensitive mouse steering is.
+    MouseSensitivity,
+    /// Whether mouse acceleration is on.
+    MouseAcceleration,
+    /// Whether the mouse's pitch is inverted.
+    InvertY,
+    /// Whether bright things bloom.
+    Bloom,
+    /// How bright the scene is drawn.
+    Exposure,
+    /// How the scene is tonemapped.
+    Tonemapping,
+    /// Whether dust streaks past at speed.
+    MotionStreaks,
+    /// How much detail asteroids are drawn with.
+    AsteroidDetail,
+    /// How many dynamic lights may shine at once.
+    DynamicLights,
+    /// How large the interface is drawn.
+    UiScale,
+    /// Which colors the HUD is drawn in.
+    Palette,
+    /// Whether warning sounds are captioned.
+    Captions,
+    /// Whether this action is held or toggled.
+    HoldMode(FlightAction),
+    /// Which controls the player is using.
+    BindingProfile,
+}
+
+/// Marks the text showing the chosen mission.
+#[derive(Component, Debug)]
+struct MissionLabel;
+
+/// Marks the text showing why the last connection attempt ended.
+#[derive(Component, Debug)]
+struct ConnectionMessageText;
+
+/// Spawns the main menu.
+fn spawn_main_menu(mut commands: Commands, config: Res<NetConfig>) {
+    let text_style = TextStyle {
+        font_size: 24.,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+            MainMenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "AEGIR",
+                TextStyle {
+                    font_size: 64.,
+                    ..text_style.clone()
+                },
+            ));
+
+            for (button, label) in [
+                (MenuButton::FlySolo, "Fly solo"),
+                (MenuButton::Host, "Host co-op"),
+                (MenuButton::Join, "Join co-op"),
+            ] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(240.),
+                                padding: UiRect::all(Val::Px(8.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(320.),
+                            padding: UiRect::all(Val::Px(6.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    MenuButton::CycleMission,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 18.,
+                                ..text_style.clone()
+                            },
+                        ),
+                        MissionLabel,
+                    ));
+                });
+
+            parent.spawn(TextBundle::from_section(
+                "Loadout",
+                TextStyle {
+                    font_size: 18.,
+                    ..text_style.clone()
+                },
+            ));
+
+            let loadout_buttons = std::iter::once((MenuButton::CycleShip, LoadoutLabel::Ship))
+                .chain(
+                    (0..MAX_LISTED_HARDPOINTS)
+                        .map(|slot| (MenuButton::CycleWeapon(slot), LoadoutLabel::Weapon(slot))),
+                )
+                .chain([
+                    (
+                        MenuButton::CycleCountermeasures,
+                        LoadoutLabel::Countermeasures,
+                    ),
+                    (MenuButton::CycleDeployables, LoadoutLabel::Deployables),
+                ]);
+            for (button, label) in loadout_buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(320.),
+                                padding: UiRect::all(Val::Px(6.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            TextBundle::from_section(
+                                "",
+                                TextStyle {
+                                    font_size: 18.,
+                                    ..text_style.clone()
+                                },
+                            ),
+                            label,
+                        ));
+                    });
+            }
+
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font_size: 18.,
+                    ..text_style.clone()
+                },
+            ));
+
+            for (button, label) in [
+                (
+                    MenuButton::ToggleDamageNumbers,
+                    SettingsLabel::DamageNumbers,
+                ),
+                (MenuButton::ToggleHitMarkers, SettingsLabel::HitMarkers),
+                (
+                    MenuButton::CycleMouseSensitivity,
+                    SettingsLabel::MouseSensitivity,
+                ),
+                (
+                    MenuButton::ToggleMouseAcceleration,
+                    SettingsLabel::MouseAcceleration,
+                ),
+                (MenuButton::ToggleInvertY, SettingsLabel::InvertY),
+                (MenuButton::ToggleBloom, SettingsLabel::Bloom),
+                (MenuButton::CycleExposure, SettingsLabel::Exposure),
+                (MenuButton::CycleTonemapping, SettingsLabel::Tonemapping),
+                (
+                    MenuButton::ToggleMotionStreaks,
+                    SettingsLabel::MotionStreaks,
+                ),
+                (
+                    MenuButton::CycleAsteroidDetail,
+                    SettingsLabel::AsteroidDetail,
+                ),
+                (MenuButton::CycleDynamicLights, SettingsLabel::DynamicLights),
+                (MenuButton::CycleUiScale, SettingsLabel::UiScale),
+                (MenuButton::CyclePalette, SettingsLabel::Palette),
+                (MenuButton::ToggleCaptions, SettingsLabel::Captions),
+                (
+                    MenuButton::CycleBindingProfile,
+                    SettingsLabel::BindingProfile,
+                ),
+            ]
+            .into_iter()
+            .chain(TOGGLEABLE_ACTIONS.map(|(action, _)| {
+                (
+                    MenuButton::ToggleHoldMode(action),
+                    SettingsLabel::HoldMode(action),
+                )
+            })) {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(320.),
+                                padding: UiRect::all(Val::Px(6.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            TextBundle::from_section(
+                                "",
+                                TextStyle {
+                                    font_size: 18.,
+                                    ..text_style.clone()
+                                },
+                            ),
+                            label,
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    address_label(&config),
+                    TextStyle {
+                        font_size: 18.,
+                        ..text_style.clone()
+                    },
+                ),
+                ServerAddressText,
+            ));
+
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.,
+                        color: Color::ORANGE_RED,
+                        ..default()
+                    },
+                ),
+                ConnectionMessageText,
+            ));
+        });
+}
+
+/// Removes the main menu.
+fn despawn_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenuRoot>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// The text describing which address the join button will connect to.
+fn address_label(config: &NetConfig) -> String {
+    format!("Join address (type to edit): {}", config.server_address)
+}
+
+/// Lightens buttons under the cursor.
+fn highlight_buttons(
+    mut query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<MenuButton>),
+    >,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::None => BUTTON_COLOR,
+            Interaction::Hovered | Interaction::Pressed => HOVERED_BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+/// Every setting the main menu can change.
+#[derive(SystemParam)]
+struct MenuSettings<'w> {
+    /// How hits are shown.
+    hit_feedback: ResMut<'w, HitFeedbackSettings>,
+    /// How the mouse steers.
+    mouse_settings: ResMut<'w, MouseSettings>,
+    /// How the scene is drawn.
+    graphics_settings: ResMut<'w, GraphicsSettings>,
+    /// How the interface is sized and captioned.
+    accessibility: ResMut<'w, AccessibilitySettings>,
+    /// The colors of the HUD.
+    theme: ResMut<'w, HudTheme>,
+    /// Which flight actions are held or toggled.
+    input_map: ResMut<'w, InputMap<FlightAction>>,
+    /// Which controls the player is using.
+    binding_profiles: ResMut<'w, BindingProfiles>,
+}
+
+/// Starts the game, hosts, joins or changes the mission, loadout or settings when the matching
+/// button is pressed.
+#[allow(clippy::too_many_arguments)]
+fn press_buttons(
+    mut commands: Commands,
+    query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    config: Res<NetConfig>,
+    ship_library: Res<ShipLibrary>,
+    weapon_library: Res<WeaponLibrary>,
+    mission_library: Res<MissionLibrary>,
+    mut loadout: ResMut<Loadout>,
+    mut mission_selection: ResMut<MissionSelection>,
+    mut settings: MenuSettings,
+    mut connection_message: ResMut<ConnectionMessage>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let MenuSettings {
+        hit_feedback,
+        mouse_settings,
+        graphics_settings,
+        accessibility,
+        theme,
+        input_map,
+        binding_profiles,
+    } = &mut settings;
+
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::FlySolo => next_state.set(GameState::Playing),
+            MenuButton::Host => match Server::bind(config.port) {
+                Ok(server) => {
+                    commands.insert_resource(server);
+                    next_state.set(GameState::Playing);
+                }
+                Err(error) => connection_message.0 = Some(format!("Could not host: {error}")),
+            },
+            MenuButton::Join => match Client::connect(&config.server_address) {
+                Ok(client) => {
+                    commands.insert_resource(client);
+                    connection_message.0 = None;
+                    next_state.set(GameState::Connecting);
+                }
+                Err(error) => connection_message.0 = Some(format!("Could not join: {error}")),
+            },
+            MenuButton::CycleShip => loadout.cycle_ship(ship_library.ships().len()),
+            &MenuButton::CycleWeapon(slot) => {
+                loadout.cycle_weapon(slot, weapon_library.weapons().len());
+            }
+            MenuButton::CycleCountermeasures => loadout.cycle_countermeasures(),
+            MenuButton::CycleDeployables => loadout.cycle_deployables(),
+            MenuButton::CycleMission => {
+                mission_selection.cycle(mission_library.missions().len());
+            }
+            MenuButton::ToggleDamageNumbers => {
+                hit_feedback.damage_numbers = !hit_feedback.damage_numbers;
+            }
+            MenuButton::ToggleHitMarkers => hit_feedback.hit_markers = !hit_feedback.hit_markers,
+            MenuButton::CycleMouseSensitivity => {
+                mouse_settings.sensitivity = MOUSE_SENSITIVITIES
+                    .into_iter()
+                    .find(|&sensitivity| sensitivity > mouse_settings.sensitivity)
+                    .unwrap_or(MOUSE_SENSITIVITIES[0]);
+            }
+            MenuButton::ToggleMouseAcceleration => {
+                mouse_settings.acceleration = if mouse_settings.acceleration > 0. {
+                    0.
+                } else {
+                    MOUSE_ACCELERATION
+                };
+            }
+            MenuButton::ToggleInvertY => mouse_settings.invert_y = !mouse_settings.invert_y,
+            MenuButton::ToggleBloom => graphics_settings.bloom = !graphics_settings.bloom,
+            MenuButton::CycleExposure => {
+                graphics_settings.exposure = EXPOSURES
+                    .into_iter()
+                    .find(|&exposure| exposure > graphics_settings.exposure)
+                    .unwrap_or(EXPOSURES[0]);
+            }
+            MenuButton::CycleTonemapping => {
+                let next = TONEMAPPERS
+                    .iter()
+                    .position(|(tonemapping, _)| *tonemapping == graphics_settings.tonemapping)
+                    .map_or(0, |index| (index + 1) % TONEMAPPERS.len());
+                graphics_settings.tonemapping = TONEMAPPERS[next].0;
+            }
+            MenuButton::ToggleMotionStreaks => {
+                graphics_settings.motion_streaks = !graphics_settings.motion_streaks;
+            }
+            MenuButton::CycleAsteroidDetail => {
+                let next = ASTEROID_DETAIL_LEVELS
+                    .iter()
+                    .position(|(detail, _)| *detail == graphics_settings.asteroid_detail)
+                    .map_or(0, |index| (index + 1) % ASTEROID_DETAIL_LEVELS.len());
+                graphics_settings.asteroid_detail = ASTEROID_DETAIL_LEVELS[next].0;
+            }
+            MenuButton::CycleDynamicLights => {
+                graphics_settings.max_dynamic_lights = DYNAMIC_LIGHT_BUDGETS
+                    .into_iter()
+                    .find(|&budget| budget > graphics_settings.max_dynamic_lights)
+                    .unwrap_or(DYNAMIC_LIGHT_BUDGETS[0]);
+            }
+            MenuButton::CycleUiScale => {
+                accessibility.ui_scale = UI_SCALES
+                    .into_iter()
+                    .find(|&scale| scale > accessibility.ui_scale)
+                    .unwrap_or(UI_SCALES[0]);
+            }
+            MenuButton::CyclePalette => {
+                let next = Palette::ALL
+                    .iter()
+                    .position(|&palette| palette == theme.palette)
+                    .map_or(0, |index| (index + 1) % Palette::ALL.len());
+                theme.palette = Palette::ALL[next];
+            }
+            MenuButton::ToggleCaptions => accessibility.captions = !accessibility.captions,
+            &MenuButton::ToggleHoldMode(action) => {
+                let toggle = !input_map.is_toggle(action);
+                input_map.set_toggle(action, toggle);
+            }
+            MenuButton::CycleBindingProfile => binding_profiles.cycle(),
+        }
+    }
+}
+
+/// Lets the player type the address of the game they want to join.
+fn edit_server_address(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard: Res<Input<KeyCode>>,
+    mut config: ResMut<NetConfig>,
+    mut query: Query<&mut Text, With<ServerAddressText>>,
+) {
+    for event in characters.iter() {
+        if !event.char.is_control() && !event.char.is_whitespace() {
+            config.server_address.push(event.char);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Back) {
+        config.server_address.pop();
+    }
+
+    if config.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = address_label(&config);
+        }
+    }
+}
+
+/// Shows why the last connection attempt failed, if it did.
+fn show_connection_message(
+    connection_message: Res<ConnectionMessage>,
+    mut query: Query<&mut Text, With<ConnectionMessageText>>,
+) {
+    if !connection_message.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = connection_message.0.clone().unwrap_or_default();
+    }
+}
+
+/// Names the chosen ship, the weapon fitted to each of its hardpoints and its countermeasures and
+/// deployables, hiding buttons for hardpoints it does not have.
+fn label_loadout(
+    loadout: Res<Loadout>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+    weapon_definitions: Res<Assets<WeaponDefinition>>,
+    mut label_query: Query<(&mut Text, &LoadoutLabel)>,
+    mut button_query: Query<(&mut Style, &MenuButton)>,
+) {
+    let ship = loadout.ship_definition(&ship_library, &ship_definitions);
+
+    for (mut text, label) in label_query.iter_mut() {
+        let label = match *label {
+            LoadoutLabel::Ship => format!("Ship: {}", ship.name),
+            LoadoutLabel::Weapon(slot) => {
+                let hardpoint = ship
+                    .hardpoints
+                    .get(slot)
+                    .map_or("", |hardpoint| hardpoint.name.as_str());
+                let weapon = weapon_library
+                    .weapons()
+                    .get(loadout.weapon(slot))
+                    .and_then(|handle| weapon_definitions.get(handle))
+                    .map_or("...", |definition| definition.name.as_str());
+                format!("{hardpoint}: {weapon}")
+            }
+            LoadoutLabel::Countermeasures => {
+                format!("Countermeasures: {}", loadout.countermeasures().name())
+            }
+            LoadoutLabel::Deployables => {
+                format!("Deployables: {}", loadout.deployables().name())
+            }
+        };
+
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+
+    for (mut style, button) in button_query.iter_mut() {
+        if let &MenuButton::CycleWeapon(slot) = button {
+            let display = if slot < ship.hardpoints.len() {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if style.display != display {
+                style.display = display;
+            }
+        }
+    }
+}
+
+/// Names the chosen mission.
+fn label_mission(
+    selection: Res<MissionSelection>,
+    library: Res<MissionLibrary>,
+    definitions: Res<Assets<MissionDefinition>>,
+    mut query: Query<&mut Text, With<MissionLabel>>,
+) {
+    let mission = match selection.0 {
+        Some(index) => library
+            .missions()
+            .get(index)
+            .and_then(|handle| definitions.get(handle))
+            .map_or("...", |definition| definition.name.as_str()),
+        None => "Free flight",
+    };
+    let label = format!("Mission: {mission}");
+
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+/// Shows whether each setting is on.
+#[allow(clippy::too_many_arguments)]
+fn label_settings(
+    hit_feedback: Res<HitFeedbackSettings>,
+    mouse_settings: Res<MouseSettings>,
+    graphics_settings: Res<GraphicsSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    theme: Res<HudTheme>,
+    input_map: Res<InputMap<FlightAction>>,
+    binding_profiles: Res<BindingProfiles>,
+    mut query: Query<(&mut Text, &SettingsLabel)>,
+) {
+    let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+
+    for (mut text, label) in query.iter_mut() {
+        let label = match label {
+            SettingsLabel::DamageNumbers => {
+                format!("Damage numbers: {}", on_off(hit_feedback.damage_numbers))
+            }
+            SettingsLabel::HitMarkers => {
+                format!("Hit markers: {}", on_off(hit_feedback.hit_markers))
+            }
+            SettingsLabel::MouseSensitivity => {
+                format!("Mouse sensitivity: {}", mouse_settings.sensitivity)
+            }
+            SettingsLabel::MouseAcceleration => format!(
+                "Mouse acceleration: {}",
+                on_off(mouse_settings.acceleration > 0.)
+            ),
+            SettingsLabel::InvertY => format!("Invert mouse: {}", on_off(mouse_settings.invert_y)),
+            SettingsLabel::Bloom => format!("Bloom: {}", on_off(graphics_settings.bloom)),
+            SettingsLabel::Exposure => format!("Exposure: {:+}", graphics_settings.exposure),
+            SettingsLabel::Tonemapping => {
+                format!("Tonemapping: {}", graphics_settings.tonemapping_name())
+            }
+            SettingsLabel::MotionStreaks => format!(
+                "Motion streaks: {}",
+                on_off(graphics_settings.motion_streaks)
+            ),
+            SettingsLabel::AsteroidDetail => format!(
+                "Asteroid detail: {}",
+                graphics_settings.asteroid_detail_name()
+            ),
+            SettingsLabel::DynamicLights => match graphics_settings.max_dynamic_lights {
+                0 => "Dynamic lights: Off".to_string(),
+                budget => format!("Dynamic lights: {budget}"),
+            },
+            SettingsLabel::UiScale => format!("Interface scale: {}x", accessibility.ui_scale),
+            SettingsLabel::Palette => format!("HUD colors: {}", theme.palette.name()),
+            SettingsLabel::Captions => format!("Captions: {}", on_off(accessibility.captions)),
+            &SettingsLabel::HoldMode(action) => {
+                let name = TOGGLEABLE_ACTIONS
+                    .iter()
+                    .find(|(toggleable, _)| *toggleable == action)
+                    .map_or("Action", |(_, name)| name);
+                let mode = if input_map.is_toggle(action) {
+                    "Toggle"
+                } else {
+                    "Hold"
+                };
+                format!("{name}: {mode}")
+            }
+            SettingsLabel::BindingProfile => {
+                format!("Controls: {}", binding_profiles.active().name)
+            }
+        };
+
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
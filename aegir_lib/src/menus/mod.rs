@@ -0,0 +1,14 @@
+//! Menus shown outside of (or on top of) the game world.
+use bevy::prelude::{App, Plugin};
+
+mod debrief;
+mod main_menu;
+
+/// Adds every menu screen.
+pub struct MenusPlugin;
+
+impl Plugin for MenusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((debrief::DebriefPlugin, main_menu::MainMenuPlugin));
+    }
+}
@@ -0,0 +1,207 @@
+//! Joining a game hosted by another player.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+
+use super::combat::{DamageReceived, DestructionReceived, ProjectileReceived};
+use super::lag_compensation::HitConfirmed;
+use super::protocol::{Message, PeerId, ShipState, PROTOCOL_VERSION};
+use super::replication::{PeerLeft, ShipStateReceived};
+use super::transport::Transport;
+use super::{ConnectionMessage, NetSet, PEER_TIMEOUT, SEND_INTERVAL};
+
+/// Client logic
+pub(super) struct ClientPlugin;
+
+impl Plugin for ClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            receive_on_client
+                .in_set(NetSet::Receive)
+                .run_if(resource_exists::<Client>()),
+        )
+        .add_systems(
+            Update,
+            send_from_client
+                .in_set(NetSet::Send)
+                .run_if(resource_exists::<Client>()),
+        );
+    }
+}
+
+/// A connection to a game hosted by another player.
+///
+/// Insert this as a resource to start joining.
+#[derive(Resource, Debug)]
+pub struct Client {
+    /// The socket used to talk to the server.
+    transport: Transport,
+    /// Where the server is listening.
+    server: SocketAddr,
+    /// The identity assigned by the server, once it has welcomed us.
+    peer: Option<PeerId>,
+    /// How long it has been since the server was last heard from, in seconds.
+    silent_for: f64,
+    /// Paces how often our ship (or our request to join) is sent to the server.
+    send_timer: Timer,
+}
+
+impl Client {
+    /// Prepares to join the game hosted at `address`.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let server = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the address could not be found",
+            )
+        })?;
+        let local_address = if server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+
+        Ok(Client {
+            transport: Transport::bind(local_address)?,
+            server,
+            peer: None,
+            silent_for: 0.,
+            send_timer: Timer::from_seconds(SEND_INTERVAL, TimerMode::Repeating),
+        })
+    }
+
+    /// Our identity in the game, once the server has welcomed us.
+    pub fn peer(&self) -> Option<PeerId> {
+        self.peer
+    }
+
+    /// Sends `message` to the server.
+    pub(super) fn send(&self, message: &Message) {
+        self.transport.send(message, self.server);
+    }
+}
+
+/// Handles messages from the server, and gives up if it stops responding.
+#[allow(clippy::too_many_arguments)]
+fn receive_on_client(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut client: ResMut<Client>,
+    mut connection_message: ResMut<ConnectionMessage>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ship_states: EventWriter<ShipStateReceived>,
+    mut confirmed_hits: EventWriter<HitConfirmed>,
+    mut projectiles: EventWriter<ProjectileReceived>,
+    mut damage_reports: EventWriter<DamageReceived>,
+    mut destruction_reports: EventWriter<DestructionReceived>,
+    mut departures: EventWriter<PeerLeft>,
+) {
+    let client = &mut *client;
+    client.silent_for += time.delta_seconds_f64();
+
+    for (message, address) in client.transport.receive() {
+        if address != client.server {
+            continue;
+        }
+
+        client.silent_for = 0.;
+        match message {
+            Message::Welcome { peer } => {
+                if client.peer.is_none() {
+                    info!("Joined {} as {peer:?}", client.server);
+                    client.peer = Some(peer);
+                    next_state.set(GameState::Playing);
+                }
+            }
+            Message::Rejected(reason) => {
+                commands.remove_resource::<Client>();
+                connection_message.0 = Some(format!("Could not join: {reason}"));
+                next_state.set(GameState::Menu);
+                return;
+            }
+            Message::ShipState(state) => {
+                if Some(state.peer) != client.peer {
+                    ship_states.send(ShipStateReceived(state));
+                }
+            }
+            Message::Hit(hit) => {
+                if Some(hit.target) == client.peer {
+                    confirmed_hits.send(HitConfirmed(hit));
+                }
+            }
+            Message::ProjectileFired(fired) => {
+                if Some(fired.peer) != client.peer {
+                    projectiles.send(ProjectileReceived(fired));
+                }
+            }
+            Message::Damaged(report) => {
+                if Some(report.peer) != client.peer {
+                    damage_reports.send(DamageReceived(report));
+                }
+            }
+            Message::Destroyed(report) => {
+                if Some(report.peer) != client.peer {
+                    destruction_reports.send(DestructionReceived(report));
+                }
+            }
+            Message::Goodbye { peer: PeerId::HOST } => {
+                commands.remove_resource::<Client>();
+                connection_message.0 = Some("The host ended the game".to_string());
+                next_state.set(GameState::Menu);
+                return;
+            }
+            Message::Goodbye { peer } => {
+                departures.send(PeerLeft(peer));
+            }
+            // Only clients send these
+            Message::Hello { .. } | Message::HitClaim(_) => (),
+        }
+    }
+
+    if client.silent_for > PEER_TIMEOUT {
+        let reason = match client.peer {
+            Some(_) => "Lost connection to the host",
+            None => "The host did not respond",
+        };
+        commands.remove_resource::<Client>();
+        connection_message.0 = Some(reason.to_string());
+        next_state.set(GameState::Menu);
+    }
+}
+
+/// Asks the server to let us join, then keeps it up to date with the state of our ship.
+fn send_from_client(
+    time: Res<Time>,
+    mut client: ResMut<Client>,
+    query: Query<(&Transform, &Velocity), With<PlayerShip>>,
+) {
+    if !client.send_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let message = match client.peer {
+        None => Message::Hello {
+            version: PROTOCOL_VERSION,
+        },
+        Some(peer) => {
+            let Ok((transform, velocity)) = query.get_single() else {
+                return;
+            };
+            Message::ShipState(ShipState::new(
+                peer,
+                time.elapsed_seconds_f64(),
+                transform,
+                velocity,
+            ))
+        }
+    };
+
+    client.transport.send(&message, client.server);
+}
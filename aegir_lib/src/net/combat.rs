@@ -0,0 +1,299 @@
+//! Mirrors the shots, damage and destruction of players' ships between peers.
+//!
+//! Each peer tells the others about the projectiles its own ship fires, and flies copies of theirs
+//! from its copies of their ships. Only the ship that is hit decides whether it was hit, so
+//! copies of projectiles can only harm the local player's ship. Each peer also reports the damage
+//! its own ship takes and its destruction, so that the others can show and credit them. The host
+//! relays all of these to everyone else.
+
+use bevy::prelude::*;
+
+use crate::player::ship::PlayerShip;
+use crate::pooling::EntityPool;
+use crate::simulation::flight::Velocity;
+use crate::simulation::health::{Damaged, Destroyed};
+use crate::simulation::weapons::{
+    spawn_projectile, Projectile, Seeker, WeaponDefinition, WeaponFired, WeaponLibrary,
+};
+
+use super::protocol::{DamageReport, DestructionReport, Message, PeerId, ProjectileFired};
+use super::replication::RemoteShip;
+use super::{Client, NetSet, Server};
+
+/// Combat replication logic
+pub(super) struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProjectileReceived>()
+            .add_event::<DamageReceived>()
+            .add_event::<DestructionReceived>()
+            .add_systems(
+                Update,
+                (
+                    send_fired_projectiles,
+                    report_player_damage,
+                    fire_remote_projectiles,
+                    apply_remote_damage,
+                )
+                    .after(NetSet::Receive)
+                    .before(NetSet::Send),
+            );
+    }
+}
+
+/// Another player's ship has fired a projectile.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ProjectileReceived(pub ProjectileFired);
+
+/// Another player's ship has been damaged.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageReceived(pub DamageReport);
+
+/// Another player's ship has been destroyed.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DestructionReceived(pub DestructionReport);
+
+/// Our identity in the game, if we are hosting or have joined one.
+fn local_peer(server: Option<&Server>, client: Option<&Client>) -> Option<PeerId> {
+    match (server, client) {
+        (Some(_), _) => Some(PeerId::HOST),
+        (None, Some(client)) => client.peer(),
+        (None, None) => None,
+    }
+}
+
+/// Sends `message` to every other peer.
+fn send_to_peers(server: Option<&Server>, client: Option<&Client>, message: &Message) {
+    match (server, client) {
+        (Some(server), _) => server.broadcast(message, None),
+        (None, Some(client)) => client.send(message),
+        (None, None) => (),
+    }
+}
+
+/// Tells the other peers about each projectile the local player's ship fires.
+fn send_fired_projectiles(
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    weapon_library: Res<WeaponLibrary>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    remote_query: Query<&RemoteShip>,
+    projectile_query: Query<
+        (&Transform, &Velocity, &Projectile, Option<&Seeker>),
+        Added<Projectile>,
+    >,
+) {
+    let (server, client) = (server.as_deref(), client.as_deref());
+    let Some(peer) = local_peer(server, client) else {
+        return;
+    };
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+
+    for (transform, velocity, projectile, seeker) in projectile_query.iter() {
+        if projectile.source != player {
+            continue;
+        }
+        let Some(weapon) = weapon_library
+            .weapons()
+            .iter()
+            .position(|weapon| *weapon == projectile.weapon)
+        else {
+            continue;
+        };
+
+        let fired = ProjectileFired {
+            peer,
+            weapon: weapon as u32,
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            velocity: velocity.0.to_array(),
+            target: seeker
+                .and_then(|seeker| seeker.target)
+                .and_then(|target| remote_query.get(target).ok())
+                .map(|remote_ship| remote_ship.peer),
+        };
+        send_to_peers(server, client, &Message::ProjectileFired(fired));
+    }
+}
+
+/// Tells the other peers about the damage the local player's ship takes, and its destruction.
+fn report_player_damage(
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventReader<Destroyed>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    remote_query: Query<&RemoteShip>,
+) {
+    let (server, client) = (server.as_deref(), client.as_deref());
+    let (Some(peer), Ok(player)) = (local_peer(server, client), player_query.get_single()) else {
+        damaged.clear();
+        destroyed.clear();
+        return;
+    };
+    let remote_peer = |entity: Option<Entity>| {
+        entity
+            .and_then(|entity| remote_query.get(entity).ok())
+            .map(|remote_ship| remote_ship.peer)
+    };
+
+    for event in damaged.iter().filter(|event| event.target == player) {
+        let report = DamageReport {
+            peer,
+            source: remote_peer(event.source),
+            amount: event.amount,
+            position: event.position.to_array(),
+        };
+        send_to_peers(server, client, &Message::Damaged(report));
+    }
+
+    for event in destroyed.iter().filter(|event| event.entity == player) {
+        let report = DestructionReport {
+            peer,
+            killer: remote_peer(event.killer),
+            position: event.position.to_array(),
+        };
+        send_to_peers(server, client, &Message::Destroyed(report));
+    }
+}
+
+/// Fires a copy of each projectile fired by another player's ship from our copy of that ship.
+///
+/// Copies of homing projectiles track the ship they tracked for the player who fired them.
+#[allow(clippy::too_many_arguments)]
+fn fire_remote_projectiles(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Projectile>>,
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    weapon_library: Res<WeaponLibrary>,
+    weapon_definitions: Res<Assets<WeaponDefinition>>,
+    mut events: EventReader<ProjectileReceived>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    remote_query: Query<(Entity, &RemoteShip)>,
+    mut weapon_fired: EventWriter<WeaponFired>,
+) {
+    let local_peer = local_peer(server.as_deref(), client.as_deref());
+    let ship = |peer: PeerId| {
+        if Some(peer) == local_peer {
+            player_query.get_single().ok()
+        } else {
+            remote_query
+                .iter()
+                .find(|(_, remote_ship)| remote_ship.peer == peer)
+                .map(|(entity, _)| entity)
+        }
+    };
+
+    for &ProjectileReceived(fired) in events.iter() {
+        if !fired.is_well_formed() || Some(fired.peer) == local_peer {
+            continue;
+        }
+        let Some(source) = ship(fired.peer) else {
+            continue;
+        };
+        let Some(weapon) = weapon_library.weapons().get(fired.weapon as usize) else {
+            continue;
+        };
+        let Some(definition) = weapon_definitions.get(weapon) else {
+            continue;
+        };
+
+        let muzzle = fired.transform();
+        let Some(mut projectile) = spawn_projectile(
+            &mut commands,
+            &mut pool,
+            weapon,
+            definition,
+            source,
+            muzzle,
+            fired.velocity(),
+        ) else {
+            continue;
+        };
+        if let Some(seeker_definition) = definition
+            .projectile
+            .as_ref()
+            .and_then(|projectile| projectile.seeker)
+        {
+            projectile.insert(Seeker {
+                target: fired.target.and_then(ship),
+                definition: seeker_definition,
+            });
+        }
+        weapon_fired.send(WeaponFired {
+            ship: source,
+            weapon: weapon.clone(),
+            muzzle: muzzle.translation,
+        });
+    }
+}
+
+/// Deals the damage reported by other players to our copies of their ships, and destroys our
+/// copies of the ships they report destroyed.
+///
+/// Our copies have no health of their own, so the damage is only shown, not counted. Damage the
+/// local player dealt has already been seen here, so its reports are ignored.
+#[allow(clippy::too_many_arguments)]
+fn apply_remote_damage(
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    mut damage_reports: EventReader<DamageReceived>,
+    mut destruction_reports: EventReader<DestructionReceived>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    remote_query: Query<(Entity, &RemoteShip)>,
+    mut damaged: EventWriter<Damaged>,
+    mut destroyed: EventWriter<Destroyed>,
+) {
+    let local_peer = local_peer(server.as_deref(), client.as_deref());
+    let ship = |peer: PeerId| {
+        if Some(peer) == local_peer {
+            player_query.get_single().ok()
+        } else {
+            remote_query
+                .iter()
+                .find(|(_, remote_ship)| remote_ship.peer == peer)
+                .map(|(entity, _)| entity)
+        }
+    };
+
+    for &DamageReceived(report) in damage_reports.iter() {
+        if Some(report.peer) == local_peer || report.source.is_some() && report.source == local_peer
+        {
+            continue;
+        }
+        let position = Vec3::from_array(report.position);
+        if !report.amount.is_finite() || !position.is_finite() {
+            continue;
+        }
+        let Some(target) = ship(report.peer) else {
+            continue;
+        };
+
+        damaged.send(Damaged {
+            target,
+            source: report.source.and_then(ship),
+            amount: report.amount,
+            position,
+        });
+    }
+
+    for &DestructionReceived(report) in destruction_reports.iter() {
+        if Some(report.peer) == local_peer {
+            continue;
+        }
+        let position = Vec3::from_array(report.position);
+        let Some(entity) = ship(report.peer).filter(|_| position.is_finite()) else {
+            continue;
+        };
+
+        destroyed.send(Destroyed {
+            entity,
+            killer: report.killer.and_then(ship),
+            position,
+        });
+    }
+}
@@ -0,0 +1,267 @@
+//! The listen server run by the hosting player.
+
+use std::io;
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+
+use super::combat::{DamageReceived, DestructionReceived, ProjectileReceived};
+use super::lag_compensation::HitClaimed;
+use super::protocol::{Message, PeerId, RejectReason, ShipState, PROTOCOL_VERSION};
+use super::replication::{PeerLeft, ShipStateReceived};
+use super::transport::Transport;
+use super::{NetSet, MAX_PEERS, PEER_TIMEOUT, SEND_INTERVAL};
+
+/// Hosting logic
+pub(super) struct HostPlugin;
+
+impl Plugin for HostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            receive_on_host
+                .in_set(NetSet::Receive)
+                .run_if(resource_exists::<Server>()),
+        )
+        .add_systems(
+            Update,
+            (send_host_ship, drop_silent_peers)
+                .in_set(NetSet::Send)
+                .run_if(resource_exists::<Server>()),
+        );
+    }
+}
+
+/// A listen server that other players can join.
+///
+/// Insert this as a resource to start hosting.
+#[derive(Resource, Debug)]
+pub struct Server {
+    /// The socket that clients talk to.
+    transport: Transport,
+    /// Every client that has joined, keyed by the address it sends from.
+    peers: HashMap<SocketAddr, ConnectedPeer>,
+    /// Paces how often the host's own ship is sent to clients.
+    send_timer: Timer,
+}
+
+/// A client that has joined the [`Server`].
+#[derive(Debug, Clone, Copy)]
+struct ConnectedPeer {
+    /// The identity assigned to this client.
+    id: PeerId,
+    /// When the client was last heard from, in seconds since startup.
+    last_heard: f64,
+}
+
+impl Server {
+    /// Starts listening for clients on `port`.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        Ok(Server {
+            transport: Transport::bind(("0.0.0.0", port))?,
+            peers: HashMap::default(),
+            send_timer: Timer::from_seconds(SEND_INTERVAL, TimerMode::Repeating),
+        })
+    }
+
+    /// The address clients should send to.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.transport.local_address()
+    }
+
+    /// The number of clients in the game, not counting the host.
+    pub fn client_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// The lowest unused client identity, or `None` if the game is full.
+    fn next_free_id(&self) -> Option<PeerId> {
+        (1..MAX_PEERS as u8)
+            .map(PeerId)
+            .find(|&id| self.peers.values().all(|peer| peer.id != id))
+    }
+
+    /// Sends `message` to the client playing as `peer`, if it is still in the game.
+    pub(super) fn send_to(&self, peer: PeerId, message: &Message) {
+        if let Some((&address, _)) = self
+            .peers
+            .iter()
+            .find(|(_, connected)| connected.id == peer)
+        {
+            self.transport.send(message, address);
+        }
+    }
+
+    /// Sends `message` to every client except the one at `except`.
+    pub(super) fn broadcast(&self, message: &Message, except: Option<SocketAddr>) {
+        for &address in self.peers.keys() {
+            if Some(address) != except {
+                self.transport.send(message, address);
+            }
+        }
+    }
+}
+
+/// Welcomes new clients, relays the state of their ships and what befalls them to everyone else,
+/// and passes on their claimed hits to be checked.
+#[allow(clippy::too_many_arguments)]
+fn receive_on_host(
+    time: Res<Time>,
+    mut server: ResMut<Server>,
+    mut ship_states: EventWriter<ShipStateReceived>,
+    mut hit_claims: EventWriter<HitClaimed>,
+    mut projectiles: EventWriter<ProjectileReceived>,
+    mut damage_reports: EventWriter<DamageReceived>,
+    mut destruction_reports: EventWriter<DestructionReceived>,
+    mut departures: EventWriter<PeerLeft>,
+) {
+    let server = &mut *server;
+    let now = time.elapsed_seconds_f64();
+
+    for (message, address) in server.transport.receive() {
+        match message {
+            Message::Hello { version } => {
+                if version != PROTOCOL_VERSION {
+                    let rejection = Message::Rejected(RejectReason::VersionMismatch);
+                    server.transport.send(&rejection, address);
+                    continue;
+                }
+
+                let peer = match server.peers.get(&address) {
+                    // Our welcome was lost, so send it again
+                    Some(peer) => peer.id,
+                    None => match server.next_free_id() {
+                        Some(id) => {
+                            info!("{address} joined as {id:?}");
+                            server.peers.insert(
+                                address,
+                                ConnectedPeer {
+                                    id,
+                                    last_heard: now,
+                                },
+                            );
+                            id
+                        }
+                        None => {
+                            let rejection = Message::Rejected(RejectReason::Full);
+                            server.transport.send(&rejection, address);
+                            continue;
+                        }
+                    },
+                };
+
+                server.transport.send(&Message::Welcome { peer }, address);
+            }
+            Message::ShipState(mut state) => {
+                let Some(peer) = server.peers.get_mut(&address) else {
+                    continue;
+                };
+
+                peer.last_heard = now;
+                // Clients may only speak for their own ship
+                state.peer = peer.id;
+                server.broadcast(&Message::ShipState(state), Some(address));
+                ship_states.send(ShipStateReceived(state));
+            }
+            Message::HitClaim(mut claim) => {
+                let Some(peer) = server.peers.get_mut(&address) else {
+                    continue;
+                };
+
+                peer.last_heard = now;
+                // Clients may only claim hits for their own ship
+                claim.shooter = peer.id;
+                hit_claims.send(HitClaimed(claim));
+            }
+            Message::ProjectileFired(mut fired) => {
+                let Some(peer) = server.peers.get_mut(&address) else {
+                    continue;
+                };
+
+                peer.last_heard = now;
+                fired.peer = peer.id;
+                server.broadcast(&Message::ProjectileFired(fired), Some(address));
+                projectiles.send(ProjectileReceived(fired));
+            }
+            Message::Damaged(mut report) => {
+                let Some(peer) = server.peers.get_mut(&address) else {
+                    continue;
+                };
+
+                peer.last_heard = now;
+                report.peer = peer.id;
+                server.broadcast(&Message::Damaged(report), Some(address));
+                damage_reports.send(DamageReceived(report));
+            }
+            Message::Destroyed(mut report) => {
+                let Some(peer) = server.peers.get_mut(&address) else {
+                    continue;
+                };
+
+                peer.last_heard = now;
+                report.peer = peer.id;
+                server.broadcast(&Message::Destroyed(report), Some(address));
+                destruction_reports.send(DestructionReceived(report));
+            }
+            Message::Goodbye { .. } => {
+                if let Some(peer) = server.peers.remove(&address) {
+                    info!("{:?} left", peer.id);
+                    server.broadcast(&Message::Goodbye { peer: peer.id }, None);
+                    departures.send(PeerLeft(peer.id));
+                }
+            }
+            // Only servers send these
+            Message::Welcome { .. } | Message::Rejected(_) | Message::Hit(_) => (),
+        }
+    }
+}
+
+/// Sends the state of the host's ship to every client.
+fn send_host_ship(
+    time: Res<Time>,
+    mut server: ResMut<Server>,
+    query: Query<(&Transform, &Velocity), With<PlayerShip>>,
+) {
+    if !server.send_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Ok((transform, velocity)) = query.get_single() else {
+        return;
+    };
+
+    let state = ShipState::new(
+        PeerId::HOST,
+        time.elapsed_seconds_f64(),
+        transform,
+        velocity,
+    );
+    server.broadcast(&Message::ShipState(state), None);
+}
+
+/// Disconnects clients that have not been heard from in a while.
+fn drop_silent_peers(
+    time: Res<Time>,
+    mut server: ResMut<Server>,
+    mut departures: EventWriter<PeerLeft>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let silent: Vec<SocketAddr> = server
+        .peers
+        .iter()
+        .filter(|(_, peer)| now - peer.last_heard > PEER_TIMEOUT)
+        .map(|(&address, _)| address)
+        .collect();
+
+    for address in silent {
+        if let Some(peer) = server.peers.remove(&address) {
+            info!("{:?} timed out", peer.id);
+            server.broadcast(&Message::Goodbye { peer: peer.id }, None);
+            departures.send(PeerLeft(peer.id));
+        }
+    }
+}
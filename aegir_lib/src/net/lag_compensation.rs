@@ -0,0 +1,380 @@
+//! Lag compensation for beams fired at other players' ships.
+//!
+//! Each peer sees the others' ships a little in the past, so a beam that hits on the shooter's
+//! screen may miss where the target really is by the time the server hears of it. Instead of
+//! trusting either view, the shooter sends a [`HitClaim`] saying when on the target's clock it saw
+//! the target, and the server rewinds the target to that moment to check the beam would have hit.
+//! Claims reaching further back than [`MAX_REWIND`] are refused, so that lag cannot be abused.
+//!
+//! Nothing else in a claim is taken on trust either. The beam's reach comes from the server's own
+//! [`WeaponDefinition`], and each of the shooter's hardpoints can only claim as much damage as its
+//! weapon deals in the time since its last claim, give or take [`MAX_BANKED_BEAM_TIME`].
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::game_state::GameState;
+use crate::player::ship::PlayerShip;
+use crate::simulation::flight::Velocity;
+use crate::simulation::geometry::{ray_sphere_distance, Collider};
+use crate::simulation::health::{Damaged, HealthSet};
+use crate::simulation::ships::{ShipDefinition, ShipLibrary};
+use crate::simulation::weapons::{
+    BeamHit, Hardpoint, MountedWeapon, WeaponDefinition, WeaponLibrary,
+};
+
+use super::protocol::{Hit, HitClaim, Message, PeerId, ShipState};
+use super::replication::{InterpolationSettings, RemoteShip, SnapshotBuffer, REMOTE_SHIP_RADIUS};
+use super::{Client, NetSet, Server};
+
+/// How far back the server will rewind a ship to check a hit, in seconds.
+pub const MAX_REWIND: f64 = 0.5;
+
+/// How far a beam may pass outside its target and still be upheld, in meters.
+const HIT_TOLERANCE: f32 = 1.5;
+
+/// How far a beam may start from where the server last saw its shooter, in meters.
+const MAX_ORIGIN_ERROR: f32 = 60.;
+
+/// How many seconds of a beam's damage a hardpoint can build up between claims, so that claims
+/// arriving unevenly are not cut short.
+const MAX_BANKED_BEAM_TIME: f32 = 0.5;
+
+/// Lag compensation logic
+pub(super) struct LagCompensationPlugin;
+
+impl Plugin for LagCompensationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitClaimed>()
+            .add_event::<HitConfirmed>()
+            .init_resource::<HostHistory>()
+            .init_resource::<BeamAllowances>()
+            .init_resource::<UpheldHits>()
+            .add_systems(
+                Update,
+                (
+                    record_host_history.run_if(resource_exists::<Server>()),
+                    claim_beam_hits,
+                    validate_hit_claims.run_if(resource_exists::<Server>()),
+                    queue_confirmed_hits.run_if(resource_exists::<Client>()),
+                )
+                    .chain()
+                    .after(NetSet::Receive)
+                    .before(NetSet::Send),
+            )
+            .add_systems(FixedUpdate, deal_upheld_hits.before(HealthSet))
+            .add_systems(
+                OnExit(GameState::Playing),
+                (
+                    forget_host_history,
+                    forget_beam_allowances,
+                    forget_upheld_hits,
+                ),
+            );
+    }
+}
+
+/// A hit has been claimed, by a client or by the host itself, and is waiting to be checked.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct HitClaimed(pub HitClaim);
+
+/// The server has upheld a hit on the local player's ship.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct HitConfirmed(pub Hit);
+
+/// The recent states of the host's own ship, for rewinding it when clients claim to hit it.
+#[derive(Resource, Debug, Default)]
+struct HostHistory(SnapshotBuffer);
+
+/// How much damage each player's hardpoints may still claim, keyed by player and slot.
+#[derive(Resource, Debug, Default)]
+struct BeamAllowances(HashMap<(PeerId, u32), BeamAllowance>);
+
+/// How much damage one hardpoint may still claim.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BeamAllowance {
+    /// The damage banked as of the last claim.
+    banked: f32,
+    /// When the last claim was checked, in seconds on the server's clock.
+    last_claim: f64,
+}
+
+/// Damage to the local player's ship from upheld hits, waiting for the next tick to deal it.
+///
+/// Hits are checked every frame, but health only changes during ticks.
+#[derive(Resource, Debug, Default)]
+struct UpheldHits(Vec<Damaged>);
+
+/// Records where the host's ship is every frame.
+fn record_host_history(
+    time: Res<Time>,
+    mut history: ResMut<HostHistory>,
+    query: Query<(&Transform, &Velocity), With<PlayerShip>>,
+) {
+    let Ok((transform, velocity)) = query.get_single() else {
+        return;
+    };
+
+    let now = time.elapsed_seconds_f64();
+    history
+        .0
+        .push(ShipState::new(PeerId::HOST, now, transform, velocity), now);
+}
+
+/// Forgets where the host's ship has been once the game ends.
+fn forget_host_history(mut history: ResMut<HostHistory>) {
+    history.0 = SnapshotBuffer::default();
+}
+
+/// Forgets how much damage each hardpoint has claimed once the game ends.
+fn forget_beam_allowances(mut allowances: ResMut<BeamAllowances>) {
+    allowances.0.clear();
+}
+
+/// Drops damage from upheld hits that was never dealt once the game ends.
+fn forget_upheld_hits(mut upheld_hits: ResMut<UpheldHits>) {
+    upheld_hits.0.clear();
+}
+
+/// Claims each hit the local player's beams score on other players' ships, as they were shown.
+///
+/// Hits on the same ship from the same hardpoint within a frame are claimed together. The host
+/// checks its own claims just like a client's, so that it has no advantage.
+#[allow(clippy::too_many_arguments)]
+fn claim_beam_hits(
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    weapon_library: Res<WeaponLibrary>,
+    mut beam_hits: EventReader<BeamHit>,
+    player_query: Query<(), With<PlayerShip>>,
+    hardpoint_query: Query<(&Hardpoint, &MountedWeapon)>,
+    remote_query: Query<(&RemoteShip, &SnapshotBuffer)>,
+    mut hit_claims: EventWriter<HitClaimed>,
+) {
+    let shooter = match (&server, &client) {
+        (Some(_), _) => Some(PeerId::HOST),
+        (None, Some(client)) => client.peer(),
+        (None, None) => None,
+    };
+    let Some(shooter) = shooter else {
+        beam_hits.clear();
+        return;
+    };
+
+    let mut claims: Vec<HitClaim> = Vec::new();
+    for hit in beam_hits.iter() {
+        if !player_query.contains(hit.ship) {
+            continue;
+        }
+        let Ok((remote_ship, buffer)) = remote_query.get(hit.target) else {
+            continue;
+        };
+        let Some(time) = buffer.shown() else {
+            continue;
+        };
+        let Ok((hardpoint, mounted)) = hardpoint_query.get(hit.hardpoint) else {
+            continue;
+        };
+        let Some(weapon) = weapon_library
+            .weapons()
+            .iter()
+            .position(|weapon| *weapon == mounted.definition)
+        else {
+            continue;
+        };
+        let slot = hardpoint.slot as u32;
+
+        match claims
+            .iter_mut()
+            .find(|claim| claim.target == remote_ship.peer && claim.slot == slot)
+        {
+            Some(claim) => claim.amount += hit.amount,
+            None => claims.push(HitClaim {
+                shooter,
+                target: remote_ship.peer,
+                time,
+                origin: hit.origin.to_array(),
+                direction: hit.direction.to_array(),
+                weapon: weapon as u32,
+                slot,
+                amount: hit.amount,
+            }),
+        }
+    }
+
+    for claim in claims {
+        match &client {
+            Some(client) if server.is_none() => client.send(&Message::HitClaim(claim)),
+            _ => hit_claims.send(HitClaimed(claim)),
+        }
+    }
+}
+
+/// Rewinds the target of each claimed hit to when the shooter saw it, and upholds the hit if the
+/// beam would have struck it then.
+///
+/// Claims that are malformed, or that name a weapon firing no beam or a hardpoint no ship has, are
+/// refused outright. Upheld hits deal no more damage than the hardpoint's [`BeamAllowance`]. Those
+/// on the host's ship damage it on the next tick; those on a client's ship are sent to them.
+#[allow(clippy::too_many_arguments)]
+fn validate_hit_claims(
+    server: Res<Server>,
+    time: Res<Time>,
+    settings: Res<InterpolationSettings>,
+    history: Res<HostHistory>,
+    weapon_library: Res<WeaponLibrary>,
+    weapon_definitions: Res<Assets<WeaponDefinition>>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    mut allowances: ResMut<BeamAllowances>,
+    mut hit_claims: EventReader<HitClaimed>,
+    player_query: Query<(Entity, &Transform, Option<&Collider>), With<PlayerShip>>,
+    remote_query: Query<(Entity, &RemoteShip, &SnapshotBuffer)>,
+    mut upheld_hits: ResMut<UpheldHits>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let max_slots = ship_library
+        .ships()
+        .iter()
+        .filter_map(|handle| ship_definitions.get(handle))
+        .map(|definition| definition.hardpoints.len())
+        .max()
+        .unwrap_or_default();
+    let player = player_query.get_single().ok();
+    let remote = |peer: PeerId| {
+        remote_query
+            .iter()
+            .find(|(_, remote_ship, _)| remote_ship.peer == peer)
+    };
+
+    for &HitClaimed(claim) in hit_claims.iter() {
+        let definition = weapon_library
+            .weapons()
+            .get(claim.weapon as usize)
+            .and_then(|handle| weapon_definitions.get(handle));
+        let Some((damage, beam)) =
+            definition.and_then(|definition| Some((definition.damage, definition.beam?)))
+        else {
+            debug!("Refused a hit claim from {:?} for no beam", claim.shooter);
+            continue;
+        };
+        if !claim.is_well_formed() || claim.slot as usize >= max_slots {
+            debug!("Refused a malformed hit claim from {:?}", claim.shooter);
+            continue;
+        }
+
+        let (target_buffer, radius) = if claim.target == PeerId::HOST {
+            let radius = player
+                .and_then(|(_, _, collider)| collider)
+                .map_or(REMOTE_SHIP_RADIUS, |collider| collider.radius);
+            (&history.0, radius)
+        } else {
+            let Some((_, _, buffer)) = remote(claim.target) else {
+                continue;
+            };
+            (buffer, REMOTE_SHIP_RADIUS)
+        };
+        let shooter_position = if claim.shooter == PeerId::HOST {
+            player.map(|(_, transform, _)| transform.translation)
+        } else {
+            remote(claim.shooter)
+                .and_then(|(_, _, buffer)| buffer.latest())
+                .map(|latest| latest.translation())
+        };
+
+        let Some(latest) = target_buffer.latest() else {
+            continue;
+        };
+        if claim.time < latest.time - MAX_REWIND
+            || !shooter_position
+                .is_some_and(|position| position.distance(claim.origin()) <= MAX_ORIGIN_ERROR)
+        {
+            debug!(
+                "Refused a stale or misplaced hit claim from {:?}",
+                claim.shooter
+            );
+            continue;
+        }
+        let Some((rewound, _)) = target_buffer.sample(claim.time, settings.max_extrapolation)
+        else {
+            continue;
+        };
+        let upheld = ray_sphere_distance(
+            claim.origin(),
+            claim.direction(),
+            rewound,
+            radius + HIT_TOLERANCE,
+        )
+        .is_some_and(|distance| distance <= beam.range);
+        if !upheld {
+            debug!("Refused a hit claim from {:?} that missed", claim.shooter);
+            continue;
+        }
+
+        let max_banked = damage * MAX_BANKED_BEAM_TIME;
+        let allowance = allowances
+            .0
+            .entry((claim.shooter, claim.slot))
+            .or_insert(BeamAllowance {
+                banked: max_banked,
+                last_claim: now,
+            });
+        let elapsed = (now - allowance.last_claim) as f32;
+        let banked = (allowance.banked + damage * elapsed).min(max_banked);
+        let amount = claim.amount.min(banked);
+        *allowance = BeamAllowance {
+            banked: banked - amount,
+            last_claim: now,
+        };
+
+        let hit = Hit {
+            shooter: claim.shooter,
+            target: claim.target,
+            amount,
+        };
+        if claim.target == PeerId::HOST {
+            let Some((entity, transform, _)) = player else {
+                continue;
+            };
+            upheld_hits.0.push(Damaged {
+                target: entity,
+                source: remote(claim.shooter).map(|(shooter, ..)| shooter),
+                amount: hit.amount,
+                position: transform.translation,
+            });
+        } else {
+            server.send_to(claim.target, &Message::Hit(hit));
+        }
+    }
+}
+
+/// Queues damage to the local player's ship for each hit the server has upheld on it.
+fn queue_confirmed_hits(
+    mut confirmed_hits: EventReader<HitConfirmed>,
+    player_query: Query<(Entity, &Transform), With<PlayerShip>>,
+    remote_query: Query<(Entity, &RemoteShip)>,
+    mut upheld_hits: ResMut<UpheldHits>,
+) {
+    let Ok((player, transform)) = player_query.get_single() else {
+        confirmed_hits.clear();
+        return;
+    };
+
+    for &HitConfirmed(hit) in confirmed_hits.iter() {
+        let shooter = remote_query
+            .iter()
+            .find(|(_, remote_ship)| remote_ship.peer == hit.shooter)
+            .map(|(shooter, _)| shooter);
+        upheld_hits.0.push(Damaged {
+            target: player,
+            source: shooter,
+            amount: hit.amount,
+            position: transform.translation,
+        });
+    }
+}
+
+/// Deals the damage of every upheld hit queued since the last tick.
+fn deal_upheld_hits(mut upheld_hits: ResMut<UpheldHits>, mut damaged: EventWriter<Damaged>) {
+    damaged.send_batch(upheld_hits.0.drain(..));
+}
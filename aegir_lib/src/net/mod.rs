@@ -0,0 +1,105 @@
+//! Co-op multiplayer over UDP.
+//!
+//! One player hosts a listen server and up to [`MAX_PEERS`] players (including the host) fly
+//! together. Every peer is authoritative over its own ship: it sends the server regular
+//! [`ShipState`](protocol::ShipState) snapshots, and the server relays them to everyone else.
+//! Other players' ships are shown slightly in the past, smoothly blended between their snapshots,
+//! and hits scored on them with beams are checked by the server against where they were then.
+//! Each peer also shares the projectiles its ship fires and the damage it takes, so that everyone
+//! sees the same fight.
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+
+use self::protocol::Message;
+
+pub use self::client::Client;
+pub use self::host::Server;
+pub use self::protocol::PeerId;
+
+mod client;
+mod combat;
+mod host;
+pub mod lag_compensation;
+pub mod protocol;
+pub mod replication;
+mod transport;
+
+/// The port that games are hosted on unless configured otherwise.
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// The most players that can share a game, including the host.
+pub const MAX_PEERS: usize = 4;
+
+/// How often each peer sends the state of its ship, in seconds.
+const SEND_INTERVAL: f32 = 1. / 20.;
+
+/// How long a peer can stay silent before it is considered disconnected, in seconds.
+const PEER_TIMEOUT: f64 = 5.;
+
+/// Adds multiplayer networking.
+///
+/// Nothing is sent or received until a [`Server`] or [`Client`] resource is inserted.
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetConfig>()
+            .init_resource::<ConnectionMessage>()
+            .configure_sets(Update, (NetSet::Receive, NetSet::Send).chain())
+            .add_systems(OnExit(GameState::Playing), disconnect)
+            .add_plugins((
+                host::HostPlugin,
+                client::ClientPlugin,
+                combat::CombatPlugin,
+                lag_compensation::LagCompensationPlugin,
+                replication::ReplicationPlugin,
+            ));
+    }
+}
+
+/// Systems that talk to other peers.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetSet {
+    /// Reads incoming messages.
+    Receive,
+    /// Sends the state of the local ship.
+    Send,
+}
+
+/// Where to host and join games.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct NetConfig {
+    /// The address (including port) of the game to join.
+    pub server_address: String,
+    /// The port to host games on.
+    pub port: u16,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        NetConfig {
+            server_address: format!("127.0.0.1:{DEFAULT_PORT}"),
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// Explains why the last connection attempt failed or ended, for display in the menus.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionMessage(pub Option<String>);
+
+/// Stops hosting or leaves the hosted game when play ends, telling the other peers first.
+fn disconnect(mut commands: Commands, server: Option<Res<Server>>, client: Option<Res<Client>>) {
+    if let Some(server) = server {
+        server.broadcast(&Message::Goodbye { peer: PeerId::HOST }, None);
+    }
+    if let Some(client) = client {
+        if let Some(peer) = client.peer() {
+            client.send(&Message::Goodbye { peer });
+        }
+    }
+
+    commands.remove_resource::<Server>();
+    commands.remove_resource::<Client>();
+}
@@ -0,0 +1,238 @@
+//! The messages exchanged between peers.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::flight::Velocity;
+
+/// Bumped whenever [`Message`] changes, so that mismatched builds refuse to play together.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Identifies a player in a multiplayer game.
+///
+/// The host is always [`PeerId::HOST`].
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Component,
+)]
+pub struct PeerId(pub u8);
+
+impl PeerId {
+    /// The player hosting the game.
+    pub const HOST: PeerId = PeerId(0);
+}
+
+/// A single datagram sent between peers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// Sent by a client until the server welcomes or rejects it.
+    Hello {
+        /// The [`PROTOCOL_VERSION`] of the client.
+        version: u32,
+    },
+    /// Accepts a client into the game.
+    Welcome {
+        /// The identity the client should use from now on.
+        peer: PeerId,
+    },
+    /// Refuses to let a client join.
+    Rejected(RejectReason),
+    /// The latest state of one peer's ship.
+    ShipState(ShipState),
+    /// A client's claim that its beam hit another player's ship, for the server to check.
+    HitClaim(HitClaim),
+    /// The server has upheld a claimed hit on the receiving client's ship.
+    Hit(Hit),
+    /// One peer's ship has fired a projectile.
+    ProjectileFired(ProjectileFired),
+    /// One peer's ship has been damaged, as that peer saw it.
+    Damaged(DamageReport),
+    /// One peer's ship has been destroyed, as that peer saw it.
+    Destroyed(DestructionReport),
+    /// A peer has left the game.
+    Goodbye {
+        /// The player that left.
+        peer: PeerId,
+    },
+}
+
+/// Why a server refused to let a client join.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// The game already has [`MAX_PEERS`](super::MAX_PEERS) players.
+    Full,
+    /// The client and server disagree on the [`PROTOCOL_VERSION`].
+    VersionMismatch,
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectReason::Full => write!(f, "the game is full"),
+            RejectReason::VersionMismatch => write!(f, "the host is running a different version"),
+        }
+    }
+}
+
+/// A snapshot of one peer's ship.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShipState {
+    /// The player flying the ship.
+    pub peer: PeerId,
+    /// When the snapshot was taken, in seconds on the flying peer's clock.
+    pub time: f64,
+    /// The ship's position.
+    pub translation: [f32; 3],
+    /// The ship's orientation, as a quaternion.
+    pub rotation: [f32; 4],
+    /// The ship's [`Velocity`].
+    pub velocity: [f32; 3],
+}
+
+impl ShipState {
+    /// Captures the state of a ship flown by `peer` at `time` on its clock.
+    pub fn new(peer: PeerId, time: f64, transform: &Transform, velocity: &Velocity) -> Self {
+        ShipState {
+            peer,
+            time,
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            velocity: velocity.0.to_array(),
+        }
+    }
+
+    /// The ship's position.
+    pub fn translation(&self) -> Vec3 {
+        Vec3::from_array(self.translation)
+    }
+
+    /// The ship's orientation.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_array(self.rotation).normalize()
+    }
+
+    /// The ship's velocity.
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::from_array(self.velocity)
+    }
+}
+
+/// A hit that one player's beam scored on another player's ship, as the shooter saw it.
+///
+/// The shooter sees other ships a little in the past, so the claim says when that was, and the
+/// server rewinds the target to that moment to check it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HitClaim {
+    /// The player whose beam hit.
+    pub shooter: PeerId,
+    /// The player whose ship was hit.
+    pub target: PeerId,
+    /// When the target was where the shooter saw it, in seconds on the target's clock.
+    pub time: f64,
+    /// Where the beam left the weapon.
+    pub origin: [f32; 3],
+    /// Which way the beam pointed.
+    pub direction: [f32; 3],
+    /// The weapon that fired the beam, as its place in the
+    /// [`WeaponLibrary`](crate::simulation::weapons::WeaponLibrary).
+    pub weapon: u32,
+    /// Which of the shooter's hardpoints fired the beam.
+    pub slot: u32,
+    /// How much damage the hit dealt.
+    pub amount: f32,
+}
+
+impl HitClaim {
+    /// Where the beam left the weapon.
+    pub fn origin(&self) -> Vec3 {
+        Vec3::from_array(self.origin)
+    }
+
+    /// Which way the beam pointed.
+    pub fn direction(&self) -> Vec3 {
+        Vec3::from_array(self.direction).normalize_or_zero()
+    }
+
+    /// Is every number in the claim finite, with a beam that points somewhere and does no
+    /// negative damage?
+    pub fn is_well_formed(&self) -> bool {
+        self.time.is_finite()
+            && self.origin().is_finite()
+            && Vec3::from_array(self.direction).is_finite()
+            && self.direction() != Vec3::ZERO
+            && self.amount.is_finite()
+            && self.amount >= 0.
+    }
+}
+
+/// A hit the server has upheld.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hit {
+    /// The player whose beam hit.
+    pub shooter: PeerId,
+    /// The player whose ship was hit.
+    pub target: PeerId,
+    /// How much damage the hit deals.
+    pub amount: f32,
+}
+
+/// A projectile fired by one peer's ship, for the other peers to fly too.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileFired {
+    /// The player whose ship fired.
+    pub peer: PeerId,
+    /// The weapon that fired, as its place in the
+    /// [`WeaponLibrary`](crate::simulation::weapons::WeaponLibrary).
+    pub weapon: u32,
+    /// Where the projectile left the weapon.
+    pub translation: [f32; 3],
+    /// Which way the projectile faced, as a quaternion.
+    pub rotation: [f32; 4],
+    /// How fast and which way the projectile flies.
+    pub velocity: [f32; 3],
+    /// The player whose ship a homing projectile is tracking, if any.
+    pub target: Option<PeerId>,
+}
+
+impl ProjectileFired {
+    /// Where and which way the projectile left the weapon.
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.translation))
+            .with_rotation(Quat::from_array(self.rotation).normalize())
+    }
+
+    /// How fast and which way the projectile flies.
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::from_array(self.velocity)
+    }
+
+    /// Is every number in the message finite?
+    pub fn is_well_formed(&self) -> bool {
+        Vec3::from_array(self.translation).is_finite()
+            && Quat::from_array(self.rotation).is_finite()
+            && self.velocity().is_finite()
+    }
+}
+
+/// Damage one peer's ship has taken.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DamageReport {
+    /// The player whose ship was damaged.
+    pub peer: PeerId,
+    /// The player responsible, if it was another player.
+    pub source: Option<PeerId>,
+    /// How much health the ship lost.
+    pub amount: f32,
+    /// Where the damage landed.
+    pub position: [f32; 3],
+}
+
+/// One peer's ship has been destroyed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DestructionReport {
+    /// The player whose ship was destroyed.
+    pub peer: PeerId,
+    /// The player who dealt the final blow, if it was another player.
+    pub killer: Option<PeerId>,
+    /// Where the ship was.
+    pub position: [f32; 3],
+}
@@ -0,0 +1,423 @@
+//! Mirrors the ships of other players into the local world.
+//!
+//! Snapshots of each remote ship are kept in a [`SnapshotBuffer`], and the ship is shown where the
+//! buffer says it was a short [`InterpolationSettings::delay`] ago, so there is usually a snapshot
+//! either side to blend between however unevenly they arrive. If the snapshots run dry, the ship
+//! carries on along its last known velocity for a little while, then stops to wait for more.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::{GameState, InGame};
+use crate::player::targeting::Targetable;
+use crate::simulation::factions::Faction;
+use crate::simulation::flight::Velocity;
+use crate::simulation::geometry::Collider;
+use crate::simulation::health::Destroyed;
+
+use super::protocol::{PeerId, ShipState};
+use super::NetSet;
+
+/// How much history a [`SnapshotBuffer`] keeps, in seconds.
+const BUFFER_LENGTH: f64 = 1.;
+
+/// How quickly a buffer's estimate of the clock offset drifts up towards later arrivals.
+///
+/// Earlier arrivals lower it at once, so that the estimate settles on the quickest delivery.
+const CLOCK_DRIFT: f64 = 0.01;
+
+/// How big a remote ship is to shoot at, in meters.
+pub const REMOTE_SHIP_RADIUS: f32 = 6.;
+
+/// Replication logic
+pub(super) struct ReplicationPlugin;
+
+impl Plugin for ReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShipStateReceived>()
+            .add_event::<PeerLeft>()
+            .init_resource::<RemoteShips>()
+            .init_resource::<InterpolationSettings>()
+            .add_console_command(
+                "interpolation",
+                "interpolation <delay ms> [extrapolation ms]",
+                interpolation_command,
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_ship_states,
+                    despawn_departed_ships,
+                    forget_destroyed_ships,
+                    interpolate_remote_ships,
+                )
+                    .chain()
+                    .after(NetSet::Receive),
+            )
+            .add_systems(OnExit(GameState::Playing), forget_remote_ships);
+    }
+}
+
+/// A snapshot of another player's ship has arrived.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ShipStateReceived(pub ShipState);
+
+/// Another player has left the game.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerLeft(pub PeerId);
+
+/// How far in the past remote ships are shown, and how far they may be guessed ahead.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct InterpolationSettings {
+    /// How far behind the latest snapshot remote ships are shown, in seconds.
+    ///
+    /// Longer delays ride out more jitter and loss, at the cost of seeing other players later.
+    pub delay: f32,
+    /// How long a remote ship keeps moving on its last known velocity once its snapshots run
+    /// out, in seconds.
+    pub max_extrapolation: f32,
+}
+
+impl Default for InterpolationSettings {
+    fn default() -> Self {
+        InterpolationSettings {
+            delay: 0.1,
+            max_extrapolation: 0.25,
+        }
+    }
+}
+
+/// A ship flown by another player.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct RemoteShip {
+    /// The player flying this ship.
+    pub peer: PeerId,
+}
+
+/// The recent snapshots of a ship, in the order they were taken.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct SnapshotBuffer {
+    /// Every snapshot from the last [`BUFFER_LENGTH`] seconds, oldest first.
+    snapshots: VecDeque<ShipState>,
+    /// How far the local clock runs ahead of the ship's, including the quickest delivery seen.
+    clock_offset: Option<f64>,
+    /// The time on the ship's clock that it was last shown at.
+    shown: Option<f64>,
+}
+
+impl SnapshotBuffer {
+    /// Adds a snapshot that arrived at `received_at` on the local clock.
+    ///
+    /// Snapshots that arrive out of order are slotted into place, and repeats are ignored.
+    pub fn push(&mut self, state: ShipState, received_at: f64) {
+        let offset = received_at - state.time;
+        self.clock_offset = Some(match self.clock_offset {
+            Some(current) if offset >= current => current + (offset - current) * CLOCK_DRIFT,
+            _ => offset,
+        });
+
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.time < state.time);
+        if self
+            .snapshots
+            .get(index)
+            .is_some_and(|snapshot| snapshot.time == state.time)
+        {
+            return;
+        }
+        self.snapshots.insert(index, state);
+
+        let Some(newest) = self.latest().map(|latest| latest.time) else {
+            return;
+        };
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|oldest| oldest.time < newest - BUFFER_LENGTH)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// The most recent snapshot.
+    pub fn latest(&self) -> Option<&ShipState> {
+        self.snapshots.back()
+    }
+
+    /// The time on the ship's clock matching `local_time` on ours, once a snapshot has arrived.
+    pub fn remote_time(&self, local_time: f64) -> Option<f64> {
+        self.clock_offset.map(|offset| local_time - offset)
+    }
+
+    /// The time on the ship's clock that it was last shown at, if it has been shown.
+    pub fn shown(&self) -> Option<f64> {
+        self.shown
+    }
+
+    /// Where the ship was at `time` on its clock, and which way it faced.
+    ///
+    /// Times between snapshots are blended between them. Times after the latest snapshot carry
+    /// on along its velocity for up to `max_extrapolation` seconds, and times before the oldest
+    /// are held at the oldest.
+    pub fn sample(&self, time: f64, max_extrapolation: f32) -> Option<(Vec3, Quat)> {
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.time <= time);
+        let Some(before) = index.checked_sub(1).map(|index| &self.snapshots[index]) else {
+            let oldest = self.snapshots.front()?;
+            return Some((oldest.translation(), oldest.rotation()));
+        };
+
+        Some(match self.snapshots.get(index) {
+            Some(after) => {
+                let amount = ((time - before.time) / (after.time - before.time)) as f32;
+                (
+                    before.translation().lerp(after.translation(), amount),
+                    before.rotation().slerp(after.rotation(), amount),
+                )
+            }
+            None => {
+                let ahead = ((time - before.time) as f32).min(max_extrapolation);
+                (
+                    before.translation() + before.velocity() * ahead,
+                    before.rotation(),
+                )
+            }
+        })
+    }
+}
+
+/// The entity mirroring each remote player's ship.
+#[derive(Resource, Debug, Default)]
+struct RemoteShips(HashMap<PeerId, Entity>);
+
+/// Updates remote ships from their latest snapshots, spawning ships for players we have not seen
+/// yet.
+fn apply_ship_states(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut events: EventReader<ShipStateReceived>,
+    mut remote_ships: ResMut<RemoteShips>,
+    mut query: Query<(&mut SnapshotBuffer, &mut Velocity)>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    for &ShipStateReceived(state) in events.iter() {
+        if let Some(&entity) = remote_ships.0.get(&state.peer) {
+            // Ships spawned earlier this frame cannot be queried yet, but already hold a snapshot
+            if let Ok((mut buffer, mut velocity)) = query.get_mut(entity) {
+                buffer.push(state, now);
+                if let Some(latest) = buffer.latest() {
+                    velocity.0 = latest.velocity();
+                }
+            }
+            continue;
+        }
+
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(state, now);
+
+        let entity = commands
+            .spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_translation(state.translation())
+                        .with_rotation(state.rotation()),
+                ),
+                RemoteShip { peer: state.peer },
+                buffer,
+                Velocity(state.velocity()),
+                Collider {
+                    radius: REMOTE_SHIP_RADIUS,
+                },
+                Targetable,
+                Faction::Aegir,
+                InGame,
+            ))
+            .id();
+
+        remote_ships.0.insert(state.peer, entity);
+    }
+}
+
+/// Removes the ships of players that have left.
+fn despawn_departed_ships(
+    mut commands: Commands,
+    mut events: EventReader<PeerLeft>,
+    mut remote_ships: ResMut<RemoteShips>,
+) {
+    for PeerLeft(peer) in events.iter() {
+        if let Some(entity) = remote_ships.0.remove(peer) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Despawns and forgets remote ships that have been destroyed, so that a new one is spawned once
+/// their player flies again.
+///
+/// Remote ships are destroyed outside of ticks, so they are despawned here rather than being left
+/// for the simulation to clear up.
+fn forget_destroyed_ships(
+    mut commands: Commands,
+    mut events: EventReader<Destroyed>,
+    mut remote_ships: ResMut<RemoteShips>,
+) {
+    for event in events.iter() {
+        remote_ships.0.retain(|_, &mut entity| {
+            let destroyed = entity == event.entity;
+            if destroyed {
+                commands.entity(entity).despawn_recursive();
+            }
+            !destroyed
+        });
+    }
+}
+
+/// Shows each remote ship where its snapshots say it was [`InterpolationSettings::delay`] ago.
+///
+/// Remote ships are also moved by their [`Velocity`] like any other body, but this puts them back
+/// where their snapshots say every frame.
+fn interpolate_remote_ships(
+    time: Res<Time>,
+    settings: Res<InterpolationSettings>,
+    mut query: Query<(&mut Transform, &mut SnapshotBuffer), With<RemoteShip>>,
+) {
+    let now = time.elapsed_seconds_f64();
+
+    for (mut transform, mut buffer) in query.iter_mut() {
+        let Some(shown) = buffer
+            .remote_time(now)
+            .map(|remote_now| remote_now - settings.delay as f64)
+        else {
+            continue;
+        };
+        let Some((translation, rotation)) = buffer.sample(shown, settings.max_extrapolation) else {
+            continue;
+        };
+
+        transform.translation = translation;
+        transform.rotation = rotation;
+        buffer.shown = Some(shown);
+    }
+}
+
+/// Forgets every remote ship once the game ends, since their entities are despawned with it.
+fn forget_remote_ships(mut remote_ships: ResMut<RemoteShips>) {
+    remote_ships.0.clear();
+}
+
+/// Sets how far in the past remote ships are shown, and how far they may be guessed ahead.
+fn interpolation_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (delay, extrapolation) = match *arguments {
+        [delay] => (delay, None),
+        [delay, extrapolation] => (delay, Some(extrapolation)),
+        _ => return Err("expected a delay and an optional extrapolation limit".to_string()),
+    };
+    let seconds = |milliseconds: &str| {
+        milliseconds
+            .parse()
+            .ok()
+            .filter(|&milliseconds: &f32| milliseconds >= 0.)
+            .map(|milliseconds| milliseconds / 1000.)
+            .ok_or_else(|| format!("`{milliseconds}` is not a duration"))
+    };
+    let delay = seconds(delay)?;
+    let extrapolation = extrapolation.map(seconds).transpose()?;
+
+    let mut settings = world.resource_mut::<InterpolationSettings>();
+    settings.delay = delay;
+    if let Some(extrapolation) = extrapolation {
+        settings.max_extrapolation = extrapolation;
+    }
+
+    Ok(format!(
+        "remote ships shown {:.0} ms behind, guessed up to {:.0} ms ahead",
+        settings.delay * 1000.,
+        settings.max_extrapolation * 1000.
+    ))
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A snapshot taken at `time` of a ship at `x` along the x axis, flying along it at `speed`.
+    fn snapshot(time: f64, x: f32, speed: f32) -> ShipState {
+        ShipState::new(
+            PeerId(1),
+            time,
+            &Transform::from_xyz(x, 0., 0.),
+            &Velocity(Vec3::new(speed, 0., 0.)),
+        )
+    }
+
+    /// A buffer holding `snapshots`, each delivered instantly.
+    fn buffer(snapshots: &[ShipState]) -> SnapshotBuffer {
+        let mut buffer = SnapshotBuffer::default();
+        for &snapshot in snapshots {
+            buffer.push(snapshot, snapshot.time);
+        }
+        buffer
+    }
+
+    /// An empty buffer has nothing to show.
+    #[test]
+    fn sample_of_an_empty_buffer_is_none() {
+        assert_eq!(SnapshotBuffer::default().sample(1., 0.25), None);
+    }
+
+    /// Times between snapshots are blended between them.
+    #[test]
+    fn sample_interpolates_between_snapshots() {
+        let buffer = buffer(&[snapshot(1., 0., 10.), snapshot(1.2, 10., 10.)]);
+
+        let (translation, _) = buffer.sample(1.05, 0.25).unwrap();
+        assert!((translation.x - 2.5).abs() < 1e-4);
+
+        let (translation, _) = buffer.sample(1.2, 0.25).unwrap();
+        assert!((translation.x - 10.).abs() < 1e-4);
+    }
+
+    /// Times before the oldest snapshot are held at the oldest.
+    #[test]
+    fn sample_holds_at_the_oldest_snapshot() {
+        let buffer = buffer(&[snapshot(1., 0., 10.), snapshot(1.2, 10., 10.)]);
+
+        let (translation, _) = buffer.sample(0.5, 0.25).unwrap();
+        assert_eq!(translation.x, 0.);
+    }
+
+    /// Times after the latest snapshot carry on along its velocity, but only so far.
+    #[test]
+    fn sample_extrapolates_up_to_the_limit() {
+        let buffer = buffer(&[snapshot(1., 0., 10.), snapshot(1.2, 10., 10.)]);
+
+        let (translation, _) = buffer.sample(1.3, 0.25).unwrap();
+        assert!((translation.x - 11.).abs() < 1e-4);
+
+        let (translation, _) = buffer.sample(3., 0.25).unwrap();
+        assert!((translation.x - 12.5).abs() < 1e-4);
+
+        let (translation, _) = buffer.sample(3., 0.).unwrap();
+        assert!((translation.x - 10.).abs() < 1e-4);
+    }
+
+    /// Snapshots arriving out of order are slotted into place.
+    #[test]
+    fn push_sorts_late_snapshots_into_place() {
+        let buffer = buffer(&[
+            snapshot(1., 0., 10.),
+            snapshot(1.4, 20., 10.),
+            snapshot(1.2, 10., 10.),
+        ]);
+
+        assert_eq!(buffer.latest().map(|latest| latest.time), Some(1.4));
+        let (translation, _) = buffer.sample(1.1, 0.25).unwrap();
+        assert!((translation.x - 5.).abs() < 1e-4);
+    }
+}
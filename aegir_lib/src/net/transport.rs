@@ -0,0 +1,71 @@
+//! A thin non-blocking wrapper around a UDP socket that sends and receives [`Message`]s.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use bevy::log::warn;
+
+use super::protocol::Message;
+
+/// The largest datagram we expect to receive, in bytes.
+const MAX_DATAGRAM_SIZE: usize = 1200;
+
+/// A bound, non-blocking UDP socket.
+#[derive(Debug)]
+pub(super) struct Transport {
+    /// The underlying socket.
+    socket: UdpSocket,
+}
+
+impl Transport {
+    /// Binds a socket to `address`.
+    pub(super) fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Transport { socket })
+    }
+
+    /// The address this socket is bound to.
+    pub(super) fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Sends `message` to `address`, logging rather than failing if it cannot be sent.
+    pub(super) fn send(&self, message: &Message, address: SocketAddr) {
+        let bytes = match bincode::serialize(message) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                warn!("Could not encode {message:?}: {error}");
+                return;
+            }
+        };
+
+        if let Err(error) = self.socket.send_to(&bytes, address) {
+            warn!("Could not send to {address}: {error}");
+        }
+    }
+
+    /// Drains every datagram that has arrived since the last call, skipping any that are malformed.
+    pub(super) fn receive(&self) -> Vec<(Message, SocketAddr)> {
+        let mut messages = Vec::new();
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, address)) => match bincode::deserialize(&buffer[..length]) {
+                    Ok(message) => messages.push((message, address)),
+                    Err(error) => warn!("Ignoring malformed datagram from {address}: {error}"),
+                },
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                // Windows reports ICMP port unreachable replies as errors on the next read
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(error) => {
+                    warn!("Could not receive: {error}");
+                    break;
+                }
+            }
+        }
+
+        messages
+    }
+}
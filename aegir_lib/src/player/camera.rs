@@ -1,17 +1,334 @@
 //! Code needed to run the game camera
+//!
+//! Press [`FlightAction::ToggleCockpit`] while flying to switch between the chase camera and the
+//! cockpit.
 
 use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::game_state::GameState;
+use crate::graphics::interpolation::InterpolationSet;
+use crate::simulation::asteroids::Asteroid;
+use crate::simulation::geometry::{ray_sphere_distance, Collider};
+use crate::simulation::health::{Damaged, Health};
+use crate::simulation::planets::Planet;
+use crate::simulation::stations::Station;
+use crate::simulation::weapons::{WeaponDefinition, WeaponFired};
+
+use super::input::{ActionState, FlightAction, InputSet};
+use super::photo_mode::PhotoMode;
+use super::ship::PlayerShip;
+
+/// Where the pilot's eyes are in the cockpit, in the ship's local space.
+pub const COCKPIT_EYE: Vec3 = Vec3::new(0., 0.35, -0.4);
 
 /// Camera logic
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, camera_setup);
+        app.init_resource::<CameraMode>()
+            .add_systems(Startup, camera_setup)
+            .add_systems(Update, shake_on_impact)
+            .add_systems(
+                FixedUpdate,
+                toggle_cockpit
+                    .in_set(InputSet::Apply)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(PhotoMode::Off)),
+            )
+            .add_systems(
+                PostUpdate,
+                (remove_camera_shake, follow_player, apply_camera_shake)
+                    .chain()
+                    .run_if(in_state(PhotoMode::Off))
+                    .after(InterpolationSet)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Which view of the world the player is looking through.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CameraMode {
+    /// Trailing behind the player's ship with a [`ChaseCamera`].
+    #[default]
+    Chase,
+    /// Sitting in the pilot's seat, looking out through the cockpit.
+    Cockpit,
+}
+
+/// A camera that trails behind the player's ship.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ChaseCamera {
+    /// Where the camera sits relative to the ship, in the ship's local space.
+    pub offset: Vec3,
+    /// How quickly the camera catches up with the ship; higher values are stiffer.
+    pub stiffness: f32,
+}
+
+impl Default for ChaseCamera {
+    fn default() -> Self {
+        ChaseCamera {
+            offset: Vec3::new(0., 2., 10.),
+            stiffness: 8.,
+        }
+    }
+}
+
+/// Keeps a [`ChaseCamera`] from passing through asteroids, planets and stations, by pulling it in
+/// front of anything between it and the ship.
+///
+/// The arm shortens instantly when blocked, so the camera never sees inside an obstruction, and
+/// extends smoothly back out once the view clears.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct SpringArm {
+    /// How far in front of an obstruction the camera is held, in meters.
+    pub margin: f32,
+    /// How quickly the arm extends once the view clears; higher values are stiffer.
+    pub stiffness: f32,
+    /// How long the arm currently is, in meters.
+    length: f32,
+}
+
+impl Default for SpringArm {
+    fn default() -> Self {
+        SpringArm {
+            margin: 1.,
+            stiffness: 3.,
+            length: f32::INFINITY,
+        }
+    }
+}
+
+/// Shakes a camera in proportion to the square of its trauma, which decays over time.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraShake {
+    /// How shaken the camera is, between `0.0` and `1.0`.
+    trauma: f32,
+    /// How much trauma wears off each second.
+    pub decay: f32,
+    /// How far the camera moves at full trauma, in meters.
+    pub max_offset: f32,
+    /// How far the camera turns at full trauma, in radians.
+    pub max_angle: f32,
+    /// How quickly the camera shakes back and forth.
+    pub frequency: f32,
+    /// How long the camera has been shaking, used to drive the shake.
+    elapsed: f32,
+    /// The movement applied last frame, which is undone before the camera follows its target.
+    applied_offset: Vec3,
+    /// The rotation applied last frame, which is undone before the camera follows its target.
+    applied_rotation: Quat,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake {
+            trauma: 0.,
+            decay: 1.5,
+            max_offset: 0.4,
+            max_angle: 0.05,
+            frequency: 25.,
+            elapsed: 0.,
+            applied_offset: Vec3::ZERO,
+            applied_rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+impl CameraShake {
+    /// How shaken the camera is, between `0.0` and `1.0`.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Shakes the camera harder, up to full trauma.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0., 1.);
     }
 }
 
 /// Spawn the player camera
 fn camera_setup(mut commands: Commands) {
-    commands.spawn(Camera3dBundle::default());
+    commands.spawn((
+        Camera3dBundle {
+            // See far enough for planets on the other side of a sector
+            projection: Projection::Perspective(PerspectiveProjection {
+                far: 20_000.,
+                ..default()
+            }),
+            ..default()
+        },
+        ChaseCamera::default(),
+        SpringArm::default(),
+        CameraShake::default(),
+    ));
+}
+
+/// Shakes the camera when the player's ship is hit or fires a weapon with recoil.
+fn shake_on_impact(
+    mut damaged: EventReader<Damaged>,
+    mut weapon_fired: EventReader<WeaponFired>,
+    weapon_definitions: Res<Assets<WeaponDefinition>>,
+    ship_query: Query<(Entity, &Health), With<PlayerShip>>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    let Ok((ship, health)) = ship_query.get_single() else {
+        damaged.clear();
+        weapon_fired.clear();
+        return;
+    };
+
+    let hits = damaged
+        .iter()
+        .filter(|event| event.target == ship)
+        .map(|event| 2. * event.amount / health.max().max(1.));
+    let recoil = weapon_fired
+        .iter()
+        .filter(|event| event.ship == ship)
+        .filter_map(|event| weapon_definitions.get(&event.weapon))
+        .map(|definition| definition.recoil);
+    let trauma: f32 = hits.sum::<f32>() + recoil.sum::<f32>();
+
+    if trauma > 0. {
+        for mut shake in camera_query.iter_mut() {
+            shake.add_trauma(trauma);
+        }
+    }
+}
+
+/// Undoes last frame's shake, so that cameras follow their targets from where they really are.
+fn remove_camera_shake(mut query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut shake) in query.iter_mut() {
+        transform.translation -= shake.applied_offset;
+        transform.rotation *= shake.applied_rotation.inverse();
+        shake.applied_offset = Vec3::ZERO;
+        shake.applied_rotation = Quat::IDENTITY;
+    }
+}
+
+/// Shakes each camera according to its trauma, then lets the trauma wear off.
+fn apply_camera_shake(time: Res<Time>, mut query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut shake) in query.iter_mut() {
+        shake.elapsed += time.delta_seconds();
+        let intensity = shake.trauma * shake.trauma;
+        if intensity > 0. {
+            // Sums of sines at unrelated frequencies wobble without visibly repeating
+            let t = shake.elapsed * shake.frequency;
+            let wobble =
+                |seed: f32| (t * (1. + 0.37 * seed) + seed).sin() * (t * 0.61 + seed * 2.3).cos();
+
+            let offset = transform.rotation
+                * Vec3::new(wobble(1.), wobble(2.), 0.)
+                * shake.max_offset
+                * intensity;
+            let rotation = Quat::from_euler(
+                EulerRot::XYZ,
+                wobble(3.) * shake.max_angle * intensity,
+                wobble(4.) * shake.max_angle * intensity,
+                wobble(5.) * shake.max_angle * intensity,
+            );
+
+            transform.translation += offset;
+            transform.rotation *= rotation;
+            shake.applied_offset = offset;
+            shake.applied_rotation = rotation;
+        }
+
+        shake.trauma = (shake.trauma - shake.decay * time.delta_seconds()).max(0.);
+    }
+}
+
+/// Switches between the chase camera and the cockpit when the player presses
+/// [`FlightAction::ToggleCockpit`].
+fn toggle_cockpit(
+    action_state: Res<ActionState<FlightAction>>,
+    mut camera_mode: ResMut<CameraMode>,
+) {
+    if !action_state.just_pressed(FlightAction::ToggleCockpit) {
+        return;
+    }
+
+    *camera_mode = match *camera_mode {
+        CameraMode::Chase => CameraMode::Cockpit,
+        CameraMode::Cockpit => CameraMode::Chase,
+    };
+}
+
+/// Moves each [`ChaseCamera`] towards its place behind the player's ship, keeping it on the near
+/// side of any obstructions when it has a [`SpringArm`].
+///
+/// In the cockpit, the camera is fixed at the pilot's eyes instead.
+fn follow_player(
+    time: Res<Time>,
+    camera_mode: Res<CameraMode>,
+    ship_query: Query<&Transform, (With<PlayerShip>, Without<ChaseCamera>)>,
+    obstacle_query: Query<
+        (&Transform, &Collider),
+        (
+            Or<(With<Asteroid>, With<Planet>, With<Station>)>,
+            Without<ChaseCamera>,
+        ),
+    >,
+    mut camera_query: Query<(&mut Transform, &ChaseCamera, Option<&mut SpringArm>)>,
+) {
+    let Ok(ship) = ship_query.get_single() else {
+        return;
+    };
+    let delta_time = time.delta_seconds();
+
+    for (mut transform, chase, arm) in camera_query.iter_mut() {
+        if *camera_mode == CameraMode::Cockpit {
+            transform.translation = ship.translation + ship.rotation * COCKPIT_EYE;
+            transform.rotation = ship.rotation;
+            continue;
+        }
+
+        let blend = (chase.stiffness * delta_time).min(1.);
+        let desired_translation = ship.translation + ship.rotation * chase.offset;
+        transform.translation = transform.translation.lerp(desired_translation, blend);
+        transform.rotation = transform.rotation.slerp(ship.rotation, blend);
+
+        let Some(mut arm) = arm else {
+            continue;
+        };
+        let arm_vector = transform.translation - ship.translation;
+        let full_length = arm_vector.length();
+        let Some(direction) = arm_vector.try_normalize() else {
+            continue;
+        };
+
+        let clear_length = obstacle_query
+            .iter()
+            // Obstructions around the ship itself, such as a station it is docked inside, would
+            // pull the camera all the way in, so look past them
+            .filter(|(obstacle, collider)| {
+                obstacle.translation.distance(ship.translation) > collider.radius
+            })
+            .filter_map(|(obstacle, collider)| {
+                ray_sphere_distance(
+                    ship.translation,
+                    direction,
+                    obstacle.translation,
+                    collider.radius,
+                )
+            })
+            .map(|distance| (distance - arm.margin).max(0.))
+            .fold(full_length, f32::min);
+
+        arm.length = if clear_length < arm.length {
+            clear_length
+        } else {
+            let extend = (arm.stiffness * delta_time).min(1.);
+            // Snap the last few centimeters rather than creeping towards them forever
+            let length = arm.length + (clear_length - arm.length) * extend;
+            if clear_length - length < 0.01 {
+                clear_length
+            } else {
+                length
+            }
+        };
+        transform.translation = ship.translation + direction * arm.length;
+    }
 }
@@ -0,0 +1,1358 @@
+//! Translates raw keyboard, mouse and gamepad input into player actions.
+//!
+//! Actions are grouped by the [`InputContext`] they apply in: [`FlightAction`]s while flying,
+//! [`DockAction`]s while docked and [`MenuAction`]s while a menu is open. Only the actions of the
+//! current context are triggered, so the same key can mean different things in each.
+//!
+//! Each action is either held, lasting as long as its input is, or toggled, switching on with one
+//! press and off with the next, as chosen in its [`InputMap`].
+//!
+//! The input maps come from the active [`BindingProfile`], one for each kind of controller, which
+//! can be switched at any time. Further players sharing the machine are [`LocalPlayer`]s, each
+//! with their own gamepad, and carry their own [`InputMap`] and [`ActionState`] as components
+//! rather than the resources the first player's live in.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::GameState;
+use crate::simulation::navigation::NavigationSet;
+use crate::simulation::stations::DockingComputer;
+use crate::simulation::time_control::TimeSet;
+
+use super::ship::PlayerShip;
+
+/// How many pixels of smooth scrolling count as one line of wheel scrolling.
+pub(crate) const PIXELS_PER_LINE: f32 = 20.;
+
+/// How far a stick or lever must be pushed before a binding to it is held.
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// How far a stick must be pushed before it steers at all.
+const STICK_DEAD_ZONE: f32 = 0.1;
+
+/// The name of the binding profile that further local players start with.
+pub const GAMEPAD_PROFILE: &str = "Gamepad";
+
+/// How fast the mouse must move to fully deflect the controls at a sensitivity of `1.0`, in
+/// counts per second.
+///
+/// Mouse motion is measured in the device's own counts rather than in pixels, so this does not
+/// depend on the size of the window.
+const FULL_DEFLECTION_SPEED: f32 = 1500.;
+
+/// Input handling logic
+pub(super) struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<FlightAction>>()
+            .init_resource::<InputMap<DockAction>>()
+            .init_resource::<InputMap<MenuAction>>()
+            .init_resource::<MouseSettings>()
+            .init_resource::<ActionState<FlightAction>>()
+            .init_resource::<ActionState<DockAction>>()
+            .init_resource::<ActionState<MenuAction>>()
+            .init_resource::<KeyboardFocus>()
+            .init_resource::<InputContext>()
+            .init_resource::<BindingProfiles>()
+            .add_console_command(
+                "action",
+                "action <flight action> <hold|toggle>",
+                action_command,
+            )
+            .add_console_command(
+                "mouse",
+                "mouse <sensitivity|acceleration|pitch|yaw|invert> <value>",
+                mouse_command,
+            )
+            .add_console_command(
+                "set",
+                "set <sensitivity|acceleration|pitch|yaw|invert> <value>",
+                mouse_command,
+            )
+            .add_console_command("profile", "profile <binding profile>", profile_command)
+            .configure_sets(
+                FixedUpdate,
+                (InputSet::Prepare, InputSet::Apply)
+                    .chain()
+                    .before(TimeSet)
+                    .before(NavigationSet),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    update_input_context,
+                    apply_binding_profile,
+                    read_input_devices,
+                    read_local_player_devices,
+                )
+                    .chain()
+                    .after(bevy::input::InputSystem),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    forget_presses::<FlightAction>,
+                    forget_presses::<DockAction>,
+                    forget_local_player_presses,
+                )
+                    .after(InputSet::Apply),
+            )
+            // Menus are drawn every frame rather than every tick, so forget their presses then
+            .add_systems(Last, forget_presses::<MenuAction>);
+    }
+}
+
+/// Where the [`ActionState`]s of [`FlightAction`]s and [`DockAction`]s are used within each
+/// simulation tick.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSet {
+    /// Records or replaces the actions for this tick, before anything reads them.
+    Prepare,
+    /// Turns the actions for this tick into commands for the player's ship.
+    Apply,
+}
+
+/// What the player's input is being used for, which decides the [`Actionlike`] set it triggers.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum InputContext {
+    /// A menu has the input, whether in play or not; [`MenuAction`]s are triggered.
+    #[default]
+    Menu,
+    /// The player is flying their ship; [`FlightAction`]s are triggered.
+    Flight,
+    /// The player's ship is docked with a station; [`DockAction`]s are triggered.
+    Docked,
+    /// Something is being typed, so no actions are triggered.
+    Typing,
+}
+
+/// A set of actions that are triggered together, in a single [`InputContext`].
+pub trait Actionlike: Debug + Clone + Copy + Eq + Hash + Send + Sync + 'static {
+    /// The context these actions are triggered in.
+    const CONTEXT: InputContext;
+}
+
+/// Everything the player can ask their ship to do in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FlightAction {
+    /// Tilt the nose up.
+    PitchUp,
+    /// Tilt the nose down.
+    PitchDown,
+    /// Turn the nose to the left.
+    YawLeft,
+    /// Turn the nose to the right.
+    YawRight,
+    /// Rotate counter-clockwise around the direction of travel.
+    RollLeft,
+    /// Rotate clockwise around the direction of travel.
+    RollRight,
+    /// Open the throttle a little further.
+    ThrottleUp,
+    /// Close the throttle a little further.
+    ThrottleDown,
+    /// Set the throttle so that the ship cruises at the speed of the current target.
+    MatchSpeed,
+    /// Close the throttle completely.
+    FullStop,
+    /// Select the next targetable entity.
+    CycleTarget,
+    /// Fire the afterburner while held.
+    Thrust,
+    /// Move power towards the engines.
+    DivertToEngines,
+    /// Move power towards the weapons.
+    DivertToWeapons,
+    /// Move power towards the shields.
+    DivertToShields,
+    /// Split power evenly between every subsystem.
+    BalancePower,
+    /// Fire the mining laser while held.
+    Mine,
+    /// Select the next waypoint.
+    CycleWaypoint,
+    /// Engage or disengage the autopilot.
+    ToggleAutopilot,
+    /// Fire the mounted weapons while held.
+    FireWeapons,
+    /// Hold the tractor beam on while held.
+    Activate,
+    /// Dock with a nearby station.
+    Dock,
+    /// Charge the jump drive for the sector chosen on the galaxy map, or cancel a charging jump.
+    Jump,
+    /// Drop a countermeasure to spoof missiles tracking the ship.
+    Countermeasures,
+    /// Drop a mine or turret pod behind the ship.
+    Deploy,
+    /// Slow time for a few seconds, or end slow motion early.
+    SlowMotion,
+    /// Open or close the menu of orders for the player's wingmen.
+    WingCommands,
+    /// Order the wingmen to attack the current target, while the wing command menu is open.
+    OrderAttack,
+    /// Order the wingmen back into formation, while the wing command menu is open.
+    OrderFormUp,
+    /// Order the wingmen to break formation and engage nearby enemies, while the wing command menu
+    /// is open.
+    OrderEngage,
+    /// Order the wingmen to attack anything attacking the player, while the wing command menu is
+    /// open.
+    OrderDefend,
+    /// Open the map of the current sector.
+    SectorMap,
+    /// Switch between the chase camera and the cockpit.
+    ToggleCockpit,
+}
+
+impl Actionlike for FlightAction {
+    const CONTEXT: InputContext = InputContext::Flight;
+}
+
+/// Everything the player can do while their ship is docked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DockAction {
+    /// Undock from the station.
+    Launch,
+}
+
+impl Actionlike for DockAction {
+    const CONTEXT: InputContext = InputContext::Docked;
+}
+
+/// Everything the player can do in a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MenuAction {
+    /// Choose the highlighted option, or carry on.
+    Select,
+    /// Close the menu, or go back to the previous one.
+    Back,
+}
+
+impl Actionlike for MenuAction {
+    const CONTEXT: InputContext = InputContext::Menu;
+}
+
+/// A physical input that can trigger an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputKind {
+    /// A key on the keyboard.
+    Keyboard(KeyCode),
+    /// A button on the mouse.
+    Mouse(MouseButton),
+    /// A button on a gamepad or joystick.
+    GamepadButton(GamepadButtonType),
+    /// A stick or lever on a gamepad or joystick, pushed at least halfway in one direction.
+    GamepadAxis(GamepadAxisType, AxisDirection),
+    /// A button on a gamepad or joystick pressed while the first, a modifier, is held.
+    ///
+    /// The button does not trigger its own actions while it is pressed as part of a chord.
+    GamepadChord(GamepadButtonType, GamepadButtonType),
+}
+
+/// Which way a stick or lever is pushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisDirection {
+    /// Up, right or forwards.
+    Positive,
+    /// Down, left or backwards.
+    Negative,
+}
+
+impl InputKind {
+    /// A short name for the input, for prompts.
+    pub fn label(self) -> String {
+        match self {
+            InputKind::Keyboard(key) => {
+                let name = format!("{key:?}");
+                // Number keys are called `Key1` and so on
+                match name.strip_prefix("Key") {
+                    Some(digit) if !digit.is_empty() => digit.to_string(),
+                    _ => name,
+                }
+            }
+            InputKind::Mouse(MouseButton::Other(button)) => format!("Mouse {button}"),
+            InputKind::Mouse(button) => format!("{button:?} Mouse"),
+            InputKind::GamepadButton(button) => format!("{button:?}"),
+            InputKind::GamepadAxis(axis, AxisDirection::Positive) => format!("{axis:?}+"),
+            InputKind::GamepadAxis(axis, AxisDirection::Negative) => format!("{axis:?}-"),
+            InputKind::GamepadChord(modifier, button) => format!("{modifier:?}+{button:?}"),
+        }
+    }
+}
+
+/// Which inputs trigger each action of one [`Actionlike`] set.
+///
+/// The same input may be bound in several sets, since only one set is triggered at a time.
+///
+/// The first player's maps are resources; those of further [`LocalPlayer`]s are components.
+#[derive(Resource, Component, Debug, Clone)]
+pub struct InputMap<A: Actionlike> {
+    /// The inputs bound to each action.
+    bindings: HashMap<A, Vec<InputKind>>,
+    /// The actions that each press switches on or off, rather than lasting while held.
+    toggled: HashSet<A>,
+}
+
+impl<A: Actionlike> InputMap<A> {
+    /// A map with nothing bound.
+    pub fn empty() -> Self {
+        InputMap {
+            bindings: HashMap::default(),
+            toggled: HashSet::default(),
+        }
+    }
+
+    /// Binds `input` to `action`, in addition to any existing bindings.
+    pub fn insert(&mut self, action: A, input: InputKind) -> &mut Self {
+        self.bindings.entry(action).or_default().push(input);
+        self
+    }
+
+    /// Removes every binding for `action`.
+    pub fn clear(&mut self, action: A) -> &mut Self {
+        self.bindings.remove(&action);
+        self
+    }
+
+    /// Replaces every binding with those of `other`, keeping which actions are toggled.
+    pub fn rebind_from(&mut self, other: &InputMap<A>) -> &mut Self {
+        self.bindings = other.bindings.clone();
+        self
+    }
+
+    /// The inputs currently bound to `action`.
+    pub fn bindings(&self, action: A) -> &[InputKind] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Makes each press of `action`'s inputs switch it on or off if `toggle`, or makes it last
+    /// while they are held otherwise.
+    pub fn set_toggle(&mut self, action: A, toggle: bool) -> &mut Self {
+        if toggle {
+            self.toggled.insert(action);
+        } else {
+            self.toggled.remove(&action);
+        }
+        self
+    }
+
+    /// Does each press of `action`'s inputs switch it on or off, rather than it lasting while they
+    /// are held?
+    pub fn is_toggle(&self, action: A) -> bool {
+        self.toggled.contains(&action)
+    }
+}
+
+impl Default for InputMap<FlightAction> {
+    fn default() -> Self {
+        use InputKind::{Keyboard, Mouse};
+
+        let mut input_map = InputMap::empty();
+
+        input_map
+            .insert(FlightAction::PitchUp, Keyboard(KeyCode::Down))
+            .insert(FlightAction::PitchDown, Keyboard(KeyCode::Up))
+            .insert(FlightAction::YawLeft, Keyboard(KeyCode::A))
+            .insert(FlightAction::YawRight, Keyboard(KeyCode::D))
+            .insert(FlightAction::RollLeft, Keyboard(KeyCode::Q))
+            .insert(FlightAction::RollRight, Keyboard(KeyCode::E))
+            .insert(FlightAction::ThrottleUp, Keyboard(KeyCode::W))
+            .insert(FlightAction::ThrottleDown, Keyboard(KeyCode::S))
+            .insert(FlightAction::MatchSpeed, Keyboard(KeyCode::M))
+            .insert(FlightAction::FullStop, Keyboard(KeyCode::X))
+            .insert(FlightAction::CycleTarget, Keyboard(KeyCode::T))
+            .insert(FlightAction::Thrust, Keyboard(KeyCode::ShiftLeft))
+            .insert(FlightAction::DivertToEngines, Keyboard(KeyCode::Key1))
+            .insert(FlightAction::DivertToWeapons, Keyboard(KeyCode::Key2))
+            .insert(FlightAction::DivertToShields, Keyboard(KeyCode::Key3))
+            .insert(FlightAction::BalancePower, Keyboard(KeyCode::Key4))
+            .insert(FlightAction::Mine, Keyboard(KeyCode::F))
+            .insert(FlightAction::CycleWaypoint, Keyboard(KeyCode::N))
+            .insert(FlightAction::ToggleAutopilot, Keyboard(KeyCode::Z))
+            .insert(FlightAction::FireWeapons, Keyboard(KeyCode::Space))
+            .insert(FlightAction::FireWeapons, Mouse(MouseButton::Left))
+            .insert(FlightAction::Activate, Keyboard(KeyCode::R))
+            .insert(FlightAction::Dock, Keyboard(KeyCode::L))
+            .insert(FlightAction::Jump, Keyboard(KeyCode::J))
+            .insert(FlightAction::Countermeasures, Keyboard(KeyCode::C))
+            .insert(FlightAction::Deploy, Keyboard(KeyCode::K))
+            .insert(FlightAction::SlowMotion, Keyboard(KeyCode::H))
+            .insert(FlightAction::WingCommands, Keyboard(KeyCode::Tab))
+            // The number keys divert power unless the wing command menu is open
+            .insert(FlightAction::OrderAttack, Keyboard(KeyCode::Key1))
+            .insert(FlightAction::OrderFormUp, Keyboard(KeyCode::Key2))
+            .insert(FlightAction::OrderEngage, Keyboard(KeyCode::Key3))
+            .insert(FlightAction::OrderDefend, Keyboard(KeyCode::Key4))
+            .insert(FlightAction::SectorMap, Keyboard(KeyCode::O))
+            .insert(FlightAction::ToggleCockpit, Keyboard(KeyCode::V));
+
+        input_map
+    }
+}
+
+impl Default for InputMap<DockAction> {
+    fn default() -> Self {
+        use InputKind::Keyboard;
+
+        let mut input_map = InputMap::empty();
+
+        input_map.insert(DockAction::Launch, Keyboard(KeyCode::L));
+
+        input_map
+    }
+}
+
+impl Default for InputMap<MenuAction> {
+    fn default() -> Self {
+        use InputKind::Keyboard;
+
+        let mut input_map = InputMap::empty();
+
+        input_map
+            .insert(MenuAction::Select, Keyboard(KeyCode::Return))
+            .insert(MenuAction::Select, Keyboard(KeyCode::Space))
+            .insert(MenuAction::Back, Keyboard(KeyCode::Escape));
+
+        input_map
+    }
+}
+
+/// How moving the mouse steers the ship.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct MouseSettings {
+    /// How strongly mouse movement deflects the controls; `1.0` fully deflects them when the
+    /// mouse moves at a brisk pace.
+    pub sensitivity: f32,
+    /// How much faster movements are amplified beyond slow ones, where `0.0` responds linearly.
+    pub acceleration: f32,
+    /// Does pushing the mouse forward pitch the nose down, like a flight stick?
+    pub invert_y: bool,
+    /// Scales the sensitivity of pitching, from moving the mouse forward and back.
+    pub pitch_scale: f32,
+    /// Scales the sensitivity of yawing, from moving the mouse from side to side.
+    pub yaw_scale: f32,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        MouseSettings {
+            sensitivity: 1.,
+            acceleration: 0.,
+            invert_y: false,
+            pitch_scale: 1.,
+            yaw_scale: 1.,
+        }
+    }
+}
+
+impl MouseSettings {
+    /// Turns the mouse's `velocity`, in counts per second, into how far it deflects the pitch and
+    /// yaw controls, in that order.
+    pub fn deflection(&self, velocity: Vec2) -> Vec2 {
+        let linear = velocity * self.sensitivity / FULL_DEFLECTION_SPEED;
+        let curved = linear * (1. + self.acceleration * linear.length());
+        // Mouse motion grows downwards and rightwards, while the controls pitch up and yaw left
+        // when positive
+        let pitch = if self.invert_y { curved.y } else { -curved.y };
+        Vec2::new(pitch * self.pitch_scale, -curved.x * self.yaw_scale)
+    }
+}
+
+/// The keyboard and mouse bindings, with gamepad bindings added for every action.
+///
+/// A gamepad has too few buttons to give every action its own, so holding the select button
+/// switches the face buttons, the sticks' buttons and the start button to a second layer of
+/// actions. Like the number keys, the directional pad gives orders while the wing command menu is
+/// open.
+fn gamepad_flight_map() -> InputMap<FlightAction> {
+    use AxisDirection::{Negative, Positive};
+    use GamepadAxisType::RightStickY;
+    use GamepadButtonType::{
+        DPadDown, DPadLeft, DPadRight, DPadUp, East, LeftThumb, LeftTrigger, LeftTrigger2, Mode,
+        North, RightThumb, RightTrigger, RightTrigger2, Select, South, Start, West,
+    };
+    use InputKind::{GamepadAxis, GamepadButton, GamepadChord};
+
+    let mut input_map = InputMap::default();
+
+    input_map
+        .insert(FlightAction::RollLeft, GamepadButton(LeftTrigger))
+        .insert(FlightAction::RollRight, GamepadButton(RightTrigger))
+        .insert(FlightAction::ThrottleUp, GamepadAxis(RightStickY, Positive))
+        .insert(
+            FlightAction::ThrottleDown,
+            GamepadAxis(RightStickY, Negative),
+        )
+        .insert(FlightAction::MatchSpeed, GamepadButton(RightThumb))
+        .insert(FlightAction::FullStop, GamepadButton(LeftThumb))
+        .insert(FlightAction::CycleTarget, GamepadButton(North))
+        .insert(FlightAction::Thrust, GamepadButton(LeftTrigger2))
+        .insert(FlightAction::DivertToEngines, GamepadButton(DPadUp))
+        .insert(FlightAction::DivertToWeapons, GamepadButton(DPadLeft))
+        .insert(FlightAction::DivertToShields, GamepadButton(DPadRight))
+        .insert(FlightAction::BalancePower, GamepadButton(DPadDown))
+        .insert(FlightAction::Mine, GamepadButton(West))
+        .insert(FlightAction::CycleWaypoint, GamepadChord(Select, North))
+        .insert(FlightAction::ToggleAutopilot, GamepadChord(Select, South))
+        .insert(FlightAction::FireWeapons, GamepadButton(RightTrigger2))
+        .insert(FlightAction::Activate, GamepadChord(Select, West))
+        .insert(FlightAction::Dock, GamepadButton(South))
+        .insert(FlightAction::Jump, GamepadButton(Mode))
+        .insert(FlightAction::Countermeasures, GamepadButton(East))
+        .insert(FlightAction::Deploy, GamepadChord(Select, East))
+        .insert(FlightAction::SlowMotion, GamepadChord(Select, RightThumb))
+        .insert(FlightAction::WingCommands, GamepadChord(Select, Start))
+        .insert(FlightAction::OrderAttack, GamepadButton(DPadUp))
+        .insert(FlightAction::OrderFormUp, GamepadButton(DPadLeft))
+        .insert(FlightAction::OrderEngage, GamepadButton(DPadRight))
+        .insert(FlightAction::OrderDefend, GamepadButton(DPadDown))
+        .insert(FlightAction::SectorMap, GamepadButton(Start))
+        .insert(FlightAction::ToggleCockpit, GamepadChord(Select, LeftThumb));
+
+    input_map
+}
+
+/// The keyboard and mouse bindings, with bindings added for a flight stick and throttle.
+///
+/// Flight sticks report their pitch and yaw on the left stick's axes, their twist on the right
+/// stick's horizontal axis and their throttle lever on the left `Z` axis. Like the number keys,
+/// the hat switch gives orders while the wing command menu is open.
+fn hotas_flight_map() -> InputMap<FlightAction> {
+    use AxisDirection::{Negative, Positive};
+    use GamepadAxisType::{LeftZ, RightStickX};
+    use GamepadButtonType::{
+        DPadDown, DPadLeft, DPadRight, DPadUp, East, LeftThumb, LeftTrigger, LeftTrigger2, Mode,
+        North, RightThumb, RightTrigger, RightTrigger2, Select, South, Start, West,
+    };
+    use InputKind::{GamepadAxis, GamepadButton};
+
+    let mut input_map = InputMap::default();
+
+    input_map
+        .insert(FlightAction::RollLeft, GamepadAxis(RightStickX, Negative))
+        .insert(FlightAction::RollRight, GamepadAxis(RightStickX, Positive))
+        .insert(FlightAction::ThrottleUp, GamepadAxis(LeftZ, Positive))
+        .insert(FlightAction::ThrottleDown, GamepadAxis(LeftZ, Negative))
+        .insert(FlightAction::FireWeapons, GamepadButton(South))
+        .insert(FlightAction::Thrust, GamepadButton(East))
+        .insert(FlightAction::CycleTarget, GamepadButton(North))
+        .insert(FlightAction::Countermeasures, GamepadButton(West))
+        .insert(FlightAction::DivertToEngines, GamepadButton(DPadUp))
+        .insert(FlightAction::DivertToWeapons, GamepadButton(DPadLeft))
+        .insert(FlightAction::DivertToShields, GamepadButton(DPadRight))
+        .insert(FlightAction::BalancePower, GamepadButton(DPadDown))
+        .insert(FlightAction::Mine, GamepadButton(LeftTrigger))
+        .insert(FlightAction::Deploy, GamepadButton(RightTrigger))
+        .insert(FlightAction::SlowMotion, GamepadButton(LeftTrigger2))
+        .insert(FlightAction::Jump, GamepadButton(RightTrigger2))
+        .insert(FlightAction::CycleWaypoint, GamepadButton(Select))
+        .insert(FlightAction::ToggleAutopilot, GamepadButton(Start))
+        .insert(FlightAction::WingCommands, GamepadButton(LeftThumb))
+        .insert(FlightAction::Dock, GamepadButton(RightThumb))
+        .insert(FlightAction::OrderAttack, GamepadButton(DPadUp))
+        .insert(FlightAction::OrderFormUp, GamepadButton(DPadLeft))
+        .insert(FlightAction::OrderEngage, GamepadButton(DPadRight))
+        .insert(FlightAction::OrderDefend, GamepadButton(DPadDown))
+        .insert(FlightAction::ToggleCockpit, GamepadButton(Mode));
+
+    input_map
+}
+
+/// The stick axes that steer the ship, deflecting the controls in proportion like the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StickLook {
+    /// The axis that pitches the nose; pushing it forwards pitches down, like a flight stick.
+    pub pitch: GamepadAxisType,
+    /// The axis that yaws the nose.
+    pub yaw: GamepadAxisType,
+}
+
+impl StickLook {
+    /// How far the stick deflects the pitch and yaw controls, in that order, while `devices` hold
+    /// it.
+    fn deflection(&self, devices: &Devices) -> Vec2 {
+        let dead_zone = |value: f32| {
+            if value.abs() < STICK_DEAD_ZONE {
+                0.
+            } else {
+                value
+            }
+        };
+        Vec2::new(
+            -dead_zone(devices.axis(self.pitch)),
+            -dead_zone(devices.axis(self.yaw)),
+        )
+    }
+}
+
+/// A named set of bindings for every [`Actionlike`] set, suited to one kind of controller.
+#[derive(Debug, Clone)]
+pub struct BindingProfile {
+    /// The name shown to the player.
+    pub name: String,
+    /// The bindings for flying.
+    pub flight: InputMap<FlightAction>,
+    /// The bindings while docked.
+    pub dock: InputMap<DockAction>,
+    /// The bindings for menus.
+    pub menu: InputMap<MenuAction>,
+    /// The stick that steers the ship alongside the mouse, if any.
+    pub stick_look: Option<StickLook>,
+}
+
+/// Every [`BindingProfile`], and which one the first player is using.
+///
+/// Choosing a profile copies its bindings into the first player's [`InputMap`]s.
+#[derive(Resource, Debug, Clone)]
+pub struct BindingProfiles {
+    /// The profiles, in the order they are offered.
+    profiles: Vec<BindingProfile>,
+    /// The index of the profile in use.
+    active: usize,
+}
+
+impl Default for BindingProfiles {
+    fn default() -> Self {
+        use InputKind::GamepadButton;
+
+        let mut gamepad_dock = InputMap::<DockAction>::default();
+        gamepad_dock.insert(DockAction::Launch, GamepadButton(GamepadButtonType::South));
+        let mut gamepad_menu = InputMap::<MenuAction>::default();
+        gamepad_menu
+            .insert(MenuAction::Select, GamepadButton(GamepadButtonType::South))
+            .insert(MenuAction::Back, GamepadButton(GamepadButtonType::East))
+            .insert(MenuAction::Back, GamepadButton(GamepadButtonType::Start));
+
+        BindingProfiles {
+            profiles: vec![
+                BindingProfile {
+                    name: "Keyboard+Mouse".to_string(),
+                    flight: InputMap::default(),
+                    dock: InputMap::default(),
+                    menu: InputMap::default(),
+                    stick_look: None,
+                },
+                BindingProfile {
+                    name: GAMEPAD_PROFILE.to_string(),
+                    flight: gamepad_flight_map(),
+                    dock: gamepad_dock.clone(),
+                    menu: gamepad_menu.clone(),
+                    stick_look: Some(StickLook {
+                        pitch: GamepadAxisType::LeftStickY,
+                        yaw: GamepadAxisType::LeftStickX,
+                    }),
+                },
+                BindingProfile {
+                    name: "HOTAS".to_string(),
+                    flight: hotas_flight_map(),
+                    dock: gamepad_dock,
+                    menu: gamepad_menu,
+                    stick_look: Some(StickLook {
+                        pitch: GamepadAxisType::LeftStickY,
+                        yaw: GamepadAxisType::LeftStickX,
+                    }),
+                },
+            ],
+            active: 0,
+        }
+    }
+}
+
+impl BindingProfiles {
+    /// Every profile, in the order they are offered.
+    pub fn profiles(&self) -> &[BindingProfile] {
+        &self.profiles
+    }
+
+    /// The profile the first player is using.
+    pub fn active(&self) -> &BindingProfile {
+        &self.profiles[self.active]
+    }
+
+    /// The profile called `name`, ignoring case, if there is one.
+    pub fn get(&self, name: &str) -> Option<&BindingProfile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Switches the first player to the profile called `name`, ignoring case, returning whether
+    /// there is one.
+    pub fn select(&mut self, name: &str) -> bool {
+        match self
+            .profiles
+            .iter()
+            .position(|profile| profile.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => {
+                self.active = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Switches the first player to the next profile, after the last going back to the first.
+    pub fn cycle(&mut self) {
+        self.active = (self.active + 1) % self.profiles.len();
+    }
+}
+
+/// A further player sharing this machine, who flies their own ship with their own gamepad.
+///
+/// The first player has the keyboard, the mouse, and any gamepad no local player has claimed, so
+/// is not a `LocalPlayer`. Local players' ships carry their own [`InputMap`] and [`ActionState`]
+/// of [`FlightAction`]s.
+///
+/// Local players can only steer, work the throttle and afterburner, and fire their weapons. Their
+/// other bindings do nothing, since the rest of the game, from targeting to docking, follows the
+/// first player.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalPlayer {
+    /// Which player this is, counting the first player as `0`.
+    pub index: u8,
+    /// The gamepad they play with.
+    pub gamepad: Gamepad,
+    /// The stick that steers their ship, if any.
+    pub stick_look: Option<StickLook>,
+}
+
+/// The raw input devices, as seen by one player.
+struct Devices<'a> {
+    /// The keyboard, if the player has it.
+    keyboard: Option<&'a Input<KeyCode>>,
+    /// The mouse's buttons, if the player has the mouse.
+    mouse_buttons: Option<&'a Input<MouseButton>>,
+    /// The buttons of every gamepad.
+    gamepad_buttons: &'a Input<GamepadButton>,
+    /// The sticks and levers of every gamepad.
+    gamepad_axes: &'a Axis<GamepadAxis>,
+    /// The gamepads the player holds.
+    gamepads: Vec<Gamepad>,
+}
+
+impl Devices<'_> {
+    /// Is `input` held on any of the player's devices?
+    fn held(&self, input: InputKind) -> bool {
+        match input {
+            InputKind::Keyboard(key) => self.keyboard.is_some_and(|keyboard| keyboard.pressed(key)),
+            InputKind::Mouse(button) => self
+                .mouse_buttons
+                .is_some_and(|mouse_buttons| mouse_buttons.pressed(button)),
+            InputKind::GamepadButton(button) => self.gamepads.iter().any(|&gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button))
+            }),
+            InputKind::GamepadAxis(axis, direction) => {
+                let value = self.axis(axis);
+                match direction {
+                    AxisDirection::Positive => value >= AXIS_PRESS_THRESHOLD,
+                    AxisDirection::Negative => value <= -AXIS_PRESS_THRESHOLD,
+                }
+            }
+            InputKind::GamepadChord(modifier, button) => self.gamepads.iter().any(|&gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, modifier))
+                    && self
+                        .gamepad_buttons
+                        .pressed(GamepadButton::new(gamepad, button))
+            }),
+        }
+    }
+
+    /// How far `axis` is pushed on whichever of the player's gamepads pushes it furthest.
+    fn axis(&self, axis: GamepadAxisType) -> f32 {
+        self.gamepads
+            .iter()
+            .filter_map(|&gamepad| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis)))
+            .fold(0., |furthest: f32, value| {
+                if value.abs() > furthest.abs() {
+                    value
+                } else {
+                    furthest
+                }
+            })
+    }
+}
+
+/// Whether the keyboard is playing the game, working a menu or typing into a text field.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyboardFocus {
+    /// Input devices trigger the actions of the [`InputContext`] for the [`GameState`].
+    #[default]
+    Game,
+    /// A menu opened during play has the keyboard, so only [`MenuAction`]s are triggered.
+    Menu,
+    /// Something is being typed, so no actions are triggered.
+    Text,
+}
+
+/// The actions the player is performing from one [`Actionlike`] set.
+///
+/// [`FlightAction`]s and [`DockAction`]s are read by the simulation in [`FixedUpdate`]. Presses and
+/// scrolling are collected until the next tick consumes them, so that ticks never miss a quick tap
+/// and never see one press twice, however many (or few) ticks run each frame. [`MenuAction`]s are
+/// read by menus in [`Update`], and their presses last for a single frame.
+///
+/// The first player's states are resources; those of further [`LocalPlayer`]s are components.
+#[derive(Resource, Component, Debug)]
+pub struct ActionState<A: Actionlike> {
+    /// Actions that are currently being performed: those whose inputs are held, and toggled actions
+    /// that are switched on.
+    pressed: HashSet<A>,
+    /// Actions whose inputs are currently held.
+    held: HashSet<A>,
+    /// Actions whose inputs were first held since the last tick.
+    just_pressed: HashSet<A>,
+    /// Actions whose inputs were already held when their context became active, which are ignored
+    /// until released.
+    suppressed: HashSet<A>,
+    /// Lines scrolled on the mouse wheel since the last tick, positive when scrolling away from the
+    /// player.
+    scroll: f32,
+    /// How far mouse movement since the last tick has deflected the pitch and yaw controls,
+    /// multiplied by how long it was deflected for, in seconds.
+    look: Vec2,
+}
+
+impl<A: Actionlike> Default for ActionState<A> {
+    fn default() -> Self {
+        ActionState {
+            pressed: HashSet::default(),
+            held: HashSet::default(),
+            just_pressed: HashSet::default(),
+            suppressed: HashSet::default(),
+            scroll: 0.,
+            look: Vec2::ZERO,
+        }
+    }
+}
+
+impl<A: Actionlike> ActionState<A> {
+    /// Is `action` currently held, or switched on if it is toggled?
+    pub fn pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Was `action` first held, or switched on if it is toggled, since the last tick?
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Lines scrolled on the mouse wheel since the last tick, positive when scrolling away from the
+    /// player.
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    /// How far mouse movement since the last tick has deflected the pitch and yaw controls,
+    /// multiplied by how long it was deflected for, in seconds.
+    ///
+    /// Dividing by the tick's length gives the deflection to steer with for the tick.
+    pub fn look(&self) -> Vec2 {
+        self.look
+    }
+
+    /// Every action that is currently held.
+    pub fn pressed_actions(&self) -> impl Iterator<Item = A> + '_ {
+        self.pressed.iter().copied()
+    }
+
+    /// Every action that was first held since the last tick.
+    pub fn just_pressed_actions(&self) -> impl Iterator<Item = A> + '_ {
+        self.just_pressed.iter().copied()
+    }
+
+    /// Replaces the actions for this tick wholesale, ignoring the input devices.
+    pub fn overwrite(
+        &mut self,
+        pressed: impl IntoIterator<Item = A>,
+        just_pressed: impl IntoIterator<Item = A>,
+        scroll: f32,
+        look: Vec2,
+    ) {
+        self.pressed = pressed.into_iter().collect();
+        self.just_pressed = just_pressed.into_iter().collect();
+        self.scroll = scroll;
+        self.look = look;
+    }
+
+    /// Returns `1.0` if only `positive` is held, `-1.0` if only `negative` is held and `0.0`
+    /// otherwise.
+    pub fn axis(&self, negative: A, positive: A) -> f32 {
+        f32::from(u8::from(self.pressed(positive))) - f32::from(u8::from(self.pressed(negative)))
+    }
+
+    /// Forgets the presses and scrolling consumed by this tick, or for menus, by this frame.
+    fn forget_presses(&mut self) {
+        self.just_pressed.clear();
+        self.scroll = 0.;
+        self.look = Vec2::ZERO;
+    }
+
+    /// Records which actions are held on the player's `devices` according to the `input_map`,
+    /// flipping toggled actions on each fresh press, or releases them all if the `context` is not
+    /// theirs.
+    fn read_buttons(&mut self, context: InputContext, input_map: &InputMap<A>, devices: &Devices) {
+        let previously_pressed = std::mem::take(&mut self.pressed);
+        let previously_held = std::mem::take(&mut self.held);
+
+        // Buttons pressed as part of a chord belong to the chord alone
+        let chorded: Vec<GamepadButtonType> = input_map
+            .bindings
+            .values()
+            .flatten()
+            .filter_map(|&input| match input {
+                InputKind::GamepadChord(_, button) if devices.held(input) => Some(button),
+                _ => None,
+            })
+            .collect();
+
+        for (&action, inputs) in input_map.bindings.iter() {
+            let held = inputs.iter().any(|&input| match input {
+                InputKind::GamepadButton(button) if chorded.contains(&button) => false,
+                _ => devices.held(input),
+            });
+
+            if !held {
+                self.suppressed.remove(&action);
+            } else if context != A::CONTEXT {
+                // Keys held as the context changes belong to the old context, so should not
+                // trigger anything in the new one
+                self.suppressed.insert(action);
+            } else if !self.suppressed.contains(&action) {
+                self.held.insert(action);
+            }
+
+            let pressed = if input_map.is_toggle(action) {
+                // Toggled actions also switch off when their context ends
+                let tapped = self.held.contains(&action) && !previously_held.contains(&action);
+                context == A::CONTEXT && previously_pressed.contains(&action) != tapped
+            } else {
+                self.held.contains(&action)
+            };
+            if pressed {
+                self.pressed.insert(action);
+                if !previously_pressed.contains(&action) {
+                    self.just_pressed.insert(action);
+                }
+            }
+        }
+    }
+}
+
+/// Works out which [`InputContext`] the player's input is being used in.
+fn update_input_context(
+    game_state: Res<State<GameState>>,
+    focus: Res<KeyboardFocus>,
+    player_query: Query<&DockingComputer, With<PlayerShip>>,
+    mut context: ResMut<InputContext>,
+) {
+    let docked = player_query
+        .get_single()
+        .is_ok_and(|computer| computer.docked().is_some());
+
+    let current = match (*focus, game_state.get()) {
+        (KeyboardFocus::Text, _) => InputContext::Typing,
+        (KeyboardFocus::Game, GameState::Playing) if docked => InputContext::Docked,
+        (KeyboardFocus::Game, GameState::Playing) => InputContext::Flight,
+        _ => InputContext::Menu,
+    };
+    if *context != current {
+        *context = current;
+    }
+}
+
+/// Copies the bindings of the first player's [`BindingProfile`] into their [`InputMap`]s whenever
+/// they choose another.
+fn apply_binding_profile(
+    profiles: Res<BindingProfiles>,
+    mut flight_map: ResMut<InputMap<FlightAction>>,
+    mut dock_map: ResMut<InputMap<DockAction>>,
+    mut menu_map: ResMut<InputMap<MenuAction>>,
+) {
+    if !profiles.is_changed() {
+        return;
+    }
+
+    let profile = profiles.active();
+    flight_map.rebind_from(&profile.flight);
+    dock_map.rebind_from(&profile.dock);
+    menu_map.rebind_from(&profile.menu);
+}
+
+/// Reads the first player's input devices and records which actions of the current
+/// [`InputContext`] are being performed.
+///
+/// The first player has the keyboard and mouse, and any gamepads no [`LocalPlayer`] has claimed.
+#[allow(clippy::too_many_arguments)]
+fn read_input_devices(
+    time: Res<Time>,
+    context: Res<InputContext>,
+    input_maps: (
+        Res<InputMap<FlightAction>>,
+        Res<InputMap<DockAction>>,
+        Res<InputMap<MenuAction>>,
+    ),
+    profiles: Res<BindingProfiles>,
+    mouse_settings: Res<MouseSettings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_input: (
+        Res<Gamepads>,
+        Res<Input<GamepadButton>>,
+        Res<Axis<GamepadAxis>>,
+    ),
+    local_player_query: Query<&LocalPlayer>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    action_states: (
+        ResMut<ActionState<FlightAction>>,
+        ResMut<ActionState<DockAction>>,
+        ResMut<ActionState<MenuAction>>,
+    ),
+) {
+    let (gamepads, gamepad_buttons, gamepad_axes) = gamepad_input;
+    let devices = Devices {
+        keyboard: Some(&keyboard),
+        mouse_buttons: Some(&mouse_buttons),
+        gamepad_buttons: &gamepad_buttons,
+        gamepad_axes: &gamepad_axes,
+        gamepads: gamepads
+            .iter()
+            .filter(|&gamepad| {
+                local_player_query
+                    .iter()
+                    .all(|player| player.gamepad != gamepad)
+            })
+            .collect(),
+    };
+
+    let (flight_map, dock_map, menu_map) = input_maps;
+    let (mut flight_state, mut dock_state, mut menu_state) = action_states;
+    flight_state.read_buttons(*context, &flight_map, &devices);
+    dock_state.read_buttons(*context, &dock_map, &devices);
+    menu_state.read_buttons(*context, &menu_map, &devices);
+
+    // Only flight is steered with the mouse and sticks
+    if *context != InputContext::Flight {
+        mouse_motion.clear();
+        mouse_wheel.clear();
+        return;
+    }
+
+    let action_state = &mut *flight_state;
+    action_state.scroll += mouse_wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum::<f32>();
+
+    // Shape the mouse's speed over this frame, rather than its raw movement, so that the
+    // acceleration curve does not depend on the frame rate
+    let delta_time = time.delta_seconds();
+    let motion: Vec2 = mouse_motion.iter().map(|event| event.delta).sum();
+    if delta_time > 0. && motion != Vec2::ZERO {
+        action_state.look += mouse_settings.deflection(motion / delta_time) * delta_time;
+    }
+    if let Some(stick_look) = profiles.active().stick_look {
+        action_state.look += stick_look.deflection(&devices) * delta_time;
+    }
+}
+
+/// Reads each [`LocalPlayer`]'s gamepad and records which of their [`FlightAction`]s are being
+/// performed.
+///
+/// Local players can only fly, so their actions are released whenever the game is not being
+/// played.
+fn read_local_player_devices(
+    time: Res<Time>,
+    game_state: Res<State<GameState>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut query: Query<(
+        &LocalPlayer,
+        &InputMap<FlightAction>,
+        &mut ActionState<FlightAction>,
+    )>,
+) {
+    let context = match game_state.get() {
+        GameState::Playing => InputContext::Flight,
+        _ => InputContext::Menu,
+    };
+    let delta_time = time.delta_seconds();
+
+    for (player, input_map, mut action_state) in query.iter_mut() {
+        let devices = Devices {
+            keyboard: None,
+            mouse_buttons: None,
+            gamepad_buttons: &gamepad_buttons,
+            gamepad_axes: &gamepad_axes,
+            gamepads: vec![player.gamepad],
+        };
+        action_state.read_buttons(context, input_map, &devices);
+
+        if let Some(stick_look) = player
+            .stick_look
+            .filter(|_| context == InputContext::Flight)
+        {
+            action_state.look += stick_look.deflection(&devices) * delta_time;
+        }
+    }
+}
+
+/// Forgets the presses and scrolling consumed by this tick, or for menus, by this frame.
+fn forget_presses<A: Actionlike>(mut action_state: ResMut<ActionState<A>>) {
+    action_state.forget_presses();
+}
+
+/// Forgets the presses and steering of each [`LocalPlayer`] consumed by this tick.
+fn forget_local_player_presses(
+    mut query: Query<&mut ActionState<FlightAction>, With<LocalPlayer>>,
+) {
+    for mut action_state in query.iter_mut() {
+        action_state.forget_presses();
+    }
+}
+
+/// Console command that makes a [`FlightAction`] held or toggled.
+fn action_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let [name, mode] = *arguments else {
+        return Err("expected an action and `hold` or `toggle`".to_string());
+    };
+
+    let action = ron::from_str::<FlightAction>(name)
+        .map_err(|_| format!("there is no flight action called `{name}`"))?;
+    let toggle = match mode {
+        "hold" => false,
+        "toggle" => true,
+        _ => return Err(format!("`{mode}` is not `hold` or `toggle`")),
+    };
+    world
+        .resource_mut::<InputMap<FlightAction>>()
+        .set_toggle(action, toggle);
+
+    Ok(format!("{name} set to {mode}"))
+}
+
+/// Console command that switches the first player to another [`BindingProfile`].
+fn profile_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let name = arguments.join(" ");
+    if name.is_empty() {
+        return Err("expected the name of a binding profile".to_string());
+    }
+
+    let mut profiles = world.resource_mut::<BindingProfiles>();
+    if !profiles.select(&name) {
+        let names: Vec<&str> = profiles
+            .profiles()
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+        return Err(format!(
+            "there is no binding profile called `{name}`; try {}",
+            names.join(", ")
+        ));
+    }
+
+    Ok(format!("using the {} bindings", profiles.active().name))
+}
+
+/// Console command that changes one of the [`MouseSettings`], as either `mouse` or `set`.
+fn mouse_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let [setting, value] = *arguments else {
+        return Err("expected a setting and a value".to_string());
+    };
+
+    let mut settings = world.resource_mut::<MouseSettings>();
+    if setting == "invert" {
+        settings.invert_y = match value {
+            "on" => true,
+            "off" => false,
+            _ => return Err(format!("`{value}` is not `on` or `off`")),
+        };
+        return Ok(format!("invert {value}"));
+    }
+
+    // No acceleration responds linearly, but any other setting at zero leaves the mouse dead
+    let (field, allows_zero) = match setting {
+        "sensitivity" => (&mut settings.sensitivity, false),
+        "acceleration" => (&mut settings.acceleration, true),
+        "pitch" => (&mut settings.pitch_scale, false),
+        "yaw" => (&mut settings.yaw_scale, false),
+        _ => return Err(format!("there is no mouse setting called `{setting}`")),
+    };
+    let expected = if allows_zero {
+        "a non-negative"
+    } else {
+        "a positive"
+    };
+    let number = value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite() && (*number > 0. || allows_zero && *number == 0.))
+        .ok_or_else(|| format!("`{value}` is not {expected} number"))?;
+    *field = number;
+
+    Ok(format!("{setting} set to {number}"))
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A map binding `action` to the space bar, toggled if `toggle`.
+    fn space_map(action: FlightAction, toggle: bool) -> InputMap<FlightAction> {
+        let mut input_map = InputMap::empty();
+        input_map
+            .insert(action, InputKind::Keyboard(KeyCode::Space))
+            .set_toggle(action, toggle);
+        input_map
+    }
+
+    /// Reads one tick of the keyboard in `context`, with the space bar held if `held`.
+    fn tick(
+        action_state: &mut ActionState<FlightAction>,
+        input_map: &InputMap<FlightAction>,
+        context: InputContext,
+        held: bool,
+    ) {
+        let mut keyboard = Input::<KeyCode>::default();
+        if held {
+            keyboard.press(KeyCode::Space);
+        }
+        let gamepad_buttons = Input::default();
+        let gamepad_axes = Axis::default();
+        let devices = Devices {
+            keyboard: Some(&keyboard),
+            mouse_buttons: None,
+            gamepad_buttons: &gamepad_buttons,
+            gamepad_axes: &gamepad_axes,
+            gamepads: Vec::new(),
+        };
+
+        action_state.forget_presses();
+        action_state.read_buttons(context, input_map, &devices);
+    }
+
+    /// Held actions last exactly as long as their inputs.
+    #[test]
+    fn held_actions_last_while_held() {
+        let input_map = space_map(FlightAction::Thrust, false);
+        let mut action_state = ActionState::default();
+
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(action_state.pressed(FlightAction::Thrust));
+        assert!(action_state.just_pressed(FlightAction::Thrust));
+
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(action_state.pressed(FlightAction::Thrust));
+        assert!(!action_state.just_pressed(FlightAction::Thrust));
+
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        assert!(!action_state.pressed(FlightAction::Thrust));
+    }
+
+    /// Toggled actions switch on with one press and off with the next, however long each lasts.
+    #[test]
+    fn toggled_actions_flip_on_each_press() {
+        let input_map = space_map(FlightAction::Thrust, true);
+        let mut action_state = ActionState::default();
+
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(action_state.just_pressed(FlightAction::Thrust));
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        assert!(action_state.pressed(FlightAction::Thrust));
+        assert!(!action_state.just_pressed(FlightAction::Thrust));
+
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(!action_state.pressed(FlightAction::Thrust));
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        assert!(!action_state.pressed(FlightAction::Thrust));
+    }
+
+    /// Toggled actions switch off when their context ends, and stay off when it returns.
+    #[test]
+    fn toggled_actions_end_with_their_context() {
+        let input_map = space_map(FlightAction::Thrust, true);
+        let mut action_state = ActionState::default();
+
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        assert!(action_state.pressed(FlightAction::Thrust));
+
+        tick(&mut action_state, &input_map, InputContext::Menu, false);
+        assert!(!action_state.pressed(FlightAction::Thrust));
+
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        assert!(!action_state.pressed(FlightAction::Thrust));
+    }
+
+    /// Inputs held as a context begins are ignored until they are released.
+    #[test]
+    fn inputs_held_into_a_context_are_ignored() {
+        let input_map = space_map(FlightAction::FireWeapons, false);
+        let mut action_state = ActionState::default();
+
+        tick(&mut action_state, &input_map, InputContext::Menu, true);
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(!action_state.pressed(FlightAction::FireWeapons));
+
+        tick(&mut action_state, &input_map, InputContext::Flight, false);
+        tick(&mut action_state, &input_map, InputContext::Flight, true);
+        assert!(action_state.pressed(FlightAction::FireWeapons));
+    }
+
+    /// A button pressed with a modifier triggers its chord's action instead of its own.
+    #[test]
+    fn chords_take_over_their_buttons() {
+        use GamepadButtonType::{North, Select};
+
+        let mut input_map = InputMap::empty();
+        input_map
+            .insert(FlightAction::CycleTarget, InputKind::GamepadButton(North))
+            .insert(
+                FlightAction::CycleWaypoint,
+                InputKind::GamepadChord(Select, North),
+            );
+        let mut action_state = ActionState::default();
+        let gamepad = Gamepad::new(0);
+        let mut read = |buttons: &[GamepadButtonType]| {
+            let mut gamepad_buttons = Input::default();
+            for &button in buttons {
+                gamepad_buttons.press(GamepadButton::new(gamepad, button));
+            }
+            let gamepad_axes = Axis::default();
+            let devices = Devices {
+                keyboard: None,
+                mouse_buttons: None,
+                gamepad_buttons: &gamepad_buttons,
+                gamepad_axes: &gamepad_axes,
+                gamepads: vec![gamepad],
+            };
+            action_state.forget_presses();
+            action_state.read_buttons(InputContext::Flight, &input_map, &devices);
+            (
+                action_state.pressed(FlightAction::CycleTarget),
+                action_state.pressed(FlightAction::CycleWaypoint),
+            )
+        };
+
+        assert_eq!(read(&[North]), (true, false));
+        assert_eq!(read(&[Select, North]), (false, true));
+        assert_eq!(read(&[Select]), (false, false));
+    }
+}
@@ -0,0 +1,113 @@
+//! Which ship the player has chosen to fly, and the weapons, countermeasures and deployables
+//! fitted to it.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::countermeasures::CountermeasureKind;
+use crate::simulation::deployables::DeployableKind;
+use crate::simulation::ships::{ShipDefinition, ShipLibrary};
+
+/// Loadout logic
+pub(super) struct LoadoutPlugin;
+
+impl Plugin for LoadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Loadout>();
+    }
+}
+
+/// The player's ship, the weapon fitted to each of its hardpoints and the countermeasures and
+/// deployables it carries, applied when their ship spawns.
+///
+/// Ships and weapons are chosen by their position in the [`ShipLibrary`] and
+/// [`WeaponLibrary`](crate::simulation::weapons::WeaponLibrary).
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loadout {
+    /// The index of the chosen ship.
+    ship: usize,
+    /// The index of the weapon fitted to each hardpoint; missing slots have the first weapon.
+    slots: Vec<usize>,
+    /// The countermeasures carried.
+    countermeasures: CountermeasureKind,
+    /// The deployables carried.
+    deployables: DeployableKind,
+}
+
+impl Loadout {
+    /// The index of the chosen ship.
+    pub fn ship(&self) -> usize {
+        self.ship
+    }
+
+    /// Chooses the next of `ship_count` ships, wrapping around to the first.
+    pub fn cycle_ship(&mut self, ship_count: usize) {
+        self.ship = (self.ship + 1) % ship_count.max(1);
+    }
+
+    /// The chosen ship in `library`, if there is one.
+    pub fn ship_handle<'a>(&self, library: &'a ShipLibrary) -> Option<&'a Handle<ShipDefinition>> {
+        library.ships().get(self.ship)
+    }
+
+    /// The chosen ship's definition, or the built-in [`ShipDefinition::default`] if it has not
+    /// loaded.
+    pub fn ship_definition(
+        &self,
+        library: &ShipLibrary,
+        definitions: &Assets<ShipDefinition>,
+    ) -> ShipDefinition {
+        self.ship_handle(library)
+            .and_then(|handle| definitions.get(handle))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The index of the weapon fitted to `slot`.
+    pub fn weapon(&self, slot: usize) -> usize {
+        self.slots.get(slot).copied().unwrap_or_default()
+    }
+
+    /// Fits the weapon at `index` to `slot`.
+    pub fn fit(&mut self, slot: usize, index: usize) {
+        if self.slots.len() <= slot {
+            self.slots.resize(slot + 1, 0);
+        }
+        self.slots[slot] = index;
+    }
+
+    /// Fits the next of `weapon_count` weapons to `slot`, wrapping around to the first.
+    pub fn cycle_weapon(&mut self, slot: usize, weapon_count: usize) {
+        self.fit(slot, (self.weapon(slot) + 1) % weapon_count.max(1));
+    }
+
+    /// The countermeasures carried.
+    pub fn countermeasures(&self) -> CountermeasureKind {
+        self.countermeasures
+    }
+
+    /// Carries the next kind of countermeasure, wrapping around to the first.
+    pub fn cycle_countermeasures(&mut self) {
+        let kinds = CountermeasureKind::ALL;
+        let index = kinds
+            .iter()
+            .position(|&kind| kind == self.countermeasures)
+            .unwrap_or_default();
+        self.countermeasures = kinds[(index + 1) % kinds.len()];
+    }
+
+    /// The deployables carried.
+    pub fn deployables(&self) -> DeployableKind {
+        self.deployables
+    }
+
+    /// Carries the next kind of deployable, wrapping around to the first.
+    pub fn cycle_deployables(&mut self) {
+        let kinds = DeployableKind::ALL;
+        let index = kinds
+            .iter()
+            .position(|&kind| kind == self.deployables)
+            .unwrap_or_default();
+        self.deployables = kinds[(index + 1) % kinds.len()];
+    }
+}
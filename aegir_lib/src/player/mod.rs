@@ -2,12 +2,28 @@
 use bevy::prelude::{App, Plugin};
 
 pub mod camera;
+pub mod input;
+pub mod loadout;
+mod navigation;
+pub mod photo_mode;
+pub mod respawn;
+pub mod ship;
+pub mod targeting;
 
 /// Create a plugin to use with the main game logic
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(camera::CameraPlugin);
+        app.add_plugins((
+            camera::CameraPlugin,
+            input::InputPlugin,
+            loadout::LoadoutPlugin,
+            navigation::NavigationPlugin,
+            photo_mode::PhotoModePlugin,
+            respawn::RespawnPlugin,
+            ship::ShipPlugin,
+            targeting::TargetingPlugin,
+        ));
     }
 }
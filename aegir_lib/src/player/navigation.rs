@@ -0,0 +1,93 @@
+//! Choosing a waypoint and handing the player's ship to the autopilot.
+
+use bevy::prelude::*;
+
+use crate::simulation::navigation::{Autopilot, Waypoint};
+
+use super::input::{ActionState, FlightAction, InputSet};
+use super::ship::PlayerShip;
+
+/// Actions that mean the player wants to fly the ship themselves.
+const MANUAL_ACTIONS: [FlightAction; 10] = [
+    FlightAction::PitchUp,
+    FlightAction::PitchDown,
+    FlightAction::YawLeft,
+    FlightAction::YawRight,
+    FlightAction::RollLeft,
+    FlightAction::RollRight,
+    FlightAction::ThrottleUp,
+    FlightAction::ThrottleDown,
+    FlightAction::FullStop,
+    FlightAction::Thrust,
+];
+
+/// Player navigation logic
+pub(super) struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (cycle_waypoint, toggle_autopilot)
+                .chain()
+                .in_set(InputSet::Apply),
+        );
+    }
+}
+
+/// Selects the next [`Waypoint`], in a stable order.
+fn cycle_waypoint(
+    action_state: Res<ActionState<FlightAction>>,
+    mut player_query: Query<&mut Autopilot, With<PlayerShip>>,
+    waypoint_query: Query<Entity, With<Waypoint>>,
+) {
+    if !action_state.just_pressed(FlightAction::CycleWaypoint) {
+        return;
+    }
+    let Ok(mut autopilot) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let mut candidates: Vec<Entity> = waypoint_query.iter().collect();
+    candidates.sort();
+
+    let next = match autopilot.waypoint() {
+        Some(waypoint) => candidates
+            .iter()
+            .position(|&candidate| candidate == waypoint)
+            .and_then(|index| candidates.get(index + 1))
+            .or(candidates.first()),
+        None => candidates.first(),
+    };
+
+    autopilot.select(next.copied());
+}
+
+/// Engages or disengages the autopilot on request, and disengages it when the player takes the
+/// controls.
+fn toggle_autopilot(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut Autopilot, With<PlayerShip>>,
+) {
+    let Ok(mut autopilot) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::ToggleAutopilot) {
+        if autopilot.is_engaged() {
+            autopilot.disengage();
+        } else {
+            autopilot.engage();
+        }
+        return;
+    }
+
+    let manual = action_state.scroll() != 0.
+        || action_state.look() != Vec2::ZERO
+        || MANUAL_ACTIONS
+            .iter()
+            .any(|&action| action_state.pressed(action));
+    if manual {
+        autopilot.disengage();
+    }
+}
@@ -0,0 +1,251 @@
+//! A photo mode that freezes the world and lets the player frame shots with a free orbit camera.
+//!
+//! Press `F9` while flying to enter or leave photo mode. While in it, drag with the left mouse
+//! button to orbit, drag with the right button to pan and scroll to zoom. `F12` saves a screenshot
+//! at any time.
+
+use std::path::Path;
+
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+
+use crate::game_state::GameState;
+use crate::net::{Client, Server};
+
+use super::camera::ChaseCamera;
+use super::input::PIXELS_PER_LINE;
+use super::ship::PlayerShip;
+
+/// The key that enters and leaves photo mode.
+const TOGGLE_KEY: KeyCode = KeyCode::F9;
+
+/// The key that saves a screenshot.
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+
+/// The folder screenshots are saved in, relative to the working directory.
+const SCREENSHOT_FOLDER: &str = "screenshots";
+
+/// How far the camera turns for each pixel the mouse is dragged, in radians.
+const ORBIT_PER_PIXEL: f32 = 0.005;
+
+/// How far the camera pans for each pixel the mouse is dragged, as a fraction of its distance.
+const PAN_PER_PIXEL: f32 = 0.002;
+
+/// How much each line scrolled on the mouse wheel zooms, as a fraction of the camera's distance.
+const ZOOM_PER_LINE: f32 = 0.1;
+
+/// Photo mode logic
+pub(super) struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PhotoMode>()
+            .init_resource::<ScreenshotCounter>()
+            .add_systems(OnEnter(PhotoMode::On), enter_photo_mode)
+            .add_systems(OnExit(PhotoMode::On), leave_photo_mode)
+            .add_systems(OnExit(GameState::Playing), turn_photo_mode_off)
+            .add_systems(
+                Update,
+                (
+                    toggle_photo_mode.run_if(in_state(GameState::Playing)),
+                    take_screenshot,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                orbit_camera
+                    .run_if(in_state(PhotoMode::On))
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Whether the player is flying or framing a shot.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PhotoMode {
+    /// The chase camera follows the player's ship.
+    #[default]
+    Off,
+    /// The world is frozen and the camera orbits freely.
+    On,
+}
+
+/// A camera that orbits around, and pans away from, the point it was looking at.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    /// The point the camera orbits around.
+    pub focus: Vec3,
+    /// How far the camera has turned around the focus, in radians.
+    pub yaw: f32,
+    /// How far the camera has tilted above the focus, in radians.
+    pub pitch: f32,
+    /// How far the camera is from its focus, in meters.
+    pub distance: f32,
+    /// Where the camera was before it started orbiting, so it can return there afterwards.
+    return_to: Transform,
+}
+
+/// The number to give the next screenshot.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ScreenshotCounter(Option<u32>);
+
+/// Enters or leaves photo mode when the player presses [`TOGGLE_KEY`].
+fn toggle_photo_mode(
+    keyboard: Res<Input<KeyCode>>,
+    photo_mode: Res<State<PhotoMode>>,
+    mut next_photo_mode: ResMut<NextState<PhotoMode>>,
+) {
+    if !keyboard.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+
+    next_photo_mode.set(match photo_mode.get() {
+        PhotoMode::Off => PhotoMode::On,
+        PhotoMode::On => PhotoMode::Off,
+    });
+}
+
+/// Leaves photo mode when play ends.
+fn turn_photo_mode_off(mut next_photo_mode: ResMut<NextState<PhotoMode>>) {
+    next_photo_mode.set(PhotoMode::Off);
+}
+
+/// Freezes the world, hides the HUD and starts orbiting around the player's ship.
+///
+/// Co-op games cannot be frozen, since the other players are still flying.
+fn enter_photo_mode(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    ship_query: Query<&Transform, With<PlayerShip>>,
+    camera_query: Query<(Entity, &Transform), With<ChaseCamera>>,
+) {
+    if server.is_none() && client.is_none() {
+        time.pause();
+    }
+
+    for (entity, transform) in camera_query.iter() {
+        let focus = ship_query
+            .get_single()
+            .map_or(transform.translation + transform.forward() * 10., |ship| {
+                ship.translation
+            });
+        let offset = transform.translation - focus;
+        let distance = offset.length().max(1.);
+        let (yaw, pitch) = (offset.x.atan2(offset.z), (offset.y / distance).asin());
+
+        commands.entity(entity).insert((
+            OrbitCamera {
+                focus,
+                yaw,
+                pitch,
+                distance,
+                return_to: *transform,
+            },
+            UiCameraConfig { show_ui: false },
+        ));
+    }
+}
+
+/// Unfreezes the world, shows the HUD and puts the camera back behind the player's ship.
+fn leave_photo_mode(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    mut query: Query<(Entity, &mut Transform, &OrbitCamera)>,
+) {
+    time.unpause();
+
+    for (entity, mut transform, orbit) in query.iter_mut() {
+        *transform = orbit.return_to;
+        commands
+            .entity(entity)
+            .remove::<(OrbitCamera, UiCameraConfig)>();
+    }
+}
+
+/// Orbits, pans and zooms the camera with the mouse.
+fn orbit_camera(
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut query: Query<(&mut Transform, &mut OrbitCamera)>,
+) {
+    let motion: Vec2 = mouse_motion.iter().map(|event| event.delta).sum();
+    let scroll: f32 = mouse_wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    for (mut transform, mut orbit) in query.iter_mut() {
+        if mouse_buttons.pressed(MouseButton::Left) {
+            orbit.yaw -= motion.x * ORBIT_PER_PIXEL;
+            orbit.pitch = (orbit.pitch + motion.y * ORBIT_PER_PIXEL).clamp(-1.54, 1.54);
+        } else if mouse_buttons.pressed(MouseButton::Right) {
+            let pan = (transform.right() * -motion.x + transform.up() * motion.y)
+                * PAN_PER_PIXEL
+                * orbit.distance;
+            orbit.focus += pan;
+        }
+        orbit.distance = (orbit.distance * (1. - scroll * ZOOM_PER_LINE)).max(1.);
+
+        let rotation = Quat::from_euler(EulerRot::YXZ, orbit.yaw, -orbit.pitch, 0.);
+        transform.translation = orbit.focus + rotation * Vec3::Z * orbit.distance;
+        transform.look_at(orbit.focus, Vec3::Y);
+    }
+}
+
+/// The first number not already used by a screenshot in `folder`.
+fn next_free_screenshot_number(folder: &Path) -> u32 {
+    std::fs::read_dir(folder)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_prefix("aegir-")?
+                .strip_suffix(".png")?
+                .parse::<u32>()
+                .ok()
+        })
+        .max()
+        .map_or(1, |last| last + 1)
+}
+
+/// Saves what the primary window shows to a numbered PNG when the player presses
+/// [`SCREENSHOT_KEY`].
+fn take_screenshot(
+    keyboard: Res<Input<KeyCode>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut counter: ResMut<ScreenshotCounter>,
+) {
+    if !keyboard.just_pressed(SCREENSHOT_KEY) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let folder = Path::new(SCREENSHOT_FOLDER);
+    if let Err(error) = std::fs::create_dir_all(folder) {
+        warn!("Could not create the screenshot folder: {error}");
+        return;
+    }
+
+    let number = counter
+        .0
+        .unwrap_or_else(|| next_free_screenshot_number(folder));
+    counter.0 = Some(number + 1);
+
+    let path = folder.join(format!("aegir-{number:04}.png"));
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => info!("Saved a screenshot to {}", path.display()),
+        Err(error) => warn!("Could not take a screenshot: {error}"),
+    }
+}
@@ -0,0 +1,249 @@
+//! Bringing the player back after their ship is destroyed.
+//!
+//! The camera lingers on the wreck for a few seconds, then a fresh ship with the player's
+//! [`Loadout`] is spawned at their [`RespawnPoint`]: the last station they docked with, the last
+//! mission checkpoint they reached, or where they arrived in the current sector, whichever came
+//! latest. The chase camera then glides over to the new ship.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::game_state::GameState;
+use crate::graphics::interpolation::InterpolationSet;
+use crate::simulation::flight::Velocity;
+use crate::simulation::health::{Destroyed, HealthSet};
+use crate::simulation::missions::{ActiveMission, MissionDefinition, ObjectiveCompleted};
+use crate::simulation::sector::SectorLoaded;
+use crate::simulation::ships::{ShipDefinition, ShipLibrary};
+use crate::simulation::stations::{ShipDocked, Station};
+use crate::simulation::time_control::SimulationTime;
+use crate::simulation::weapons::WeaponLibrary;
+use crate::simulation::SimulationAppExt;
+
+use super::camera::ChaseCamera;
+use super::loadout::Loadout;
+use super::ship::{spawn_player_ship, PlayerShip};
+
+/// How long the camera lingers on the wreck before the player respawns, in seconds.
+const RESPAWN_DELAY: f32 = 4.;
+
+/// How far from a station's center players respawn after docking with it, in meters.
+const STATION_RESPAWN_DISTANCE: f32 = 150.;
+
+/// How quickly the death camera circles the wreck, in radians per second.
+const DEATH_CAMERA_ORBIT_SPEED: f32 = 0.3;
+
+/// How far the death camera pulls back from the wreck each second, in meters.
+const DEATH_CAMERA_PULL_BACK: f32 = 6.;
+
+/// Respawn logic
+pub(super) struct RespawnPlugin;
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RespawnPoint>()
+            .add_simulation_event::<RespawnEvent>()
+            .add_systems(OnEnter(GameState::Playing), reset_respawn_point)
+            .add_systems(OnExit(GameState::Playing), cancel_respawn)
+            .add_systems(
+                FixedUpdate,
+                (
+                    arrive_in_sectors,
+                    record_respawn_points,
+                    begin_respawn,
+                    respawn_player.run_if(resource_exists::<Respawning>()),
+                )
+                    .chain()
+                    .after(HealthSet),
+            )
+            .add_systems(
+                PostUpdate,
+                circle_wreck
+                    .run_if(resource_exists::<Respawning>())
+                    .after(InterpolationSet)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Where the player's ship last became able to respawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RespawnSource {
+    /// Where the player arrived in the current sector.
+    #[default]
+    Start,
+    /// Outside a station the player docked with.
+    Station(Entity),
+    /// Where the player completed a mission checkpoint.
+    Checkpoint,
+}
+
+/// Where the player respawns after their ship is destroyed.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct RespawnPoint {
+    /// Where the new ship is placed, and which way it faces.
+    pub transform: Transform,
+    /// Why the player respawns here.
+    pub source: RespawnSource,
+}
+
+/// The player's ship has been replaced after being destroyed.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct RespawnEvent {
+    /// The new ship.
+    pub ship: Entity,
+    /// Where it was placed.
+    pub point: RespawnPoint,
+}
+
+/// Counts down to the player respawning, while the camera circles their wreck.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+struct Respawning {
+    /// How long remains before the player respawns, in seconds.
+    remaining: f32,
+    /// Where the player's ship was destroyed.
+    wreck: Vec3,
+}
+
+/// Respawns the player where play began until they dock or reach a checkpoint.
+fn reset_respawn_point(mut point: ResMut<RespawnPoint>) {
+    *point = RespawnPoint::default();
+}
+
+/// Forgets any respawn in progress when play ends.
+fn cancel_respawn(mut commands: Commands) {
+    commands.remove_resource::<Respawning>();
+}
+
+/// Places the player where they arrive in each new sector, and respawns them there until they
+/// dock or reach a checkpoint.
+fn arrive_in_sectors(
+    mut loaded: EventReader<SectorLoaded>,
+    mut player_query: Query<(&mut Transform, &mut Velocity), With<PlayerShip>>,
+    mut point: ResMut<RespawnPoint>,
+) {
+    let Some(event) = loaded.iter().last() else {
+        return;
+    };
+
+    *point = RespawnPoint {
+        transform: event.arrival,
+        source: RespawnSource::Start,
+    };
+    for (mut transform, mut velocity) in player_query.iter_mut() {
+        *transform = event.arrival;
+        velocity.0 = Vec3::ZERO;
+    }
+}
+
+/// Moves the respawn point when the player docks with a station or reaches a mission checkpoint.
+fn record_respawn_points(
+    mut docked: EventReader<ShipDocked>,
+    mut completed: EventReader<ObjectiveCompleted>,
+    mission: Option<Res<ActiveMission>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    player_query: Query<(Entity, &Transform), With<PlayerShip>>,
+    station_query: Query<&Transform, With<Station>>,
+    mut point: ResMut<RespawnPoint>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        docked.clear();
+        completed.clear();
+        return;
+    };
+
+    for event in docked.iter().filter(|event| event.ship == player) {
+        let Ok(station) = station_query.get(event.station) else {
+            continue;
+        };
+        // Face away from the station, so the player can fly straight out
+        let outward = (player_transform.translation - station.translation)
+            .try_normalize()
+            .unwrap_or(Vec3::Z);
+        let position = station.translation + outward * STATION_RESPAWN_DISTANCE;
+        *point = RespawnPoint {
+            transform: Transform::from_translation(position)
+                .looking_at(position + outward, Vec3::Y),
+            source: RespawnSource::Station(event.station),
+        };
+    }
+
+    let definition = mission
+        .as_ref()
+        .and_then(|mission| definitions.get(&mission.definition));
+    for event in completed.iter() {
+        let is_checkpoint = definition
+            .and_then(|definition| definition.objectives.get(event.index))
+            .is_some_and(|objective| objective.checkpoint);
+        if is_checkpoint {
+            *point = RespawnPoint {
+                transform: *player_transform,
+                source: RespawnSource::Checkpoint,
+            };
+        }
+    }
+}
+
+/// Starts the countdown to respawning when the player's ship is destroyed.
+fn begin_respawn(
+    mut commands: Commands,
+    mut destroyed: EventReader<Destroyed>,
+    player_query: Query<(), With<PlayerShip>>,
+) {
+    for event in destroyed.iter() {
+        if player_query.contains(event.entity) {
+            commands.insert_resource(Respawning {
+                remaining: RESPAWN_DELAY,
+                wreck: event.position,
+            });
+        }
+    }
+}
+
+/// Spawns a fresh ship for the player at their respawn point once the countdown ends.
+#[allow(clippy::too_many_arguments)]
+fn respawn_player(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut respawning: ResMut<Respawning>,
+    point: Res<RespawnPoint>,
+    loadout: Res<Loadout>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+    mut respawned: EventWriter<RespawnEvent>,
+) {
+    respawning.remaining -= time.delta_seconds();
+    if respawning.remaining > 0. {
+        return;
+    }
+    commands.remove_resource::<Respawning>();
+
+    let ship = spawn_player_ship(
+        &mut commands,
+        point.transform,
+        &loadout,
+        &ship_library,
+        &ship_definitions,
+        &weapon_library,
+    );
+    respawned.send(RespawnEvent {
+        ship,
+        point: *point,
+    });
+}
+
+/// Slowly circles and pulls back from the wreck while the player waits to respawn.
+fn circle_wreck(
+    time: Res<Time>,
+    respawning: Res<Respawning>,
+    mut camera_query: Query<&mut Transform, With<ChaseCamera>>,
+) {
+    for mut transform in camera_query.iter_mut() {
+        let offset = transform.translation - respawning.wreck;
+        let orbit = Quat::from_rotation_y(DEATH_CAMERA_ORBIT_SPEED * time.delta_seconds());
+        let pull_back = offset.normalize_or_zero() * DEATH_CAMERA_PULL_BACK * time.delta_seconds();
+        transform.translation = respawning.wreck + orbit * (offset + pull_back);
+        transform.look_at(respawning.wreck, Vec3::Y);
+    }
+}
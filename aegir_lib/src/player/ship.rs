@@ -0,0 +1,639 @@
+//! The ship flown by the player, and how their actions steer it.
+//!
+//! Further [`LocalPlayer`]s sharing the machine fly ships of their own, with a smaller set of
+//! controls for now.
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::{GameState, InGame};
+use crate::simulation::countermeasures::Countermeasures;
+use crate::simulation::deployables::Deployables;
+use crate::simulation::economy::Trader;
+use crate::simulation::energy::{PowerDistribution, Subsystem};
+use crate::simulation::factions::Faction;
+use crate::simulation::flight::{
+    Afterburner, FlightControls, FlightDynamics, GravityDrift, Throttle, Velocity,
+};
+use crate::simulation::jump_drive::{JumpDrive, JumpState};
+use crate::simulation::mining::MiningLaser;
+use crate::simulation::navigation::Autopilot;
+use crate::simulation::pickups::OreMagnet;
+use crate::simulation::sector::SectorDefinition;
+use crate::simulation::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use crate::simulation::stations::DockingComputer;
+use crate::simulation::time_control::{SimulationTime, SlowMotion};
+use crate::simulation::tractor::TractorBeam;
+use crate::simulation::weapons::{Hardpoint, Heat, MountedWeapon, WeaponLibrary, WeaponTrigger};
+use crate::simulation::wingmen::{WingCommander, WingOrder};
+
+use super::input::{
+    ActionState, BindingProfiles, DockAction, FlightAction, InputMap, InputSet, LocalPlayer,
+    GAMEPAD_PROFILE,
+};
+use super::loadout::Loadout;
+use super::targeting::CurrentTarget;
+
+/// How far each line scrolled on the mouse wheel moves the throttle.
+const THROTTLE_PER_SCROLL_LINE: f32 = 0.05;
+
+/// How far the throttle moves each second while a throttle key is held.
+const THROTTLE_PER_SECOND: f32 = 0.5;
+
+/// How far to the side of the first player's ship further local players join, in meters.
+const LOCAL_PLAYER_SPACING: f32 = 25.;
+
+/// Player ship logic
+pub(super) struct ShipPlugin;
+
+impl Plugin for ShipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JumpDestination>()
+            .init_resource::<WingCommandMenu>()
+            .add_console_command("teleport", "teleport <x> <y> <z>", teleport)
+            .add_console_command(
+                "local",
+                "local <join|leave> [gamepad]",
+                local_player_command,
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_player, close_wing_command_menu),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    steer_ship,
+                    adjust_throttle,
+                    fire_afterburner,
+                    distribute_power,
+                    fire_mining_laser,
+                    pull_trigger,
+                    hold_tractor_beam,
+                    request_docking,
+                    request_launch,
+                    request_jump,
+                    request_countermeasures,
+                    request_deployable,
+                    request_slow_motion,
+                    command_wingmen.after(distribute_power),
+                    fly_local_players,
+                )
+                    .in_set(InputSet::Apply),
+            );
+    }
+}
+
+/// Marks the ship controlled by the local player.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct PlayerShip;
+
+/// The sector chosen on the galaxy map, which [`FlightAction::Jump`] charges the jump drive for.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct JumpDestination(pub Option<Handle<SectorDefinition>>);
+
+/// Whether the menu of orders for the player's wingmen is open.
+///
+/// While it is open, the number keys give orders rather than diverting power.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WingCommandMenu {
+    /// Is the menu shown?
+    pub open: bool,
+}
+
+/// Spawns the player's chosen ship at the origin, armed with their [`Loadout`].
+fn spawn_player(
+    mut commands: Commands,
+    loadout: Res<Loadout>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+) {
+    spawn_player_ship(
+        &mut commands,
+        Transform::IDENTITY,
+        &loadout,
+        &ship_library,
+        &ship_definitions,
+        &weapon_library,
+    );
+}
+
+/// Spawns a fresh, fully repaired player ship at `transform`, armed with their [`Loadout`].
+pub(super) fn spawn_player_ship(
+    commands: &mut Commands,
+    transform: Transform,
+    loadout: &Loadout,
+    ship_library: &ShipLibrary,
+    ship_definitions: &Assets<ShipDefinition>,
+    weapon_library: &WeaponLibrary,
+) -> Entity {
+    let ship = spawn_piloted_ship(
+        commands,
+        transform,
+        loadout,
+        ship_library,
+        ship_definitions,
+        weapon_library,
+    );
+    commands.entity(ship).insert(PlayerShip);
+    ship
+}
+
+/// Spawns a fresh, fully repaired ship for a player at `transform`, armed with the [`Loadout`],
+/// without saying which player flies it.
+fn spawn_piloted_ship(
+    commands: &mut Commands,
+    transform: Transform,
+    loadout: &Loadout,
+    ship_library: &ShipLibrary,
+    ship_definitions: &Assets<ShipDefinition>,
+    weapon_library: &WeaponLibrary,
+) -> Entity {
+    let definition = loadout.ship_definition(ship_library, ship_definitions);
+
+    let mut ship = commands.spawn((
+        SpatialBundle::from_transform(transform),
+        InGame,
+        Faction::Aegir,
+        definition.dynamics(),
+        FlightControls::default(),
+        Throttle::default(),
+        Velocity::default(),
+        GravityDrift::default(),
+        Afterburner::default(),
+        definition.energy(),
+        PowerDistribution::default(),
+        definition.health(),
+        definition.shield(),
+        definition.collider(),
+    ));
+    ship.insert((
+        MiningLaser::default(),
+        definition.inventory(),
+        Trader::default(),
+        Autopilot::default(),
+        WeaponTrigger::default(),
+        TractorBeam::default(),
+        DockingComputer::default(),
+        OreMagnet::default(),
+        JumpDrive::default(),
+        loadout.countermeasures().countermeasures(),
+        loadout.deployables().deployables(),
+        SlowMotion::default(),
+        WingCommander::default(),
+    ));
+    if let Some(handle) = loadout.ship_handle(ship_library) {
+        ship.insert(ShipClass(handle.clone()));
+    }
+
+    ship.with_children(|parent| {
+        for (slot, hardpoint_definition) in definition.hardpoints.iter().enumerate() {
+            let mut hardpoint = parent.spawn((
+                SpatialBundle::from_transform(Transform::from_translation(
+                    hardpoint_definition.translation(),
+                )),
+                Hardpoint { slot },
+            ));
+
+            if let Some(weapon) = weapon_library.weapons().get(loadout.weapon(slot)) {
+                hardpoint.insert((MountedWeapon::new(weapon.clone()), Heat::default()));
+            }
+        }
+    });
+
+    ship.id()
+}
+
+/// Turns the player's rotation actions into [`FlightControls`].
+fn steer_ship(
+    time: SimulationTime,
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut FlightControls, With<PlayerShip>>,
+) {
+    let Ok(mut controls) = query.get_single_mut() else {
+        return;
+    };
+
+    apply_steering(&action_state, time.delta_seconds(), &mut controls);
+}
+
+/// Turns the rotation actions in `action_state` over a tick of `delta_seconds` into `controls`.
+fn apply_steering(
+    action_state: &ActionState<FlightAction>,
+    delta_seconds: f32,
+    controls: &mut FlightControls,
+) {
+    let look = action_state.look() / delta_seconds;
+    controls.pitch =
+        (action_state.axis(FlightAction::PitchDown, FlightAction::PitchUp) + look.x).clamp(-1., 1.);
+    controls.yaw =
+        (action_state.axis(FlightAction::YawRight, FlightAction::YawLeft) + look.y).clamp(-1., 1.);
+    controls.roll = action_state.axis(FlightAction::RollRight, FlightAction::RollLeft);
+}
+
+/// How far the throttle keys and mouse wheel in `action_state` move the throttle over a tick of
+/// `delta_seconds`.
+fn manual_throttle(action_state: &ActionState<FlightAction>, delta_seconds: f32) -> f32 {
+    let held = action_state.axis(FlightAction::ThrottleDown, FlightAction::ThrottleUp);
+    held * THROTTLE_PER_SECOND * delta_seconds + action_state.scroll() * THROTTLE_PER_SCROLL_LINE
+}
+
+/// Moves the player's throttle with the mouse wheel, the throttle keys and the speed shortcuts.
+fn adjust_throttle(
+    time: SimulationTime,
+    action_state: Res<ActionState<FlightAction>>,
+    current_target: Res<CurrentTarget>,
+    target_query: Query<&Velocity, Without<PlayerShip>>,
+    mut player_query: Query<(&mut Throttle, &FlightDynamics), With<PlayerShip>>,
+) {
+    let Ok((mut throttle, dynamics)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::FullStop) {
+        *throttle = Throttle::STOP;
+        return;
+    }
+
+    if action_state.just_pressed(FlightAction::MatchSpeed) {
+        if let Some(target_velocity) = current_target
+            .entity()
+            .and_then(|target| target_query.get(target).ok())
+        {
+            throttle.set(target_velocity.0.length() / dynamics.max_speed);
+            return;
+        }
+    }
+
+    throttle.adjust(manual_throttle(&action_state, time.delta_seconds()));
+}
+
+/// Requests the afterburner while the player holds [`FlightAction::Thrust`].
+fn fire_afterburner(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut Afterburner, With<PlayerShip>>,
+) {
+    let Ok(mut afterburner) = query.get_single_mut() else {
+        return;
+    };
+
+    afterburner.requested = action_state.pressed(FlightAction::Thrust);
+}
+
+/// Moves power pips between subsystems when the player asks.
+fn distribute_power(
+    action_state: Res<ActionState<FlightAction>>,
+    menu: Res<WingCommandMenu>,
+    mut query: Query<&mut PowerDistribution, With<PlayerShip>>,
+) {
+    let Ok(mut power) = query.get_single_mut() else {
+        return;
+    };
+    // The number keys are giving orders instead
+    if menu.open {
+        return;
+    }
+
+    if action_state.just_pressed(FlightAction::BalancePower) {
+        power.balance();
+    }
+
+    for (action, subsystem) in [
+        (FlightAction::DivertToEngines, Subsystem::Engines),
+        (FlightAction::DivertToWeapons, Subsystem::Weapons),
+        (FlightAction::DivertToShields, Subsystem::Shields),
+    ] {
+        if action_state.just_pressed(action) {
+            power.divert_to(subsystem);
+        }
+    }
+}
+
+/// Fires the mining laser while the player holds [`FlightAction::Mine`].
+fn fire_mining_laser(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut MiningLaser, With<PlayerShip>>,
+) {
+    let Ok(mut laser) = query.get_single_mut() else {
+        return;
+    };
+
+    laser.firing = action_state.pressed(FlightAction::Mine);
+}
+
+/// Holds the trigger on the player's weapons while they hold [`FlightAction::FireWeapons`],
+/// aiming any gimballed weapons at the current target.
+fn pull_trigger(
+    action_state: Res<ActionState<FlightAction>>,
+    current_target: Res<CurrentTarget>,
+    target_query: Query<&Transform, Without<PlayerShip>>,
+    mut query: Query<&mut WeaponTrigger, With<PlayerShip>>,
+) {
+    let Ok(mut trigger) = query.get_single_mut() else {
+        return;
+    };
+
+    trigger.firing = action_state.pressed(FlightAction::FireWeapons);
+    trigger.aim = current_target
+        .entity()
+        .and_then(|target| target_query.get(target).ok())
+        .map(|transform| transform.translation);
+}
+
+/// Holds the tractor beam on while the player holds [`FlightAction::Activate`].
+fn hold_tractor_beam(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut TractorBeam, With<PlayerShip>>,
+) {
+    let Ok(mut beam) = query.get_single_mut() else {
+        return;
+    };
+
+    beam.active = action_state.pressed(FlightAction::Activate);
+}
+
+/// Asks the docking computer to dock when the player presses [`FlightAction::Dock`].
+fn request_docking(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut DockingComputer, With<PlayerShip>>,
+) {
+    let Ok(mut computer) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::Dock) && computer.docked().is_none() {
+        computer.requested = true;
+    }
+}
+
+/// Asks the docking computer to undock when the player presses [`DockAction::Launch`].
+fn request_launch(
+    action_state: Res<ActionState<DockAction>>,
+    mut query: Query<&mut DockingComputer, With<PlayerShip>>,
+) {
+    let Ok(mut computer) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(DockAction::Launch) && computer.docked().is_some() {
+        computer.requested = true;
+    }
+}
+
+/// Charges the jump drive for the chosen sector when the player presses [`FlightAction::Jump`], or
+/// cancels the jump if the drive is already charging.
+fn request_jump(
+    action_state: Res<ActionState<FlightAction>>,
+    destination: Res<JumpDestination>,
+    mut query: Query<&mut JumpDrive, With<PlayerShip>>,
+) {
+    let Ok(mut drive) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::Jump) {
+        drive.requested = match drive.state() {
+            JumpState::Charging { .. } => Some(None),
+            _ => destination.0.clone().map(Some),
+        };
+    }
+}
+
+/// Asks for a countermeasure to be dropped when the player presses
+/// [`FlightAction::Countermeasures`].
+fn request_countermeasures(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut Countermeasures, With<PlayerShip>>,
+) {
+    let Ok(mut countermeasures) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::Countermeasures) {
+        countermeasures.requested = true;
+    }
+}
+
+/// Asks for a mine or turret pod to be dropped when the player presses [`FlightAction::Deploy`].
+fn request_deployable(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut Deployables, With<PlayerShip>>,
+) {
+    let Ok(mut deployables) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::Deploy) {
+        deployables.requested = true;
+    }
+}
+
+/// Asks to slow time when the player presses [`FlightAction::SlowMotion`].
+fn request_slow_motion(
+    action_state: Res<ActionState<FlightAction>>,
+    mut query: Query<&mut SlowMotion, With<PlayerShip>>,
+) {
+    let Ok(mut slow_motion) = query.get_single_mut() else {
+        return;
+    };
+
+    if action_state.just_pressed(FlightAction::SlowMotion) {
+        slow_motion.requested = true;
+    }
+}
+
+/// Opens and closes the wing command menu when the player presses [`FlightAction::WingCommands`],
+/// and gives the wingmen the order the player picks from it, closing it again.
+fn command_wingmen(
+    action_state: Res<ActionState<FlightAction>>,
+    current_target: Res<CurrentTarget>,
+    mut menu: ResMut<WingCommandMenu>,
+    mut query: Query<&mut WingCommander, With<PlayerShip>>,
+) {
+    if action_state.just_pressed(FlightAction::WingCommands) {
+        menu.open = !menu.open;
+        return;
+    }
+    if !menu.open {
+        return;
+    }
+    let Ok(mut commander) = query.get_single_mut() else {
+        return;
+    };
+
+    let order = [
+        (FlightAction::OrderFormUp, Some(WingOrder::FormUp)),
+        (
+            FlightAction::OrderAttack,
+            current_target.entity().map(WingOrder::Attack),
+        ),
+        (FlightAction::OrderEngage, Some(WingOrder::Engage)),
+        (FlightAction::OrderDefend, Some(WingOrder::Defend)),
+    ]
+    .into_iter()
+    .find(|&(action, _)| action_state.just_pressed(action));
+    if let Some((_, order)) = order {
+        // Without a target to attack, leave the menu open to pick something else
+        if let Some(order) = order {
+            commander.requested = Some(order);
+            menu.open = false;
+        }
+    }
+}
+
+/// Closes the wing command menu left open when play last ended.
+fn close_wing_command_menu(mut menu: ResMut<WingCommandMenu>) {
+    menu.open = false;
+}
+
+/// Console command that moves the player's ship to the given coordinates.
+fn teleport(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let [x, y, z] = *arguments else {
+        return Err("expected three coordinates".to_string());
+    };
+    let parse = |coordinate: &str| {
+        coordinate
+            .parse::<f32>()
+            .map_err(|_| format!("`{coordinate}` is not a number"))
+    };
+    let destination = Vec3::new(parse(x)?, parse(y)?, parse(z)?);
+
+    let mut query = world.query_filtered::<&mut Transform, With<PlayerShip>>();
+    let mut transform = query
+        .get_single_mut(world)
+        .map_err(|_| "there is no player ship".to_string())?;
+    transform.translation = destination;
+
+    Ok(format!("teleported to {destination}"))
+}
+
+/// Flies the ships of further [`LocalPlayer`]s from their own actions.
+///
+/// Local players can steer, work the throttle and afterburner, and fire their weapons.
+fn fly_local_players(
+    time: SimulationTime,
+    mut query: Query<
+        (
+            &ActionState<FlightAction>,
+            &mut FlightControls,
+            &mut Throttle,
+            &mut Afterburner,
+            &mut WeaponTrigger,
+        ),
+        With<LocalPlayer>,
+    >,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (action_state, mut controls, mut throttle, mut afterburner, mut trigger) in query.iter_mut()
+    {
+        apply_steering(action_state, delta_time, &mut controls);
+        if action_state.just_pressed(FlightAction::FullStop) {
+            *throttle = Throttle::STOP;
+        } else {
+            throttle.adjust(manual_throttle(action_state, delta_time));
+        }
+        afterburner.requested = action_state.pressed(FlightAction::Thrust);
+        trigger.firing = action_state.pressed(FlightAction::FireWeapons);
+    }
+}
+
+/// Console command that adds a local player flying with a gamepad, or removes the last to join.
+///
+/// Without a gamepad id, the first gamepad no local player has claimed is used.
+fn local_player_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (joining, gamepad_id) = match *arguments {
+        ["join"] => (true, None),
+        ["join", id] => (
+            true,
+            Some(
+                id.parse::<usize>()
+                    .map_err(|_| format!("`{id}` is not a gamepad id"))?,
+            ),
+        ),
+        ["leave"] => (false, None),
+        _ => return Err("expected `join` with an optional gamepad id, or `leave`".to_string()),
+    };
+
+    let mut local_query = world.query::<(Entity, &LocalPlayer)>();
+    let mut local_players: Vec<(Entity, LocalPlayer)> = local_query
+        .iter(world)
+        .map(|(entity, &player)| (entity, player))
+        .collect();
+    local_players.sort_by_key(|(_, player)| player.index);
+
+    if !joining {
+        let Some((ship, player)) = local_players.pop() else {
+            return Err("there are no local players to remove".to_string());
+        };
+        world.entity_mut(ship).despawn_recursive();
+        return Ok(format!("player {} left", player.index + 1));
+    }
+
+    let mut player_query = world.query_filtered::<&Transform, With<PlayerShip>>();
+    let Ok(&origin) = player_query.get_single(world) else {
+        return Err("there is no player ship to fly with".to_string());
+    };
+    let claimed = |gamepad: Gamepad| {
+        local_players
+            .iter()
+            .any(|(_, player)| player.gamepad == gamepad)
+    };
+    let gamepads = world.resource::<Gamepads>();
+    let gamepad = match gamepad_id {
+        Some(id) => Some(Gamepad::new(id))
+            .filter(|&gamepad| gamepads.contains(gamepad))
+            .ok_or_else(|| format!("gamepad {id} is not connected"))?,
+        None => gamepads
+            .iter()
+            .find(|&gamepad| !claimed(gamepad))
+            .ok_or_else(|| "every connected gamepad already has a player".to_string())?,
+    };
+    if claimed(gamepad) {
+        return Err(format!("gamepad {} already has a player", gamepad.id));
+    }
+
+    let index = local_players
+        .last()
+        .map_or(1, |(_, player)| player.index + 1);
+    let profiles = world.resource::<BindingProfiles>();
+    let profile = profiles
+        .get(GAMEPAD_PROFILE)
+        .unwrap_or_else(|| profiles.active());
+    let input_map: InputMap<FlightAction> = profile.flight.clone();
+    let local_player = LocalPlayer {
+        index,
+        gamepad,
+        stick_look: profile.stick_look,
+    };
+    let transform = Transform {
+        translation: origin.translation + origin.right() * LOCAL_PLAYER_SPACING * f32::from(index),
+        ..origin
+    };
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    let ship = spawn_piloted_ship(
+        &mut commands,
+        transform,
+        world.resource::<Loadout>(),
+        world.resource::<ShipLibrary>(),
+        world.resource::<Assets<ShipDefinition>>(),
+        world.resource::<WeaponLibrary>(),
+    );
+    commands.entity(ship).insert((
+        Name::new(format!("Player {}", index + 1)),
+        local_player,
+        input_map,
+        ActionState::<FlightAction>::default(),
+    ));
+    queue.apply(world);
+
+    Ok(format!(
+        "player {} joined with gamepad {}",
+        index + 1,
+        gamepad.id
+    ))
+}
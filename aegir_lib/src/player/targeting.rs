@@ -0,0 +1,128 @@
+//! Choosing which entity the player's ship is paying attention to.
+
+use bevy::prelude::*;
+
+use crate::simulation::factions::{Faction, Relation, Reputation};
+
+use super::input::{ActionState, FlightAction, InputSet};
+use super::ship::PlayerShip;
+
+/// Target selection logic
+pub(super) struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentTarget>()
+            .add_systems(
+                FixedUpdate,
+                (clear_missing_target, cycle_target)
+                    .chain()
+                    .in_set(InputSet::Apply),
+            )
+            .add_systems(Update, identify_contacts);
+    }
+}
+
+/// Marks entities that the player can select as a target.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Targetable;
+
+/// How an entity regards the player, which colors it on the HUD.
+///
+/// This is kept up to date from the [`Reputation`] between each entity's [`Faction`] and the
+/// player's. Entities without this component are treated as [`Disposition::Neutral`].
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Disposition {
+    /// On the player's side.
+    Friendly,
+    /// Neither for nor against the player.
+    #[default]
+    Neutral,
+    /// Out to destroy the player.
+    Hostile,
+}
+
+/// The entity currently targeted by the player, if any.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CurrentTarget(Option<Entity>);
+
+impl CurrentTarget {
+    /// The targeted entity, if any.
+    pub fn entity(&self) -> Option<Entity> {
+        self.0
+    }
+
+    /// Targets `entity`, or clears the target when `None`.
+    pub fn set(&mut self, entity: Option<Entity>) {
+        self.0 = entity;
+    }
+}
+
+/// Stops targeting entities that have been despawned or are no longer [`Targetable`].
+fn clear_missing_target(
+    mut current_target: ResMut<CurrentTarget>,
+    query: Query<(), With<Targetable>>,
+) {
+    if let Some(target) = current_target.0 {
+        if !query.contains(target) {
+            current_target.0 = None;
+        }
+    }
+}
+
+/// Selects the next [`Targetable`] entity, in a stable order.
+fn cycle_target(
+    action_state: Res<ActionState<FlightAction>>,
+    mut current_target: ResMut<CurrentTarget>,
+    query: Query<Entity, With<Targetable>>,
+) {
+    if !action_state.just_pressed(FlightAction::CycleTarget) {
+        return;
+    }
+
+    let mut candidates: Vec<Entity> = query.iter().collect();
+    candidates.sort();
+
+    let next = match current_target.0 {
+        Some(target) => candidates
+            .iter()
+            .position(|&candidate| candidate == target)
+            .and_then(|index| candidates.get(index + 1))
+            .or(candidates.first()),
+        None => candidates.first(),
+    };
+
+    current_target.0 = next.copied();
+}
+
+/// Identifies friend or foe, setting each faction member's [`Disposition`] from how its faction
+/// regards the player's.
+fn identify_contacts(
+    mut commands: Commands,
+    reputation: Res<Reputation>,
+    player_query: Query<&Faction, With<PlayerShip>>,
+    mut contact_query: Query<(Entity, &Faction, Option<&mut Disposition>), Without<PlayerShip>>,
+) {
+    let Ok(&player_faction) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, &faction, disposition) in contact_query.iter_mut() {
+        let identified = match reputation.relation(player_faction, faction) {
+            Relation::Allied => Disposition::Friendly,
+            Relation::Neutral => Disposition::Neutral,
+            Relation::Hostile => Disposition::Hostile,
+        };
+
+        match disposition {
+            Some(mut disposition) => {
+                if *disposition != identified {
+                    *disposition = identified;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(identified);
+            }
+        }
+    }
+}
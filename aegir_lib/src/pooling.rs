@@ -0,0 +1,96 @@
+//! Pools of entities that are recycled rather than despawned.
+//!
+//! Short-lived entities such as projectiles and particles come and go hundreds of times a second.
+//! Spawning and despawning each one churns entity storage and moves them between archetypes, so
+//! instead an [`EntityPool`] keeps finished entities hidden and hands them out again the next
+//! time one is needed.
+//!
+//! Released entities lose their `T` component, so systems querying for it never see them, but
+//! keep everything else, such as their meshes and children.
+
+use std::marker::PhantomData;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+/// How many idle entities a pool keeps by default; any more are despawned when released.
+const DEFAULT_MAX_IDLE: usize = 512;
+
+/// Idle entities that were last used as a `T`, waiting to be reused.
+#[derive(Resource, Debug)]
+pub struct EntityPool<T> {
+    /// Entities that have been released and not yet reused.
+    idle: Vec<Entity>,
+    /// How many idle entities are kept; any more are despawned when released.
+    max_idle: usize,
+    /// What the pooled entities are used as.
+    kind: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EntityPool<T> {
+    fn default() -> Self {
+        EntityPool::with_max_idle(DEFAULT_MAX_IDLE)
+    }
+}
+
+impl<T> EntityPool<T> {
+    /// An empty pool which keeps up to `max_idle` idle entities.
+    pub fn with_max_idle(max_idle: usize) -> Self {
+        EntityPool {
+            idle: Vec::new(),
+            max_idle,
+            kind: PhantomData,
+        }
+    }
+
+    /// How many idle entities are waiting to be reused.
+    pub fn idle(&self) -> usize {
+        self.idle.len()
+    }
+}
+
+impl<T: Component> EntityPool<T> {
+    /// Reuses an idle entity for `bundle`, or spawns a new one if none are left.
+    ///
+    /// `bundle` should include the `T` component and everything a new entity needs, since reused
+    /// entities are given it on top of whatever they kept when released.
+    pub fn spawn<'w, 's, 'a>(
+        &mut self,
+        commands: &'a mut Commands<'w, 's>,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'w, 's, 'a> {
+        // Idle entities may since have been despawned along with the rest of the game
+        while let Some(entity) = self.idle.pop() {
+            if commands.get_entity(entity).is_some() {
+                let mut entity_commands = commands.entity(entity);
+                entity_commands.insert(bundle).insert(Visibility::Inherited);
+                return entity_commands;
+            }
+        }
+        commands.spawn(bundle)
+    }
+
+    /// Hides `entity` and removes its `T` component, keeping it to be reused.
+    ///
+    /// Any other components that shouldn't carry over to the entity's next use can be removed
+    /// from the returned commands. Releasing an entity that is already idle does nothing.
+    pub fn release<'w, 's, 'a>(
+        &mut self,
+        commands: &'a mut Commands<'w, 's>,
+        entity: Entity,
+    ) -> Option<EntityCommands<'w, 's, 'a>> {
+        // Something can finish twice in one tick, such as a projectile that both hits and fizzles
+        if self.idle.contains(&entity) {
+            return None;
+        }
+        if self.idle.len() >= self.max_idle {
+            commands.entity(entity).despawn_recursive();
+            return None;
+        }
+
+        self.idle.push(entity);
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<T>().insert(Visibility::Hidden);
+        Some(entity_commands)
+    }
+}
@@ -0,0 +1,355 @@
+//! Recording the player's input, and feeding it back in to replay a flight.
+//!
+//! A replay is the [`WorldSeed`], the player's [`Loadout`] and [`MissionSelection`], and the
+//! [`ActionState`]s of every simulation tick since play began. The simulation only changes in
+//! response to those actions, so feeding them back in reproduces the same flight. Multiplayer games
+//! cannot be replayed, since other players' actions are not recorded.
+//!
+//! Record with `--record <path>` and play back with `--replay <path>`.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bincode::Options;
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::GameState;
+use crate::player::input::{ActionState, DockAction, FlightAction, InputSet};
+use crate::player::loadout::Loadout;
+use crate::simulation::missions::MissionSelection;
+use crate::simulation::{WorldSeed, TICK_RATE};
+
+/// Identifies replay files.
+const MAGIC: [u8; 4] = *b"AEGR";
+
+/// Bumped whenever the replay format (including [`FlightAction`], [`DockAction`] and [`Loadout`])
+/// changes.
+const FORMAT_VERSION: u16 = 9;
+
+/// Adds replay recording and playback.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayConfig>()
+            .init_resource::<ReplayState>()
+            .add_systems(Startup, load_replay)
+            .add_systems(OnEnter(GameState::Playing), start_recording)
+            .add_systems(
+                FixedUpdate,
+                record_or_play_tick
+                    .in_set(InputSet::Prepare)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), finish_replay)
+            .add_systems(Last, save_on_exit);
+    }
+}
+
+/// Where to record replays to and play them back from.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayConfig {
+    /// Record every flight to this file.
+    pub record_to: Option<PathBuf>,
+    /// Play back the replay in this file instead of reading the input devices.
+    pub play_from: Option<PathBuf>,
+}
+
+impl ReplayConfig {
+    /// Reads `--record <path>` and `--replay <path>` from the command line `arguments`.
+    pub fn from_args(arguments: impl IntoIterator<Item = String>) -> Self {
+        let mut config = ReplayConfig::default();
+        let mut arguments = arguments.into_iter();
+
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--record" => config.record_to = arguments.next().map(PathBuf::from),
+                "--replay" => config.play_from = arguments.next().map(PathBuf::from),
+                _ => (),
+            }
+        }
+
+        config
+    }
+}
+
+/// A recorded flight.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// The [`WorldSeed`] the flight started from.
+    pub seed: u64,
+    /// The [`TICK_RATE`] the flight was recorded at.
+    pub tick_rate: f32,
+    /// The ship and weapons the player flew.
+    pub loadout: Loadout,
+    /// The mission the player flew, if any.
+    pub mission: MissionSelection,
+    /// The player's input on every tick, in order.
+    pub ticks: Vec<TickInput>,
+}
+
+/// The player's input during a single tick.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickInput {
+    /// Every flight action that was held.
+    pub pressed: Vec<FlightAction>,
+    /// Every flight action that was first held since the previous tick.
+    pub just_pressed: Vec<FlightAction>,
+    /// Every docked action that was first held since the previous tick.
+    pub dock_just_pressed: Vec<DockAction>,
+    /// Lines scrolled on the mouse wheel since the previous tick.
+    pub scroll: f32,
+    /// How far mouse movement since the previous tick deflected the pitch and yaw controls,
+    /// multiplied by how long it was deflected for, in seconds.
+    pub look: [f32; 2],
+}
+
+impl TickInput {
+    /// Captures the `action_state` and `dock_state` for this tick.
+    pub fn capture(
+        action_state: &ActionState<FlightAction>,
+        dock_state: &ActionState<DockAction>,
+    ) -> Self {
+        TickInput {
+            pressed: action_state.pressed_actions().collect(),
+            just_pressed: action_state.just_pressed_actions().collect(),
+            dock_just_pressed: dock_state.just_pressed_actions().collect(),
+            scroll: action_state.scroll(),
+            look: action_state.look().to_array(),
+        }
+    }
+
+    /// Replaces the `action_state` and `dock_state` for this tick with the recorded input.
+    pub fn apply(
+        &self,
+        action_state: &mut ActionState<FlightAction>,
+        dock_state: &mut ActionState<DockAction>,
+    ) {
+        action_state.overwrite(
+            self.pressed.iter().copied(),
+            self.just_pressed.iter().copied(),
+            self.scroll,
+            Vec2::from(self.look),
+        );
+        // Nothing done while docked is held, so only presses need replaying
+        dock_state.overwrite([], self.dock_just_pressed.iter().copied(), 0., Vec2::ZERO);
+    }
+}
+
+/// Why a replay could not be saved or loaded.
+#[derive(Debug, Display, From)]
+pub enum ReplayError {
+    /// The file could not be read or written.
+    #[display(fmt = "could not access the replay file: {}", _0)]
+    Io(std::io::Error),
+    /// The contents of the file are corrupt.
+    #[display(fmt = "the replay is corrupt: {}", _0)]
+    Encoding(bincode::Error),
+    /// The file does not start with the replay header.
+    #[display(fmt = "this is not a replay file")]
+    #[from(ignore)]
+    NotAReplay,
+    /// The file was written in a format this build cannot read.
+    #[display(fmt = "the replay uses unsupported format version {}", _0)]
+    #[from(ignore)]
+    UnsupportedVersion(u16),
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    /// Starts an empty recording of the player flying `loadout` on `mission`, in a world generated
+    /// from `seed`.
+    pub fn new(seed: WorldSeed, loadout: Loadout, mission: MissionSelection) -> Self {
+        Replay {
+            seed: seed.0,
+            tick_rate: TICK_RATE,
+            loadout,
+            mission,
+            ticks: Vec::new(),
+        }
+    }
+
+    /// Encodes the replay into `writer`.
+    pub fn write(&self, mut writer: impl Write) -> Result<(), ReplayError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        // Variable-length integers keep quiet ticks down to a handful of bytes
+        bincode::DefaultOptions::new().serialize_into(writer, self)?;
+        Ok(())
+    }
+
+    /// Decodes a replay from `reader`.
+    pub fn read(mut reader: impl Read) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let mut version = [0; 2];
+        reader.read_exact(&mut version)?;
+        let version = u16::from_le_bytes(version);
+        if version != FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        Ok(bincode::DefaultOptions::new().deserialize_from(reader)?)
+    }
+
+    /// Saves the replay to the file at `path`, replacing it if it exists.
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads the replay in the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Replay::read(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Whether a replay is being recorded or played back.
+#[derive(Resource, Debug, Default)]
+enum ReplayState {
+    /// Input is neither recorded or replayed.
+    #[default]
+    Idle,
+    /// Input is being recorded into this replay.
+    Recording(Replay),
+    /// Input is being fed in from this replay.
+    Playing {
+        /// The replay being played.
+        replay: Replay,
+        /// The index of the tick to replay next.
+        next_tick: usize,
+    },
+}
+
+/// Loads the replay to play back, if any, and skips straight to flying it.
+fn load_replay(
+    config: Res<ReplayConfig>,
+    mut replay_state: ResMut<ReplayState>,
+    mut world_seed: ResMut<WorldSeed>,
+    mut loadout: ResMut<Loadout>,
+    mut mission_selection: ResMut<MissionSelection>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(path) = &config.play_from else {
+        return;
+    };
+
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(error) => {
+            error!("Could not play {}: {error}", path.display());
+            return;
+        }
+    };
+
+    if replay.tick_rate != TICK_RATE {
+        warn!(
+            "{} was recorded at {} ticks per second rather than {TICK_RATE}, so it will not play \
+             back faithfully",
+            path.display(),
+            replay.tick_rate
+        );
+    }
+
+    info!("Playing {} ({} ticks)", path.display(), replay.ticks.len());
+    *world_seed = WorldSeed(replay.seed);
+    *loadout = replay.loadout.clone();
+    *mission_selection = replay.mission;
+    *replay_state = ReplayState::Playing {
+        replay,
+        next_tick: 0,
+    };
+    next_state.set(GameState::Playing);
+}
+
+/// Starts recording when play begins, if asked to.
+fn start_recording(
+    config: Res<ReplayConfig>,
+    world_seed: Res<WorldSeed>,
+    loadout: Res<Loadout>,
+    mission_selection: Res<MissionSelection>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    if config.record_to.is_some() && matches!(*replay_state, ReplayState::Idle) {
+        *replay_state = ReplayState::Recording(Replay::new(
+            *world_seed,
+            loadout.clone(),
+            *mission_selection,
+        ));
+    }
+}
+
+/// Records this tick's input, or replaces it with the recorded input.
+fn record_or_play_tick(
+    mut replay_state: ResMut<ReplayState>,
+    mut action_state: ResMut<ActionState<FlightAction>>,
+    mut dock_state: ResMut<ActionState<DockAction>>,
+) {
+    let finished = match &mut *replay_state {
+        ReplayState::Idle => false,
+        ReplayState::Recording(replay) => {
+            replay
+                .ticks
+                .push(TickInput::capture(&action_state, &dock_state));
+            false
+        }
+        ReplayState::Playing { replay, next_tick } => match replay.ticks.get(*next_tick) {
+            Some(tick) => {
+                tick.apply(&mut action_state, &mut dock_state);
+                *next_tick += 1;
+                false
+            }
+            None => true,
+        },
+    };
+
+    if finished {
+        info!("Replay finished, handing control back to the player");
+        *replay_state = ReplayState::Idle;
+    }
+}
+
+/// Saves the recording, or stops playback, when play ends.
+fn finish_replay(config: Res<ReplayConfig>, mut replay_state: ResMut<ReplayState>) {
+    if let ReplayState::Recording(replay) = std::mem::take(&mut *replay_state) {
+        save_recording(&config, &replay);
+    }
+}
+
+/// Saves the recording when the app closes mid-flight.
+fn save_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    config: Res<ReplayConfig>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+
+    if let ReplayState::Recording(replay) = std::mem::take(&mut *replay_state) {
+        save_recording(&config, &replay);
+    }
+}
+
+/// Writes `replay` to the configured recording path, logging the outcome.
+fn save_recording(config: &ReplayConfig, replay: &Replay) {
+    let Some(path) = &config.record_to else {
+        return;
+    };
+
+    match replay.save(path) {
+        Ok(()) => info!("Saved {} ticks to {}", replay.ticks.len(), path.display()),
+        Err(error) => error!("Could not save the replay to {}: {error}", path.display()),
+    }
+}
@@ -0,0 +1,508 @@
+//! Computer-controlled pilots.
+//!
+//! Each [`AiPilot`] picks a goal, then flies towards it by blending steering behaviors: seeking
+//! the goal and braking to arrive at it, steering around asteroids in its path, and keeping clear
+//! of the other ships in its [`Squadron`]. Ships flying in formation on a leader, such as the
+//! player's [`Wingman`](super::wingmen::Wingman), match its speed and heading once in their slot.
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::InGame;
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::Targetable;
+
+use super::asteroids::Asteroid;
+use super::energy::{EnergySet, PowerDistribution};
+use super::factions::{Faction, Reputation};
+use super::flight::{FlightControls, FlightDynamics, GravityDrift, Throttle, Velocity};
+use super::geometry::{ray_sphere_distance, Collider};
+use super::health::Health;
+use super::navigation::NavigationSet;
+use super::sector::InSector;
+use super::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use super::spatial::SpatialIndex;
+use super::weapons::{
+    Hardpoint, Heat, MountedWeapon, WeaponDefinition, WeaponLibrary, WeaponTrigger,
+};
+
+/// How many radians of heading error make AI pilots turn at full rate.
+const FULL_TURN_ERROR: f32 = 0.4;
+
+/// How far ahead AI pilots look for obstacles, in seconds of travel at their current speed.
+const LOOKAHEAD_SECONDS: f32 = 2.5;
+
+/// How much room AI pilots leave around obstacles, in meters, beyond both colliders.
+const AVOIDANCE_MARGIN: f32 = 10.;
+
+/// How close squadmates can get before they steer apart, in meters.
+const SEPARATION_RADIUS: f32 = 40.;
+
+/// How close AI pilots come to the point they are flying to before stopping, in meters.
+const ARRIVAL_DISTANCE: f32 = 30.;
+
+/// How close AI pilots come to their slot in a formation before matching the leader's heading, in
+/// meters.
+const FORMATION_SLACK: f32 = 15.;
+
+/// How close AI pilots close in on the ship they are attacking, in meters.
+const ATTACK_STANDOFF: f32 = 120.;
+
+/// How closely AI pilots must face their target before firing, as a dot product.
+const FIRING_CONE: f32 = 0.97;
+
+/// AI logic
+pub(super) struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Squadrons>()
+            .add_console_command("spawn pirates", "spawn pirates [count]", spawn_pirates)
+            .configure_set(FixedUpdate, AiSet.after(NavigationSet).before(EnergySet))
+            .add_systems(
+                FixedUpdate,
+                (choose_targets, steer_ai_ships).chain().in_set(AiSet),
+            );
+    }
+}
+
+/// Systems that fly computer-controlled ships.
+///
+/// Like [`NavigationSet`], this runs before [`EnergySet`], and so before ships move.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AiSet;
+
+/// What an AI pilot is trying to do.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum AiGoal {
+    /// Stay put, while watching for enemies.
+    #[default]
+    Hold,
+    /// Fly to a point in world space and stop there.
+    FlyTo(Vec3),
+    /// Close in on a ship and shoot at it.
+    Attack(Entity),
+    /// Keep station on a leader, at `offset` in its frame, matching its speed and heading.
+    FormOn {
+        /// The ship being followed.
+        leader: Entity,
+        /// Where to fly relative to the leader, in meters to its right, above and behind it.
+        offset: Vec3,
+    },
+}
+
+/// How strongly each steering behavior pulls on an AI pilot's heading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SteeringWeights {
+    /// Heading towards the goal.
+    pub seek: f32,
+    /// Turning away from obstacles ahead.
+    pub avoidance: f32,
+    /// Keeping clear of squadmates.
+    pub separation: f32,
+}
+
+impl Default for SteeringWeights {
+    fn default() -> Self {
+        SteeringWeights {
+            seek: 1.,
+            avoidance: 4.,
+            separation: 2.,
+        }
+    }
+}
+
+/// Flies a ship without a player at the controls.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AiPilot {
+    /// What the pilot is trying to do.
+    pub goal: AiGoal,
+    /// How strongly each steering behavior pulls on the pilot's heading.
+    pub weights: SteeringWeights,
+    /// How far away the pilot notices enemies, in meters.
+    pub sensor_range: f32,
+    /// How far away the pilot opens fire, in meters.
+    pub weapon_range: f32,
+}
+
+impl Default for AiPilot {
+    fn default() -> Self {
+        AiPilot {
+            goal: AiGoal::Hold,
+            weights: SteeringWeights::default(),
+            sensor_range: 800.,
+            weapon_range: 400.,
+        }
+    }
+}
+
+/// Groups AI ships that fly together, and so keep clear of each other.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Squadron(pub u32);
+
+/// Hands out a new [`Squadron`] to each group of ships spawned, so separate groups only avoid
+/// rocks.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Squadrons {
+    /// The number the next squadron will be given.
+    next: u32,
+}
+
+impl Squadrons {
+    /// A squadron that no ships belong to yet.
+    pub fn allocate(&mut self) -> Squadron {
+        let squadron = Squadron(self.next);
+        self.next += 1;
+        squadron
+    }
+}
+
+/// Gives up on targets that have been destroyed, and has idle pilots attack the nearest enemy.
+fn choose_targets(
+    reputation: Res<Reputation>,
+    index: Res<SpatialIndex>,
+    mut pilots: Query<(&mut AiPilot, &Transform, &Faction)>,
+    targets: Query<&Faction, With<Health>>,
+) {
+    for (mut pilot, transform, &faction) in pilots.iter_mut() {
+        if let AiGoal::Attack(target) = pilot.goal {
+            if targets.contains(target) {
+                continue;
+            }
+            pilot.goal = AiGoal::Hold;
+        }
+        if pilot.goal != AiGoal::Hold {
+            continue;
+        }
+
+        let enemy = nearest_enemy(
+            &index,
+            &reputation,
+            &targets,
+            transform.translation,
+            pilot.sensor_range,
+            faction,
+        );
+        if let Some(target) = enemy {
+            pilot.goal = AiGoal::Attack(target);
+        }
+    }
+}
+
+/// The nearest ship within `range` of `position` that `faction` is hostile to.
+pub(super) fn nearest_enemy(
+    index: &SpatialIndex,
+    reputation: &Reputation,
+    targets: &Query<&Faction, With<Health>>,
+    position: Vec3,
+    range: f32,
+    faction: Faction,
+) -> Option<Entity> {
+    index
+        .nearest(position, range, |target| {
+            targets
+                .get(target)
+                .is_ok_and(|&other| reputation.is_hostile(faction, other))
+        })
+        .map(|(target, _)| target)
+}
+
+/// Steers away from the nearest obstacle ahead, more urgently the closer it is.
+///
+/// Obstacles are found through the `index`, so `max_radius` must be at least the radius of the
+/// largest of them.
+fn avoid_obstacles(
+    position: Vec3,
+    heading: Vec3,
+    speed: f32,
+    own_radius: f32,
+    index: &SpatialIndex,
+    obstacles: &Query<(&Transform, &Collider), With<Asteroid>>,
+    max_radius: f32,
+) -> Vec3 {
+    let lookahead = speed * LOOKAHEAD_SECONDS + AVOIDANCE_MARGIN;
+    let reach = lookahead + own_radius + AVOIDANCE_MARGIN + max_radius;
+
+    let nearest = index
+        .within(position, reach)
+        .filter_map(|(obstacle, _)| obstacles.get(obstacle).ok())
+        .filter_map(|(transform, collider)| {
+            let radius = collider.radius + own_radius + AVOIDANCE_MARGIN;
+            let distance = ray_sphere_distance(position, heading, transform.translation, radius)?;
+            (distance <= lookahead).then_some((transform.translation, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    let Some((center, distance)) = nearest else {
+        return Vec3::ZERO;
+    };
+
+    // Push sideways from the obstacle's center through the point where the path passes closest
+    let closest_approach = position + heading * heading.dot(center - position);
+    let away = (closest_approach - center)
+        .try_normalize()
+        .unwrap_or_else(|| heading.any_orthonormal_vector());
+    away * (1. - distance / lookahead)
+}
+
+/// Steers apart from squadmates that are too close, more strongly the closer they are.
+fn separate<'a>(position: Vec3, neighbours: impl Iterator<Item = &'a Transform>) -> Vec3 {
+    neighbours
+        .filter_map(|neighbour| {
+            let offset = position - neighbour.translation;
+            let distance = offset.length();
+            (distance > 0. && distance < SEPARATION_RADIUS)
+                .then(|| offset / distance * (1. - distance / SEPARATION_RADIUS))
+        })
+        .sum()
+}
+
+/// Blends each AI pilot's steering behaviors into flight controls, a throttle and a trigger.
+fn steer_ai_ships(
+    index: Res<SpatialIndex>,
+    mut pilots: Query<(
+        Entity,
+        &AiPilot,
+        &Transform,
+        &Velocity,
+        &FlightDynamics,
+        Option<&Collider>,
+        Option<&Squadron>,
+        &mut FlightControls,
+        &mut Throttle,
+        Option<&mut WeaponTrigger>,
+    )>,
+    obstacles: Query<(&Transform, &Collider), With<Asteroid>>,
+    squadmates: Query<(Entity, &Transform, &Squadron)>,
+    targets: Query<(&Transform, Option<&Velocity>)>,
+) {
+    let max_radius = obstacles.iter().fold(0., |max_radius: f32, (_, collider)| {
+        max_radius.max(collider.radius)
+    });
+
+    for (
+        entity,
+        pilot,
+        transform,
+        velocity,
+        dynamics,
+        collider,
+        squadron,
+        mut controls,
+        mut throttle,
+        trigger,
+    ) in pilots.iter_mut()
+    {
+        let position = transform.translation;
+        // The leader being kept station on, if any, as its heading and speed
+        let mut formation = None;
+        let (destination, arrival_distance) = match pilot.goal {
+            AiGoal::Hold => (None, 0.),
+            AiGoal::FlyTo(point) => (Some(point), ARRIVAL_DISTANCE),
+            AiGoal::Attack(target) => (
+                targets
+                    .get(target)
+                    .ok()
+                    .map(|(target, _)| target.translation),
+                ATTACK_STANDOFF,
+            ),
+            AiGoal::FormOn { leader, offset } => match targets.get(leader) {
+                Ok((leader, leader_velocity)) => {
+                    let speed = leader_velocity.map_or(0., |velocity| velocity.0.length());
+                    formation = Some((leader.forward(), speed));
+                    (Some(leader.transform_point(offset)), 0.)
+                }
+                Err(_) => (None, 0.),
+            },
+        };
+
+        let to_destination = destination.map_or(Vec3::ZERO, |destination| destination - position);
+        let distance = to_destination.length();
+        let seek = match formation {
+            // Once in the slot, fly the leader's heading rather than chasing small errors
+            Some((leader_heading, _)) if distance < FORMATION_SLACK => leader_heading,
+            _ => to_destination.normalize_or_zero(),
+        };
+
+        let speed = velocity.0.length();
+        let heading = velocity
+            .0
+            .try_normalize()
+            .unwrap_or_else(|| transform.forward());
+        let avoidance = avoid_obstacles(
+            position,
+            heading,
+            speed,
+            collider.map_or(0., |collider| collider.radius),
+            &index,
+            &obstacles,
+            max_radius,
+        );
+
+        let separation = match squadron {
+            Some(&squadron) => separate(
+                position,
+                squadmates
+                    .iter()
+                    .filter(|&(other, _, &other_squadron)| {
+                        other != entity && other_squadron == squadron
+                    })
+                    .map(|(_, transform, _)| transform),
+            ),
+            None => Vec3::ZERO,
+        };
+
+        let desired = seek * pilot.weights.seek
+            + avoidance * pilot.weights.avoidance
+            + separation * pilot.weights.separation;
+        *controls = match desired.try_normalize() {
+            Some(direction) => FlightControls::turn_towards(transform, direction, FULL_TURN_ERROR),
+            None => FlightControls::default(),
+        };
+
+        // Arrive by cruising no faster than the ship can brake from before reaching the goal
+        let arrival_speed = if destination.is_some() {
+            let braking_distance = (distance - arrival_distance).max(0.);
+            (2. * dynamics.acceleration * braking_distance).sqrt()
+        } else {
+            0.
+        };
+        // Keep some way on while dodging, so that steering away does not stall the ship in place
+        let evasion_speed = (avoidance.length() + separation.length()).min(1.) * dynamics.max_speed;
+        let alignment = desired
+            .try_normalize()
+            .map_or(1., |direction| transform.forward().dot(direction).max(0.2));
+        // Formations fly at the leader's speed, speeding up to catch up with a slot ahead and
+        // easing off to fall back into one behind
+        let travel_speed = match formation {
+            Some((leader_heading, leader_speed)) => (leader_speed
+                + arrival_speed * to_destination.normalize_or_zero().dot(leader_heading))
+            .max(0.),
+            None => arrival_speed,
+        };
+        let cruise = travel_speed
+            .max(evasion_speed * 0.5)
+            .min(dynamics.max_speed)
+            * alignment;
+        throttle.set(cruise / dynamics.max_speed);
+
+        if let Some(mut trigger) = trigger {
+            trigger.aim = destination.filter(|_| matches!(pilot.goal, AiGoal::Attack(_)));
+            trigger.firing = matches!(pilot.goal, AiGoal::Attack(_))
+                && destination.is_some()
+                && distance <= pilot.weapon_range
+                && transform.forward().dot(seek) >= FIRING_CONE;
+        }
+    }
+}
+
+/// Spawns a computer-controlled ship of the class `definition` at `transform`, with `weapon`
+/// fitted to every hardpoint.
+///
+/// The ship belongs to the current sector, so it is left behind if the player jumps away.
+pub fn spawn_ai_ship(
+    commands: &mut Commands,
+    transform: Transform,
+    definition: &ShipDefinition,
+    weapon: Option<&Handle<WeaponDefinition>>,
+    faction: Faction,
+    squadron: Squadron,
+    pilot: AiPilot,
+) -> Entity {
+    let mut ship = commands.spawn((
+        SpatialBundle::from_transform(transform),
+        InGame,
+        Name::new(format!("{} {}", faction.name(), definition.name)),
+        pilot,
+        squadron,
+        faction,
+        Targetable,
+        definition.dynamics(),
+        FlightControls::default(),
+        Throttle::default(),
+        Velocity::default(),
+        definition.energy(),
+        PowerDistribution::default(),
+        definition.health(),
+        definition.collider(),
+    ));
+    ship.insert((
+        GravityDrift::default(),
+        definition.shield(),
+        WeaponTrigger::default(),
+        InSector,
+    ));
+
+    ship.with_children(|parent| {
+        for (slot, hardpoint_definition) in definition.hardpoints.iter().enumerate() {
+            let mut hardpoint = parent.spawn((
+                SpatialBundle::from_transform(Transform::from_translation(
+                    hardpoint_definition.translation(),
+                )),
+                Hardpoint { slot },
+            ));
+            if let Some(weapon) = weapon {
+                hardpoint.insert((MountedWeapon::new(weapon.clone()), Heat::default()));
+            }
+        }
+    });
+
+    ship.id()
+}
+
+/// Console command that spawns a squadron of pirates ahead of the player.
+fn spawn_pirates(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let count = match *arguments {
+        [] => 3,
+        [count] => count
+            .parse::<u32>()
+            .map_err(|_| format!("`{count}` is not a whole number"))?,
+        _ => return Err("expected at most one count".to_string()),
+    };
+
+    let mut player_query = world.query_filtered::<&Transform, With<PlayerShip>>();
+    let origin = player_query
+        .get_single(world)
+        .map_or(Transform::IDENTITY, |transform| *transform);
+
+    let (class, definition) = {
+        let class = world.resource::<ShipLibrary>().ships().first().cloned();
+        let definitions = world.resource::<Assets<ShipDefinition>>();
+        let definition = class
+            .as_ref()
+            .and_then(|handle| definitions.get(handle))
+            .cloned()
+            .unwrap_or_default();
+        (class, definition)
+    };
+    let weapon = world.resource::<WeaponLibrary>().weapons().first().cloned();
+    let squadron = world.resource_mut::<Squadrons>().allocate();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    for index in 0..count {
+        let lateral = (index as f32 - (count as f32 - 1.) / 2.) * 25.;
+        let position = origin.translation + origin.forward() * 600. + origin.right() * lateral;
+        let transform =
+            Transform::from_translation(position).looking_at(origin.translation, Vec3::Y);
+
+        let ship = spawn_ai_ship(
+            &mut commands,
+            transform,
+            &definition,
+            weapon.as_ref(),
+            Faction::Pirate,
+            squadron,
+            AiPilot::default(),
+        );
+        if let Some(class) = &class {
+            commands.entity(ship).insert(ShipClass(class.clone()));
+        }
+    }
+    queue.apply(world);
+
+    Ok(format!(
+        "spawned {count} pirates in squadron {}",
+        squadron.0
+    ))
+}
@@ -0,0 +1,412 @@
+//! The asteroid field that ships fly through and mine, and which breaks apart when shot.
+//!
+//! Only asteroids near a ship can be hit or steered around: the [`Collider`]s of the rest are
+//! taken away until a ship comes within the [`AsteroidActivation`] range, so that fields of
+//! thousands of asteroids cost little more to simulate than the few near the action.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::InGame;
+use crate::player::ship::PlayerShip;
+
+use super::flight::{FlightControls, FlightSet, Velocity};
+use super::geometry::Collider;
+use super::health::{Destroyed, Health, HealthSet};
+use super::mining::DebrisBundle;
+use super::pickups::PickupBundle;
+use super::random::{RngStream, WorldRng};
+use super::sector::InSector;
+use super::spatial::SpatialSet;
+use super::time_control::SimulationTime;
+
+/// The range of asteroid radii, in meters.
+const ASTEROID_RADII: std::ops::Range<f32> = 4.0..40.0;
+
+/// How much ore each cubic meter of asteroid holds.
+const ORE_PER_CUBIC_METER: f32 = 0.01;
+
+/// How much damage asteroids can take for each meter of radius.
+const HEALTH_PER_METER: f32 = 5.;
+
+/// Asteroids whose fragments would be smaller than this radius, in meters, crumble to debris
+/// instead of splitting.
+const MIN_FRAGMENT_RADIUS: f32 = 3.;
+
+/// How fast fragments fly apart, in meters per second, on top of the asteroid's own velocity.
+const FRAGMENT_SPEEDS: std::ops::Range<f32> = 1.0..6.0;
+
+/// The share of a broken asteroid's ore that spills out as pickups, rather than staying in its
+/// fragments.
+const SPILLED_ORE: f32 = 0.2;
+
+/// How many chunks of debris the smallest asteroids crumble into.
+const CRUMBLED_DEBRIS: u32 = 8;
+
+/// How often asteroids are woken or put to sleep as ships move about, in seconds.
+const ACTIVATION_INTERVAL: f32 = 0.25;
+
+/// Asteroid logic
+pub(super) struct AsteroidPlugin;
+
+impl Plugin for AsteroidPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsteroidActivation>()
+            .add_console_command(
+                "spawn asteroid",
+                "spawn asteroid <count>",
+                spawn_asteroids_command,
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    activate_asteroids.after(FlightSet).before(SpatialSet),
+                    break_asteroids.after(HealthSet),
+                ),
+            );
+    }
+}
+
+/// How a sector's asteroid field is scattered.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AsteroidField {
+    /// How many asteroids there are.
+    pub count: usize,
+    /// The middle of the field, in world space.
+    pub center: [f32; 3],
+    /// How far from its center the field extends, in meters.
+    pub radius: f32,
+    /// How far from the center asteroids are kept clear of, in meters, leaving room to spawn.
+    pub clearing: f32,
+    /// The smallest asteroid's radius, in meters.
+    pub min_asteroid_radius: f32,
+    /// The largest asteroid's radius, in meters.
+    pub max_asteroid_radius: f32,
+}
+
+impl Default for AsteroidField {
+    fn default() -> Self {
+        AsteroidField {
+            count: 200,
+            center: [0.; 3],
+            radius: 1500.,
+            clearing: ASTEROID_RADII.end * 2.,
+            min_asteroid_radius: ASTEROID_RADII.start,
+            max_asteroid_radius: ASTEROID_RADII.end,
+        }
+    }
+}
+
+impl AsteroidField {
+    /// Scatters the field's asteroids, returning each one spawned.
+    pub fn spawn(&self, commands: &mut Commands, rng: &mut impl Rng) -> Vec<Entity> {
+        let center = Vec3::from(self.center);
+        let radii =
+            self.min_asteroid_radius..self.max_asteroid_radius.max(self.min_asteroid_radius + 0.1);
+
+        let mut spawned = Vec::with_capacity(self.count);
+        while spawned.len() < self.count {
+            let offset = random_point_in_sphere(self.radius, rng);
+            if offset.length() < self.clearing {
+                continue;
+            }
+
+            let radius = rng.gen_range(radii.clone());
+            let ore = OreType::choose(rng);
+            spawned.push(
+                commands
+                    .spawn(AsteroidBundle::new(center + offset, radius, ore, rng))
+                    .id(),
+            );
+        }
+        spawned
+    }
+}
+
+/// How close a ship must be for an asteroid to have a [`Collider`].
+///
+/// This must reach further than any shot can fly, or asteroids will not stop shots aimed at them.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AsteroidActivation {
+    /// The distance from the nearest ship within which asteroids can be hit, in meters.
+    pub range: f32,
+}
+
+impl Default for AsteroidActivation {
+    fn default() -> Self {
+        AsteroidActivation { range: 1500. }
+    }
+}
+
+/// A rock floating in space.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Asteroid {
+    /// The radius of the asteroid's bounding sphere, in meters.
+    pub radius: f32,
+}
+
+/// The kinds of ore that can be mined from asteroids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum OreType {
+    /// Common and cheap.
+    Iron,
+    /// Common, and found alongside iron.
+    Nickel,
+    /// Water ice, useful for life support.
+    Ice,
+    /// Rare and valuable.
+    Platinum,
+}
+
+impl OreType {
+    /// Every ore type, in display order.
+    pub const ALL: [OreType; 4] = [
+        OreType::Iron,
+        OreType::Nickel,
+        OreType::Ice,
+        OreType::Platinum,
+    ];
+
+    /// The name of this ore, for display.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OreType::Iron => "Iron",
+            OreType::Nickel => "Nickel",
+            OreType::Ice => "Ice",
+            OreType::Platinum => "Platinum",
+        }
+    }
+
+    /// Picks an ore type, weighted by how common each is.
+    fn choose(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..20) {
+            0..=8 => OreType::Iron,
+            9..=13 => OreType::Nickel,
+            14..=18 => OreType::Ice,
+            _ => OreType::Platinum,
+        }
+    }
+}
+
+/// The ore that remains in an asteroid.
+///
+/// This is not called `Resource`, to avoid confusion with Bevy's resources.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct OreDeposit {
+    /// The kind of ore.
+    pub ore: OreType,
+    /// How many units of ore are left.
+    pub quantity: f32,
+}
+
+/// Everything needed to spawn an asteroid.
+#[derive(Bundle, Debug)]
+pub struct AsteroidBundle {
+    /// The asteroid itself.
+    pub asteroid: Asteroid,
+    /// The ore it holds.
+    pub deposit: OreDeposit,
+    /// How much damage it can take before it breaks apart.
+    pub health: Health,
+    /// What shots hit.
+    pub collider: Collider,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl AsteroidBundle {
+    /// Generates a random asteroid centered on `position`.
+    pub fn random(position: Vec3, rng: &mut impl Rng) -> Self {
+        let radius = rng.gen_range(ASTEROID_RADII);
+        let ore = OreType::choose(rng);
+        AsteroidBundle::new(position, radius, ore, rng)
+    }
+
+    /// Creates an asteroid of `radius` holding `ore` at `position`, in a random orientation.
+    pub fn new(position: Vec3, radius: f32, ore: OreType, rng: &mut impl Rng) -> Self {
+        let volume = 4. / 3. * std::f32::consts::PI * radius.powi(3);
+        let rotation = Quat::from_euler(
+            EulerRot::XYZ,
+            rng.gen_range(0.0..std::f32::consts::TAU),
+            rng.gen_range(0.0..std::f32::consts::TAU),
+            rng.gen_range(0.0..std::f32::consts::TAU),
+        );
+
+        AsteroidBundle {
+            asteroid: Asteroid { radius },
+            deposit: OreDeposit {
+                ore,
+                quantity: volume * ORE_PER_CUBIC_METER,
+            },
+            health: Health::new(radius * HEALTH_PER_METER),
+            collider: Collider { radius },
+            spatial: SpatialBundle::from_transform(
+                Transform::from_translation(position)
+                    .with_rotation(rotation)
+                    .with_scale(Vec3::splat(radius)),
+            ),
+            in_game: InGame,
+        }
+    }
+}
+
+/// A random point within `radius` of the origin.
+fn random_point_in_sphere(radius: f32, rng: &mut impl Rng) -> Vec3 {
+    loop {
+        let point = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        if point.length_squared() <= 1. {
+            return point * radius;
+        }
+    }
+}
+
+/// Console command that spawns asteroids in front of the player's ship.
+fn spawn_asteroids_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let count: usize = match arguments {
+        [] => 1,
+        [count] => count
+            .parse()
+            .map_err(|_| format!("`{count}` is not a count"))?,
+        _ => return Err("expected a single count".to_string()),
+    };
+
+    let mut query = world.query_filtered::<&Transform, With<PlayerShip>>();
+    let origin = query
+        .get_single(world)
+        .map(|transform| transform.translation + transform.forward() * 200.)
+        .unwrap_or_default();
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let position = origin + random_point_in_sphere(150., &mut rng);
+        world.spawn(AsteroidBundle::random(position, &mut rng));
+    }
+
+    Ok(format!("spawned {count} asteroids"))
+}
+
+/// Gives asteroids within the [`AsteroidActivation`] range of a ship their [`Collider`] back, and
+/// takes it from those beyond the range of every ship.
+///
+/// Dormant asteroids are not in the [`SpatialIndex`](super::spatial::SpatialIndex), so the ships
+/// are bucketed into a grid of their own, with cells as wide as the furthest an asteroid can be
+/// reached from. Each asteroid then only needs to check the ships in its own and neighbouring
+/// cells.
+fn activate_asteroids(
+    mut commands: Commands,
+    time: SimulationTime,
+    activation: Res<AsteroidActivation>,
+    mut countdown: Local<f32>,
+    ships: Query<&Transform, With<FlightControls>>,
+    asteroids: Query<(Entity, &Transform, &Asteroid, Option<&Collider>)>,
+) {
+    *countdown -= time.delta_seconds();
+    if *countdown > 0. {
+        return;
+    }
+    *countdown = ACTIVATION_INTERVAL;
+
+    let largest_radius = asteroids
+        .iter()
+        .map(|(_, _, asteroid, _)| asteroid.radius)
+        .fold(0., f32::max);
+    let cell_size = (activation.range + largest_radius).max(1.);
+    let cell = |position: Vec3| (position / cell_size).floor().as_ivec3();
+
+    let mut ship_cells: HashMap<IVec3, Vec<Vec3>> = HashMap::default();
+    for ship in ships.iter() {
+        ship_cells
+            .entry(cell(ship.translation))
+            .or_default()
+            .push(ship.translation);
+    }
+
+    for (entity, transform, asteroid, collider) in asteroids.iter() {
+        let reach = activation.range + asteroid.radius;
+        let center = cell(transform.translation);
+        let active = (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .filter_map(|offset| ship_cells.get(&(center + offset)))
+            .flatten()
+            .any(|ship| ship.distance_squared(transform.translation) <= reach * reach);
+
+        match (active, collider) {
+            (true, None) => {
+                commands.entity(entity).insert(Collider {
+                    radius: asteroid.radius,
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<Collider>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Breaks destroyed asteroids into two to four fragments, spilling some of their ore as pickups,
+/// or into debris once the fragments would be too small.
+fn break_asteroids(
+    mut commands: Commands,
+    mut world_rng: ResMut<WorldRng>,
+    mut destroyed: EventReader<Destroyed>,
+    query: Query<(&Asteroid, &OreDeposit, Option<&Velocity>)>,
+) {
+    let rng = world_rng.stream(RngStream::Fragments);
+
+    for event in destroyed.iter() {
+        let Ok((asteroid, deposit, velocity)) = query.get(event.entity) else {
+            continue;
+        };
+        let velocity = velocity.map_or(Vec3::ZERO, |velocity| velocity.0);
+        let scatter = |rng: &mut ChaCha8Rng| {
+            random_point_in_sphere(1., rng).normalize_or_zero() * rng.gen_range(FRAGMENT_SPEEDS)
+        };
+
+        let fragments = rng.gen_range(2..=4);
+        // Shrink a little beyond an even split of the volume, as some of the rock is blasted away
+        let radius = asteroid.radius / (fragments as f32).cbrt() * 0.8;
+        if radius < MIN_FRAGMENT_RADIUS {
+            for _ in 0..CRUMBLED_DEBRIS {
+                let offset = random_point_in_sphere(asteroid.radius, rng);
+                commands.spawn(DebrisBundle::new(
+                    event.position + offset,
+                    velocity + scatter(rng),
+                ));
+            }
+        } else {
+            let quantity = deposit.quantity * (1. - SPILLED_ORE) / fragments as f32;
+            for _ in 0..fragments {
+                let direction = random_point_in_sphere(1., rng).normalize_or_zero();
+                let position = event.position + direction * asteroid.radius / 2.;
+                let speed = rng.gen_range(FRAGMENT_SPEEDS);
+                let fragment_radius = radius * rng.gen_range(0.85..1.15);
+
+                let mut fragment = AsteroidBundle::new(position, fragment_radius, deposit.ore, rng);
+                fragment.deposit.quantity = quantity;
+                commands.spawn((fragment, Velocity(velocity + direction * speed), InSector));
+            }
+        }
+
+        let pickups = rng.gen_range(1..=3);
+        for _ in 0..pickups {
+            commands.spawn(PickupBundle::new(
+                deposit.ore,
+                deposit.quantity * SPILLED_ORE / pickups as f32,
+                event.position + random_point_in_sphere(asteroid.radius / 2., rng),
+                velocity + scatter(rng),
+            ));
+        }
+    }
+}
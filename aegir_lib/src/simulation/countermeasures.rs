@@ -0,0 +1,186 @@
+//! Countermeasures: decoys that ships drop to spoof the [`Seeker`]s of missiles tracking them.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::InGame;
+
+use super::energy::EnergySet;
+use super::flight::{FlightSet, Velocity};
+use super::time_control::SimulationTime;
+use super::weapons::Seeker;
+use super::SimulationAppExt;
+
+/// How fast decoys are thrown clear of the ship, in meters per second.
+const EJECTION_SPEED: f32 = 20.;
+
+/// Countermeasure logic
+pub(super) struct CountermeasuresPlugin;
+
+impl Plugin for CountermeasuresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<CountermeasuresDeployed>()
+            .add_systems(
+                FixedUpdate,
+                (deploy_countermeasures, age_decoys)
+                    .chain()
+                    .after(EnergySet)
+                    .before(FlightSet),
+            );
+    }
+}
+
+/// The kinds of countermeasure a ship can carry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CountermeasureKind {
+    /// Hot flares: plenty of charges, but only spoofing missiles close behind.
+    #[default]
+    Flares,
+    /// Clouds of chaff: few charges, but spoofing missiles from further away.
+    Chaff,
+}
+
+impl CountermeasureKind {
+    /// Every kind of countermeasure, in the order they are offered to the player.
+    pub const ALL: [CountermeasureKind; 2] =
+        [CountermeasureKind::Flares, CountermeasureKind::Chaff];
+
+    /// The name shown to the player.
+    pub fn name(self) -> &'static str {
+        match self {
+            CountermeasureKind::Flares => "Flares",
+            CountermeasureKind::Chaff => "Chaff",
+        }
+    }
+
+    /// A full load of this kind of countermeasure.
+    pub fn countermeasures(self) -> Countermeasures {
+        let (charges, range, cooldown, decoy_lifetime) = match self {
+            CountermeasureKind::Flares => (12, 300., 0.5, 4.),
+            CountermeasureKind::Chaff => (6, 600., 1., 6.),
+        };
+
+        Countermeasures {
+            kind: self,
+            charges,
+            max_charges: charges,
+            range,
+            cooldown_time: cooldown,
+            decoy_lifetime,
+            requested: false,
+            cooldown: 0.,
+        }
+    }
+}
+
+/// A ship's store of countermeasures.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Countermeasures {
+    /// What the ship carries.
+    pub kind: CountermeasureKind,
+    /// How many decoys are left.
+    pub charges: u32,
+    /// How many decoys a full load holds.
+    pub max_charges: u32,
+    /// How close missiles tracking the ship must be to be spoofed, in meters.
+    pub range: f32,
+    /// How long the launcher takes to ready another decoy, in seconds.
+    pub cooldown_time: f32,
+    /// How long each decoy lasts, in seconds.
+    pub decoy_lifetime: f32,
+    /// Has the pilot asked to drop a decoy?
+    pub requested: bool,
+    /// How long until another decoy can be dropped, in seconds.
+    cooldown: f32,
+}
+
+impl Default for Countermeasures {
+    fn default() -> Self {
+        CountermeasureKind::default().countermeasures()
+    }
+}
+
+/// A decoy dropped by [`Countermeasures`], which spoofed missiles chase instead of the ship.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Decoy {
+    /// What kind of countermeasure it is.
+    pub kind: CountermeasureKind,
+    /// How long it has left before it burns out, in seconds.
+    lifetime: f32,
+}
+
+/// A ship has dropped a decoy.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountermeasuresDeployed {
+    /// The ship that dropped it.
+    pub ship: Entity,
+    /// How many missiles tracking the ship it spoofed.
+    pub spoofed: u32,
+}
+
+/// Drops a decoy behind each ship whose pilot asks for one, turning the missiles tracking the ship
+/// onto it.
+///
+/// Only missiles close enough, and whose seekers can see the decoy, are spoofed.
+fn deploy_countermeasures(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut ships: Query<(Entity, &Transform, &Velocity, &mut Countermeasures), Without<Seeker>>,
+    mut seekers: Query<(&Transform, &mut Seeker)>,
+    mut deployed: EventWriter<CountermeasuresDeployed>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (ship, transform, velocity, mut countermeasures) in ships.iter_mut() {
+        countermeasures.cooldown = (countermeasures.cooldown - delta_time).max(0.);
+        if !std::mem::take(&mut countermeasures.requested)
+            || countermeasures.charges == 0
+            || countermeasures.cooldown > 0.
+        {
+            continue;
+        }
+        countermeasures.charges -= 1;
+        countermeasures.cooldown = countermeasures.cooldown_time;
+
+        let position = transform.translation;
+        let decoy = commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_translation(position)),
+                Velocity(velocity.0 + transform.back() * EJECTION_SPEED),
+                Decoy {
+                    kind: countermeasures.kind,
+                    lifetime: countermeasures.decoy_lifetime,
+                },
+                InGame,
+            ))
+            .id();
+
+        let mut spoofed = 0;
+        for (seeker_transform, mut seeker) in seekers.iter_mut() {
+            if seeker.target == Some(ship)
+                && seeker_transform.translation.distance(position) <= countermeasures.range
+                && seeker.sees(seeker_transform, position)
+            {
+                seeker.target = Some(decoy);
+                spoofed += 1;
+            }
+        }
+        deployed.send(CountermeasuresDeployed { ship, spoofed });
+    }
+}
+
+/// Burns out decoys that have lasted long enough.
+fn age_decoys(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut query: Query<(Entity, &mut Decoy)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut decoy) in query.iter_mut() {
+        decoy.lifetime -= delta_time;
+        if decoy.lifetime <= 0. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
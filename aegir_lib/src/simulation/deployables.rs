@@ -0,0 +1,391 @@
+//! Deployables: proximity mines and turret pods that ships leave behind them.
+//!
+//! Each ship carries one kind of deployable, drawn from a limited store of [`Deployables`]. Mines
+//! arm after a delay, then detonate when an enemy ship strays close, found through the
+//! [`SpatialIndex`]. Turret pods pick out the nearest enemy and fire on it with a weapon of their
+//! own, through the same pipeline as the weapons mounted on ships.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::InGame;
+use crate::player::targeting::Targetable;
+
+use super::energy::{Energy, EnergySet};
+use super::factions::{Faction, Reputation};
+use super::flight::{FlightControls, FlightSet, Velocity};
+use super::geometry::Collider;
+use super::health::{Damaged, Health, HealthSet};
+use super::sector::InSector;
+use super::spatial::{SpatialIndex, SpatialSet};
+use super::time_control::SimulationTime;
+use super::weapons::{Hardpoint, Heat, MountedWeapon, WeaponLibrary, WeaponTrigger};
+
+/// How far behind a ship's collider deployables are dropped, in meters.
+const DROP_DISTANCE: f32 = 8.;
+
+/// How long a mine takes to arm once dropped, in seconds.
+const MINE_ARM_DELAY: f32 = 2.;
+
+/// How close an enemy ship must come to set off an armed mine, in meters.
+const MINE_TRIGGER_RADIUS: f32 = 40.;
+
+/// How far a mine's blast reaches, in meters; damage falls off to nothing at its edge.
+const MINE_BLAST_RADIUS: f32 = 60.;
+
+/// How much damage a mine's blast deals at its center.
+const MINE_DAMAGE: f32 = 80.;
+
+/// How long a mine lies in wait before it disarms itself, in seconds.
+const MINE_LIFETIME: f32 = 90.;
+
+/// How much damage a mine can take before it is destroyed without detonating.
+const MINE_HEALTH: f32 = 10.;
+
+/// How far a turret pod can see and shoot, in meters.
+const TURRET_RANGE: f32 = 500.;
+
+/// How quickly a turret pod turns to face its target, in radians per second.
+const TURRET_TURN_RATE: f32 = 1.5;
+
+/// How closely a turret pod must face its target before firing, as a dot product.
+const TURRET_FIRING_CONE: f32 = 0.98;
+
+/// How long a turret pod keeps firing before it shuts down, in seconds.
+const TURRET_LIFETIME: f32 = 60.;
+
+/// How much damage a turret pod can take before it is destroyed.
+const TURRET_HEALTH: f32 = 60.;
+
+/// Deployables logic
+pub(super) struct DeployablesPlugin;
+
+impl Plugin for DeployablesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (drop_deployables, aim_turrets)
+                    .chain()
+                    .after(EnergySet)
+                    .before(FlightSet),
+                (arm_mines, detonate_mines, age_deployables)
+                    .chain()
+                    .after(SpatialSet)
+                    .before(HealthSet),
+            ),
+        );
+    }
+}
+
+/// The kinds of deployable a ship can carry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeployableKind {
+    /// Proximity mines, which lie in wait behind the ship.
+    #[default]
+    Mines,
+    /// Turret pods, which fire on any enemies nearby.
+    Turrets,
+}
+
+impl DeployableKind {
+    /// Every kind of deployable, in the order they are offered to the player.
+    pub const ALL: [DeployableKind; 2] = [DeployableKind::Mines, DeployableKind::Turrets];
+
+    /// The name shown to the player.
+    pub fn name(self) -> &'static str {
+        match self {
+            DeployableKind::Mines => "Mines",
+            DeployableKind::Turrets => "Turrets",
+        }
+    }
+
+    /// A full load of this kind of deployable.
+    pub fn deployables(self) -> Deployables {
+        let (charges, cooldown) = match self {
+            DeployableKind::Mines => (4, 1.),
+            DeployableKind::Turrets => (2, 3.),
+        };
+
+        Deployables {
+            kind: self,
+            charges,
+            max_charges: charges,
+            cooldown_time: cooldown,
+            requested: false,
+            cooldown: 0.,
+        }
+    }
+}
+
+/// A ship's store of deployables.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Deployables {
+    /// What the ship carries.
+    pub kind: DeployableKind,
+    /// How many are left.
+    pub charges: u32,
+    /// How many a full load holds.
+    pub max_charges: u32,
+    /// How long the ship takes to ready another, in seconds.
+    pub cooldown_time: f32,
+    /// Has the pilot asked to drop one?
+    pub requested: bool,
+    /// How long until another can be dropped, in seconds.
+    cooldown: f32,
+}
+
+impl Default for Deployables {
+    fn default() -> Self {
+        DeployableKind::default().deployables()
+    }
+}
+
+/// Something a ship has dropped, which is cleaned up once it has lasted long enough.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Deployed {
+    /// The ship that dropped it.
+    pub owner: Entity,
+    /// How long it has left, in seconds.
+    lifetime: f32,
+}
+
+/// A mine that detonates when an enemy ship comes close, once it has armed.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Mine {
+    /// How long until the mine arms, in seconds.
+    arming: f32,
+}
+
+impl Mine {
+    /// Is the mine ready to detonate?
+    pub fn is_armed(&self) -> bool {
+        self.arming <= 0.
+    }
+}
+
+/// A stationary pod that turns to face the nearest enemy and fires on it.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Turret {
+    /// The enemy being fired on, if there is one in range.
+    pub target: Option<Entity>,
+}
+
+/// Drops a deployable behind each ship whose pilot asks for one.
+///
+/// Turret pods are fitted with the first weapon in the [`WeaponLibrary`], and fight for the
+/// ship's faction.
+fn drop_deployables(
+    mut commands: Commands,
+    time: SimulationTime,
+    weapon_library: Res<WeaponLibrary>,
+    mut ships: Query<(
+        Entity,
+        &Transform,
+        &Faction,
+        Option<&Collider>,
+        &mut Deployables,
+    )>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (ship, transform, &faction, collider, mut deployables) in ships.iter_mut() {
+        deployables.cooldown = (deployables.cooldown - delta_time).max(0.);
+        if !std::mem::take(&mut deployables.requested)
+            || deployables.charges == 0
+            || deployables.cooldown > 0.
+        {
+            continue;
+        }
+        deployables.charges -= 1;
+        deployables.cooldown = deployables.cooldown_time;
+
+        let distance = collider.map_or(0., |collider| collider.radius) + DROP_DISTANCE;
+        let drop = Transform {
+            translation: transform.translation + transform.back() * distance,
+            rotation: transform.rotation,
+            ..default()
+        };
+        match deployables.kind {
+            DeployableKind::Mines => {
+                commands.spawn((
+                    SpatialBundle::from_transform(drop),
+                    Name::new("Mine"),
+                    Mine {
+                        arming: MINE_ARM_DELAY,
+                    },
+                    Deployed {
+                        owner: ship,
+                        lifetime: MINE_LIFETIME,
+                    },
+                    faction,
+                    Health::new(MINE_HEALTH),
+                    Collider { radius: 1.5 },
+                    InGame,
+                    InSector,
+                ));
+            }
+            DeployableKind::Turrets => {
+                let mut turret = commands.spawn((
+                    SpatialBundle::from_transform(drop),
+                    Name::new(format!("{} Turret", faction.name())),
+                    Turret::default(),
+                    Deployed {
+                        owner: ship,
+                        lifetime: TURRET_LIFETIME,
+                    },
+                    faction,
+                    Targetable,
+                    Velocity::default(),
+                    WeaponTrigger::default(),
+                    Energy::default(),
+                    Health::new(TURRET_HEALTH),
+                    Collider { radius: 2.5 },
+                    InGame,
+                    InSector,
+                ));
+
+                if let Some(weapon) = weapon_library.weapons().first() {
+                    turret.with_children(|parent| {
+                        parent.spawn((
+                            SpatialBundle::default(),
+                            Hardpoint { slot: 0 },
+                            MountedWeapon::new(weapon.clone()),
+                            Heat::default(),
+                        ));
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Turns each turret pod towards the nearest enemy in range, firing once it faces it.
+///
+/// Turrets keep firing on the same enemy until it is destroyed or leaves their range.
+fn aim_turrets(
+    time: SimulationTime,
+    reputation: Res<Reputation>,
+    index: Res<SpatialIndex>,
+    mut turrets: Query<(&mut Transform, &mut Turret, &mut WeaponTrigger, &Faction)>,
+    targets: Query<(&Transform, &Faction), (With<Health>, Without<Turret>)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut transform, mut turret, mut trigger, &faction) in turrets.iter_mut() {
+        let position = transform.translation;
+        let enemy_in_range = |target: Entity| {
+            targets.get(target).is_ok_and(|(target_transform, &other)| {
+                reputation.is_hostile(faction, other)
+                    && target_transform.translation.distance(position) <= TURRET_RANGE
+            })
+        };
+
+        turret.target = turret
+            .target
+            .filter(|&target| enemy_in_range(target))
+            .or_else(|| {
+                index
+                    .nearest(position, TURRET_RANGE, enemy_in_range)
+                    .map(|(target, _)| target)
+            });
+        let Some((target_transform, _)) = turret.target.and_then(|target| targets.get(target).ok())
+        else {
+            if trigger.firing || trigger.aim.is_some() {
+                trigger.firing = false;
+                trigger.aim = None;
+            }
+            continue;
+        };
+        trigger.aim = Some(target_transform.translation);
+        let Some(desired) = (target_transform.translation - position).try_normalize() else {
+            continue;
+        };
+
+        let heading = transform.forward();
+        let angle = heading.angle_between(desired);
+        let max_turn = TURRET_TURN_RATE * delta_time;
+        let heading = if angle <= max_turn {
+            desired
+        } else {
+            Quat::IDENTITY.slerp(Quat::from_rotation_arc(heading, desired), max_turn / angle)
+                * heading
+        };
+        transform.look_at(position + heading, Vec3::Y);
+        trigger.firing = heading.dot(desired) >= TURRET_FIRING_CONE;
+    }
+}
+
+/// Counts down each mine until it arms.
+fn arm_mines(time: SimulationTime, mut mines: Query<&mut Mine>) {
+    let delta_time = time.delta_seconds();
+
+    for mut mine in mines.iter_mut() {
+        if !mine.is_armed() {
+            mine.arming -= delta_time;
+        }
+    }
+}
+
+/// Detonates each armed mine that an enemy ship has come close to, damaging everything caught in
+/// its blast, the mine included.
+fn detonate_mines(
+    reputation: Res<Reputation>,
+    index: Res<SpatialIndex>,
+    mines: Query<(Entity, &Transform, &Mine, &Deployed, &Faction)>,
+    ships: Query<&Faction, With<FlightControls>>,
+    victims: Query<(), With<Health>>,
+    mut damaged: EventWriter<Damaged>,
+) {
+    for (mine, transform, state, deployed, &faction) in mines.iter() {
+        if !state.is_armed() {
+            continue;
+        }
+        let position = transform.translation;
+        let triggered = index
+            .within(position, MINE_TRIGGER_RADIUS)
+            .any(|(ship, _)| {
+                ships
+                    .get(ship)
+                    .is_ok_and(|&other| reputation.is_hostile(faction, other))
+            });
+        if !triggered {
+            continue;
+        }
+
+        damaged.send(Damaged {
+            target: mine,
+            source: Some(deployed.owner),
+            amount: MINE_HEALTH,
+            position,
+        });
+        for (victim, victim_position) in index.within(position, MINE_BLAST_RADIUS) {
+            if victim == mine || !victims.contains(victim) {
+                continue;
+            }
+            let falloff = 1. - victim_position.distance(position) / MINE_BLAST_RADIUS;
+            damaged.send(Damaged {
+                target: victim,
+                source: Some(deployed.owner),
+                amount: MINE_DAMAGE * falloff.max(0.),
+                position: victim_position,
+            });
+        }
+    }
+}
+
+/// Cleans up deployables that have lasted long enough.
+fn age_deployables(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut query: Query<(Entity, &mut Deployed)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut deployed) in query.iter_mut() {
+        deployed.lifetime -= delta_time;
+        if deployed.lifetime <= 0. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! Trading commodities with stations.
+//!
+//! Each station's [`Market`] lists the commodities it trades. Prices wander around each listing's
+//! base price over time, and move with every trade: selling a commodity to a station lowers its
+//! price there, and buying it raises it. Stations charge ships from factions they regard poorly
+//! more and pay them less, and refuse to trade with hostile factions at all.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::asteroids::OreType;
+use super::factions::{Faction, Reputation, HOSTILE_STANDING, MAX_STANDING};
+use super::mining::Inventory;
+use super::random::{RngStream, WorldRng};
+use super::stations::DockingComputer;
+use super::time_control::SimulationTime;
+use super::SimulationAppExt;
+
+/// How far apart a station's buying and selling prices are, as a fraction of the going price.
+const SPREAD: f32 = 0.1;
+
+/// How much a station's prices are discounted for the best of friends, and marked up for the
+/// worst of enemies that it will still trade with, as a fraction.
+const STANDING_DISCOUNT: f32 = 0.2;
+
+/// How far prices may wander from their base price, as a multiple of it either way.
+const MAX_PRICE_SWING: f32 = 2.;
+
+/// How strongly prices are pulled back towards their base price, as a fraction of the gap each
+/// second.
+const PRICE_REVERSION: f32 = 0.01;
+
+/// How far prices wander at random, as a fraction of their base price each second.
+const PRICE_VOLATILITY: f32 = 0.02;
+
+/// How far from their base price markets may open, as a fraction of it either way.
+const OPENING_PRICE_SPREAD: f32 = 0.1;
+
+/// How much each unit traded moves the price, as a fraction of it.
+const PRICE_IMPACT: f32 = 0.005;
+
+/// The credits a new pilot starts with.
+const STARTING_CREDITS: f32 = 500.;
+
+/// Economy logic
+pub(super) struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<Traded>()
+            .add_systems(FixedUpdate, (drift_prices, execute_trades).chain());
+    }
+}
+
+/// Something that can be carried in a ship's [`Inventory`] and traded at stations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Commodity {
+    /// Ore mined from asteroids.
+    Ore(OreType),
+    /// Rations and fresh produce.
+    Food,
+    /// Drugs and medical supplies.
+    Medicine,
+    /// Parts and tools for stations and ships.
+    Machinery,
+}
+
+impl Commodity {
+    /// Every commodity, in display order.
+    pub const ALL: [Commodity; 7] = [
+        Commodity::Ore(OreType::Iron),
+        Commodity::Ore(OreType::Nickel),
+        Commodity::Ore(OreType::Ice),
+        Commodity::Ore(OreType::Platinum),
+        Commodity::Food,
+        Commodity::Medicine,
+        Commodity::Machinery,
+    ];
+
+    /// The name of this commodity, for display.
+    pub fn name(self) -> &'static str {
+        match self {
+            Commodity::Ore(ore) => ore.name(),
+            Commodity::Food => "Food",
+            Commodity::Medicine => "Medicine",
+            Commodity::Machinery => "Machinery",
+        }
+    }
+
+    /// What a unit of this commodity is usually worth, in credits.
+    pub fn base_price(self) -> f32 {
+        match self {
+            Commodity::Ore(OreType::Iron) => 8.,
+            Commodity::Ore(OreType::Nickel) => 12.,
+            Commodity::Ore(OreType::Ice) => 6.,
+            Commodity::Ore(OreType::Platinum) => 60.,
+            Commodity::Food => 15.,
+            Commodity::Medicine => 40.,
+            Commodity::Machinery => 30.,
+        }
+    }
+}
+
+impl From<OreType> for Commodity {
+    fn from(ore: OreType) -> Self {
+        Commodity::Ore(ore)
+    }
+}
+
+/// A commodity a station trades, as given in a sector file.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ListingDefinition {
+    /// What is traded.
+    pub commodity: Commodity,
+    /// What a unit is usually worth here, in credits, or `None` for the commodity's
+    /// [`base_price`](Commodity::base_price).
+    #[serde(default)]
+    pub price: Option<f32>,
+}
+
+/// A commodity listed on a [`Market`], and what it is going for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listing {
+    /// What is traded.
+    pub commodity: Commodity,
+    /// What a unit is usually worth here, in credits, which the going price is pulled towards.
+    pub base_price: f32,
+    /// What a unit is going for now, before the spread and any faction markup, in credits.
+    price: f32,
+}
+
+impl Listing {
+    /// Lists `commodity` at `base_price` credits a unit.
+    pub fn new(commodity: Commodity, base_price: f32) -> Self {
+        Listing {
+            commodity,
+            base_price,
+            price: base_price,
+        }
+    }
+
+    /// What a unit is going for now, before the spread and any faction markup, in credits.
+    pub fn price(&self) -> f32 {
+        self.price
+    }
+
+    /// What the station charges a ship for each unit, given the standing between their factions.
+    pub fn buy_price(&self, standing: f32) -> f32 {
+        self.price * (1. + SPREAD) * standing_markup(standing)
+    }
+
+    /// What the station pays a ship for each unit, given the standing between their factions.
+    pub fn sell_price(&self, standing: f32) -> f32 {
+        self.price * (1. - SPREAD) / standing_markup(standing)
+    }
+
+    /// Sets the going price, keeping it within [`MAX_PRICE_SWING`] of the base price.
+    fn set_price(&mut self, price: f32) {
+        self.price = price.clamp(
+            self.base_price / MAX_PRICE_SWING,
+            self.base_price * MAX_PRICE_SWING,
+        );
+    }
+}
+
+/// How much a station's prices are scaled for a ship whose faction has `standing` with its own.
+fn standing_markup(standing: f32) -> f32 {
+    1. - standing / MAX_STANDING * STANDING_DISCOUNT
+}
+
+/// The commodities a station trades.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Market {
+    /// Each commodity traded, in display order.
+    listings: Vec<Listing>,
+}
+
+impl Market {
+    /// A market trading each of `listings`.
+    pub fn new(listings: impl IntoIterator<Item = Listing>) -> Self {
+        Market {
+            listings: listings.into_iter().collect(),
+        }
+    }
+
+    /// A market trading what `definitions` list.
+    pub fn from_definitions(definitions: &[ListingDefinition]) -> Self {
+        Market::new(definitions.iter().map(|definition| {
+            let base_price = definition
+                .price
+                .unwrap_or_else(|| definition.commodity.base_price());
+            Listing::new(definition.commodity, base_price)
+        }))
+    }
+
+    /// Moves each price to somewhere near its base price, so that markets do not all open at the
+    /// same prices.
+    pub fn scatter_prices(&mut self, rng: &mut impl Rng) {
+        for listing in self.listings.iter_mut() {
+            let spread = rng.gen_range(-OPENING_PRICE_SPREAD..OPENING_PRICE_SPREAD);
+            listing.set_price(listing.base_price * (1. + spread));
+        }
+    }
+
+    /// Changes what the market trades to what `definitions` list, keeping the going price of
+    /// anything it already traded, within reach of its new base price.
+    pub fn retune(&mut self, definitions: &[ListingDefinition]) {
+        let mut market = Market::from_definitions(definitions);
+        for listing in market.listings.iter_mut() {
+            if let Some(old) = self.listing(listing.commodity) {
+                listing.set_price(old.price);
+            }
+        }
+        *self = market;
+    }
+
+    /// Each commodity traded, in display order.
+    pub fn listings(&self) -> &[Listing] {
+        &self.listings
+    }
+
+    /// The listing for `commodity`, if it is traded here.
+    pub fn listing(&self, commodity: Commodity) -> Option<&Listing> {
+        self.listings
+            .iter()
+            .find(|listing| listing.commodity == commodity)
+    }
+
+    /// A mutable reference to the listing for `commodity`, if it is traded here.
+    fn listing_mut(&mut self, commodity: Commodity) -> Option<&mut Listing> {
+        self.listings
+            .iter_mut()
+            .find(|listing| listing.commodity == commodity)
+    }
+
+    /// Makes `order` with a ship whose faction has `standing` with the station's, as far as the
+    /// `trader`'s credits and the space and cargo in its `inventory` allow.
+    ///
+    /// Returns how many units and how many credits changed hands, or `None` if the commodity is not
+    /// traded here or the station refuses to trade with the ship's faction.
+    fn trade(
+        &mut self,
+        order: TradeOrder,
+        standing: f32,
+        trader: &mut Trader,
+        inventory: &mut Inventory,
+    ) -> Option<(f32, f32)> {
+        if standing <= HOSTILE_STANDING {
+            return None;
+        }
+        let listing = self.listing_mut(order.commodity)?;
+
+        let traded = match order.kind {
+            TradeKind::Buy => {
+                let unit_price = listing.buy_price(standing);
+                let affordable = trader.credits / unit_price;
+                let stored = inventory.add(order.commodity, order.amount.min(affordable).max(0.));
+                let cost = stored * unit_price;
+                trader.credits -= cost;
+                listing.set_price(listing.price * (1. + PRICE_IMPACT).powf(stored));
+                (stored, cost)
+            }
+            TradeKind::Sell => {
+                let unit_price = listing.sell_price(standing);
+                let removed = inventory.remove(order.commodity, order.amount.max(0.));
+                let earnings = removed * unit_price;
+                trader.credits += earnings;
+                listing.set_price(listing.price * (1. - PRICE_IMPACT).powf(removed));
+                (removed, earnings)
+            }
+        };
+
+        Some(traded)
+    }
+}
+
+impl Default for Market {
+    /// Buys and sells every ore at its usual price.
+    fn default() -> Self {
+        Market::new(
+            OreType::ALL
+                .into_iter()
+                .map(|ore| Listing::new(ore.into(), Commodity::from(ore).base_price())),
+        )
+    }
+}
+
+/// Whether a [`TradeOrder`] buys from or sells to the station.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradeKind {
+    /// The ship buys from the station.
+    Buy,
+    /// The ship sells to the station.
+    Sell,
+}
+
+/// A trade a pilot wants to make with the station they are docked with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeOrder {
+    /// Whether the ship is buying or selling.
+    pub kind: TradeKind,
+    /// What is traded.
+    pub commodity: Commodity,
+    /// The most units to trade; fewer are traded if the hold, the ship's credits or its cargo
+    /// run short.
+    pub amount: f32,
+}
+
+/// Lets a ship trade with the stations it docks with, and holds the credits it trades with.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Trader {
+    /// The credits the ship has to spend.
+    credits: f32,
+    /// Trades the pilot has asked for, which are made at the next tick if the ship is docked.
+    pub orders: Vec<TradeOrder>,
+}
+
+impl Trader {
+    /// A trader with `credits` to spend.
+    pub fn new(credits: f32) -> Self {
+        Trader {
+            credits,
+            orders: Vec::new(),
+        }
+    }
+
+    /// The credits the ship has to spend.
+    pub fn credits(&self) -> f32 {
+        self.credits
+    }
+}
+
+impl Default for Trader {
+    fn default() -> Self {
+        Trader::new(STARTING_CREDITS)
+    }
+}
+
+/// A ship has traded with a station.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Traded {
+    /// The ship that traded.
+    pub ship: Entity,
+    /// The station it traded with.
+    pub station: Entity,
+    /// What was traded, with how many units actually changed hands.
+    pub order: TradeOrder,
+    /// How many credits changed hands.
+    pub credits: f32,
+}
+
+/// Lets every market's prices wander at random, while pulling them back towards their base
+/// prices.
+fn drift_prices(
+    time: SimulationTime,
+    mut world_rng: ResMut<WorldRng>,
+    mut markets: Query<&mut Market>,
+) {
+    let delta_time = time.delta_seconds();
+    let rng = world_rng.stream(RngStream::Economy);
+
+    for mut market in markets.iter_mut() {
+        for listing in market.listings.iter_mut() {
+            let reversion = (listing.base_price - listing.price) * PRICE_REVERSION * delta_time;
+            // Scaling by the square root of the tick keeps the wandering independent of tick rate
+            let noise = rng.gen_range(-1.0..1.0)
+                * listing.base_price
+                * PRICE_VOLATILITY
+                * delta_time.sqrt();
+            listing.set_price(listing.price + reversion + noise);
+        }
+    }
+}
+
+/// Makes the trades that docked ships have asked for, as far as their credits, holds and cargo
+/// allow.
+fn execute_trades(
+    reputation: Res<Reputation>,
+    mut ships: Query<(
+        Entity,
+        &DockingComputer,
+        &mut Trader,
+        &mut Inventory,
+        Option<&Faction>,
+    )>,
+    mut stations: Query<(&mut Market, &Faction)>,
+    mut traded: EventWriter<Traded>,
+) {
+    for (ship, computer, mut trader, mut inventory, faction) in ships.iter_mut() {
+        if trader.orders.is_empty() {
+            continue;
+        }
+        let orders = std::mem::take(&mut trader.orders);
+
+        let Some(station) = computer.docked() else {
+            continue;
+        };
+        let Ok((mut market, &station_faction)) = stations.get_mut(station) else {
+            continue;
+        };
+        // Ships without a faction are strangers, neither liked nor disliked
+        let standing = faction.map_or(0., |&faction| reputation.standing(faction, station_faction));
+
+        for order in orders {
+            let Some((amount, credits)) =
+                market.trade(order, standing, &mut trader, &mut inventory)
+            else {
+                continue;
+            };
+
+            if amount > 0. {
+                traded.send(Traded {
+                    ship,
+                    station,
+                    order: TradeOrder { amount, ..order },
+                    credits,
+                });
+            }
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An order to `kind` `amount` units of food.
+    fn food(kind: TradeKind, amount: f32) -> TradeOrder {
+        TradeOrder {
+            kind,
+            commodity: Commodity::Food,
+            amount,
+        }
+    }
+
+    /// A market trading only food, at 10 credits a unit.
+    fn food_market() -> Market {
+        Market::new([Listing::new(Commodity::Food, 10.)])
+    }
+
+    /// Ships buy at a markup and sell at a discount, both better the better their standing.
+    #[test]
+    fn prices_follow_the_spread_and_standing() {
+        let listing = Listing::new(Commodity::Food, 10.);
+
+        assert_eq!(listing.buy_price(0.), 10. * (1. + SPREAD));
+        assert_eq!(listing.sell_price(0.), 10. * (1. - SPREAD));
+        assert!(listing.buy_price(MAX_STANDING) < listing.buy_price(0.));
+        assert!(listing.buy_price(HOSTILE_STANDING) > listing.buy_price(0.));
+        assert!(listing.sell_price(MAX_STANDING) > listing.sell_price(0.));
+        assert!(listing.sell_price(HOSTILE_STANDING) < listing.sell_price(0.));
+        assert!(listing.sell_price(MAX_STANDING) < listing.buy_price(MAX_STANDING));
+    }
+
+    /// Ships only buy as much as they can afford.
+    #[test]
+    fn purchases_are_limited_by_credits() {
+        let mut market = food_market();
+        let unit_price = market.listings()[0].buy_price(0.);
+        let mut trader = Trader::new(unit_price * 3.);
+        let mut inventory = Inventory::new(100.);
+
+        let (amount, credits) = market
+            .trade(food(TradeKind::Buy, 10.), 0., &mut trader, &mut inventory)
+            .unwrap();
+
+        assert!((amount - 3.).abs() < 1e-4);
+        assert!((credits - unit_price * 3.).abs() < 1e-3);
+        assert!(trader.credits().abs() < 1e-3);
+        assert_eq!(inventory.amount(Commodity::Food), amount);
+        assert!(market.listings()[0].price() > 10.);
+    }
+
+    /// Ships only buy as much as their hold has room for, and only sell what they carry.
+    #[test]
+    fn trades_are_limited_by_the_hold() {
+        let mut market = food_market();
+        let mut trader = Trader::new(1000.);
+        let mut inventory = Inventory::new(5.);
+
+        let (bought, _) = market
+            .trade(food(TradeKind::Buy, 10.), 0., &mut trader, &mut inventory)
+            .unwrap();
+        assert_eq!(bought, 5.);
+
+        let (sold, earnings) = market
+            .trade(food(TradeKind::Sell, 10.), 0., &mut trader, &mut inventory)
+            .unwrap();
+        assert_eq!(sold, 5.);
+        assert!(earnings > 0.);
+        assert_eq!(inventory.amount(Commodity::Food), 0.);
+    }
+
+    /// Stations refuse to trade with hostile factions, or in commodities they do not list.
+    #[test]
+    fn trades_are_refused_to_enemies_and_unlisted_commodities() {
+        let mut market = food_market();
+        let mut trader = Trader::new(1000.);
+        let mut inventory = Inventory::new(100.);
+
+        let hostile = market.trade(
+            food(TradeKind::Buy, 1.),
+            HOSTILE_STANDING,
+            &mut trader,
+            &mut inventory,
+        );
+        let unlisted = market.trade(
+            TradeOrder {
+                commodity: Commodity::Medicine,
+                ..food(TradeKind::Buy, 1.)
+            },
+            0.,
+            &mut trader,
+            &mut inventory,
+        );
+
+        assert_eq!(hostile, None);
+        assert_eq!(unlisted, None);
+        assert_eq!(trader.credits(), 1000.);
+        assert_eq!(inventory.total(), 0.);
+    }
+}
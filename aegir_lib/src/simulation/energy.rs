@@ -0,0 +1,397 @@
+//! Ship power: a shared energy pool, how it is divided between subsystems, and the shield it feeds.
+
+use bevy::prelude::*;
+
+use super::flight::{Afterburner, FlightSet};
+use super::time_control::SimulationTime;
+
+/// The number of half-pips split between all subsystems.
+const TOTAL_HALF_PIPS: u8 = 12;
+
+/// The most half-pips a single subsystem can hold.
+const MAX_HALF_PIPS: u8 = 8;
+
+/// The number of half-pips each subsystem holds when power is balanced.
+const BALANCED_HALF_PIPS: u8 = TOTAL_HALF_PIPS / 3;
+
+/// How much energy it takes to restore one point of [`Shield`] when power is balanced.
+const SHIELD_ENERGY_COST: f32 = 1.5;
+
+/// Energy logic
+pub(super) struct EnergyPlugin;
+
+impl Plugin for EnergyPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_set(FixedUpdate, EnergySet.before(FlightSet))
+            .add_systems(
+                FixedUpdate,
+                (recharge_energy, drain_afterburners, recharge_shields)
+                    .chain()
+                    .in_set(EnergySet),
+            );
+    }
+}
+
+/// Systems that fill and drain ship [`Energy`].
+///
+/// This runs in [`FixedUpdate`] before [`FlightSet`], so that afterburners requested during
+/// [`Update`] are engaged or refused before ships move.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EnergySet;
+
+/// A pool of energy shared by a ship's engines, weapons and shields.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Energy {
+    /// The energy currently stored.
+    current: f32,
+    /// The most energy that can be stored.
+    capacity: f32,
+    /// How much energy is restored each second.
+    pub recharge_rate: f32,
+}
+
+impl Energy {
+    /// Creates a full energy pool.
+    pub fn new(capacity: f32, recharge_rate: f32) -> Self {
+        Energy {
+            current: capacity,
+            capacity,
+            recharge_rate,
+        }
+    }
+
+    /// The energy currently stored.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// The most energy that can be stored.
+    pub fn capacity(&self) -> f32 {
+        self.capacity
+    }
+
+    /// The fraction of the pool that is full, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        if self.capacity > 0. {
+            self.current / self.capacity
+        } else {
+            0.
+        }
+    }
+
+    /// Removes `amount` from the pool if there is enough stored, returning whether it succeeded.
+    pub fn try_drain(&mut self, amount: f32) -> bool {
+        if amount <= self.current {
+            self.current -= amount;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds `amount` to the pool, up to its capacity.
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.capacity);
+    }
+
+    /// Changes the most energy that can be stored, keeping the pool as full as it was.
+    pub fn set_capacity(&mut self, capacity: f32) {
+        self.current = self.fraction() * capacity;
+        self.capacity = capacity;
+    }
+}
+
+impl Default for Energy {
+    fn default() -> Self {
+        Energy::new(100., 15.)
+    }
+}
+
+/// An energy barrier that soaks up damage before it reaches the hull.
+///
+/// Shields are recharged from the ship's [`Energy`], drawing less of it the more power is diverted
+/// to them.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Shield {
+    /// The damage the shield can still soak up.
+    current: f32,
+    /// The most damage the shield can soak up.
+    capacity: f32,
+    /// How much of the shield is restored each second while there is energy to spare.
+    pub recharge_rate: f32,
+}
+
+impl Shield {
+    /// Creates a fully charged shield.
+    pub fn new(capacity: f32, recharge_rate: f32) -> Self {
+        Shield {
+            current: capacity,
+            capacity,
+            recharge_rate,
+        }
+    }
+
+    /// The damage the shield can still soak up.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// The most damage the shield can soak up.
+    pub fn capacity(&self) -> f32 {
+        self.capacity
+    }
+
+    /// The fraction of the shield remaining, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        if self.capacity > 0. {
+            self.current / self.capacity
+        } else {
+            0.
+        }
+    }
+
+    /// How much the shield is short of full.
+    pub fn missing(&self) -> f32 {
+        self.capacity - self.current
+    }
+
+    /// Soaks up as much of `amount` as the shield can, returning the damage that gets through.
+    pub fn absorb(&mut self, amount: f32) -> f32 {
+        let absorbed = amount.clamp(0., self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+
+    /// Restores `amount` of the shield, up to its capacity.
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.capacity);
+    }
+
+    /// Changes the most damage the shield can soak up, keeping it as charged as it was.
+    pub fn set_capacity(&mut self, capacity: f32) {
+        self.current = self.fraction() * capacity;
+        self.capacity = capacity;
+    }
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Shield::new(50., 5.)
+    }
+}
+
+/// The subsystems that draw from a ship's [`Energy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    /// Afterburners and other propulsion.
+    Engines,
+    /// Anything that fires.
+    Weapons,
+    /// Recharging the [`Shield`].
+    Shields,
+}
+
+impl Subsystem {
+    /// Every subsystem, in display order.
+    pub const ALL: [Subsystem; 3] = [Subsystem::Engines, Subsystem::Weapons, Subsystem::Shields];
+}
+
+/// How a ship's power is split between its subsystems, measured in half-pips.
+///
+/// Subsystems with more pips draw less energy to do the same work.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerDistribution {
+    /// Half-pips devoted to the engines.
+    engines: u8,
+    /// Half-pips devoted to the weapons.
+    weapons: u8,
+    /// Half-pips devoted to the shields.
+    shields: u8,
+}
+
+impl Default for PowerDistribution {
+    fn default() -> Self {
+        PowerDistribution {
+            engines: BALANCED_HALF_PIPS,
+            weapons: BALANCED_HALF_PIPS,
+            shields: BALANCED_HALF_PIPS,
+        }
+    }
+}
+
+impl PowerDistribution {
+    /// The number of half-pips devoted to `subsystem`.
+    pub fn half_pips(&self, subsystem: Subsystem) -> u8 {
+        match subsystem {
+            Subsystem::Engines => self.engines,
+            Subsystem::Weapons => self.weapons,
+            Subsystem::Shields => self.shields,
+        }
+    }
+
+    /// A mutable reference to the half-pips devoted to `subsystem`.
+    fn half_pips_mut(&mut self, subsystem: Subsystem) -> &mut u8 {
+        match subsystem {
+            Subsystem::Engines => &mut self.engines,
+            Subsystem::Weapons => &mut self.weapons,
+            Subsystem::Shields => &mut self.shields,
+        }
+    }
+
+    /// Moves up to one half-pip from each other subsystem into `subsystem`.
+    pub fn divert_to(&mut self, subsystem: Subsystem) {
+        for other in Subsystem::ALL {
+            if other == subsystem || self.half_pips(subsystem) >= MAX_HALF_PIPS {
+                continue;
+            }
+
+            let donor = self.half_pips_mut(other);
+            if *donor > 0 {
+                *donor -= 1;
+                *self.half_pips_mut(subsystem) += 1;
+            }
+        }
+    }
+
+    /// Splits power evenly between every subsystem.
+    pub fn balance(&mut self) {
+        *self = PowerDistribution::default();
+    }
+
+    /// How much the energy drawn by `subsystem` is scaled by.
+    ///
+    /// This is `1.0` when power is balanced, falling to two thirds at maximum power and rising to
+    /// double with no power at all.
+    pub fn cost_multiplier(&self, subsystem: Subsystem) -> f32 {
+        let efficiency = f32::from(self.half_pips(subsystem)) / f32::from(BALANCED_HALF_PIPS);
+        2. / (1. + efficiency)
+    }
+}
+
+/// Restores each ship's [`Energy`] at its recharge rate.
+fn recharge_energy(time: SimulationTime, mut query: Query<&mut Energy>) {
+    let delta_time = time.delta_seconds();
+
+    for mut energy in query.iter_mut() {
+        let recharge = energy.recharge_rate * delta_time;
+        energy.restore(recharge);
+    }
+}
+
+/// Engages requested afterburners while there is enough [`Energy`] to feed them.
+fn drain_afterburners(
+    time: SimulationTime,
+    mut query: Query<(&mut Afterburner, &mut Energy, Option<&PowerDistribution>)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut afterburner, mut energy, power) in query.iter_mut() {
+        let cost_multiplier = power.map_or(1., |power| power.cost_multiplier(Subsystem::Engines));
+        let cost = afterburner.drain_per_second * cost_multiplier * delta_time;
+        afterburner.engaged = afterburner.requested && energy.try_drain(cost);
+    }
+}
+
+/// Recharges each ship's [`Shield`] from its [`Energy`], once its afterburners have been fed.
+fn recharge_shields(
+    time: SimulationTime,
+    mut query: Query<(&mut Shield, &mut Energy, Option<&PowerDistribution>)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut shield, mut energy, power) in query.iter_mut() {
+        let recharge = (shield.recharge_rate * delta_time).min(shield.missing());
+        if recharge <= 0. {
+            continue;
+        }
+
+        let cost_multiplier = power.map_or(1., |power| power.cost_multiplier(Subsystem::Shields));
+        if energy.try_drain(recharge * SHIELD_ENERGY_COST * cost_multiplier) {
+            shield.restore(recharge);
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Diverting power takes a half-pip from each other subsystem.
+    #[test]
+    fn divert_to_takes_from_every_other_subsystem() {
+        let mut power = PowerDistribution::default();
+
+        power.divert_to(Subsystem::Engines);
+
+        assert_eq!(power.half_pips(Subsystem::Engines), BALANCED_HALF_PIPS + 2);
+        assert_eq!(power.half_pips(Subsystem::Weapons), BALANCED_HALF_PIPS - 1);
+        assert_eq!(power.half_pips(Subsystem::Shields), BALANCED_HALF_PIPS - 1);
+    }
+
+    /// Diverting power never exceeds the most a subsystem can hold, nor loses any half-pips.
+    #[test]
+    fn divert_to_stops_at_the_maximum() {
+        let mut power = PowerDistribution::default();
+
+        for _ in 0..10 {
+            power.divert_to(Subsystem::Weapons);
+        }
+
+        assert_eq!(power.half_pips(Subsystem::Weapons), MAX_HALF_PIPS);
+        let total: u8 = Subsystem::ALL
+            .iter()
+            .map(|&subsystem| power.half_pips(subsystem))
+            .sum();
+        assert_eq!(total, TOTAL_HALF_PIPS);
+    }
+
+    /// Subsystems with no power left have nothing more to give.
+    #[test]
+    fn divert_to_skips_empty_subsystems() {
+        let mut power = PowerDistribution::default();
+        for _ in 0..10 {
+            power.divert_to(Subsystem::Weapons);
+        }
+        power.divert_to(Subsystem::Shields);
+        power.divert_to(Subsystem::Shields);
+        assert_eq!(power.half_pips(Subsystem::Engines), 0);
+
+        power.divert_to(Subsystem::Shields);
+
+        assert_eq!(power.half_pips(Subsystem::Engines), 0);
+        assert_eq!(power.half_pips(Subsystem::Weapons), 5);
+        assert_eq!(power.half_pips(Subsystem::Shields), 7);
+    }
+
+    /// Balanced power costs the normal amount, with more power costing less and none costing
+    /// double.
+    #[test]
+    fn cost_multiplier_follows_power() {
+        let mut power = PowerDistribution::default();
+        assert_eq!(power.cost_multiplier(Subsystem::Engines), 1.);
+
+        for _ in 0..10 {
+            power.divert_to(Subsystem::Engines);
+        }
+        assert!((power.cost_multiplier(Subsystem::Engines) - 2. / 3.).abs() < 1e-6);
+        assert!(power.cost_multiplier(Subsystem::Weapons) > 1.);
+
+        for _ in 0..10 {
+            power.divert_to(Subsystem::Shields);
+        }
+        assert_eq!(power.half_pips(Subsystem::Weapons), 0);
+        assert_eq!(power.cost_multiplier(Subsystem::Weapons), 2.);
+    }
+
+    /// Balancing undoes any diversion.
+    #[test]
+    fn balance_restores_the_default() {
+        let mut power = PowerDistribution::default();
+        power.divert_to(Subsystem::Shields);
+
+        power.balance();
+
+        assert_eq!(power, PowerDistribution::default());
+    }
+}
@@ -0,0 +1,197 @@
+//! Factions, and how they regard each other.
+//!
+//! Standings between factions live in the [`Reputation`] matrix, and sour whenever one faction
+//! attacks another that was not already hostile to it.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::debug::console::ConsoleAppExt;
+
+use super::health::{Damaged, HealthSet};
+
+/// The highest standing two factions can have; the lowest is its negative.
+pub const MAX_STANDING: f32 = 100.;
+
+/// Factions at or above this standing are allied.
+pub const ALLIED_STANDING: f32 = 25.;
+
+/// Factions at or below this standing are hostile.
+pub const HOSTILE_STANDING: f32 = -25.;
+
+/// How much standing is lost for each point of damage dealt to a faction that was not hostile.
+pub const STANDING_PER_DAMAGE: f32 = 0.5;
+
+/// Faction logic
+pub(super) struct FactionsPlugin;
+
+impl Plugin for FactionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Reputation>()
+            .add_console_command(
+                "reputation",
+                "reputation <faction> <faction> [standing]",
+                reputation_command,
+            )
+            .add_systems(FixedUpdate, sour_reputation.after(HealthSet));
+    }
+}
+
+/// The side an entity is on.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Faction {
+    /// The Aegir fleet, which players fly for.
+    Aegir,
+    /// Miners, traders and everyone else minding their own business.
+    Independent,
+    /// Raiders who prey on everyone else.
+    Pirate,
+}
+
+impl Faction {
+    /// Every faction, in the order they are stored in the [`Reputation`] matrix.
+    pub const ALL: [Faction; 3] = [Faction::Aegir, Faction::Independent, Faction::Pirate];
+
+    /// The name shown to the player.
+    pub fn name(self) -> &'static str {
+        match self {
+            Faction::Aegir => "Aegir",
+            Faction::Independent => "Independent",
+            Faction::Pirate => "Pirate",
+        }
+    }
+
+    /// This faction's row and column in the [`Reputation`] matrix.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How one faction regards another, derived from their standing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    /// They fight on the same side.
+    Allied,
+    /// They leave each other alone.
+    Neutral,
+    /// They attack each other on sight.
+    Hostile,
+}
+
+/// The standing between every pair of factions, from [`-MAX_STANDING`](MAX_STANDING) to
+/// [`MAX_STANDING`].
+///
+/// Standings are symmetric, and every faction is always allied with itself.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Reputation {
+    /// The standing between each pair of factions, indexed by [`Faction::index`].
+    standings: [[f32; Faction::ALL.len()]; Faction::ALL.len()],
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        let mut reputation = Reputation {
+            standings: [[0.; Faction::ALL.len()]; Faction::ALL.len()],
+        };
+        reputation.set(Faction::Aegir, Faction::Independent, 10.);
+        reputation.set(Faction::Aegir, Faction::Pirate, -MAX_STANDING);
+        reputation.set(Faction::Independent, Faction::Pirate, -60.);
+        reputation
+    }
+}
+
+impl Reputation {
+    /// The standing between `a` and `b`.
+    pub fn standing(&self, a: Faction, b: Faction) -> f32 {
+        if a == b {
+            return MAX_STANDING;
+        }
+        self.standings[a.index()][b.index()]
+    }
+
+    /// How `a` and `b` regard each other.
+    pub fn relation(&self, a: Faction, b: Faction) -> Relation {
+        let standing = self.standing(a, b);
+        if standing >= ALLIED_STANDING {
+            Relation::Allied
+        } else if standing <= HOSTILE_STANDING {
+            Relation::Hostile
+        } else {
+            Relation::Neutral
+        }
+    }
+
+    /// Do `a` and `b` attack each other on sight?
+    pub fn is_hostile(&self, a: Faction, b: Faction) -> bool {
+        self.relation(a, b) == Relation::Hostile
+    }
+
+    /// Sets the standing between `a` and `b`, within the allowed range.
+    ///
+    /// A faction's standing with itself cannot be changed.
+    pub fn set(&mut self, a: Faction, b: Faction, standing: f32) {
+        if a == b {
+            return;
+        }
+        let standing = standing.clamp(-MAX_STANDING, MAX_STANDING);
+        self.standings[a.index()][b.index()] = standing;
+        self.standings[b.index()][a.index()] = standing;
+    }
+
+    /// Raises, or with a negative `change` lowers, the standing between `a` and `b`.
+    pub fn adjust(&mut self, a: Faction, b: Faction, change: f32) {
+        self.set(a, b, self.standing(a, b) + change);
+    }
+}
+
+/// Lowers the standing between factions whenever one damages another it was not hostile to.
+fn sour_reputation(
+    mut damaged: EventReader<Damaged>,
+    mut reputation: ResMut<Reputation>,
+    query: Query<&Faction>,
+) {
+    for event in damaged.iter() {
+        let Some(source) = event.source else {
+            continue;
+        };
+        let (Ok(&attacker), Ok(&victim)) = (query.get(source), query.get(event.target)) else {
+            continue;
+        };
+
+        if attacker != victim && !reputation.is_hostile(attacker, victim) {
+            reputation.adjust(attacker, victim, -event.amount * STANDING_PER_DAMAGE);
+        }
+    }
+}
+
+/// Console command that shows, or with a third argument sets, the standing between two factions.
+fn reputation_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (a, b, standing) = match *arguments {
+        [a, b] => (a, b, None),
+        [a, b, standing] => (a, b, Some(standing)),
+        _ => return Err("expected two factions and an optional standing".to_string()),
+    };
+    let parse_faction = |name: &str| {
+        Faction::ALL
+            .into_iter()
+            .find(|faction| faction.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("there is no faction called `{name}`"))
+    };
+    let (a, b) = (parse_faction(a)?, parse_faction(b)?);
+
+    let mut reputation = world.resource_mut::<Reputation>();
+    if let Some(standing) = standing {
+        let standing = standing
+            .parse::<f32>()
+            .map_err(|_| format!("`{standing}` is not a number"))?;
+        reputation.set(a, b, standing);
+    }
+
+    Ok(format!(
+        "{} and {}: {:.0} ({:?})",
+        a.name(),
+        b.name(),
+        reputation.standing(a, b),
+        reputation.relation(a, b),
+    ))
+}
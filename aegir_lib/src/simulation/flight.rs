@@ -0,0 +1,389 @@
+//! The flight model shared by every ship.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::stations::DockingComputer;
+use super::time_control::SimulationTime;
+
+/// How quickly ships shed their [`GravityDrift`], in meters per second squared.
+const DRIFT_RECOVERY: f32 = 3.;
+
+/// Flight logic
+pub(super) struct FlightPlugin;
+
+impl Plugin for FlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                steer,
+                approach_cruise_speed,
+                apply_gravity,
+                integrate_velocity,
+            )
+                .chain()
+                .in_set(FlightSet),
+        );
+    }
+}
+
+/// Systems that move ships and other bodies through space.
+///
+/// These run in [`FixedUpdate`]; anything that writes [`FlightControls`] or [`Throttle`] should
+/// run before this set, or in [`Update`] to be picked up by the next tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlightSet;
+
+/// How far and in which direction a body moves each second, in world space.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity(pub Vec3);
+
+/// The performance envelope of a ship.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct FlightDynamics {
+    /// The speed reached at full throttle, in meters per second.
+    pub max_speed: f32,
+    /// How quickly the ship can change its velocity, in meters per second squared.
+    pub acceleration: f32,
+    /// How quickly the ship can rotate around each axis, in radians per second.
+    pub turn_rate: f32,
+}
+
+impl Default for FlightDynamics {
+    fn default() -> Self {
+        FlightDynamics {
+            max_speed: 100.,
+            acceleration: 40.,
+            turn_rate: 1.5,
+        }
+    }
+}
+
+/// The rotation a pilot is asking of their ship, with each axis in `-1.0..=1.0`.
+///
+/// Positive values pitch the nose up, yaw it to the left and roll counter-clockwise.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlightControls {
+    /// Rotation around the ship's local X axis.
+    pub pitch: f32,
+    /// Rotation around the ship's local Y axis.
+    pub yaw: f32,
+    /// Rotation around the ship's local Z axis.
+    pub roll: f32,
+}
+
+impl FlightControls {
+    /// Controls that turn a ship at `transform` to face along `direction`, without rolling.
+    ///
+    /// The ship turns at full rate while it is `full_turn_error` radians or more off course, and
+    /// more gently as it comes around.
+    pub fn turn_towards(transform: &Transform, direction: Vec3, full_turn_error: f32) -> Self {
+        // Forward is -Z, so pitching up turns towards +Y and yawing left turns towards -X
+        let local = transform.rotation.inverse() * direction;
+        let pitch_error = local.y.atan2(-local.z);
+        let yaw_error = (-local.x).atan2(-local.z);
+
+        FlightControls {
+            pitch: (pitch_error / full_turn_error).clamp(-1., 1.),
+            yaw: (yaw_error / full_turn_error).clamp(-1., 1.),
+            roll: 0.,
+        }
+    }
+}
+
+/// The fraction of its maximum speed that a ship is trying to cruise at.
+///
+/// Ships accelerate or brake along their nose until they reach this cruise speed.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Throttle(f32);
+
+impl Throttle {
+    /// A closed throttle: the ship will brake to a halt.
+    pub const STOP: Throttle = Throttle(0.);
+
+    /// A fully open throttle: the ship will cruise at its maximum speed.
+    pub const FULL: Throttle = Throttle(1.);
+
+    /// Creates a new throttle setting, clamping `fraction` to `0.0..=1.0`.
+    pub fn new(fraction: f32) -> Self {
+        Throttle(fraction.clamp(0., 1.))
+    }
+
+    /// The throttle setting, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        self.0
+    }
+
+    /// The throttle setting as a percentage, between `0.0` and `100.0`.
+    pub fn percent(&self) -> f32 {
+        self.0 * 100.
+    }
+
+    /// Sets the throttle, clamping `fraction` to `0.0..=1.0`.
+    pub fn set(&mut self, fraction: f32) {
+        *self = Throttle::new(fraction);
+    }
+
+    /// Opens (or closes, when `delta` is negative) the throttle by `delta`.
+    pub fn adjust(&mut self, delta: f32) {
+        self.set(self.0 + delta);
+    }
+
+    /// The speed that a ship with these `dynamics` will cruise at.
+    pub fn cruise_speed(&self, dynamics: &FlightDynamics) -> f32 {
+        self.0 * dynamics.max_speed
+    }
+}
+
+/// An afterburner that multiplies a ship's acceleration and top speed while it burns energy.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Afterburner {
+    /// How much acceleration is multiplied by while engaged.
+    pub acceleration_multiplier: f32,
+    /// How much the maximum speed is multiplied by while engaged.
+    pub speed_multiplier: f32,
+    /// How much energy is drained each second while engaged, before power distribution.
+    pub drain_per_second: f32,
+    /// Is the pilot asking for the afterburner?
+    pub requested: bool,
+    /// Is the afterburner firing?
+    ///
+    /// This is only set when there is enough energy to feed it.
+    pub engaged: bool,
+}
+
+impl Default for Afterburner {
+    fn default() -> Self {
+        Afterburner {
+            acceleration_multiplier: 3.,
+            speed_multiplier: 1.75,
+            drain_per_second: 30.,
+            requested: false,
+            engaged: false,
+        }
+    }
+}
+
+/// A source of gravity, such as a planet or a large station, that pulls on every moving body
+/// within its radius.
+///
+/// The pull follows the inverse-square law, so bodies can be slung around a well or fall into a
+/// decaying orbit. It stops growing at the surface, so nothing is flung away by passing through
+/// the center.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct GravityWell {
+    /// How far from its center the well pulls, in meters.
+    pub radius: f32,
+    /// The radius of the body making the well, in meters.
+    pub surface_radius: f32,
+    /// How strongly the well pulls at its surface, in meters per second squared.
+    pub surface_gravity: f32,
+}
+
+/// The velocity a ship has picked up from [`GravityWell`]s, which its engines cruise relative to
+/// rather than fight.
+///
+/// A ship in a well falls with it whatever its throttle, so it can be slung around the well or
+/// sink into a decaying orbit. Engines work the drift off slowly, at [`DRIFT_RECOVERY`], so a
+/// ship carries what it gained from a slingshot for a while after leaving the well.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct GravityDrift(pub Vec3);
+
+impl GravityWell {
+    /// The acceleration felt by a body `offset` from the well's center, in meters per second
+    /// squared.
+    pub fn acceleration(&self, offset: Vec3) -> Vec3 {
+        let distance = offset.length();
+        if distance > self.radius || distance == 0. {
+            return Vec3::ZERO;
+        }
+
+        let falloff = (self.surface_radius / distance.max(self.surface_radius)).powi(2);
+        -offset / distance * self.surface_gravity * falloff
+    }
+}
+
+/// Rotates ships according to their [`FlightControls`].
+fn steer(
+    time: SimulationTime,
+    mut query: Query<(&mut Transform, &FlightControls, &FlightDynamics)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut transform, controls, dynamics) in query.iter_mut() {
+        let max_rotation = dynamics.turn_rate * delta_time;
+        transform.rotate_local_x(controls.pitch.clamp(-1., 1.) * max_rotation);
+        transform.rotate_local_y(controls.yaw.clamp(-1., 1.) * max_rotation);
+        transform.rotate_local_z(controls.roll.clamp(-1., 1.) * max_rotation);
+    }
+}
+
+/// Accelerates or brakes ships towards the cruise speed set by their [`Throttle`], on top of their
+/// [`GravityDrift`].
+///
+/// Ships with an engaged [`Afterburner`] instead push towards their boosted top speed.
+fn approach_cruise_speed(
+    time: SimulationTime,
+    mut query: Query<(
+        &Transform,
+        &Throttle,
+        &FlightDynamics,
+        Option<&Afterburner>,
+        Option<&mut GravityDrift>,
+        &mut Velocity,
+    )>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (transform, throttle, dynamics, afterburner, drift, mut velocity) in query.iter_mut() {
+        let (speed, acceleration) = match afterburner {
+            Some(afterburner) if afterburner.engaged => (
+                dynamics.max_speed * afterburner.speed_multiplier,
+                dynamics.acceleration * afterburner.acceleration_multiplier,
+            ),
+            _ => (throttle.cruise_speed(dynamics), dynamics.acceleration),
+        };
+
+        let drift = drift.map_or(Vec3::ZERO, |mut drift| {
+            let recovery = drift.0.clamp_length_max(DRIFT_RECOVERY * delta_time);
+            drift.0 -= recovery;
+            drift.0
+        });
+        let target = transform.forward() * speed + drift;
+        let max_change = acceleration * delta_time;
+        velocity.0 += (target - velocity.0).clamp_length_max(max_change);
+    }
+}
+
+/// Pulls every moving body, other than docked ships, towards the [`GravityWell`]s it is inside.
+///
+/// What ships gain is added to their [`GravityDrift`] too, so that their engines do not cancel it
+/// on the next tick.
+fn apply_gravity(
+    time: SimulationTime,
+    wells: Query<(&Transform, &GravityWell)>,
+    mut bodies: Query<
+        (
+            &Transform,
+            &mut Velocity,
+            Option<&mut GravityDrift>,
+            Option<&DockingComputer>,
+        ),
+        Without<GravityWell>,
+    >,
+) {
+    if wells.is_empty() {
+        return;
+    }
+    let delta_time = time.delta_seconds();
+
+    for (transform, mut velocity, drift, docking) in bodies.iter_mut() {
+        if docking.is_some_and(|computer| computer.docked().is_some()) {
+            continue;
+        }
+        let acceleration: Vec3 = wells
+            .iter()
+            .map(|(well_transform, well)| {
+                well.acceleration(transform.translation - well_transform.translation)
+            })
+            .sum();
+        velocity.0 += acceleration * delta_time;
+        if let Some(mut drift) = drift {
+            drift.0 += acceleration * delta_time;
+        }
+    }
+}
+
+/// Moves every body according to its [`Velocity`].
+fn integrate_velocity(time: SimulationTime, mut query: Query<(&mut Transform, &Velocity)>) {
+    let delta_time = time.delta_seconds();
+
+    for (mut transform, velocity) in query.iter_mut() {
+        transform.translation += velocity.0 * delta_time;
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings outside the throttle's range are clamped into it.
+    #[test]
+    fn set_clamps_to_the_throttle_range() {
+        let mut throttle = Throttle::STOP;
+
+        throttle.set(1.5);
+        assert_eq!(throttle, Throttle::FULL);
+
+        throttle.set(-0.5);
+        assert_eq!(throttle, Throttle::STOP);
+
+        throttle.set(0.25);
+        assert_eq!(throttle.fraction(), 0.25);
+    }
+
+    /// Adjustments open and close the throttle, stopping at either end.
+    #[test]
+    fn adjust_stops_at_either_end() {
+        let mut throttle = Throttle::new(0.5);
+
+        throttle.adjust(0.25);
+        assert_eq!(throttle.fraction(), 0.75);
+
+        throttle.adjust(0.5);
+        assert_eq!(throttle, Throttle::FULL);
+
+        throttle.adjust(-0.25);
+        assert_eq!(throttle.fraction(), 0.75);
+
+        throttle.adjust(-2.);
+        assert_eq!(throttle, Throttle::STOP);
+    }
+
+    /// A well pulling at 10 m/s² at the surface of a 100 m body, out to 1 km.
+    fn well() -> GravityWell {
+        GravityWell {
+            radius: 1000.,
+            surface_radius: 100.,
+            surface_gravity: 10.,
+        }
+    }
+
+    /// Wells pull towards their center.
+    #[test]
+    fn acceleration_points_at_the_center() {
+        let acceleration = well().acceleration(Vec3::new(0., 300., 0.));
+
+        assert!(acceleration.y < 0.);
+        assert_eq!(acceleration.x, 0.);
+        assert_eq!(acceleration.z, 0.);
+    }
+
+    /// The pull falls off with the square of the distance from the center.
+    #[test]
+    fn acceleration_follows_the_inverse_square_law() {
+        let at_surface = well().acceleration(Vec3::new(100., 0., 0.));
+        let twice_as_far = well().acceleration(Vec3::new(200., 0., 0.));
+
+        assert_eq!(at_surface.length(), 10.);
+        assert_eq!(twice_as_far.length(), 2.5);
+    }
+
+    /// The pull stops growing beneath the surface, and vanishes at the very center.
+    #[test]
+    fn acceleration_is_capped_at_the_surface() {
+        let below_surface = well().acceleration(Vec3::new(0., 0., 10.));
+
+        assert_eq!(below_surface.length(), 10.);
+        assert_eq!(well().acceleration(Vec3::ZERO), Vec3::ZERO);
+    }
+
+    /// Nothing outside the well's radius is pulled.
+    #[test]
+    fn acceleration_is_zero_outside_the_radius() {
+        assert_eq!(well().acceleration(Vec3::new(1001., 0., 0.)), Vec3::ZERO);
+        assert_ne!(well().acceleration(Vec3::new(999., 0., 0.)), Vec3::ZERO);
+    }
+}
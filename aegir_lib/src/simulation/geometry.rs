@@ -0,0 +1,39 @@
+//! Geometric queries shared by the simulation.
+
+use bevy::prelude::{Component, Vec3};
+
+/// A sphere, centered on an entity's translation, that shots and beams collide with.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    /// The radius of the sphere, in meters.
+    pub radius: f32,
+}
+
+/// How far along a ray it first touches a sphere, if it does.
+///
+/// `direction` must be normalized. Rays starting inside the sphere hit it immediately.
+pub fn ray_sphere_distance(
+    origin: Vec3,
+    direction: Vec3,
+    center: Vec3,
+    radius: f32,
+) -> Option<f32> {
+    let to_center = center - origin;
+    let distance_squared = to_center.length_squared();
+    let radius_squared = radius * radius;
+    if distance_squared <= radius_squared {
+        return Some(0.);
+    }
+
+    let closest_approach = to_center.dot(direction);
+    if closest_approach < 0. {
+        return None;
+    }
+
+    let miss_squared = distance_squared - closest_approach * closest_approach;
+    if miss_squared > radius_squared {
+        return None;
+    }
+
+    Some(closest_approach - (radius_squared - miss_squared).sqrt())
+}
@@ -0,0 +1,188 @@
+//! Hull integrity, and what happens when it runs out.
+
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::player::ship::PlayerShip;
+
+use super::energy::Shield;
+use super::flight::FlightSet;
+use super::SimulationAppExt;
+
+/// The fraction of health below which an entity is critically damaged.
+pub const CRITICAL_HEALTH: f32 = 0.3;
+
+/// Health logic
+pub(super) struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<Damaged>()
+            .add_simulation_event::<Destroyed>()
+            .add_console_command("god", "god", god_command)
+            .configure_set(FixedUpdate, HealthSet.after(FlightSet))
+            .add_systems(
+                FixedUpdate,
+                (apply_damage, despawn_destroyed).chain().in_set(HealthSet),
+            );
+    }
+}
+
+/// Systems that apply [`Damaged`] events and clean up what they destroy.
+///
+/// This runs in [`FixedUpdate`] after [`FlightSet`], so anything that deals damage during a tick
+/// should run before it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HealthSet;
+
+/// How much damage an entity can take before it is destroyed.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    /// The health remaining.
+    current: f32,
+    /// The most health the entity can have.
+    max: f32,
+}
+
+impl Health {
+    /// Creates an undamaged entity's health.
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+
+    /// The health remaining.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// The most health the entity can have.
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// The fraction of health remaining, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0. {
+            self.current / self.max
+        } else {
+            0.
+        }
+    }
+
+    /// Is the entity below [`CRITICAL_HEALTH`], but not yet destroyed?
+    pub fn is_critical(&self) -> bool {
+        !self.is_depleted() && self.fraction() < CRITICAL_HEALTH
+    }
+
+    /// Has all of the health been lost?
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.
+    }
+
+    /// Removes `amount` of health, stopping at zero.
+    pub fn damage(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.);
+    }
+
+    /// Restores `amount` of health, up to the maximum.
+    pub fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    /// Changes the most health the entity can have, keeping the same fraction of it remaining.
+    pub fn set_max(&mut self, max: f32) {
+        self.current = self.fraction() * max;
+        self.max = max;
+    }
+}
+
+/// Marks an entity that ignores all damage.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Invulnerable;
+
+/// Something has hurt an entity.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Damaged {
+    /// The entity that was hurt.
+    pub target: Entity,
+    /// Whoever is responsible, if anyone.
+    pub source: Option<Entity>,
+    /// How much health to remove.
+    pub amount: f32,
+    /// Where the damage landed, in world space.
+    pub position: Vec3,
+}
+
+/// An entity's [`Health`] has run out, and it will be despawned at the end of the tick.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Destroyed {
+    /// The entity that was destroyed.
+    pub entity: Entity,
+    /// Whoever dealt the final blow, if anyone.
+    pub killer: Option<Entity>,
+    /// Where the entity was, in world space.
+    pub position: Vec3,
+}
+
+/// Removes health from damaged entities, reporting those that have none left.
+///
+/// Damage is soaked up by the entity's [`Shield`] first, if it has one, and only what gets
+/// through reaches its health. [`Invulnerable`] entities are left untouched.
+fn apply_damage(
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventWriter<Destroyed>,
+    mut query: Query<(&mut Health, Option<&mut Shield>, &Transform), Without<Invulnerable>>,
+) {
+    for event in damaged.iter() {
+        let Ok((mut health, shield, transform)) = query.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_depleted() {
+            continue;
+        }
+
+        let amount = match shield {
+            Some(mut shield) => shield.absorb(event.amount),
+            None => event.amount,
+        };
+        health.damage(amount);
+        if health.is_depleted() {
+            destroyed.send(Destroyed {
+                entity: event.target,
+                killer: event.source,
+                position: transform.translation,
+            });
+        }
+    }
+}
+
+/// Despawns entities that have been destroyed.
+fn despawn_destroyed(mut commands: Commands, mut destroyed: EventReader<Destroyed>) {
+    for event in destroyed.iter() {
+        if let Some(entity) = commands.get_entity(event.entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+/// Console command that makes the player's ship [`Invulnerable`], or mortal again.
+fn god_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    if !arguments.is_empty() {
+        return Err("expected no arguments".to_string());
+    }
+
+    let mut query = world.query_filtered::<(Entity, Option<&Invulnerable>), With<PlayerShip>>();
+    let (ship, invulnerable) = query
+        .get_single(world)
+        .map(|(ship, invulnerable)| (ship, invulnerable.is_some()))
+        .map_err(|_| "there is no player ship".to_string())?;
+
+    let mut ship = world.entity_mut(ship);
+    if invulnerable {
+        ship.remove::<Invulnerable>();
+        Ok("god mode off".to_string())
+    } else {
+        ship.insert(Invulnerable);
+        Ok("god mode on".to_string())
+    }
+}
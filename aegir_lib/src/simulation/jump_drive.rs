@@ -0,0 +1,270 @@
+//! Jump drives, which carry the player's ship to another sector.
+//!
+//! A jump begins with a charge-up, during which any damage to the ship interrupts it. Once
+//! charged, the ship enters the jump tunnel and the destination sector is spawned in place of the
+//! current one, the tunnel lasting at least long enough to hide the swap.
+
+use bevy::prelude::*;
+
+use crate::player::ship::PlayerShip;
+
+use super::flight::{Throttle, Velocity};
+use super::health::{Damaged, HealthSet};
+use super::sector::{CurrentSector, LoadSectorEvent, SectorDefinition, SectorLoaded};
+use super::stations::DockingComputer;
+use super::time_control::SimulationTime;
+use super::SimulationAppExt;
+
+/// Jump drive logic
+pub(super) struct JumpDrivePlugin;
+
+impl Plugin for JumpDrivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<JumpStarted>()
+            .add_simulation_event::<JumpInterrupted>()
+            .add_simulation_event::<JumpCompleted>()
+            .add_systems(
+                FixedUpdate,
+                (begin_jumps, charge_jump_drives, travel_through_tunnels)
+                    .chain()
+                    .after(HealthSet),
+            );
+    }
+}
+
+/// What a jump drive is doing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum JumpState {
+    /// Waiting to be used.
+    #[default]
+    Idle,
+    /// Charging up to jump, which damage interrupts.
+    Charging {
+        /// The sector being jumped to.
+        destination: Handle<SectorDefinition>,
+        /// How long the drive has been charging, in seconds.
+        elapsed: f32,
+    },
+    /// Travelling through the jump tunnel.
+    Jumping {
+        /// The sector being jumped to.
+        destination: Handle<SectorDefinition>,
+        /// How long the ship has been in the tunnel, in seconds.
+        elapsed: f32,
+        /// Has the destination been spawned yet?
+        arrived: bool,
+    },
+}
+
+/// Lets a ship jump to another sector.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct JumpDrive {
+    /// How long the drive takes to charge, in seconds.
+    pub charge_time: f32,
+    /// The shortest time the ship spends in the jump tunnel, in seconds.
+    pub tunnel_time: f32,
+    /// The sector the pilot has asked to jump to, or to cancel a charging jump if `None`, until
+    /// the drive reads the request.
+    pub requested: Option<Option<Handle<SectorDefinition>>>,
+    /// What the drive is doing.
+    state: JumpState,
+}
+
+impl Default for JumpDrive {
+    fn default() -> Self {
+        JumpDrive {
+            charge_time: 6.,
+            tunnel_time: 2.5,
+            requested: None,
+            state: JumpState::Idle,
+        }
+    }
+}
+
+impl JumpDrive {
+    /// What the drive is doing.
+    pub fn state(&self) -> &JumpState {
+        &self.state
+    }
+
+    /// How far through charging the drive is, between `0.0` and `1.0`, or `None` if it is not
+    /// charging.
+    pub fn charge(&self) -> Option<f32> {
+        match self.state {
+            JumpState::Charging { elapsed, .. } => Some((elapsed / self.charge_time).min(1.)),
+            _ => None,
+        }
+    }
+
+    /// Is the ship in the jump tunnel?
+    pub fn is_jumping(&self) -> bool {
+        matches!(self.state, JumpState::Jumping { .. })
+    }
+}
+
+/// A ship's jump drive has finished charging, and the ship has entered the jump tunnel.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct JumpStarted {
+    /// The ship that jumped.
+    pub ship: Entity,
+    /// The sector it is jumping to.
+    pub destination: Handle<SectorDefinition>,
+}
+
+/// Why a jump stopped before the ship entered the tunnel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JumpInterruption {
+    /// The pilot cancelled it.
+    Cancelled,
+    /// The ship was hit while charging.
+    Damaged,
+}
+
+/// A ship's jump drive stopped charging before it could jump.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JumpInterrupted {
+    /// The ship whose jump was interrupted.
+    pub ship: Entity,
+    /// Why it stopped.
+    pub reason: JumpInterruption,
+}
+
+/// A ship has left the jump tunnel in its destination sector.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct JumpCompleted {
+    /// The ship that arrived.
+    pub ship: Entity,
+}
+
+/// Starts charging drives whose pilots have asked to jump, and cancels those asked to stop.
+///
+/// Docked ships, and ships asked to jump to the sector they are already in, stay put.
+fn begin_jumps(
+    current: Res<CurrentSector>,
+    mut query: Query<(Entity, &mut JumpDrive, Option<&DockingComputer>)>,
+    mut interrupted: EventWriter<JumpInterrupted>,
+) {
+    for (ship, mut drive, docking) in query.iter_mut() {
+        let Some(request) = drive.requested.take() else {
+            continue;
+        };
+
+        match (request, &drive.state) {
+            (None, JumpState::Charging { .. }) => {
+                drive.state = JumpState::Idle;
+                interrupted.send(JumpInterrupted {
+                    ship,
+                    reason: JumpInterruption::Cancelled,
+                });
+            }
+            (Some(destination), JumpState::Idle) => {
+                let docked = docking.is_some_and(|computer| computer.docked().is_some());
+                if docked || current.sector() == Some(&destination) {
+                    continue;
+                }
+                drive.state = JumpState::Charging {
+                    destination,
+                    elapsed: 0.,
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Charges jump drives, interrupting those whose ships are hit, and sends fully charged ships into
+/// the tunnel.
+fn charge_jump_drives(
+    time: SimulationTime,
+    mut damaged: EventReader<Damaged>,
+    mut query: Query<(Entity, &mut JumpDrive, Option<&PlayerShip>)>,
+    mut interrupted: EventWriter<JumpInterrupted>,
+    mut started: EventWriter<JumpStarted>,
+    mut load_sector: EventWriter<LoadSectorEvent>,
+) {
+    let delta_time = time.delta_seconds();
+    let hit: Vec<Entity> = damaged
+        .iter()
+        .filter(|event| event.amount > 0.)
+        .map(|event| event.target)
+        .collect();
+
+    for (ship, mut drive, player) in query.iter_mut() {
+        let is_player = player.is_some();
+        let charge_time = drive.charge_time;
+        let JumpState::Charging {
+            destination,
+            elapsed,
+        } = &mut drive.state
+        else {
+            continue;
+        };
+
+        if hit.contains(&ship) {
+            drive.state = JumpState::Idle;
+            interrupted.send(JumpInterrupted {
+                ship,
+                reason: JumpInterruption::Damaged,
+            });
+            continue;
+        }
+
+        *elapsed += delta_time;
+        if *elapsed < charge_time {
+            continue;
+        }
+
+        let destination = destination.clone();
+        // Only the player's jump moves the world; anyone else in the sector is left behind
+        if is_player {
+            load_sector.send(LoadSectorEvent {
+                sector: destination.clone(),
+                spawn_point: None,
+            });
+        }
+        started.send(JumpStarted {
+            ship,
+            destination: destination.clone(),
+        });
+        drive.state = JumpState::Jumping {
+            destination,
+            elapsed: 0.,
+            arrived: !is_player,
+        };
+    }
+}
+
+/// Holds ships still in the jump tunnel until their destination has been spawned and the tunnel
+/// has lasted long enough.
+fn travel_through_tunnels(
+    time: SimulationTime,
+    mut loaded: EventReader<SectorLoaded>,
+    mut query: Query<(Entity, &mut JumpDrive, &mut Velocity, &mut Throttle)>,
+    mut completed: EventWriter<JumpCompleted>,
+) {
+    let delta_time = time.delta_seconds();
+    let loaded: Vec<Handle<SectorDefinition>> =
+        loaded.iter().map(|event| event.sector.clone()).collect();
+
+    for (ship, mut drive, mut velocity, mut throttle) in query.iter_mut() {
+        let tunnel_time = drive.tunnel_time;
+        let JumpState::Jumping {
+            destination,
+            elapsed,
+            arrived,
+        } = &mut drive.state
+        else {
+            continue;
+        };
+
+        velocity.0 = Vec3::ZERO;
+        *throttle = Throttle::STOP;
+        *elapsed += delta_time;
+        *arrived |= loaded.contains(destination);
+
+        if *arrived && *elapsed >= tunnel_time {
+            drive.state = JumpState::Idle;
+            completed.send(JumpCompleted { ship });
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Mining lasers, which cut ore out of asteroids and into a ship's cargo hold.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+
+use crate::game_state::InGame;
+
+use super::asteroids::{Asteroid, OreDeposit, OreType};
+use super::economy::Commodity;
+use super::flight::{FlightSet, Velocity};
+use super::geometry::{ray_sphere_distance, Collider};
+use super::random::{RngStream, WorldRng};
+use super::time_control::SimulationTime;
+use super::tractor::{Grabbable, Tethered};
+use super::SimulationAppExt;
+
+/// How often a mining laser knocks a chunk of debris off the asteroid it is cutting, in seconds.
+const DEBRIS_INTERVAL: f32 = 0.2;
+
+/// How long chunks of debris drift before they are cleaned up, in seconds.
+const DEBRIS_LIFETIME: f32 = 3.;
+
+/// How fast chunks of debris fly away from the asteroid, in meters per second.
+const DEBRIS_SPEED: f32 = 8.;
+
+/// Mining logic
+pub(super) struct MiningPlugin;
+
+impl Plugin for MiningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<OreMined>().add_systems(
+            FixedUpdate,
+            (mine_asteroids, age_debris).chain().after(FlightSet),
+        );
+    }
+}
+
+/// A laser that cuts ore out of asteroids directly ahead of the ship.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct MiningLaser {
+    /// How far the laser reaches, in meters.
+    pub range: f32,
+    /// How many units of ore the laser extracts each second.
+    pub rate: f32,
+    /// Is the pilot firing the laser?
+    pub firing: bool,
+    /// Where the beam ends this tick, if the laser is firing.
+    pub beam_end: Option<Vec3>,
+    /// Counts down to the next chunk of debris.
+    debris_timer: Timer,
+}
+
+impl Default for MiningLaser {
+    fn default() -> Self {
+        MiningLaser {
+            range: 150.,
+            rate: 2.,
+            firing: false,
+            beam_end: None,
+            debris_timer: Timer::from_seconds(DEBRIS_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// The ore and other commodities carried by a ship.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Inventory {
+    /// The most cargo that can be carried, in units.
+    pub capacity: f32,
+    /// How many units of each commodity are being carried.
+    contents: HashMap<Commodity, f32>,
+}
+
+impl Inventory {
+    /// Creates an empty hold that can carry `capacity` units of cargo.
+    pub fn new(capacity: f32) -> Self {
+        Inventory {
+            capacity,
+            contents: HashMap::default(),
+        }
+    }
+
+    /// How many units of `commodity` are being carried.
+    pub fn amount(&self, commodity: impl Into<Commodity>) -> f32 {
+        self.contents
+            .get(&commodity.into())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// How many units of cargo are being carried in total.
+    pub fn total(&self) -> f32 {
+        self.contents.values().sum()
+    }
+
+    /// How many more units of cargo can be carried.
+    pub fn free_space(&self) -> f32 {
+        (self.capacity - self.total()).max(0.)
+    }
+
+    /// Stores as much of `amount` units of `commodity` as will fit, returning how much was stored.
+    pub fn add(&mut self, commodity: impl Into<Commodity>, amount: f32) -> f32 {
+        let stored = amount.min(self.free_space());
+        *self.contents.entry(commodity.into()).or_default() += stored;
+        stored
+    }
+
+    /// Removes up to `amount` units of `commodity`, returning how much was removed.
+    pub fn remove(&mut self, commodity: impl Into<Commodity>, amount: f32) -> f32 {
+        let held = self.contents.entry(commodity.into()).or_default();
+        let removed = amount.min(*held);
+        *held -= removed;
+        removed
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory::new(100.)
+    }
+}
+
+/// Ore has been moved from an asteroid into a ship's [`Inventory`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct OreMined {
+    /// The ship that mined the ore.
+    pub miner: Entity,
+    /// The asteroid the ore came from.
+    pub asteroid: Entity,
+    /// The kind of ore.
+    pub ore: OreType,
+    /// How many units were mined.
+    pub amount: f32,
+}
+
+/// A chunk of rock knocked off an asteroid, which drifts away and vanishes.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Debris {
+    /// Counts down until the chunk is cleaned up.
+    lifetime: Timer,
+}
+
+/// Everything needed to spawn a chunk of [`Debris`].
+#[derive(Bundle, Debug)]
+pub struct DebrisBundle {
+    /// The chunk itself.
+    pub debris: Debris,
+    /// How it drifts.
+    pub velocity: Velocity,
+    /// What tractor beams grab.
+    pub collider: Collider,
+    /// Lets tractor beams grab it.
+    pub grabbable: Grabbable,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl DebrisBundle {
+    /// Creates a chunk of debris at `position`, drifting at `velocity`.
+    pub fn new(position: Vec3, velocity: Vec3) -> Self {
+        DebrisBundle {
+            debris: Debris {
+                lifetime: Timer::from_seconds(DEBRIS_LIFETIME, TimerMode::Once),
+            },
+            velocity: Velocity(velocity),
+            collider: Collider { radius: 0.4 },
+            grabbable: Grabbable { mass: 0.5 },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
+
+/// Cuts ore out of the nearest asteroid ahead of each firing laser.
+fn mine_asteroids(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut world_rng: ResMut<WorldRng>,
+    mut miners: Query<(Entity, &Transform, &mut MiningLaser, &mut Inventory)>,
+    mut asteroids: Query<(Entity, &Transform, &Asteroid, &mut OreDeposit)>,
+    mut ore_mined: EventWriter<OreMined>,
+) {
+    let delta_time = time.delta_seconds();
+    let rng = world_rng.stream(RngStream::Mining);
+
+    for (miner, transform, mut laser, mut inventory) in miners.iter_mut() {
+        if !laser.firing {
+            laser.beam_end = None;
+            continue;
+        }
+
+        let origin = transform.translation;
+        let direction = transform.forward();
+        let hit = asteroids
+            .iter_mut()
+            .filter_map(|(entity, asteroid_transform, asteroid, deposit)| {
+                ray_sphere_distance(
+                    origin,
+                    direction,
+                    asteroid_transform.translation,
+                    asteroid.radius,
+                )
+                .filter(|&distance| distance <= laser.range)
+                .map(|distance| (entity, distance, deposit))
+            })
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        let Some((asteroid, distance, mut deposit)) = hit else {
+            laser.beam_end = Some(origin + direction * laser.range);
+            continue;
+        };
+
+        let impact = origin + direction * distance;
+        laser.beam_end = Some(impact);
+
+        let cut = (laser.rate * delta_time).min(deposit.quantity);
+        let stored = inventory.add(deposit.ore, cut);
+        if stored <= 0. {
+            continue;
+        }
+
+        deposit.quantity -= stored;
+        ore_mined.send(OreMined {
+            miner,
+            asteroid,
+            ore: deposit.ore,
+            amount: stored,
+        });
+
+        if laser.debris_timer.tick(time.delta()).just_finished() {
+            let scatter = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            let velocity = (-direction + scatter).normalize_or_zero() * DEBRIS_SPEED;
+
+            commands.spawn(DebrisBundle::new(impact, velocity));
+        }
+    }
+}
+
+/// Cleans up debris that has drifted for long enough, unless a tractor beam is holding it.
+fn age_debris(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut query: Query<(Entity, &mut Debris), Without<Tethered>>,
+) {
+    for (entity, mut debris) in query.iter_mut() {
+        if debris.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cargo is stored and counted per commodity.
+    #[test]
+    fn add_stores_each_commodity_separately() {
+        let mut inventory = Inventory::new(100.);
+
+        assert_eq!(inventory.add(OreType::Iron, 30.), 30.);
+        assert_eq!(inventory.add(Commodity::Food, 20.), 20.);
+
+        assert_eq!(inventory.amount(OreType::Iron), 30.);
+        assert_eq!(inventory.amount(Commodity::Food), 20.);
+        assert_eq!(inventory.amount(OreType::Platinum), 0.);
+        assert_eq!(inventory.total(), 50.);
+        assert_eq!(inventory.free_space(), 50.);
+    }
+
+    /// Only as much cargo as fits is stored.
+    #[test]
+    fn add_stops_when_the_hold_is_full() {
+        let mut inventory = Inventory::new(50.);
+        inventory.add(OreType::Iron, 40.);
+
+        assert_eq!(inventory.add(OreType::Nickel, 25.), 10.);
+        assert_eq!(inventory.free_space(), 0.);
+        assert_eq!(inventory.add(OreType::Ice, 5.), 0.);
+        assert_eq!(inventory.total(), 50.);
+    }
+
+    /// No more cargo can be removed than is carried.
+    #[test]
+    fn remove_takes_no_more_than_is_carried() {
+        let mut inventory = Inventory::new(100.);
+        inventory.add(OreType::Ice, 15.);
+
+        assert_eq!(inventory.remove(OreType::Ice, 10.), 10.);
+        assert_eq!(inventory.remove(OreType::Ice, 10.), 5.);
+        assert_eq!(inventory.remove(OreType::Platinum, 10.), 0.);
+        assert_eq!(inventory.total(), 0.);
+    }
+
+    /// A hold shrunk below its cargo keeps the cargo but has no room for more.
+    #[test]
+    fn free_space_is_never_negative() {
+        let mut inventory = Inventory::new(100.);
+        inventory.add(OreType::Iron, 80.);
+
+        inventory.capacity = 50.;
+
+        assert_eq!(inventory.amount(OreType::Iron), 80.);
+        assert_eq!(inventory.free_space(), 0.);
+        assert_eq!(inventory.add(OreType::Iron, 10.), 0.);
+    }
+}
@@ -0,0 +1,996 @@
+//! Missions: a graph of objectives, loaded from `.mission.ron` files in the `missions` asset
+//! folder.
+//!
+//! Each objective unlocks once every objective it requires is complete, and progresses in
+//! response to events from the rest of the simulation, or to the player's own actions. Objectives
+//! that teach the controls, with prompts and targets that appear as they unlock, make up the
+//! tutorial. Objectives can also be left to [scripts](super::scripting), which settle them with a
+//! [`SetObjective`] event.
+
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use serde::{Deserialize, Serialize};
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::{GameState, InGame};
+use crate::player::input::{ActionState, FlightAction};
+use crate::player::ship::PlayerShip;
+use crate::player::targeting::Targetable;
+
+use super::factions::Faction;
+use super::flight::Velocity;
+use super::geometry::Collider;
+use super::health::{Destroyed, Health, HealthSet};
+use super::navigation::{Waypoint, WaypointBundle, WaypointReached};
+use super::ron_asset::RonAssetLoader;
+use super::stations::{ShipDocked, Station};
+use super::time_control::SimulationTime;
+use super::SimulationAppExt;
+
+/// The asset folder that mission definitions are loaded from.
+const MISSIONS_FOLDER: &str = "missions";
+
+/// Mission logic
+pub(super) struct MissionsPlugin;
+
+impl Plugin for MissionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<MissionDefinition>()
+            .add_asset_loader(RonAssetLoader::<MissionDefinition>::new(&["mission.ron"]))
+            .init_resource::<MissionLibrary>()
+            .init_resource::<MissionSelection>()
+            .add_simulation_event::<StartMission>()
+            .add_simulation_event::<ObjectiveCompleted>()
+            .add_simulation_event::<MissionEnded>()
+            .add_simulation_event::<SetObjective>()
+            .add_console_command("mission", "mission <name>", mission_command)
+            .add_systems(OnEnter(GameState::Playing), start_selected_mission)
+            .add_systems(OnExit(GameState::Playing), end_mission)
+            .add_systems(
+                Update,
+                (sort_mission_library, begin_missions, refit_active_mission),
+            )
+            .add_systems(
+                FixedUpdate,
+                (set_objectives, track_objectives).chain().after(HealthSet),
+            );
+    }
+}
+
+/// A mission, as loaded from a `.mission.ron` file.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "c4a1f7e3-0b92-4d6a-b8e5-71f3a2d9c604"]
+pub struct MissionDefinition {
+    /// The name shown to the player.
+    pub name: String,
+    /// What the player is told before the mission starts.
+    #[serde(default)]
+    pub briefing: String,
+    /// Waypoints placed when the mission starts.
+    #[serde(default)]
+    pub waypoints: Vec<MissionWaypoint>,
+    /// Targets placed when the mission starts.
+    #[serde(default)]
+    pub targets: Vec<TargetGroup>,
+    /// What the player must do.
+    pub objectives: Vec<ObjectiveDefinition>,
+}
+
+/// A waypoint placed for a mission.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MissionWaypoint {
+    /// The waypoint's name, which objectives refer to it by.
+    pub name: String,
+    /// Where the waypoint is, in world space.
+    pub position: [f32; 3],
+}
+
+/// A cluster of target drones placed for a mission.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TargetGroup {
+    /// A tag given to every drone in the group, which objectives refer to them by.
+    pub tag: String,
+    /// Where the middle of the group is, in world space.
+    pub position: [f32; 3],
+    /// How many drones there are.
+    pub count: u32,
+    /// How far apart neighbouring drones are, in meters.
+    #[serde(default = "TargetGroup::default_spacing")]
+    pub spacing: f32,
+    /// How much damage each drone can take.
+    #[serde(default = "TargetGroup::default_health")]
+    pub health: f32,
+}
+
+impl TargetGroup {
+    /// The spacing used when a definition does not give one.
+    fn default_spacing() -> f32 {
+        20.
+    }
+
+    /// The health used when a definition does not give one.
+    fn default_health() -> f32 {
+        30.
+    }
+}
+
+/// One node of a mission's objective graph.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ObjectiveDefinition {
+    /// A name other objectives can require this one by.
+    pub id: String,
+    /// What the HUD tells the player to do.
+    pub description: String,
+    /// Instructions shown in the middle of the screen while the objective is active, where
+    /// `{Action}` is replaced by the inputs bound to that [`FlightAction`].
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Targets placed when the objective begins.
+    #[serde(default)]
+    pub targets: Vec<TargetGroup>,
+    /// The ids of objectives that must be complete before this one begins.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// How long the player has, once the objective begins, before it fails, in seconds.
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+    /// Can the mission be completed without this objective?
+    #[serde(default)]
+    pub optional: bool,
+    /// Does completing this objective let the player respawn where they completed it, rather
+    /// than failing the mission when they are destroyed?
+    #[serde(default)]
+    pub checkpoint: bool,
+    /// What completes the objective.
+    pub goal: Goal,
+}
+
+/// What completes an objective.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Goal {
+    /// Fly to the named waypoint.
+    ReachWaypoint {
+        /// The waypoint's name.
+        waypoint: String,
+    },
+    /// Destroy this many targets, counting only those with the tag if one is given.
+    Destroy {
+        /// How many targets must be destroyed.
+        count: u32,
+        /// The tag targets must have to count.
+        #[serde(default)]
+        tag: Option<String>,
+    },
+    /// Stay alive for this long.
+    Survive {
+        /// How long, in seconds.
+        seconds: f32,
+    },
+    /// Dock with the named station.
+    Dock {
+        /// The station's name.
+        station: String,
+    },
+    /// Perform any of these actions, holding them for this long in total.
+    Perform {
+        /// The actions that count.
+        actions: Vec<FlightAction>,
+        /// How long they must be held, in seconds, or `0.0` for a single press.
+        #[serde(default)]
+        seconds: f32,
+    },
+    /// Left to a script, which completes or fails the objective with a [`SetObjective`] event.
+    Scripted,
+}
+
+/// Every mission that can be flown, in the order they are offered to the player.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MissionLibrary {
+    /// Handles to each mission, sorted by their asset path.
+    missions: Vec<Handle<MissionDefinition>>,
+}
+
+impl MissionLibrary {
+    /// Handles to each mission, in the order they are offered to the player.
+    pub fn missions(&self) -> &[Handle<MissionDefinition>] {
+        &self.missions
+    }
+}
+
+impl FromWorld for MissionLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let missions = match asset_server.load_folder(MISSIONS_FOLDER) {
+            Ok(handles) => handles.into_iter().map(HandleUntyped::typed).collect(),
+            Err(error) => {
+                warn!("Could not load mission definitions: {error}");
+                Vec::new()
+            }
+        };
+
+        MissionLibrary { missions }
+    }
+}
+
+/// The mission to start when play begins, by its position in the [`MissionLibrary`], or `None`
+/// to fly freely.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissionSelection(pub Option<usize>);
+
+impl MissionSelection {
+    /// Selects the next of `mission_count` missions, then free flight, then the first again.
+    pub fn cycle(&mut self, mission_count: usize) {
+        self.0 = match self.0 {
+            None if mission_count > 0 => Some(0),
+            Some(index) if index + 1 < mission_count => Some(index + 1),
+            _ => None,
+        };
+    }
+}
+
+/// Asks for a mission to begin, replacing any mission in progress.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct StartMission(pub Handle<MissionDefinition>);
+
+/// How far through a mission the player is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissionStatus {
+    /// Objectives remain.
+    InProgress,
+    /// Every required objective is complete.
+    Complete,
+    /// A required objective failed, or the player was destroyed.
+    Failed,
+}
+
+/// How far through an objective the player is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectiveState {
+    /// Waiting on the objectives it requires.
+    Locked,
+    /// Under way.
+    Active,
+    /// Done.
+    Complete,
+    /// Ran out of time, or can no longer be done.
+    Failed,
+}
+
+/// The progress of one objective of the [`ActiveMission`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectiveProgress {
+    /// How far through the objective the player is.
+    pub state: ObjectiveState,
+    /// How many targets have been destroyed towards the objective.
+    pub destroyed: u32,
+    /// How long the objective has been active, in seconds.
+    pub elapsed: f32,
+    /// How long the actions of a [`Goal::Perform`] have been held, in seconds.
+    pub performed: f32,
+}
+
+impl Default for ObjectiveProgress {
+    /// A locked objective with nothing done towards it.
+    fn default() -> Self {
+        ObjectiveProgress {
+            state: ObjectiveState::Locked,
+            destroyed: 0,
+            elapsed: 0.,
+            performed: 0.,
+        }
+    }
+}
+
+/// The mission being flown.
+#[derive(Resource, Debug, Clone)]
+pub struct ActiveMission {
+    /// The mission being flown.
+    pub definition: Handle<MissionDefinition>,
+    /// The progress of each objective, in the order they are defined.
+    objectives: Vec<ObjectiveProgress>,
+    /// How far through the mission the player is.
+    status: MissionStatus,
+}
+
+impl ActiveMission {
+    /// The progress of each objective, in the order they are defined.
+    pub fn objectives(&self) -> &[ObjectiveProgress] {
+        &self.objectives
+    }
+
+    /// How far through the mission the player is.
+    pub fn status(&self) -> MissionStatus {
+        self.status
+    }
+
+    /// Advances the objectives of the mission's `definition` through one `tick`.
+    fn advance(&mut self, definition: &MissionDefinition, tick: &MissionTick) -> MissionChanges {
+        let mut changes = MissionChanges::default();
+        if self.status != MissionStatus::InProgress {
+            return changes;
+        }
+
+        let checkpoint_reached =
+            definition
+                .objectives
+                .iter()
+                .zip(&self.objectives)
+                .any(|(objective, progress)| {
+                    objective.checkpoint && progress.state == ObjectiveState::Complete
+                });
+        if tick.player_destroyed {
+            if checkpoint_reached {
+                // The player starts whatever they were doing over again once they respawn
+                for progress in &mut self.objectives {
+                    if progress.state == ObjectiveState::Active {
+                        progress.elapsed = 0.;
+                    }
+                }
+            } else {
+                self.status = MissionStatus::Failed;
+                changes.ended = Some(MissionStatus::Failed);
+                return changes;
+            }
+        }
+
+        for (index, objective) in definition.objectives.iter().enumerate() {
+            let unlocked = objective.requires.iter().all(|required| {
+                definition
+                    .objectives
+                    .iter()
+                    .position(|other| &other.id == required)
+                    .and_then(|other| self.objectives.get(other))
+                    .is_some_and(|other| other.state == ObjectiveState::Complete)
+            });
+            let Some(progress) = self.objectives.get_mut(index) else {
+                break;
+            };
+            if progress.state == ObjectiveState::Locked && unlocked {
+                progress.state = ObjectiveState::Active;
+                changes.unlocked.push(index);
+            }
+
+            if progress.state != ObjectiveState::Active {
+                continue;
+            }
+            progress.elapsed += tick.delta_time;
+
+            let done = match &objective.goal {
+                Goal::ReachWaypoint { waypoint } => tick.reached.contains(&waypoint.as_str()),
+                Goal::Destroy { count, tag } => {
+                    progress.destroyed += tick
+                        .kills
+                        .iter()
+                        .filter(|kill| match tag {
+                            Some(tag) => kill.tag == Some(tag.as_str()),
+                            None => kill.targetable,
+                        })
+                        .count() as u32;
+                    progress.destroyed >= *count
+                }
+                Goal::Survive { seconds } => progress.elapsed >= *seconds,
+                Goal::Dock { station } => tick.docked.contains(&station.as_str()),
+                Goal::Perform { actions, seconds } => {
+                    let held = actions
+                        .iter()
+                        .any(|action| tick.performing.contains(action));
+                    if held {
+                        progress.performed += tick.delta_time;
+                    }
+                    held && progress.performed >= *seconds
+                }
+                Goal::Scripted => false,
+            };
+
+            if done {
+                progress.state = ObjectiveState::Complete;
+                changes.completed.push(index);
+            } else if objective
+                .time_limit
+                .is_some_and(|limit| progress.elapsed > limit)
+            {
+                progress.state = ObjectiveState::Failed;
+            }
+        }
+
+        let required = || {
+            definition
+                .objectives
+                .iter()
+                .zip(&self.objectives)
+                .filter(|(objective, _)| !objective.optional)
+        };
+        let status = if required().any(|(_, progress)| progress.state == ObjectiveState::Failed) {
+            MissionStatus::Failed
+        } else if required().all(|(_, progress)| progress.state == ObjectiveState::Complete) {
+            MissionStatus::Complete
+        } else {
+            MissionStatus::InProgress
+        };
+
+        if status != MissionStatus::InProgress {
+            self.status = status;
+            changes.ended = Some(status);
+        }
+
+        changes
+    }
+}
+
+/// What happened during one tick that the active mission's objectives can progress on.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct MissionTick<'a> {
+    /// How long the tick lasted, in seconds.
+    delta_time: f32,
+    /// Was the player's ship destroyed?
+    player_destroyed: bool,
+    /// The entities the player destroyed.
+    kills: Vec<Kill<'a>>,
+    /// The names of the waypoints the player reached.
+    reached: Vec<&'a str>,
+    /// The names of the stations the player docked with.
+    docked: Vec<&'a str>,
+    /// The actions the player was performing.
+    performing: Vec<FlightAction>,
+}
+
+/// An entity the player destroyed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Kill<'a> {
+    /// The entity's [`MissionTag`], if it had one.
+    tag: Option<&'a str>,
+    /// Could the entity be targeted?
+    targetable: bool,
+}
+
+/// How one tick changed the active mission.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct MissionChanges {
+    /// The objectives that unlocked, whose targets should be placed.
+    unlocked: Vec<usize>,
+    /// The objectives that were completed.
+    completed: Vec<usize>,
+    /// How the mission ended, if it did.
+    ended: Option<MissionStatus>,
+}
+
+/// An objective of the [`ActiveMission`] has been completed.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectiveCompleted {
+    /// The objective's position in its mission's definition.
+    pub index: usize,
+}
+
+/// Asks for an objective of the [`ActiveMission`] to be unlocked, completed or failed, whatever
+/// its goal.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SetObjective {
+    /// The objective's id.
+    pub id: String,
+    /// What to set it to; setting it back to [`ObjectiveState::Locked`] is ignored.
+    pub state: ObjectiveState,
+}
+
+/// The [`ActiveMission`] has been completed or failed.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissionEnded(pub MissionStatus);
+
+/// Marks entities that mission objectives can refer to by tag.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct MissionTag(pub String);
+
+/// A stationary drone for the player to shoot at.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct TargetDrone;
+
+/// Everything needed to spawn a [`TargetDrone`].
+#[derive(Bundle, Debug)]
+pub struct TargetDroneBundle {
+    /// The drone itself.
+    pub drone: TargetDrone,
+    /// What the kill feed calls it.
+    pub name: Name,
+    /// How mission objectives refer to it.
+    pub tag: MissionTag,
+    /// How much damage it can take.
+    pub health: Health,
+    /// What shots hit.
+    pub collider: Collider,
+    /// Lets the player target it.
+    pub targetable: Targetable,
+    /// Who it flies for.
+    pub faction: Faction,
+    /// It does not move, but can be matched speed with.
+    pub velocity: Velocity,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl TargetDroneBundle {
+    /// Creates a drone tagged `tag` at `position`.
+    pub fn new(tag: impl Into<String>, position: Vec3, health: f32) -> Self {
+        TargetDroneBundle {
+            drone: TargetDrone,
+            name: Name::new("Target drone"),
+            tag: MissionTag(tag.into()),
+            health: Health::new(health),
+            collider: Collider { radius: 2. },
+            targetable: Targetable,
+            faction: Faction::Pirate,
+            velocity: Velocity::default(),
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
+
+/// Keeps the library in a stable order as mission definitions finish loading.
+fn sort_mission_library(
+    mut events: EventReader<AssetEvent<MissionDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<MissionLibrary>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+
+    library.missions.sort_by_cached_key(|handle| {
+        asset_server
+            .get_handle_path(handle)
+            .map(|path| path.path().to_path_buf())
+    });
+}
+
+/// Starts the mission chosen before play began, if any.
+fn start_selected_mission(
+    selection: Res<MissionSelection>,
+    library: Res<MissionLibrary>,
+    mut start: EventWriter<StartMission>,
+) {
+    if let Some(handle) = selection.0.and_then(|index| library.missions().get(index)) {
+        start.send(StartMission(handle.clone()));
+    }
+}
+
+/// Forgets the mission in progress when play ends.
+fn end_mission(mut commands: Commands) {
+    commands.remove_resource::<ActiveMission>();
+}
+
+/// Places each requested mission's waypoints and targets and begins tracking its objectives.
+///
+/// Missions can be asked for outside of ticks, from the console and when play begins, so this runs
+/// every frame rather than in [`FixedUpdate`].
+fn begin_missions(
+    mut commands: Commands,
+    mut start: EventReader<StartMission>,
+    definitions: Res<Assets<MissionDefinition>>,
+) {
+    for StartMission(handle) in start.iter() {
+        let Some(definition) = definitions.get(handle) else {
+            warn!("Cannot start a mission that has not loaded");
+            continue;
+        };
+
+        for waypoint in &definition.waypoints {
+            commands.spawn(WaypointBundle::new(
+                waypoint.name.clone(),
+                Vec3::from_array(waypoint.position),
+            ));
+        }
+
+        for group in &definition.targets {
+            spawn_target_group(&mut commands, group);
+        }
+
+        commands.insert_resource(ActiveMission {
+            definition: handle.clone(),
+            objectives: vec![ObjectiveProgress::default(); definition.objectives.len()],
+            status: MissionStatus::InProgress,
+        });
+    }
+}
+
+/// Spawns the drones of a target `group`, laid out in a square grid around its position.
+fn spawn_target_group(commands: &mut Commands, group: &TargetGroup) {
+    let columns = (group.count as f32).sqrt().ceil().max(1.) as u32;
+    let center = Vec3::from_array(group.position);
+    let half_width = (columns - 1) as f32 * group.spacing / 2.;
+    for index in 0..group.count {
+        let offset = Vec3::new(
+            (index % columns) as f32 * group.spacing - half_width,
+            0.,
+            (index / columns) as f32 * group.spacing - half_width,
+        );
+        commands.spawn(TargetDroneBundle::new(
+            group.tag.clone(),
+            center + offset,
+            group.health,
+        ));
+    }
+}
+
+/// Sets the state of objectives of the active mission on request.
+///
+/// Objectives unlocked this way place their targets as usual. Whether the mission is over is left
+/// for [`track_objectives`] to work out.
+fn set_objectives(
+    mut commands: Commands,
+    mut requests: EventReader<SetObjective>,
+    mission: Option<ResMut<ActiveMission>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    mut completed: EventWriter<ObjectiveCompleted>,
+) {
+    let Some(mut mission) = mission else {
+        requests.clear();
+        return;
+    };
+    let Some(definition) = definitions.get(&mission.definition) else {
+        return;
+    };
+
+    for SetObjective { id, state } in requests.iter() {
+        let Some(index) = definition
+            .objectives
+            .iter()
+            .position(|objective| &objective.id == id)
+        else {
+            warn!("The mission has no objective called `{id}`");
+            continue;
+        };
+        if mission.status != MissionStatus::InProgress || *state == ObjectiveState::Locked {
+            continue;
+        }
+        let Some(progress) = mission.objectives.get_mut(index) else {
+            continue;
+        };
+        if progress.state == *state {
+            continue;
+        }
+
+        if progress.state == ObjectiveState::Locked {
+            for group in &definition.objectives[index].targets {
+                spawn_target_group(&mut commands, group);
+            }
+        }
+        progress.state = *state;
+        if *state == ObjectiveState::Complete {
+            completed.send(ObjectiveCompleted { index });
+        }
+    }
+}
+
+/// Unlocks, advances, completes and fails the active mission's objectives.
+///
+/// Without the player's [`ActionState`], as on a dedicated server, no actions are ever performed.
+#[allow(clippy::too_many_arguments)]
+fn track_objectives(
+    mut commands: Commands,
+    time: SimulationTime,
+    action_state: Option<Res<ActionState<FlightAction>>>,
+    mission: Option<ResMut<ActiveMission>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    mut destroyed: EventReader<Destroyed>,
+    mut reached: EventReader<WaypointReached>,
+    mut docked: EventReader<ShipDocked>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    tag_query: Query<&MissionTag>,
+    targetable_query: Query<(), With<Targetable>>,
+    waypoint_query: Query<&Waypoint>,
+    station_query: Query<&Station>,
+    mut completed: EventWriter<ObjectiveCompleted>,
+    mut ended: EventWriter<MissionEnded>,
+) {
+    let player = player_query.get_single().ok();
+    let is_player = |entity: Entity| Some(entity) == player;
+
+    let destroyed: Vec<Destroyed> = destroyed.iter().copied().collect();
+    let tick = MissionTick {
+        delta_time: time.delta_seconds(),
+        player_destroyed: destroyed.iter().any(|event| is_player(event.entity)),
+        kills: destroyed
+            .iter()
+            .filter(|event| event.killer.is_some_and(is_player))
+            .map(|event| Kill {
+                tag: tag_query.get(event.entity).ok().map(|tag| tag.0.as_str()),
+                targetable: targetable_query.contains(event.entity),
+            })
+            .collect(),
+        reached: reached
+            .iter()
+            .filter(|event| is_player(event.ship))
+            .filter_map(|event| waypoint_query.get(event.waypoint).ok())
+            .map(|waypoint| waypoint.name.as_str())
+            .collect(),
+        docked: docked
+            .iter()
+            .filter(|event| is_player(event.ship))
+            .filter_map(|event| station_query.get(event.station).ok())
+            .map(|station| station.name.as_str())
+            .collect(),
+        performing: action_state
+            .map(|action_state| action_state.pressed_actions().collect())
+            .unwrap_or_default(),
+    };
+
+    let Some(mut mission) = mission else {
+        return;
+    };
+    let Some(definition) = definitions.get(&mission.definition) else {
+        return;
+    };
+
+    let changes = mission.advance(definition, &tick);
+    for &index in &changes.unlocked {
+        for group in &definition.objectives[index].targets {
+            spawn_target_group(&mut commands, group);
+        }
+    }
+    completed.send_batch(
+        changes
+            .completed
+            .iter()
+            .map(|&index| ObjectiveCompleted { index }),
+    );
+    if let Some(status) = changes.ended {
+        ended.send(MissionEnded(status));
+    }
+}
+
+/// Fits the active mission's progress to its definition when the definition is hot-reloaded.
+///
+/// Objectives keep their progress by position. Any added are locked until what they require is
+/// complete, and any removed are forgotten.
+fn refit_active_mission(
+    mut events: EventReader<AssetEvent<MissionDefinition>>,
+    definitions: Res<Assets<MissionDefinition>>,
+    mission: Option<ResMut<ActiveMission>>,
+) {
+    let Some(mut mission) = mission else {
+        events.clear();
+        return;
+    };
+
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        if *handle != mission.definition {
+            continue;
+        }
+        let Some(definition) = definitions.get(handle) else {
+            continue;
+        };
+
+        let count = definition.objectives.len();
+        mission
+            .objectives
+            .resize(count, ObjectiveProgress::default());
+        info!(
+            "Refitted the active mission to the changed {}",
+            definition.name
+        );
+    }
+}
+
+/// Console command that starts the mission with the given name.
+fn mission_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    if arguments.is_empty() {
+        return Err("expected a mission name".to_string());
+    }
+    let name = arguments.join(" ");
+
+    let library = world.resource::<MissionLibrary>();
+    let definitions = world.resource::<Assets<MissionDefinition>>();
+    let handle = library
+        .missions()
+        .iter()
+        .find(|handle| {
+            definitions
+                .get(handle)
+                .is_some_and(|definition| definition.name.eq_ignore_ascii_case(&name))
+        })
+        .cloned()
+        .ok_or_else(|| format!("there is no mission called `{name}`"))?;
+
+    world.send_event(StartMission(handle));
+
+    Ok(format!("starting {name}"))
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A required objective called `id` that begins once the objectives in `requires` are complete.
+    fn objective(id: &str, requires: &[&str], goal: Goal) -> ObjectiveDefinition {
+        ObjectiveDefinition {
+            id: id.to_string(),
+            description: String::new(),
+            prompt: None,
+            targets: Vec::new(),
+            requires: requires
+                .iter()
+                .map(|required| required.to_string())
+                .collect(),
+            time_limit: None,
+            optional: false,
+            checkpoint: false,
+            goal,
+        }
+    }
+
+    /// A mission made of `objectives`, and a fresh start at flying it.
+    fn mission(objectives: Vec<ObjectiveDefinition>) -> (MissionDefinition, ActiveMission) {
+        let active = ActiveMission {
+            definition: Handle::default(),
+            objectives: vec![ObjectiveProgress::default(); objectives.len()],
+            status: MissionStatus::InProgress,
+        };
+        let definition = MissionDefinition {
+            name: "Test".to_string(),
+            briefing: String::new(),
+            waypoints: Vec::new(),
+            targets: Vec::new(),
+            objectives,
+        };
+        (definition, active)
+    }
+
+    /// A tick of `seconds` in which nothing happens.
+    fn wait(seconds: f32) -> MissionTick<'static> {
+        MissionTick {
+            delta_time: seconds,
+            ..default()
+        }
+    }
+
+    /// The state of each objective of the `mission`.
+    fn states(mission: &ActiveMission) -> Vec<ObjectiveState> {
+        mission
+            .objectives()
+            .iter()
+            .map(|progress| progress.state)
+            .collect()
+    }
+
+    /// Objectives stay locked until everything they require is complete.
+    #[test]
+    fn objectives_unlock_once_requirements_complete() {
+        let (definition, mut active) = mission(vec![
+            objective("first", &[], Goal::Survive { seconds: 1. }),
+            objective("second", &["first"], Goal::Survive { seconds: 1. }),
+        ]);
+
+        let changes = active.advance(&definition, &wait(0.5));
+        assert_eq!(changes.unlocked, vec![0]);
+        assert_eq!(
+            states(&active),
+            vec![ObjectiveState::Active, ObjectiveState::Locked]
+        );
+
+        let changes = active.advance(&definition, &wait(0.5));
+        assert_eq!(changes.completed, vec![0]);
+        assert_eq!(changes.unlocked, vec![1]);
+        assert_eq!(
+            states(&active),
+            vec![ObjectiveState::Complete, ObjectiveState::Active]
+        );
+        assert_eq!(active.status(), MissionStatus::InProgress);
+    }
+
+    /// Tagged destroy objectives only count the player's kills of entities with the tag.
+    #[test]
+    fn destroy_objectives_count_tagged_kills() {
+        let goal = Goal::Destroy {
+            count: 2,
+            tag: Some("drone".to_string()),
+        };
+        let (definition, mut active) = mission(vec![objective("destroy", &[], goal)]);
+        let drone = Kill {
+            tag: Some("drone"),
+            targetable: true,
+        };
+
+        let tick = MissionTick {
+            kills: vec![
+                drone,
+                Kill {
+                    tag: Some("cargo"),
+                    targetable: true,
+                },
+                Kill {
+                    tag: None,
+                    targetable: true,
+                },
+            ],
+            ..wait(0.1)
+        };
+        active.advance(&definition, &tick);
+        assert_eq!(active.objectives()[0].destroyed, 1);
+        assert_eq!(active.objectives()[0].state, ObjectiveState::Active);
+
+        let tick = MissionTick {
+            kills: vec![drone],
+            ..wait(0.1)
+        };
+        let changes = active.advance(&definition, &tick);
+        assert_eq!(active.objectives()[0].state, ObjectiveState::Complete);
+        assert_eq!(changes.ended, Some(MissionStatus::Complete));
+    }
+
+    /// A required objective that runs out of time fails, and fails the mission with it.
+    #[test]
+    fn time_limits_fail_objectives() {
+        let (definition, mut active) = mission(vec![ObjectiveDefinition {
+            time_limit: Some(5.),
+            ..objective("reach", &[], Goal::Scripted)
+        }]);
+
+        active.advance(&definition, &wait(4.));
+        assert_eq!(active.status(), MissionStatus::InProgress);
+
+        let changes = active.advance(&definition, &wait(2.));
+        assert_eq!(states(&active), vec![ObjectiveState::Failed]);
+        assert_eq!(changes.ended, Some(MissionStatus::Failed));
+        assert_eq!(
+            active.advance(&definition, &wait(1.)),
+            MissionChanges::default()
+        );
+    }
+
+    /// Optional objectives neither hold the mission back nor fail it.
+    #[test]
+    fn optional_objectives_are_not_required() {
+        let (definition, mut active) = mission(vec![
+            objective("main", &[], Goal::Survive { seconds: 2. }),
+            ObjectiveDefinition {
+                time_limit: Some(1.),
+                optional: true,
+                ..objective("bonus", &[], Goal::Scripted)
+            },
+        ]);
+
+        active.advance(&definition, &wait(1.5));
+        assert_eq!(active.objectives()[1].state, ObjectiveState::Failed);
+        assert_eq!(active.status(), MissionStatus::InProgress);
+
+        let changes = active.advance(&definition, &wait(1.));
+        assert_eq!(changes.ended, Some(MissionStatus::Complete));
+    }
+
+    /// Being destroyed fails the mission, unless a checkpoint has been reached, in which case
+    /// active objectives start over.
+    #[test]
+    fn checkpoints_survive_the_player_being_destroyed() {
+        let destroyed = MissionTick {
+            player_destroyed: true,
+            ..wait(0.1)
+        };
+        let (definition, mut active) = mission(vec![
+            ObjectiveDefinition {
+                checkpoint: true,
+                ..objective("checkpoint", &[], Goal::Survive { seconds: 1. })
+            },
+            objective("after", &["checkpoint"], Goal::Survive { seconds: 10. }),
+        ]);
+
+        active.advance(&definition, &wait(1.));
+        active.advance(&definition, &wait(3.));
+        assert_eq!(active.advance(&definition, &destroyed).ended, None);
+        assert_eq!(active.objectives()[1].state, ObjectiveState::Active);
+        assert_eq!(active.objectives()[1].elapsed, 0.1);
+
+        let (definition, mut active) = mission(vec![
+            objective("first", &[], Goal::Survive { seconds: 1. }),
+            objective("second", &["first"], Goal::Survive { seconds: 10. }),
+        ]);
+        active.advance(&definition, &wait(1.));
+        let changes = active.advance(&definition, &destroyed);
+        assert_eq!(changes.ended, Some(MissionStatus::Failed));
+        assert_eq!(active.status(), MissionStatus::Failed);
+    }
+}
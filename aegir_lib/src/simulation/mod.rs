@@ -1,3 +1,138 @@
-/// Code to run game logic. 
-/// 
-/// This should not contain logic to render and should be able to work without a render pipeline. 
\ No newline at end of file
+//! Code to run game logic.
+//!
+//! This should not contain logic to render and should be able to work without a render pipeline.
+use bevy::prelude::*;
+
+pub mod ai;
+pub mod asteroids;
+pub mod countermeasures;
+pub mod deployables;
+pub mod economy;
+pub mod energy;
+pub mod factions;
+pub mod flight;
+pub mod geometry;
+pub mod health;
+pub mod jump_drive;
+pub mod mining;
+pub mod missions;
+pub mod navigation;
+pub mod pickups;
+pub mod planets;
+pub mod random;
+pub mod ron_asset;
+pub mod scripting;
+pub mod sector;
+pub mod ships;
+pub mod spatial;
+pub mod stations;
+pub mod time_control;
+pub mod tractor;
+pub mod waves;
+pub mod weapons;
+pub mod wingmen;
+
+/// How many times each second the simulation advances.
+pub const TICK_RATE: f32 = 60.;
+
+/// Adds the game logic that runs regardless of whether the world is being rendered.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(1. / TICK_RATE))
+            .init_resource::<WorldSeed>()
+            // Tuples of plugins can only hold fifteen, so they are added in two
+            .add_plugins((
+                ai::AiPlugin,
+                asteroids::AsteroidPlugin,
+                countermeasures::CountermeasuresPlugin,
+                deployables::DeployablesPlugin,
+                economy::EconomyPlugin,
+                energy::EnergyPlugin,
+                factions::FactionsPlugin,
+                flight::FlightPlugin,
+                health::HealthPlugin,
+                jump_drive::JumpDrivePlugin,
+                mining::MiningPlugin,
+            ))
+            .add_plugins((
+                missions::MissionsPlugin,
+                navigation::NavigationPlugin,
+                pickups::PickupsPlugin,
+                random::RandomPlugin,
+                scripting::ScriptingPlugin,
+                sector::SectorPlugin,
+                ships::ShipsPlugin,
+                spatial::SpatialPlugin,
+                stations::StationsPlugin,
+                time_control::TimeControlPlugin,
+                tractor::TractorPlugin,
+                waves::WavesPlugin,
+                weapons::WeaponsPlugin,
+                wingmen::WingmenPlugin,
+            ));
+    }
+}
+
+/// Adds events that the simulation sends or reads to an [`App`].
+pub trait SimulationAppExt {
+    /// Adds the event `T`, keeping each one until at least one tick has run since it was sent.
+    ///
+    /// Events added with [`App::add_event`] are dropped two frames after they are sent, and at
+    /// high frame rates two frames can pass without a tick, so systems in [`FixedUpdate`] would
+    /// miss them.
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl SimulationAppExt for App {
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<TickedSinceEventUpdate>() {
+            self.init_resource::<TickedSinceEventUpdate>()
+                .configure_set(
+                    First,
+                    SimulationEventUpdates.run_if(resource_equals(TickedSinceEventUpdate(true))),
+                )
+                .add_systems(First, clear_tick_signal.after(SimulationEventUpdates))
+                .add_systems(FixedUpdate, signal_tick);
+        }
+        if !self.world.contains_resource::<Events<T>>() {
+            self.init_resource::<Events<T>>().add_systems(
+                First,
+                Events::<T>::update_system.in_set(SimulationEventUpdates),
+            );
+        }
+        self
+    }
+}
+
+/// Has a tick run since the simulation's events were last updated?
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TickedSinceEventUpdate(bool);
+
+/// The systems that update the simulation's events, which only run once a tick has passed.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct SimulationEventUpdates;
+
+/// Notes that a tick has run, so that events sent before it can be dropped.
+fn signal_tick(mut ticked: ResMut<TickedSinceEventUpdate>) {
+    ticked.0 = true;
+}
+
+/// Waits for the next tick before the simulation's events are updated again.
+fn clear_tick_signal(mut ticked: ResMut<TickedSinceEventUpdate>) {
+    ticked.0 = false;
+}
+
+/// The seed that procedural generation draws from, so that a world can be reproduced.
+///
+/// This is chosen at random on startup unless inserted beforehand. Each subsystem draws from its
+/// own stream of it, through [`WorldSeed::rng`] or the [`WorldRng`](random::WorldRng).
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorldSeed(pub u64);
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        WorldSeed(rand::random())
+    }
+}
@@ -0,0 +1,242 @@
+//! Waypoints, and an autopilot that flies ships to them.
+
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::InGame;
+
+use super::energy::EnergySet;
+use super::flight::{FlightControls, FlightDynamics, Throttle};
+use super::SimulationAppExt;
+
+/// How many radians of heading error make the autopilot turn at full rate.
+const FULL_TURN_ERROR: f32 = 0.5;
+
+/// How closely the autopilot must face its waypoint before it opens the throttle, as a dot product.
+const ALIGNED: f32 = 0.95;
+
+/// Navigation logic
+pub(super) struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<WaypointReached>()
+            .add_console_command("waypoint", "waypoint <name> <x> <y> <z>", waypoint_command)
+            .configure_set(FixedUpdate, NavigationSet.before(EnergySet))
+            .add_systems(
+                FixedUpdate,
+                (clear_missing_waypoints, detect_arrivals, fly_autopilots)
+                    .chain()
+                    .in_set(NavigationSet),
+            );
+    }
+}
+
+/// Systems that fly ships on autopilot.
+///
+/// This runs in [`FixedUpdate`] before [`EnergySet`], and so before ships move, overriding any
+/// [`FlightControls`] and [`Throttle`] written earlier in the tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NavigationSet;
+
+/// A named point in space that ships can navigate to.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    /// The name shown on the HUD.
+    pub name: String,
+    /// How close a ship must come to reach the waypoint, in meters.
+    pub radius: f32,
+}
+
+/// Everything needed to spawn a waypoint.
+#[derive(Bundle, Debug)]
+pub struct WaypointBundle {
+    /// The waypoint itself.
+    pub waypoint: Waypoint,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl WaypointBundle {
+    /// Creates a waypoint called `name` at `position`.
+    pub fn new(name: impl Into<String>, position: Vec3) -> Self {
+        WaypointBundle {
+            waypoint: Waypoint {
+                name: name.into(),
+                radius: 50.,
+            },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
+
+/// Which waypoint a ship is navigating to, and whether it is flying there by itself.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Autopilot {
+    /// The selected waypoint, if any.
+    waypoint: Option<Entity>,
+    /// Is the autopilot flying the ship?
+    engaged: bool,
+    /// How close to the waypoint counts as arriving, in meters.
+    pub arrival_distance: f32,
+    /// The waypoint the ship is within reach of, if any.
+    within_reach: Option<Entity>,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Autopilot {
+            waypoint: None,
+            engaged: false,
+            arrival_distance: 50.,
+            within_reach: None,
+        }
+    }
+}
+
+impl Autopilot {
+    /// The selected waypoint, if any.
+    pub fn waypoint(&self) -> Option<Entity> {
+        self.waypoint
+    }
+
+    /// Selects `waypoint`, or clears the selection and disengages when `None`.
+    pub fn select(&mut self, waypoint: Option<Entity>) {
+        self.waypoint = waypoint;
+        self.engaged &= waypoint.is_some();
+    }
+
+    /// Is the autopilot flying the ship?
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Starts flying to the selected waypoint, returning whether there is one.
+    pub fn engage(&mut self) -> bool {
+        self.engaged = self.waypoint.is_some();
+        self.engaged
+    }
+
+    /// Hands control back to the pilot.
+    pub fn disengage(&mut self) {
+        self.engaged = false;
+    }
+}
+
+/// Deselects waypoints that have been despawned.
+fn clear_missing_waypoints(
+    mut autopilots: Query<&mut Autopilot>,
+    waypoints: Query<(), With<Waypoint>>,
+) {
+    for mut autopilot in autopilots.iter_mut() {
+        if let Some(waypoint) = autopilot.waypoint {
+            if !waypoints.contains(waypoint) {
+                autopilot.select(None);
+            }
+        }
+    }
+}
+
+/// A ship has come within reach of a [`Waypoint`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaypointReached {
+    /// The ship that arrived.
+    pub ship: Entity,
+    /// The waypoint it reached.
+    pub waypoint: Entity,
+}
+
+/// Reports ships coming within reach of a waypoint, once for each visit.
+fn detect_arrivals(
+    mut ships: Query<(Entity, &Transform, &mut Autopilot)>,
+    waypoints: Query<(Entity, &Transform, &Waypoint)>,
+    mut reached: EventWriter<WaypointReached>,
+) {
+    for (ship, transform, mut autopilot) in ships.iter_mut() {
+        let within_reach = waypoints
+            .iter()
+            .find(|(_, waypoint_transform, waypoint)| {
+                waypoint_transform
+                    .translation
+                    .distance(transform.translation)
+                    <= waypoint.radius
+            })
+            .map(|(waypoint, ..)| waypoint);
+
+        if within_reach != autopilot.within_reach {
+            autopilot.within_reach = within_reach;
+            if let Some(waypoint) = within_reach {
+                reached.send(WaypointReached { ship, waypoint });
+            }
+        }
+    }
+}
+
+/// Turns engaged autopilots' ships towards their waypoints and cruises there, braking to arrive.
+fn fly_autopilots(
+    mut ships: Query<(
+        &mut Autopilot,
+        &Transform,
+        &FlightDynamics,
+        &mut FlightControls,
+        &mut Throttle,
+    )>,
+    waypoints: Query<&Transform, With<Waypoint>>,
+) {
+    for (mut autopilot, transform, dynamics, mut controls, mut throttle) in ships.iter_mut() {
+        if !autopilot.engaged {
+            continue;
+        }
+        let Some(destination) = autopilot
+            .waypoint
+            .and_then(|waypoint| waypoints.get(waypoint).ok())
+        else {
+            autopilot.disengage();
+            continue;
+        };
+
+        let offset = destination.translation - transform.translation;
+        let distance = offset.length();
+        if distance <= autopilot.arrival_distance {
+            autopilot.disengage();
+            *controls = FlightControls::default();
+            *throttle = Throttle::STOP;
+            continue;
+        }
+
+        *controls = FlightControls::turn_towards(transform, offset, FULL_TURN_ERROR);
+
+        let alignment = transform.forward().dot(offset / distance);
+        if alignment < ALIGNED {
+            // Slow down while turning, so as not to swing wide of the waypoint
+            throttle.set(throttle.fraction().min(0.25));
+            continue;
+        }
+
+        // Cruise no faster than the ship can brake from before reaching the waypoint
+        let braking_distance = distance - autopilot.arrival_distance / 2.;
+        let stopping_speed = (2. * dynamics.acceleration * braking_distance).sqrt();
+        let speed = stopping_speed.min(dynamics.max_speed);
+        throttle.set(speed / dynamics.max_speed);
+    }
+}
+
+/// Console command that places a waypoint at the given coordinates.
+fn waypoint_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let [name, x, y, z] = *arguments else {
+        return Err("expected a name and three coordinates".to_string());
+    };
+    let parse = |coordinate: &str| {
+        coordinate
+            .parse::<f32>()
+            .map_err(|_| format!("`{coordinate}` is not a number"))
+    };
+    let position = Vec3::new(parse(x)?, parse(y)?, parse(z)?);
+
+    world.spawn(WaypointBundle::new(name, position));
+
+    Ok(format!("placed waypoint {name} at {position}"))
+}
@@ -0,0 +1,181 @@
+//! Loose ore floating in space, and the magnets that pull it into ships' holds.
+
+use bevy::prelude::*;
+
+use crate::game_state::InGame;
+
+use super::asteroids::OreType;
+use super::energy::EnergySet;
+use super::flight::{FlightSet, Velocity};
+use super::geometry::Collider;
+use super::mining::Inventory;
+use super::time_control::SimulationTime;
+use super::SimulationAppExt;
+
+/// How long pickups drift before they are cleaned up, in seconds.
+const PICKUP_LIFETIME: f32 = 60.;
+
+/// Pickup logic
+pub(super) struct PickupsPlugin;
+
+impl Plugin for PickupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<PickupCollected>().add_systems(
+            FixedUpdate,
+            (attract_pickups, age_pickups)
+                .chain()
+                .after(EnergySet)
+                .before(FlightSet),
+        );
+    }
+}
+
+/// A canister of ore that any ship with an [`OreMagnet`] can collect.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Pickup {
+    /// The kind of ore.
+    pub ore: OreType,
+    /// How many units of ore are left in it.
+    pub amount: f32,
+    /// Counts down until the pickup is cleaned up.
+    lifetime: Timer,
+}
+
+/// Everything needed to spawn a [`Pickup`].
+#[derive(Bundle, Debug)]
+pub struct PickupBundle {
+    /// The pickup itself.
+    pub pickup: Pickup,
+    /// How it drifts.
+    pub velocity: Velocity,
+    /// How close it must come to be collected.
+    pub collider: Collider,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl PickupBundle {
+    /// Creates a pickup holding `amount` units of `ore` at `position`, drifting at `velocity`.
+    pub fn new(ore: OreType, amount: f32, position: Vec3, velocity: Vec3) -> Self {
+        PickupBundle {
+            pickup: Pickup {
+                ore,
+                amount,
+                lifetime: Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once),
+            },
+            velocity: Velocity(velocity),
+            collider: Collider { radius: 0.5 },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
+
+/// Pulls nearby [`Pickup`]s towards a ship, and stores them in its [`Inventory`] on contact.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct OreMagnet {
+    /// How far away pickups are pulled from, in meters.
+    pub range: f32,
+    /// How quickly pulled pickups accelerate towards the ship, in meters per second squared.
+    pub pull: f32,
+    /// How close pickups must come to be collected, in meters, beyond both colliders.
+    pub reach: f32,
+}
+
+impl Default for OreMagnet {
+    fn default() -> Self {
+        OreMagnet {
+            range: 80.,
+            pull: 60.,
+            reach: 2.,
+        }
+    }
+}
+
+/// A ship's [`OreMagnet`] has moved ore from a [`Pickup`] into its [`Inventory`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PickupCollected {
+    /// The ship that collected the ore.
+    pub ship: Entity,
+    /// The kind of ore.
+    pub ore: OreType,
+    /// How many units were collected.
+    pub amount: f32,
+}
+
+/// Pulls pickups towards the nearest magnet in range with room in its hold, collecting those that
+/// reach it.
+fn attract_pickups(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut ships: Query<(
+        Entity,
+        &Transform,
+        &Velocity,
+        Option<&Collider>,
+        &OreMagnet,
+        &mut Inventory,
+    )>,
+    mut pickups: Query<
+        (Entity, &Transform, &mut Velocity, &Collider, &mut Pickup),
+        Without<OreMagnet>,
+    >,
+    mut collected: EventWriter<PickupCollected>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (pickup_entity, pickup_transform, mut pickup_velocity, pickup_collider, mut pickup) in
+        pickups.iter_mut()
+    {
+        let nearest = ships
+            .iter_mut()
+            .filter(|(.., inventory)| inventory.free_space() > 0.)
+            .map(|ship| {
+                let distance = ship.1.translation.distance(pickup_transform.translation);
+                (ship, distance)
+            })
+            .filter(|((.., magnet, _), distance)| *distance <= magnet.range)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let Some(((ship, transform, velocity, collider, magnet, mut inventory), distance)) =
+            nearest
+        else {
+            continue;
+        };
+
+        let contact = collider.map_or(0., |collider| collider.radius) + pickup_collider.radius;
+        if distance <= contact + magnet.reach {
+            let stored = inventory.add(pickup.ore, pickup.amount);
+            pickup.amount -= stored;
+            collected.send(PickupCollected {
+                ship,
+                ore: pickup.ore,
+                amount: stored,
+            });
+            if pickup.amount <= 0. {
+                commands.entity(pickup_entity).despawn_recursive();
+            }
+            continue;
+        }
+
+        // Accelerate towards the ship, matching its velocity so that fast ships still catch up
+        let direction = (transform.translation - pickup_transform.translation) / distance;
+        let relative = pickup_velocity.0 - velocity.0;
+        pickup_velocity.0 += (direction * magnet.pull - relative) * delta_time;
+    }
+}
+
+/// Cleans up pickups that have drifted for long enough.
+fn age_pickups(
+    mut commands: Commands,
+    time: SimulationTime,
+    mut query: Query<(Entity, &mut Pickup)>,
+) {
+    for (entity, mut pickup) in query.iter_mut() {
+        if pickup.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
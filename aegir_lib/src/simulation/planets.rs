@@ -0,0 +1,45 @@
+//! Planets: vast bodies that ships cannot land on, but whose gravity they must reckon with.
+
+use bevy::prelude::*;
+
+use crate::game_state::InGame;
+
+use super::flight::GravityWell;
+use super::geometry::Collider;
+
+/// A planet, which pulls on everything nearby with its [`GravityWell`].
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Planet {
+    /// The name shown to the player.
+    pub name: String,
+}
+
+/// Everything needed to spawn a [`Planet`].
+#[derive(Bundle, Debug)]
+pub struct PlanetBundle {
+    /// The planet itself.
+    pub planet: Planet,
+    /// How it pulls on nearby bodies.
+    pub gravity_well: GravityWell,
+    /// What shots hit, and what the camera stays clear of.
+    pub collider: Collider,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl PlanetBundle {
+    /// Creates a planet called `name` at `position`, as large as `gravity_well`'s surface.
+    pub fn new(name: impl Into<String>, position: Vec3, gravity_well: GravityWell) -> Self {
+        PlanetBundle {
+            planet: Planet { name: name.into() },
+            gravity_well,
+            collider: Collider {
+                radius: gravity_well.surface_radius,
+            },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Deterministic random numbers, drawn from the [`WorldSeed`] so that the same seed reproduces the
+//! same world.
+//!
+//! Each subsystem draws from its own [`RngStream`], so that a change to how one of them uses its
+//! numbers, such as scattering another asteroid, does not shift the numbers seen by the others.
+//! Generation that happens once, such as laying out a sector, starts a fresh stream with
+//! [`WorldSeed::rng`] each time. Systems that draw every tick share the [`WorldRng`], which starts
+//! over from the seed whenever play begins.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::game_state::GameState;
+
+use super::WorldSeed;
+
+/// Random number logic
+pub(super) struct RandomPlugin;
+
+impl Plugin for RandomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldRng>()
+            .add_systems(OnEnter(GameState::Playing), reseed_world_rng)
+            .add_systems(
+                First,
+                reseed_world_rng.run_if(resource_changed::<WorldSeed>()),
+            );
+    }
+}
+
+/// The independent sequences of random numbers that each subsystem draws from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    /// Where a sector's asteroids are scattered, and their sizes, ores and orientations.
+    Asteroids,
+    /// The prices that each station's market opens at.
+    Stations,
+    /// How market prices wander over time.
+    Economy,
+    /// How destroyed asteroids break apart.
+    Fragments,
+    /// The debris thrown up by mining lasers.
+    Mining,
+    /// The stress scene spawned by the benchmark.
+    Benchmark,
+    /// The ships of a wave, numbered from zero, and where they arrive.
+    Wave(u32),
+}
+
+impl RngStream {
+    /// The ChaCha stream number for this stream, which no other stream shares.
+    fn id(self) -> u64 {
+        match self {
+            RngStream::Asteroids => 0,
+            RngStream::Stations => 1,
+            RngStream::Economy => 2,
+            RngStream::Fragments => 3,
+            RngStream::Mining => 4,
+            RngStream::Benchmark => 5,
+            // Leave room for more subsystems below the waves
+            RngStream::Wave(wave) => (1 << 32) + u64::from(wave),
+        }
+    }
+}
+
+impl WorldSeed {
+    /// A random number generator for `stream`, starting from the beginning of its sequence.
+    pub fn rng(self, stream: RngStream) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.0);
+        rng.set_stream(stream.id());
+        rng
+    }
+}
+
+/// The random number generators that simulation systems draw from each tick, one for each
+/// [`RngStream`], carrying on from where they left off.
+#[derive(Resource, Debug, Clone)]
+pub struct WorldRng {
+    /// The seed each stream starts from.
+    seed: WorldSeed,
+    /// The streams drawn from so far.
+    streams: HashMap<RngStream, ChaCha8Rng>,
+}
+
+impl FromWorld for WorldRng {
+    fn from_world(world: &mut World) -> Self {
+        WorldRng::new(*world.resource::<WorldSeed>())
+    }
+}
+
+impl WorldRng {
+    /// Generators for every stream, starting from the beginning of their sequences for `seed`.
+    pub fn new(seed: WorldSeed) -> Self {
+        WorldRng {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    /// The generator for `stream`.
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        let seed = self.seed;
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| seed.rng(stream))
+    }
+}
+
+/// Starts every stream over from the [`WorldSeed`], so that each flight from the same seed plays
+/// out the same way.
+fn reseed_world_rng(world_seed: Res<WorldSeed>, mut world_rng: ResMut<WorldRng>) {
+    *world_rng = WorldRng::new(*world_seed);
+}
@@ -0,0 +1,66 @@
+//! Loading game data assets from RON files.
+//!
+//! With the `hot_reload` feature, the asset folder is watched and these files are reloaded as they
+//! are saved, so ships, weapons, sectors and waves can be tuned while the game runs.
+
+use std::marker::PhantomData;
+#[cfg(feature = "hot_reload")]
+use std::time::Duration;
+
+#[cfg(feature = "hot_reload")]
+use bevy::asset::ChangeWatcher;
+use bevy::asset::{Asset, AssetLoader, AssetPlugin, LoadContext, LoadedAsset};
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+
+/// How long to wait after a file changes before reloading it, so that editors saving in several
+/// writes are only reloaded from once.
+#[cfg(feature = "hot_reload")]
+const HOT_RELOAD_DELAY: Duration = Duration::from_millis(200);
+
+/// The [`AssetPlugin`] the game should be built with, which watches for changes to assets if the
+/// `hot_reload` feature is enabled.
+pub fn asset_plugin() -> AssetPlugin {
+    AssetPlugin {
+        #[cfg(feature = "hot_reload")]
+        watch_for_changes: ChangeWatcher::with_delay(HOT_RELOAD_DELAY),
+        ..Default::default()
+    }
+}
+
+/// Loads assets of type `A` from RON files with the given extensions.
+#[derive(Debug)]
+pub struct RonAssetLoader<A> {
+    /// The file extensions this loader handles, without the leading dot.
+    extensions: &'static [&'static str],
+    /// The type of asset loaded.
+    asset: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetLoader<A> {
+    /// Creates a loader for files ending in `extensions`, such as `"weapon.ron"`.
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        RonAssetLoader {
+            extensions,
+            asset: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset: A = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -0,0 +1,704 @@
+//! Scripts that add missions and encounters without recompiling the game, written in
+//! [Rhai](https://rhai.rs) and loaded from `.rhai` files in the `scripts` asset folder.
+//!
+//! Scripts subscribe to events by defining handlers named after them, listed in
+//! [`ScriptEvent`], such as `fn on_destroyed(event) { ... }`. Each handler is passed the event as
+//! an object map, and `this` is an object map kept for the script between calls, so handlers can
+//! count kills or remember what they have spawned. Code outside of functions is never run; setting
+//! up belongs in `on_start`.
+//!
+//! Scripts cannot reach into the world themselves. They ask for changes through a small command
+//! API (`spawn_ship`, `give_item`, `set_objective`, `start_mission` and `add_waypoint`), whose
+//! requests are checked and carried out once the handlers have run. Each call is limited in how
+//! much work it may do, and a script that fails only logs a warning, so a broken mod cannot hang
+//! or crash the game.
+
+use std::sync::{Arc, Mutex};
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::BoxedFuture;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+
+use crate::game_state::GameState;
+use crate::player::ship::PlayerShip;
+
+use super::ai::{spawn_ai_ship, AiPilot, Squadrons};
+use super::economy::Commodity;
+use super::factions::Faction;
+use super::health::{Damaged, Destroyed, HealthSet};
+use super::mining::Inventory;
+use super::missions::{
+    ActiveMission, MissionDefinition, MissionEnded, MissionLibrary, MissionStatus, MissionTag,
+    ObjectiveCompleted, ObjectiveState, SetObjective, StartMission,
+};
+use super::navigation::WaypointBundle;
+use super::sector::InSector;
+use super::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use super::stations::{ShipDocked, ShipUndocked, Station};
+use super::time_control::SimulationTime;
+use super::weapons::WeaponLibrary;
+
+/// The asset folder that scripts are loaded from.
+const SCRIPTS_FOLDER: &str = "scripts";
+
+/// The most operations a single handler may run before it is stopped.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// The deepest that functions may call each other within a script.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// The longest string, array or object map a script may build.
+const MAX_COLLECTION_SIZE: usize = 10_000;
+
+/// Scripting logic
+pub(super) struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Script>()
+            .add_asset_loader(ScriptLoader {
+                engine: sandboxed_engine(),
+            })
+            .init_resource::<ScriptLibrary>()
+            .init_resource::<ScriptRuntime>()
+            .add_systems(OnEnter(GameState::Playing), start_scripts)
+            .add_systems(Update, reload_scripts)
+            .add_systems(
+                FixedUpdate,
+                (run_scripts, apply_script_commands)
+                    .chain()
+                    .after(HealthSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// A compiled script, as loaded from a `.rhai` file.
+#[derive(Debug, Clone, TypeUuid, TypePath)]
+#[uuid = "e2d7c9a4-5f13-4b8e-a6c1-9d0f3b7e2a58"]
+pub struct Script {
+    /// The compiled script.
+    ast: AST,
+}
+
+/// Compiles `.rhai` files into [`Script`]s, so that syntax errors are reported as they load.
+#[derive(Debug)]
+struct ScriptLoader {
+    /// An engine with the same limits scripts are run under.
+    engine: Engine,
+}
+
+impl AssetLoader for ScriptLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let mut ast = self.engine.compile(std::str::from_utf8(bytes)?)?;
+            ast.set_source(load_context.path().to_string_lossy().into_owned());
+            load_context.set_default_asset(LoadedAsset::new(Script { ast }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rhai"]
+    }
+}
+
+/// Every script that is run during play.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ScriptLibrary {
+    /// Handles to each script.
+    scripts: Vec<Handle<Script>>,
+}
+
+impl ScriptLibrary {
+    /// Handles to each script.
+    pub fn scripts(&self) -> &[Handle<Script>] {
+        &self.scripts
+    }
+}
+
+impl FromWorld for ScriptLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let scripts = match asset_server.load_folder(SCRIPTS_FOLDER) {
+            Ok(handles) => handles.into_iter().map(HandleUntyped::typed).collect(),
+            Err(error) => {
+                warn!("Could not load scripts: {error}");
+                Vec::new()
+            }
+        };
+
+        ScriptLibrary { scripts }
+    }
+}
+
+/// The events scripts can subscribe to, each handled by the function named after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptEvent {
+    /// `on_start()`, once when play begins or the script is reloaded.
+    Start,
+    /// `on_tick(delta)`, every tick, with the seconds it covered.
+    Tick,
+    /// `on_damaged(event)`, with the `target`, `source`, `amount` and `position`.
+    Damaged,
+    /// `on_destroyed(event)`, with the `entity`, `killer`, `position` and the entity's mission
+    /// `tag`, if it has one.
+    Destroyed,
+    /// `on_docked(event)`, with the `ship`, `station` and `station_name`.
+    Docked,
+    /// `on_undocked(event)`, with the `ship`, `station` and `station_name`.
+    Undocked,
+    /// `on_objective_completed(event)`, with the `mission` name and `objective` id.
+    ObjectiveCompleted,
+    /// `on_mission_ended(event)`, with the `mission` name and its `status`, either `"complete"`
+    /// or `"failed"`.
+    MissionEnded,
+}
+
+impl ScriptEvent {
+    /// The name of the function that handles this event.
+    pub fn handler(self) -> &'static str {
+        match self {
+            ScriptEvent::Start => "on_start",
+            ScriptEvent::Tick => "on_tick",
+            ScriptEvent::Damaged => "on_damaged",
+            ScriptEvent::Destroyed => "on_destroyed",
+            ScriptEvent::Docked => "on_docked",
+            ScriptEvent::Undocked => "on_undocked",
+            ScriptEvent::ObjectiveCompleted => "on_objective_completed",
+            ScriptEvent::MissionEnded => "on_mission_ended",
+        }
+    }
+}
+
+/// A change a script has asked for, carried out once its handlers have run.
+#[derive(Debug, Clone, PartialEq)]
+enum ScriptCommand {
+    /// Spawn a computer-controlled ship of the named class, flying for the named faction.
+    SpawnShip {
+        /// The name of the ship's class.
+        class: String,
+        /// The name of the ship's faction.
+        faction: String,
+        /// Where to spawn it, in world space.
+        position: Vec3,
+        /// A tag mission objectives can refer to it by, if any.
+        tag: Option<String>,
+    },
+    /// Put cargo in the player's hold.
+    GiveItem {
+        /// The name of the commodity.
+        commodity: String,
+        /// How many units to give.
+        amount: f32,
+    },
+    /// Unlock, complete or fail an objective of the active mission.
+    SetObjective {
+        /// The objective's id.
+        id: String,
+        /// `"active"`, `"complete"` or `"failed"`.
+        state: String,
+    },
+    /// Start the named mission.
+    StartMission(String),
+    /// Place a waypoint in the current sector.
+    AddWaypoint {
+        /// The waypoint's name.
+        name: String,
+        /// Where it is, in world space.
+        position: Vec3,
+    },
+}
+
+/// What scripts can ask about the world while their handlers run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ScriptContext {
+    /// The player's ship and where it is, if they have one.
+    player: Option<(Entity, Vec3)>,
+}
+
+/// A script being run, with the state it keeps between calls.
+#[derive(Debug, Clone)]
+struct ScriptInstance {
+    /// The script.
+    script: Handle<Script>,
+    /// The script's path, for warnings.
+    name: String,
+    /// The object map bound to `this` in the script's handlers.
+    this: Dynamic,
+    /// Has `on_start` been called?
+    started: bool,
+}
+
+impl ScriptInstance {
+    /// An instance of `script` that has not started yet.
+    fn new(script: Handle<Script>, asset_server: &AssetServer) -> Self {
+        let name = asset_server.get_handle_path(&script).map_or_else(
+            || "a script".to_string(),
+            |path| path.path().display().to_string(),
+        );
+
+        ScriptInstance {
+            script,
+            name,
+            this: Dynamic::from_map(Map::new()),
+            started: false,
+        }
+    }
+}
+
+/// The engine scripts run in, with the command API registered, and the scripts being run.
+#[derive(Resource, Debug)]
+struct ScriptRuntime {
+    /// The engine handlers are called through.
+    engine: Engine,
+    /// The scripts being run, in the order they are called.
+    instances: Vec<ScriptInstance>,
+    /// What scripts can ask about the world, updated before each tick's handlers run.
+    context: Arc<Mutex<ScriptContext>>,
+    /// The changes asked for by the handler being run.
+    requested: Arc<Mutex<Vec<ScriptCommand>>>,
+    /// The changes asked for this tick, with the name of the script that asked for each.
+    pending: Vec<(String, ScriptCommand)>,
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        let requested = Arc::new(Mutex::new(Vec::new()));
+
+        let mut engine = sandboxed_engine();
+        engine
+            .on_print(|text| info!("{text}"))
+            .on_debug(|text, source, position| {
+                debug!("{} {position}: {text}", source.unwrap_or("script"))
+            });
+
+        let player = Arc::clone(&context);
+        engine.register_fn("player", move || -> Dynamic {
+            let context = player.lock().map(|context| *context).unwrap_or_default();
+            context
+                .player
+                .map_or(Dynamic::UNIT, |(entity, _)| entity_id(entity))
+        });
+        let player = Arc::clone(&context);
+        engine.register_fn("player_position", move || -> Dynamic {
+            let context = player.lock().map(|context| *context).unwrap_or_default();
+            context
+                .player
+                .map_or(Dynamic::UNIT, |(_, position)| vector(position))
+        });
+
+        let queue = Arc::clone(&requested);
+        let request = move |command: ScriptCommand| {
+            if let Ok(mut requested) = queue.lock() {
+                requested.push(command);
+            }
+        };
+        let spawn = request.clone();
+        engine.register_fn(
+            "spawn_ship",
+            move |class: &str, faction: &str, position: Array| {
+                spawn(ScriptCommand::SpawnShip {
+                    class: class.to_string(),
+                    faction: faction.to_string(),
+                    position: position_argument(&position)?,
+                    tag: None,
+                });
+                Ok::<_, Box<EvalAltResult>>(())
+            },
+        );
+        let spawn = request.clone();
+        engine.register_fn(
+            "spawn_ship",
+            move |class: &str, faction: &str, position: Array, tag: &str| {
+                spawn(ScriptCommand::SpawnShip {
+                    class: class.to_string(),
+                    faction: faction.to_string(),
+                    position: position_argument(&position)?,
+                    tag: Some(tag.to_string()),
+                });
+                Ok::<_, Box<EvalAltResult>>(())
+            },
+        );
+        let give = request.clone();
+        engine.register_fn("give_item", move |commodity: &str, amount: FLOAT| {
+            give(ScriptCommand::GiveItem {
+                commodity: commodity.to_string(),
+                amount: amount as f32,
+            });
+        });
+        let set = request.clone();
+        engine.register_fn("set_objective", move |id: &str, state: &str| {
+            set(ScriptCommand::SetObjective {
+                id: id.to_string(),
+                state: state.to_string(),
+            });
+        });
+        let start = request.clone();
+        engine.register_fn("start_mission", move |name: &str| {
+            start(ScriptCommand::StartMission(name.to_string()));
+        });
+        let add = request;
+        engine.register_fn("add_waypoint", move |name: &str, position: Array| {
+            add(ScriptCommand::AddWaypoint {
+                name: name.to_string(),
+                position: position_argument(&position)?,
+            });
+            Ok::<_, Box<EvalAltResult>>(())
+        });
+
+        ScriptRuntime {
+            engine,
+            instances: Vec::new(),
+            context,
+            requested,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// An engine that cannot load other files or evaluate code built at runtime, and that stops
+/// scripts which run too long, recurse too deeply or build collections that are too large.
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_COLLECTION_SIZE)
+        .set_max_array_size(MAX_COLLECTION_SIZE)
+        .set_max_map_size(MAX_COLLECTION_SIZE);
+    engine
+}
+
+/// How an entity is passed to scripts.
+fn entity_id(entity: Entity) -> Dynamic {
+    Dynamic::from_int(entity.to_bits() as INT)
+}
+
+/// How a point is passed to scripts, as an array of its coordinates.
+fn vector(point: Vec3) -> Dynamic {
+    Dynamic::from_array(
+        point
+            .to_array()
+            .map(|coordinate| Dynamic::from_float(coordinate as FLOAT))
+            .to_vec(),
+    )
+}
+
+/// An object map of `fields`, for passing events to scripts.
+fn object<const N: usize>(fields: [(&str, Dynamic); N]) -> Dynamic {
+    Dynamic::from_map(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.into(), value))
+            .collect(),
+    )
+}
+
+/// Reads a point passed by a script as an array of three numbers.
+fn position_argument(array: &Array) -> Result<Vec3, Box<EvalAltResult>> {
+    let coordinates: Option<Vec<f32>> = array
+        .iter()
+        .map(|value| {
+            value
+                .as_float()
+                .ok()
+                .or_else(|| value.as_int().ok().map(|value| value as FLOAT))
+                .map(|value| value as f32)
+        })
+        .collect();
+    match coordinates.as_deref() {
+        Some(&[x, y, z]) => Ok(Vec3::new(x, y, z)),
+        _ => Err("a position must be an array of three numbers, [x, y, z]".into()),
+    }
+}
+
+/// Calls `script`'s handler for `event` with `this` bound, if it defines one.
+fn call_handler(
+    engine: &Engine,
+    script: &AST,
+    this: &mut Dynamic,
+    event: ScriptEvent,
+    arguments: Vec<Dynamic>,
+) -> Result<(), Box<EvalAltResult>> {
+    let handler = event.handler();
+    let defined = script
+        .iter_functions()
+        .any(|function| function.name == handler && function.params.len() == arguments.len());
+    if !defined {
+        return Ok(());
+    }
+
+    let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(this);
+    engine
+        .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), script, handler, arguments)
+        .map(|_| ())
+}
+
+/// Starts every script afresh when play begins.
+fn start_scripts(
+    library: Res<ScriptLibrary>,
+    asset_server: Res<AssetServer>,
+    mut runtime: ResMut<ScriptRuntime>,
+) {
+    runtime.instances = library
+        .scripts()
+        .iter()
+        .map(|script| ScriptInstance::new(script.clone(), &asset_server))
+        .collect();
+}
+
+/// Starts scripts afresh as they are saved, when assets are reloaded while the game runs.
+fn reload_scripts(mut events: EventReader<AssetEvent<Script>>, mut runtime: ResMut<ScriptRuntime>) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        for instance in &mut runtime.instances {
+            if &instance.script == handle {
+                instance.this = Dynamic::from_map(Map::new());
+                instance.started = false;
+            }
+        }
+    }
+}
+
+/// Passes this tick's events to each script's handlers, collecting the changes they ask for.
+#[allow(clippy::too_many_arguments)]
+fn run_scripts(
+    time: SimulationTime,
+    mut runtime: ResMut<ScriptRuntime>,
+    scripts: Res<Assets<Script>>,
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventReader<Destroyed>,
+    mut docked: EventReader<ShipDocked>,
+    mut undocked: EventReader<ShipUndocked>,
+    mut completed: EventReader<ObjectiveCompleted>,
+    mut ended: EventReader<MissionEnded>,
+    mission: Option<Res<ActiveMission>>,
+    mission_definitions: Res<Assets<MissionDefinition>>,
+    player_query: Query<(Entity, &Transform), With<PlayerShip>>,
+    tag_query: Query<&MissionTag>,
+    station_query: Query<&Station>,
+) {
+    let station_name = |station: Entity| {
+        station_query
+            .get(station)
+            .map_or(Dynamic::UNIT, |station| station.name.clone().into())
+    };
+    let mission = mission
+        .as_ref()
+        .and_then(|mission| mission_definitions.get(&mission.definition));
+    let mission_name = || mission.map_or(Dynamic::UNIT, |mission| mission.name.clone().into());
+
+    let mut events = Vec::new();
+    events.extend(damaged.iter().map(|event| {
+        let fields = [
+            ("target", entity_id(event.target)),
+            ("source", event.source.map_or(Dynamic::UNIT, entity_id)),
+            ("amount", Dynamic::from_float(event.amount as FLOAT)),
+            ("position", vector(event.position)),
+        ];
+        (ScriptEvent::Damaged, object(fields))
+    }));
+    events.extend(destroyed.iter().map(|event| {
+        let tag = tag_query
+            .get(event.entity)
+            .map_or(Dynamic::UNIT, |tag| tag.0.clone().into());
+        let fields = [
+            ("entity", entity_id(event.entity)),
+            ("killer", event.killer.map_or(Dynamic::UNIT, entity_id)),
+            ("position", vector(event.position)),
+            ("tag", tag),
+        ];
+        (ScriptEvent::Destroyed, object(fields))
+    }));
+    events.extend(docked.iter().map(|event| {
+        let fields = [
+            ("ship", entity_id(event.ship)),
+            ("station", entity_id(event.station)),
+            ("station_name", station_name(event.station)),
+        ];
+        (ScriptEvent::Docked, object(fields))
+    }));
+    events.extend(undocked.iter().map(|event| {
+        let fields = [
+            ("ship", entity_id(event.ship)),
+            ("station", entity_id(event.station)),
+            ("station_name", station_name(event.station)),
+        ];
+        (ScriptEvent::Undocked, object(fields))
+    }));
+    events.extend(completed.iter().map(|event| {
+        let objective = mission
+            .and_then(|mission| mission.objectives.get(event.index))
+            .map_or(Dynamic::UNIT, |objective| objective.id.clone().into());
+        let fields = [("mission", mission_name()), ("objective", objective)];
+        (ScriptEvent::ObjectiveCompleted, object(fields))
+    }));
+    events.extend(ended.iter().map(|MissionEnded(status)| {
+        let status = match status {
+            MissionStatus::InProgress => "in progress",
+            MissionStatus::Complete => "complete",
+            MissionStatus::Failed => "failed",
+        };
+        let fields = [("mission", mission_name()), ("status", status.into())];
+        (ScriptEvent::MissionEnded, object(fields))
+    }));
+    let delta_time = Dynamic::from_float(time.delta_seconds() as FLOAT);
+
+    let runtime = &mut *runtime;
+    if let Ok(mut context) = runtime.context.lock() {
+        context.player = player_query
+            .get_single()
+            .ok()
+            .map(|(entity, transform)| (entity, transform.translation));
+    }
+    for instance in &mut runtime.instances {
+        let Some(Script { ast }) = scripts.get(&instance.script) else {
+            continue;
+        };
+
+        let mut calls = Vec::new();
+        if !std::mem::replace(&mut instance.started, true) {
+            calls.push((ScriptEvent::Start, Vec::new()));
+        }
+        calls.extend(
+            events
+                .iter()
+                .map(|(event, argument)| (*event, vec![argument.clone()])),
+        );
+        calls.push((ScriptEvent::Tick, vec![delta_time.clone()]));
+
+        for (event, arguments) in calls {
+            let result = call_handler(&runtime.engine, ast, &mut instance.this, event, arguments);
+            if let Err(error) = result {
+                warn!("{} failed in {}: {error}", instance.name, event.handler());
+            }
+        }
+
+        if let Ok(mut requested) = runtime.requested.lock() {
+            runtime.pending.extend(
+                requested
+                    .drain(..)
+                    .map(|command| (instance.name.clone(), command)),
+            );
+        }
+    }
+}
+
+/// Carries out the changes scripts have asked for, warning about any that cannot be.
+#[allow(clippy::too_many_arguments)]
+fn apply_script_commands(
+    mut commands: Commands,
+    mut runtime: ResMut<ScriptRuntime>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+    mut squadrons: ResMut<Squadrons>,
+    mission_library: Res<MissionLibrary>,
+    mission_definitions: Res<Assets<MissionDefinition>>,
+    mut player_query: Query<&mut Inventory, With<PlayerShip>>,
+    mut set_objective: EventWriter<SetObjective>,
+    mut start_mission: EventWriter<StartMission>,
+) {
+    for (script, command) in runtime.pending.drain(..) {
+        let result = match command {
+            ScriptCommand::SpawnShip {
+                class,
+                faction,
+                position,
+                tag,
+            } => {
+                let class = ship_library.ships().iter().find(|handle| {
+                    ship_definitions
+                        .get(handle)
+                        .is_some_and(|definition| definition.name.eq_ignore_ascii_case(&class))
+                });
+                let faction = Faction::ALL
+                    .into_iter()
+                    .find(|candidate| candidate.name().eq_ignore_ascii_case(&faction));
+                match (class, faction) {
+                    (Some(class), Some(faction)) => {
+                        let ship = spawn_ai_ship(
+                            &mut commands,
+                            Transform::from_translation(position),
+                            &ship_definitions.get(class).cloned().unwrap_or_default(),
+                            weapon_library.weapons().first(),
+                            faction,
+                            squadrons.allocate(),
+                            AiPilot::default(),
+                        );
+                        commands.entity(ship).insert(ShipClass(class.clone()));
+                        if let Some(tag) = tag {
+                            commands.entity(ship).insert(MissionTag(tag));
+                        }
+                        Ok(())
+                    }
+                    (None, _) => Err(format!("there is no ship class called `{class}`")),
+                    (_, None) => Err(format!("there is no faction called `{faction}`")),
+                }
+            }
+            ScriptCommand::GiveItem { commodity, amount } => {
+                let found = Commodity::ALL
+                    .into_iter()
+                    .find(|candidate| candidate.name().eq_ignore_ascii_case(&commodity));
+                match (found, player_query.get_single_mut()) {
+                    (Some(commodity), Ok(mut inventory)) => {
+                        inventory.add(commodity, amount.max(0.));
+                        Ok(())
+                    }
+                    (None, _) => Err(format!("there is no commodity called `{commodity}`")),
+                    (_, Err(_)) => Err("the player has no ship to give cargo to".to_string()),
+                }
+            }
+            ScriptCommand::SetObjective { id, state } => {
+                let state = match state.as_str() {
+                    "active" => Some(ObjectiveState::Active),
+                    "complete" => Some(ObjectiveState::Complete),
+                    "failed" => Some(ObjectiveState::Failed),
+                    _ => None,
+                };
+                match state {
+                    Some(state) => {
+                        set_objective.send(SetObjective { id, state });
+                        Ok(())
+                    }
+                    None => Err("objectives can be `active`, `complete` or `failed`".to_string()),
+                }
+            }
+            ScriptCommand::StartMission(name) => {
+                let mission = mission_library.missions().iter().find(|handle| {
+                    mission_definitions
+                        .get(handle)
+                        .is_some_and(|definition| definition.name.eq_ignore_ascii_case(&name))
+                });
+                match mission {
+                    Some(mission) => {
+                        start_mission.send(StartMission(mission.clone()));
+                        Ok(())
+                    }
+                    None => Err(format!("there is no mission called `{name}`")),
+                }
+            }
+            ScriptCommand::AddWaypoint { name, position } => {
+                commands.spawn((WaypointBundle::new(name, position), InSector));
+                Ok(())
+            }
+        };
+
+        if let Err(error) = result {
+            warn!("{script}: {error}");
+        }
+    }
+}
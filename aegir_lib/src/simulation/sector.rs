@@ -0,0 +1,455 @@
+//! Sectors: hand-authored areas of space, loaded from `.sector.ron` files in the `sectors` asset
+//! folder.
+//!
+//! A sector describes its stations, planets, waypoints, asteroid field, spawn points, lighting and
+//! sky.
+//! Sending a [`LoadSectorEvent`] despawns everything that belongs to the current sector and
+//! spawns the new one in its place once it has loaded. When assets are hot-reloaded, changes to the
+//! current sector's stations are carried over without reloading it.
+
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use serde::Deserialize;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::GameState;
+
+use super::asteroids::AsteroidField;
+use super::economy::{ListingDefinition, Market};
+use super::factions::Faction;
+use super::flight::GravityWell;
+use super::navigation::{NavigationSet, WaypointBundle};
+use super::planets::PlanetBundle;
+use super::random::RngStream;
+use super::ron_asset::RonAssetLoader;
+use super::stations::{Station, StationBundle};
+use super::{SimulationAppExt, WorldSeed};
+
+/// The asset folder that sector definitions are loaded from.
+const SECTORS_FOLDER: &str = "sectors";
+
+/// The sector that play begins in.
+const HOME_SECTOR: &str = "sectors/aegir.sector.ron";
+
+/// Sector logic
+pub(super) struct SectorPlugin;
+
+impl Plugin for SectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SectorDefinition>()
+            .add_asset_loader(RonAssetLoader::<SectorDefinition>::new(&["sector.ron"]))
+            .init_resource::<SectorLibrary>()
+            .init_resource::<CurrentSector>()
+            .add_simulation_event::<LoadSectorEvent>()
+            .add_simulation_event::<SectorLoaded>()
+            .add_console_command("sector", "sector <name> [spawn point]", sector_command)
+            .add_systems(OnEnter(GameState::Playing), load_home_sector)
+            .add_systems(OnExit(GameState::Playing), forget_sector)
+            .add_systems(Update, retune_sector)
+            .add_systems(
+                FixedUpdate,
+                (queue_sectors, spawn_pending_sector)
+                    .chain()
+                    .before(NavigationSet),
+            );
+    }
+}
+
+/// A sector, as loaded from a `.sector.ron` file.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "5b0e9d3a-72c4-4f1e-9a86-d3c7e2b1f049"]
+pub struct SectorDefinition {
+    /// The name shown to the player.
+    pub name: String,
+    /// Where the sector is drawn on the galaxy map, as fractions of the map's width and height
+    /// from its top-left corner.
+    #[serde(default)]
+    pub map_position: [f32; 2],
+    /// Stations placed in the sector.
+    #[serde(default)]
+    pub stations: Vec<SectorStation>,
+    /// Planets placed in the sector.
+    #[serde(default)]
+    pub planets: Vec<SectorPlanet>,
+    /// Waypoints placed in the sector.
+    #[serde(default)]
+    pub waypoints: Vec<SectorWaypoint>,
+    /// How the sector's asteroids are scattered.
+    #[serde(default)]
+    pub asteroid_field: AsteroidField,
+    /// Where ships arriving in the sector can be placed. The first is used unless another is
+    /// asked for.
+    #[serde(default)]
+    pub spawn_points: Vec<SpawnPoint>,
+    /// How the sector is lit.
+    #[serde(default)]
+    pub lighting: SectorLighting,
+    /// What is drawn behind everything else.
+    #[serde(default)]
+    pub sky: Sky,
+}
+
+impl SectorDefinition {
+    /// Where a ship arriving at the named spawn point is placed, or at the first spawn point if
+    /// no name is given or no spawn point has it.
+    pub fn spawn_transform(&self, spawn_point: Option<&str>) -> Transform {
+        spawn_point
+            .and_then(|name| self.spawn_points.iter().find(|point| point.name == name))
+            .or_else(|| self.spawn_points.first())
+            .map_or(Transform::IDENTITY, SpawnPoint::transform)
+    }
+}
+
+/// A station placed in a sector.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SectorStation {
+    /// The station's name, which missions refer to it by.
+    pub name: String,
+    /// Where the station is, in world space.
+    pub position: [f32; 3],
+    /// Who runs it.
+    #[serde(default = "SectorStation::default_faction")]
+    pub faction: Faction,
+    /// How strongly the station pulls on nearby ships, if it is large enough to.
+    #[serde(default)]
+    pub gravity_well: Option<GravityWell>,
+    /// What the station trades, or `None` to trade every ore at its usual price.
+    #[serde(default)]
+    pub market: Option<Vec<ListingDefinition>>,
+}
+
+impl SectorStation {
+    /// The faction used when a definition does not give one.
+    fn default_faction() -> Faction {
+        Faction::Aegir
+    }
+}
+
+/// A planet placed in a sector.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SectorPlanet {
+    /// The name shown to the player.
+    pub name: String,
+    /// Where the planet's center is, in world space.
+    pub position: [f32; 3],
+    /// How the planet pulls on nearby bodies, which also sets its size.
+    pub gravity_well: GravityWell,
+}
+
+/// A waypoint placed in a sector.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SectorWaypoint {
+    /// The name shown on the HUD.
+    pub name: String,
+    /// Where the waypoint is, in world space.
+    pub position: [f32; 3],
+}
+
+/// A place where ships arriving in a sector can be placed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpawnPoint {
+    /// A name that the spawn point can be asked for by.
+    pub name: String,
+    /// Where ships are placed, in world space.
+    pub position: [f32; 3],
+    /// Which way ships face when placed.
+    #[serde(default = "SpawnPoint::default_facing")]
+    pub facing: [f32; 3],
+}
+
+impl SpawnPoint {
+    /// The facing used when a definition does not give one: straight ahead, along negative Z.
+    fn default_facing() -> [f32; 3] {
+        [0., 0., -1.]
+    }
+
+    /// Where ships are placed, and which way they face.
+    pub fn transform(&self) -> Transform {
+        let position = Vec3::from(self.position);
+        let facing = Vec3::from(self.facing)
+            .try_normalize()
+            .unwrap_or(Vec3::NEG_Z);
+        Transform::from_translation(position).looking_to(facing, Vec3::Y)
+    }
+}
+
+/// How a sector is lit.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SectorLighting {
+    /// The color of the light that reaches everything, in sRGB.
+    pub ambient_color: [f32; 3],
+    /// How bright the ambient light is.
+    pub ambient_brightness: f32,
+    /// The color of the sun, in sRGB.
+    pub sun_color: [f32; 3],
+    /// How bright the sun is, in lux.
+    pub sun_illuminance: f32,
+    /// Which way the sun's light travels.
+    pub sun_direction: [f32; 3],
+}
+
+impl Default for SectorLighting {
+    fn default() -> Self {
+        SectorLighting {
+            ambient_color: [1.; 3],
+            ambient_brightness: 1.,
+            sun_color: [1.; 3],
+            sun_illuminance: 100_000.,
+            sun_direction: [-30., -100., -30.],
+        }
+    }
+}
+
+/// What is drawn behind everything else in a sector.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Sky {
+    /// A flat color, in sRGB.
+    Color([f32; 3]),
+    /// A cubemap, stored as an image with its six faces stacked vertically.
+    Cubemap(String),
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Sky::Color([0.4; 3])
+    }
+}
+
+/// Every sector that can be flown to.
+#[derive(Resource, Debug, Clone)]
+pub struct SectorLibrary {
+    /// The sector that play begins in.
+    home: Handle<SectorDefinition>,
+    /// Handles to each sector, including the home sector.
+    sectors: Vec<Handle<SectorDefinition>>,
+}
+
+impl SectorLibrary {
+    /// The sector that play begins in.
+    pub fn home(&self) -> &Handle<SectorDefinition> {
+        &self.home
+    }
+
+    /// Handles to each sector, including the home sector.
+    pub fn sectors(&self) -> &[Handle<SectorDefinition>] {
+        &self.sectors
+    }
+}
+
+impl FromWorld for SectorLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let home = asset_server.load(HOME_SECTOR);
+        let sectors = match asset_server.load_folder(SECTORS_FOLDER) {
+            Ok(handles) => handles.into_iter().map(HandleUntyped::typed).collect(),
+            Err(error) => {
+                warn!("Could not load sector definitions: {error}");
+                vec![home.clone()]
+            }
+        };
+
+        SectorLibrary { home, sectors }
+    }
+}
+
+/// Asks for the current sector to be replaced by another.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct LoadSectorEvent {
+    /// The sector to load.
+    pub sector: Handle<SectorDefinition>,
+    /// The spawn point the player arrives at, or `None` for the sector's first.
+    pub spawn_point: Option<String>,
+}
+
+/// A sector has been spawned, replacing the last one.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SectorLoaded {
+    /// The sector that was spawned.
+    pub sector: Handle<SectorDefinition>,
+    /// Where the player should be placed.
+    pub arrival: Transform,
+}
+
+/// The sector being flown in, and the one waiting to replace it.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CurrentSector {
+    /// The sector that has been spawned, if any.
+    sector: Option<Handle<SectorDefinition>>,
+    /// The sector waiting to finish loading before it replaces the current one, if any.
+    pending: Option<LoadSectorEvent>,
+}
+
+impl CurrentSector {
+    /// The sector that has been spawned, if any.
+    pub fn sector(&self) -> Option<&Handle<SectorDefinition>> {
+        self.sector.as_ref()
+    }
+}
+
+/// Marks entities that belong to the current sector, which are despawned when it is replaced.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct InSector;
+
+/// Begins play in the home sector.
+fn load_home_sector(library: Res<SectorLibrary>, mut events: EventWriter<LoadSectorEvent>) {
+    events.send(LoadSectorEvent {
+        sector: library.home().clone(),
+        spawn_point: None,
+    });
+}
+
+/// Forgets the sector when play ends, since everything in it is despawned.
+fn forget_sector(mut current: ResMut<CurrentSector>) {
+    *current = CurrentSector::default();
+}
+
+/// Queues the latest requested sector to replace the current one once it has loaded.
+fn queue_sectors(mut events: EventReader<LoadSectorEvent>, mut current: ResMut<CurrentSector>) {
+    if let Some(event) = events.iter().last() {
+        current.pending = Some(event.clone());
+    }
+}
+
+/// Replaces the current sector with the pending one once it has loaded.
+fn spawn_pending_sector(
+    mut commands: Commands,
+    world_seed: Res<WorldSeed>,
+    definitions: Res<Assets<SectorDefinition>>,
+    mut current: ResMut<CurrentSector>,
+    old_query: Query<Entity, With<InSector>>,
+    mut loaded: EventWriter<SectorLoaded>,
+) {
+    let Some(pending) = current.pending.as_ref() else {
+        return;
+    };
+    let Some(definition) = definitions.get(&pending.sector) else {
+        return;
+    };
+
+    for entity in old_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mut station_rng = world_seed.rng(RngStream::Stations);
+    for station in &definition.stations {
+        let mut market = station
+            .market
+            .as_deref()
+            .map(Market::from_definitions)
+            .unwrap_or_default();
+        market.scatter_prices(&mut station_rng);
+        let mut entity = commands.spawn((
+            StationBundle {
+                faction: station.faction,
+                market,
+                ..StationBundle::new(station.name.clone(), Vec3::from(station.position))
+            },
+            InSector,
+        ));
+        if let Some(gravity_well) = station.gravity_well {
+            entity.insert(gravity_well);
+        }
+    }
+    for planet in &definition.planets {
+        commands.spawn((
+            PlanetBundle::new(
+                planet.name.clone(),
+                Vec3::from(planet.position),
+                planet.gravity_well,
+            ),
+            InSector,
+        ));
+    }
+    for waypoint in &definition.waypoints {
+        commands.spawn((
+            WaypointBundle::new(waypoint.name.clone(), Vec3::from(waypoint.position)),
+            InSector,
+        ));
+    }
+
+    let mut asteroid_rng = world_seed.rng(RngStream::Asteroids);
+    for asteroid in definition
+        .asteroid_field
+        .spawn(&mut commands, &mut asteroid_rng)
+    {
+        commands.entity(asteroid).insert(InSector);
+    }
+
+    info!("Entered {} (world seed {})", definition.name, world_seed.0);
+    loaded.send(SectorLoaded {
+        sector: pending.sector.clone(),
+        arrival: definition.spawn_transform(pending.spawn_point.as_deref()),
+    });
+    current.sector = current.pending.take().map(|pending| pending.sector);
+}
+
+/// Carries changes to the current sector's definition over to its stations' factions and markets,
+/// so that prices can be tuned while the game runs.
+///
+/// Everything else, such as the asteroid field, changes the next time the sector is entered.
+fn retune_sector(
+    mut events: EventReader<AssetEvent<SectorDefinition>>,
+    definitions: Res<Assets<SectorDefinition>>,
+    current: Res<CurrentSector>,
+    mut station_query: Query<(&Station, &mut Faction, &mut Market), With<InSector>>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        if current.sector() != Some(handle) {
+            continue;
+        }
+        let Some(definition) = definitions.get(handle) else {
+            continue;
+        };
+
+        for (station, mut faction, mut market) in station_query.iter_mut() {
+            let Some(station_definition) = definition
+                .stations
+                .iter()
+                .find(|station_definition| station_definition.name == station.name)
+            else {
+                continue;
+            };
+            *faction = station_definition.faction;
+            match &station_definition.market {
+                Some(listings) => market.retune(listings),
+                None => *market = Market::default(),
+            }
+        }
+        info!("Retuned the stations of {}", definition.name);
+    }
+}
+
+/// Console command that flies the player to the named sector.
+fn sector_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let (name, spawn_point) = match *arguments {
+        [name] => (name, None),
+        [name, spawn_point] => (name, Some(spawn_point.to_string())),
+        _ => return Err("expected a sector name and an optional spawn point".to_string()),
+    };
+
+    let library = world.resource::<SectorLibrary>();
+    let definitions = world.resource::<Assets<SectorDefinition>>();
+    let Some(sector) = library
+        .sectors()
+        .iter()
+        .find(|handle| {
+            definitions
+                .get(handle)
+                .is_some_and(|definition| definition.name.eq_ignore_ascii_case(name))
+        })
+        .cloned()
+    else {
+        return Err(format!("there is no sector called `{name}`"));
+    };
+
+    world.send_event(LoadSectorEvent {
+        sector,
+        spawn_point,
+    });
+
+    Ok(format!("flying to {name}"))
+}
@@ -0,0 +1,275 @@
+//! Ship classes, described by [`ShipDefinition`] assets.
+//!
+//! Every `.ship.ron` file in the `ships` asset folder is loaded at startup, so new ships can be
+//! added without touching any code. When assets are hot-reloaded, changes to a definition are
+//! carried over to every ship of that class already in flight.
+
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use serde::Deserialize;
+
+use super::energy::{Energy, Shield};
+use super::flight::FlightDynamics;
+use super::geometry::Collider;
+use super::health::Health;
+use super::mining::Inventory;
+use super::ron_asset::RonAssetLoader;
+
+/// The asset folder that ship definitions are loaded from.
+const SHIPS_FOLDER: &str = "ships";
+
+/// Ship class logic
+pub(super) struct ShipsPlugin;
+
+impl Plugin for ShipsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ShipDefinition>()
+            .add_asset_loader(RonAssetLoader::<ShipDefinition>::new(&["ship.ron"]))
+            .init_resource::<ShipLibrary>()
+            .add_systems(Update, (sort_ship_library, retune_ships));
+    }
+}
+
+/// A class of ship, as loaded from a `.ship.ron` file.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "2d7e4a91-6b3f-4c08-8e15-a9f3c6d20b47"]
+pub struct ShipDefinition {
+    /// The name shown to the player.
+    pub name: String,
+    /// The asset path of the scene drawn for this ship, or `None` for a plain box.
+    ///
+    /// Nodes in the scene named `hardpoint_*` and `thruster_*` place the ship's hardpoints and
+    /// engine exhausts, overriding [`hardpoints`](Self::hardpoints) where they overlap.
+    #[serde(default)]
+    pub mesh: Option<String>,
+    /// How heavy the ship is, in tonnes.
+    pub mass: f32,
+    /// How hard the engines push, in kilonewtons.
+    pub thrust: f32,
+    /// The speed reached at full throttle, in meters per second.
+    pub max_speed: f32,
+    /// How quickly the ship can rotate around each axis, in radians per second.
+    pub turn_rate: f32,
+    /// How much damage the hull can take.
+    pub health: f32,
+    /// How much energy the ship can store.
+    pub energy_capacity: f32,
+    /// How much energy is restored each second.
+    pub energy_recharge: f32,
+    /// How much damage the shield can soak up.
+    pub shield_capacity: f32,
+    /// How much of the shield is restored each second, energy permitting.
+    pub shield_recharge: f32,
+    /// How many units of cargo the hold can carry.
+    #[serde(default = "ShipDefinition::default_cargo_capacity")]
+    pub cargo_capacity: f32,
+    /// Where weapons can be mounted, in the order they are fitted.
+    pub hardpoints: Vec<HardpointDefinition>,
+    /// The shape that shots collide with.
+    pub collider: ColliderShape,
+}
+
+impl Default for ShipDefinition {
+    /// The ship flown when no definitions have loaded, such as in tests.
+    fn default() -> Self {
+        ShipDefinition {
+            name: "Prototype".to_string(),
+            mesh: None,
+            mass: 20.,
+            thrust: 800.,
+            max_speed: 100.,
+            turn_rate: 1.5,
+            health: 100.,
+            energy_capacity: 100.,
+            energy_recharge: 15.,
+            shield_capacity: 50.,
+            shield_recharge: 5.,
+            cargo_capacity: ShipDefinition::default_cargo_capacity(),
+            hardpoints: vec![
+                HardpointDefinition {
+                    name: "Port".to_string(),
+                    position: [-0.9, -0.1, -0.5],
+                },
+                HardpointDefinition {
+                    name: "Starboard".to_string(),
+                    position: [0.9, -0.1, -0.5],
+                },
+            ],
+            collider: ColliderShape::Sphere { radius: 1.5 },
+        }
+    }
+}
+
+impl ShipDefinition {
+    /// The cargo capacity used when a definition does not give one.
+    fn default_cargo_capacity() -> f32 {
+        100.
+    }
+
+    /// How this ship flies.
+    pub fn dynamics(&self) -> FlightDynamics {
+        FlightDynamics {
+            max_speed: self.max_speed,
+            acceleration: self.thrust / self.mass,
+            turn_rate: self.turn_rate,
+        }
+    }
+
+    /// A full energy pool for this ship.
+    pub fn energy(&self) -> Energy {
+        Energy::new(self.energy_capacity, self.energy_recharge)
+    }
+
+    /// An empty cargo hold for this ship.
+    pub fn inventory(&self) -> Inventory {
+        Inventory::new(self.cargo_capacity)
+    }
+
+    /// Undamaged health for this ship.
+    pub fn health(&self) -> Health {
+        Health::new(self.health)
+    }
+
+    /// A fully charged shield for this ship.
+    pub fn shield(&self) -> Shield {
+        Shield::new(self.shield_capacity, self.shield_recharge)
+    }
+
+    /// What shots collide with.
+    pub fn collider(&self) -> Collider {
+        match self.collider {
+            ColliderShape::Sphere { radius } => Collider { radius },
+        }
+    }
+}
+
+/// A place on a ship where a weapon can be mounted.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HardpointDefinition {
+    /// The name shown to the player.
+    pub name: String,
+    /// Where the hardpoint sits, in the ship's local space.
+    pub position: [f32; 3],
+}
+
+impl HardpointDefinition {
+    /// Where the hardpoint sits, in the ship's local space.
+    pub fn translation(&self) -> Vec3 {
+        Vec3::from_array(self.position)
+    }
+}
+
+/// The shapes a ship can collide as.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ColliderShape {
+    /// A sphere centered on the ship.
+    Sphere {
+        /// The radius of the sphere, in meters.
+        radius: f32,
+    },
+}
+
+/// Every ship class that can be flown, in the order they are offered to the player.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ShipLibrary {
+    /// Handles to each ship, sorted by their asset path.
+    ships: Vec<Handle<ShipDefinition>>,
+}
+
+impl ShipLibrary {
+    /// Handles to each ship, in the order they are offered to the player.
+    pub fn ships(&self) -> &[Handle<ShipDefinition>] {
+        &self.ships
+    }
+}
+
+impl FromWorld for ShipLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        let ships = match asset_server.load_folder(SHIPS_FOLDER) {
+            Ok(handles) => handles.into_iter().map(HandleUntyped::typed).collect(),
+            Err(error) => {
+                warn!("Could not load ship definitions: {error}");
+                Vec::new()
+            }
+        };
+
+        ShipLibrary { ships }
+    }
+}
+
+/// Keeps the library in a stable order as ship definitions finish loading.
+fn sort_ship_library(
+    mut events: EventReader<AssetEvent<ShipDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<ShipLibrary>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+
+    library.ships.sort_by_cached_key(|handle| {
+        asset_server
+            .get_handle_path(handle)
+            .map(|path| path.path().to_path_buf())
+    });
+}
+
+/// Which class of ship an entity is.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ShipClass(pub Handle<ShipDefinition>);
+
+/// Carries changes to ship definitions over to the ships of each class already in flight, so that
+/// ships can be tuned while the game runs.
+///
+/// Energy, health and shields keep the same fraction of their new capacities. Cargo already aboard
+/// is kept even if the hold shrinks below it.
+fn retune_ships(
+    mut events: EventReader<AssetEvent<ShipDefinition>>,
+    definitions: Res<Assets<ShipDefinition>>,
+    mut query: Query<(
+        &ShipClass,
+        &mut FlightDynamics,
+        &mut Collider,
+        &mut Energy,
+        &mut Health,
+        Option<&mut Shield>,
+        Option<&mut Inventory>,
+    )>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        let Some(definition) = definitions.get(handle) else {
+            continue;
+        };
+
+        let mut retuned = 0;
+        for (class, mut dynamics, mut collider, mut energy, mut health, shield, inventory) in
+            query.iter_mut()
+        {
+            if class.0 != *handle {
+                continue;
+            }
+
+            *dynamics = definition.dynamics();
+            *collider = definition.collider();
+            energy.set_capacity(definition.energy_capacity);
+            energy.recharge_rate = definition.energy_recharge;
+            health.set_max(definition.health);
+            if let Some(mut shield) = shield {
+                shield.set_capacity(definition.shield_capacity);
+                shield.recharge_rate = definition.shield_recharge;
+            }
+            if let Some(mut inventory) = inventory {
+                inventory.capacity = definition.cargo_capacity;
+            }
+            retuned += 1;
+        }
+        info!(
+            "Retuned {retuned} ships to the changed {} class",
+            definition.name
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! A uniform grid of everything that can be targeted or collided with, for "what's near here?"
+//! queries.
+//!
+//! The [`SpatialIndex`] is brought up to date once a tick, after everything has moved. Entities
+//! are only moved between cells when they cross a cell boundary. Despawned entities are dropped
+//! every frame, since frames without a tick would otherwise miss their removal.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::player::targeting::Targetable;
+
+use super::flight::FlightSet;
+use super::geometry::Collider;
+use super::health::HealthSet;
+
+/// The width, height and depth of each grid cell, in meters.
+const CELL_SIZE: f32 = 250.;
+
+/// Spatial partitioning logic
+pub(super) struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialIndex>()
+            .configure_set(FixedUpdate, SpatialSet.after(FlightSet).before(HealthSet))
+            .add_systems(FixedUpdate, update_spatial_index.in_set(SpatialSet))
+            .add_systems(PostUpdate, prune_spatial_index);
+    }
+}
+
+/// Systems that bring the [`SpatialIndex`] up to date.
+///
+/// This runs in [`FixedUpdate`] after [`FlightSet`], so queries made later in the tick see where
+/// everything has just moved to, and queries made earlier see where it was last tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpatialSet;
+
+/// Where every [`Targetable`] entity and [`Collider`] is, bucketed into a uniform grid.
+///
+/// Positions are those of the last tick, and entities despawned since the end of the last frame may
+/// linger until the end of this one, so callers should look up anything they find before relying
+/// on it.
+#[derive(Resource, Debug, Default)]
+pub struct SpatialIndex {
+    /// The entities in each occupied cell.
+    cells: HashMap<IVec3, Vec<Entity>>,
+    /// The cell each entity is in, and its position when last indexed.
+    entries: HashMap<Entity, (IVec3, Vec3)>,
+}
+
+impl SpatialIndex {
+    /// The grid cell containing `position`.
+    fn cell(position: Vec3) -> IVec3 {
+        (position / CELL_SIZE).floor().as_ivec3()
+    }
+
+    /// Adds `entity` at `position`, or moves it there if it is already indexed.
+    fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = Self::cell(position);
+        if let Some((old_cell, old_position)) = self.entries.get_mut(&entity) {
+            *old_position = position;
+            if *old_cell == cell {
+                return;
+            }
+            let old_cell = std::mem::replace(old_cell, cell);
+            self.remove_from_cell(entity, old_cell);
+        } else {
+            self.entries.insert(entity, (cell, position));
+        }
+        self.cells.entry(cell).or_default().push(entity);
+    }
+
+    /// Drops `entity` from the index, if it is there.
+    fn remove(&mut self, entity: Entity) {
+        if let Some((cell, _)) = self.entries.remove(&entity) {
+            self.remove_from_cell(entity, cell);
+        }
+    }
+
+    /// Takes `entity` out of `cell`, forgetting the cell once it is empty.
+    fn remove_from_cell(&mut self, entity: Entity, cell: IVec3) {
+        if let Some(occupants) = self.cells.get_mut(&cell) {
+            occupants.retain(|&occupant| occupant != entity);
+            if occupants.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    /// Every indexed entity within `radius` meters of `position`, with where it was indexed.
+    pub fn within(&self, position: Vec3, radius: f32) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let min = Self::cell(position - Vec3::splat(radius));
+        let max = Self::cell(position + Vec3::splat(radius));
+        let span = max - min + IVec3::ONE;
+        let searched_cells = span.x as i64 * span.y as i64 * span.z as i64;
+
+        // Wide searches of a sparse grid are quicker over the occupied cells than every cell
+        let sparse = searched_cells > self.cells.len() as i64;
+        let candidates: Box<dyn Iterator<Item = &Entity> + '_> = if sparse {
+            Box::new(
+                self.cells
+                    .iter()
+                    .filter(move |(cell, _)| cell.cmpge(min).all() && cell.cmple(max).all())
+                    .flat_map(|(_, occupants)| occupants),
+            )
+        } else {
+            let cells = (min.x..=max.x).flat_map(move |x| {
+                (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z)))
+            });
+            Box::new(cells.filter_map(|cell| self.cells.get(&cell)).flatten())
+        };
+
+        candidates.filter_map(move |&entity| {
+            let (_, indexed) = self.entries[&entity];
+            (indexed.distance_squared(position) <= radius * radius).then_some((entity, indexed))
+        })
+    }
+
+    /// The closest indexed entity within `radius` meters of `position` that passes `filter`, and
+    /// how far away it is.
+    pub fn nearest(
+        &self,
+        position: Vec3,
+        radius: f32,
+        mut filter: impl FnMut(Entity) -> bool,
+    ) -> Option<(Entity, f32)> {
+        self.within(position, radius)
+            .filter(|&(entity, _)| filter(entity))
+            .map(|(entity, indexed)| (entity, indexed.distance(position)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+/// Re-buckets everything that has moved or become indexable.
+fn update_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    changed: Query<
+        (Entity, &Transform),
+        (
+            Or<(With<Targetable>, With<Collider>)>,
+            Or<(Changed<Transform>, Added<Targetable>, Added<Collider>)>,
+        ),
+    >,
+) {
+    for (entity, transform) in changed.iter() {
+        index.insert(entity, transform.translation);
+    }
+}
+
+/// Drops whatever is no longer indexable, having been despawned or lost the components that made
+/// it so.
+///
+/// Removed components are only remembered for a frame or two, so this runs every frame rather than
+/// every tick.
+fn prune_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    indexable: Query<(), Or<(With<Targetable>, With<Collider>)>>,
+    mut removed_targetables: RemovedComponents<Targetable>,
+    mut removed_colliders: RemovedComponents<Collider>,
+) {
+    for entity in removed_targetables.iter().chain(removed_colliders.iter()) {
+        if !indexable.contains(entity) {
+            index.remove(entity);
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The entities found within `radius` of `position`, in a stable order.
+    fn found(index: &SpatialIndex, position: Vec3, radius: f32) -> Vec<Entity> {
+        let mut found: Vec<Entity> = index
+            .within(position, radius)
+            .map(|(entity, _)| entity)
+            .collect();
+        found.sort();
+        found
+    }
+
+    /// Entities are found by distance, not by whether their cell overlaps the search.
+    #[test]
+    fn within_finds_entities_in_range() {
+        let near = Entity::from_raw(0);
+        let far = Entity::from_raw(1);
+        let mut index = SpatialIndex::default();
+        index.insert(near, Vec3::new(10., 0., 0.));
+        index.insert(far, Vec3::new(60., 0., 0.));
+
+        assert_eq!(found(&index, Vec3::ZERO, 50.), vec![near]);
+        assert_eq!(found(&index, Vec3::ZERO, 100.), vec![near, far]);
+    }
+
+    /// Searches reach into neighbouring cells, whether they are made cell by cell or over the
+    /// occupied cells.
+    #[test]
+    fn within_searches_across_cells() {
+        let entities: Vec<Entity> = (0..4).map(Entity::from_raw).collect();
+        let mut index = SpatialIndex::default();
+        index.insert(entities[0], Vec3::new(-1., 0., 0.));
+        index.insert(entities[1], Vec3::new(1., 0., 0.));
+        index.insert(entities[2], Vec3::new(0., CELL_SIZE + 1., 0.));
+        index.insert(entities[3], Vec3::new(0., 0., -CELL_SIZE * 10.));
+        // Enough distant cells that small searches go cell by cell, and large ones don't
+        for filler in 10..20 {
+            let position = Vec3::new(CELL_SIZE * (100 + filler) as f32, 0., 0.);
+            index.insert(Entity::from_raw(filler), position);
+        }
+
+        assert_eq!(found(&index, Vec3::ZERO, 10.), entities[..2].to_vec());
+        assert_eq!(
+            found(&index, Vec3::ZERO, CELL_SIZE * 2.),
+            entities[..3].to_vec()
+        );
+        assert_eq!(found(&index, Vec3::ZERO, CELL_SIZE * 20.), entities);
+    }
+
+    /// Moving an entity into another cell stops it from being found in the old one.
+    #[test]
+    fn insert_moves_entities_between_cells() {
+        let entity = Entity::from_raw(0);
+        let mut index = SpatialIndex::default();
+        index.insert(entity, Vec3::ZERO);
+
+        index.insert(entity, Vec3::splat(CELL_SIZE * 3.));
+
+        assert!(found(&index, Vec3::ZERO, 10.).is_empty());
+        assert_eq!(
+            found(&index, Vec3::splat(CELL_SIZE * 3.), 10.),
+            vec![entity]
+        );
+        assert_eq!(index.cells.len(), 1);
+    }
+
+    /// Removed entities are no longer found, and empty cells are forgotten.
+    #[test]
+    fn remove_drops_entities() {
+        let kept = Entity::from_raw(0);
+        let removed = Entity::from_raw(1);
+        let mut index = SpatialIndex::default();
+        index.insert(kept, Vec3::ZERO);
+        index.insert(removed, Vec3::ONE);
+        index.insert(removed, Vec3::splat(CELL_SIZE * 2.));
+
+        index.remove(removed);
+        index.remove(removed);
+
+        assert_eq!(found(&index, Vec3::ZERO, CELL_SIZE * 10.), vec![kept]);
+        assert_eq!(index.cells.len(), 1);
+        assert!(!index.entries.contains_key(&removed));
+    }
+
+    /// The nearest entity that passes the filter is found, with how far away it is.
+    #[test]
+    fn nearest_skips_filtered_entities() {
+        let closest = Entity::from_raw(0);
+        let next = Entity::from_raw(1);
+        let mut index = SpatialIndex::default();
+        index.insert(closest, Vec3::new(5., 0., 0.));
+        index.insert(next, Vec3::new(0., CELL_SIZE + 20., 0.));
+
+        assert_eq!(
+            index.nearest(Vec3::ZERO, 500., |_| true),
+            Some((closest, 5.))
+        );
+        assert_eq!(
+            index.nearest(Vec3::ZERO, 500., |entity| entity != closest),
+            Some((next, CELL_SIZE + 20.))
+        );
+        assert_eq!(
+            index.nearest(Vec3::ZERO, 100., |entity| entity != closest),
+            None
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Stations, and docking with them.
+
+use bevy::prelude::*;
+
+use crate::game_state::InGame;
+
+use super::economy::Market;
+use super::energy::EnergySet;
+use super::factions::Faction;
+use super::flight::{FlightSet, Throttle, Velocity};
+use super::geometry::Collider;
+use super::SimulationAppExt;
+
+/// Station logic
+pub(super) struct StationsPlugin;
+
+impl Plugin for StationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<ShipDocked>()
+            .add_simulation_event::<ShipUndocked>()
+            .add_systems(
+                FixedUpdate,
+                (dock_or_undock, hold_docked_ships)
+                    .chain()
+                    .after(EnergySet)
+                    .before(FlightSet),
+            );
+    }
+}
+
+/// A place where ships can dock.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Station {
+    /// The name shown to the player.
+    pub name: String,
+}
+
+/// How close, and how slowly, ships must approach a [`Station`] to dock with it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct DockingPort {
+    /// How far from the station's center ships can dock, in meters.
+    pub radius: f32,
+    /// The fastest a ship can be moving and still dock, in meters per second.
+    pub max_speed: f32,
+}
+
+impl Default for DockingPort {
+    fn default() -> Self {
+        DockingPort {
+            radius: 120.,
+            max_speed: 15.,
+        }
+    }
+}
+
+/// Everything needed to spawn a station.
+#[derive(Bundle, Debug)]
+pub struct StationBundle {
+    /// The station itself.
+    pub station: Station,
+    /// Where ships dock.
+    pub port: DockingPort,
+    /// Who runs it.
+    pub faction: Faction,
+    /// What it trades.
+    pub market: Market,
+    /// What shots hit.
+    pub collider: Collider,
+    /// Where it is.
+    pub spatial: SpatialBundle,
+    /// Despawns it when play ends.
+    pub in_game: InGame,
+}
+
+impl StationBundle {
+    /// Creates a station called `name` at `position`.
+    pub fn new(name: impl Into<String>, position: Vec3) -> Self {
+        StationBundle {
+            station: Station { name: name.into() },
+            port: DockingPort::default(),
+            faction: Faction::Aegir,
+            market: Market::default(),
+            collider: Collider { radius: 40. },
+            spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+            in_game: InGame,
+        }
+    }
+}
+
+/// Lets a ship dock with stations.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DockingComputer {
+    /// Has the pilot asked to dock, or to undock if already docked?
+    pub requested: bool,
+    /// The station the ship is docked with, if any.
+    docked: Option<Entity>,
+}
+
+impl DockingComputer {
+    /// The station the ship is docked with, if any.
+    pub fn docked(&self) -> Option<Entity> {
+        self.docked
+    }
+}
+
+/// A ship has docked with a station.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShipDocked {
+    /// The ship that docked.
+    pub ship: Entity,
+    /// The station it docked with.
+    pub station: Entity,
+}
+
+/// A ship has left the station it was docked with.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShipUndocked {
+    /// The ship that undocked.
+    pub ship: Entity,
+    /// The station it left.
+    pub station: Entity,
+}
+
+/// Docks ships that ask to while inside a port and slow enough, and undocks those that ask to
+/// leave.
+fn dock_or_undock(
+    mut ships: Query<(Entity, &Transform, &Velocity, &mut DockingComputer)>,
+    stations: Query<(Entity, &Transform, &DockingPort), With<Station>>,
+    mut docked_events: EventWriter<ShipDocked>,
+    mut undocked_events: EventWriter<ShipUndocked>,
+) {
+    for (ship, transform, velocity, mut computer) in ships.iter_mut() {
+        if !computer.requested {
+            continue;
+        }
+        computer.requested = false;
+
+        if let Some(station) = computer.docked.take() {
+            undocked_events.send(ShipUndocked { ship, station });
+            continue;
+        }
+
+        let speed = velocity.0.length();
+        let port = stations
+            .iter()
+            .filter(|(_, station_transform, port)| {
+                speed <= port.max_speed
+                    && station_transform
+                        .translation
+                        .distance(transform.translation)
+                        <= port.radius
+            })
+            .map(|(station, ..)| station)
+            .next();
+
+        if let Some(station) = port {
+            computer.docked = Some(station);
+            docked_events.send(ShipDocked { ship, station });
+        }
+    }
+}
+
+/// Holds docked ships still, undocking them if their station disappears.
+fn hold_docked_ships(
+    mut ships: Query<(&mut Velocity, &mut Throttle, &mut DockingComputer)>,
+    stations: Query<(), With<Station>>,
+) {
+    for (mut velocity, mut throttle, mut computer) in ships.iter_mut() {
+        let Some(station) = computer.docked else {
+            continue;
+        };
+        if !stations.contains(station) {
+            computer.docked = None;
+            continue;
+        }
+
+        velocity.0 = Vec3::ZERO;
+        *throttle = Throttle::STOP;
+    }
+}
@@ -0,0 +1,280 @@
+//! Controlling how fast simulated time passes.
+//!
+//! The simulation always ticks [`TICK_RATE`](super::TICK_RATE) times each second, but each tick
+//! advances the world by the fixed timestep multiplied by the [`TimeScale`]. Simulation systems
+//! read their time step from [`SimulationTime`], so slowing time slows everything evenly while
+//! motion stays smooth.
+//!
+//! Ships with [`SlowMotion`] can slow time for a few seconds at a time. The `time` console command
+//! changes the scale directly, and freezes the simulation to step through it a tick at a time.
+
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::time::TimeSystem;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::net::{Client, Server};
+
+use super::navigation::NavigationSet;
+
+/// How quickly slow motion eases in and out, in scale per real second.
+const SLOW_MOTION_EASING: f32 = 4.;
+
+/// The slowest and fastest the console can set time to pass.
+const SCALE_RANGE: (f32, f32) = (0.05, 4.);
+
+/// Time control logic
+pub(super) struct TimeControlPlugin;
+
+impl Plugin for TimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .add_console_command(
+                "time",
+                "time <scale <factor>|pause|resume|step [ticks]>",
+                time_command,
+            )
+            .configure_set(FixedUpdate, TimeSet.before(NavigationSet))
+            .add_systems(First, step_paused_simulation.before(TimeSystem))
+            .add_systems(FixedUpdate, engage_slow_motion.in_set(TimeSet));
+    }
+}
+
+/// Systems that decide how fast time passes during each tick.
+///
+/// This runs in [`FixedUpdate`] before [`NavigationSet`], so that everything else in the tick
+/// advances by the same step.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeSet;
+
+/// How fast simulated time passes, relative to real time.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale {
+    /// How fast time passes when nothing is slowing it, set from the console.
+    base: f32,
+    /// How far [`SlowMotion`] is slowing time, easing between `1.0` and its scale.
+    slow_motion: f32,
+    /// Is the simulation frozen until it is stepped or resumed from the console?
+    paused: bool,
+    /// How many ticks to run while paused.
+    steps: u32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale {
+            base: 1.,
+            slow_motion: 1.,
+            paused: false,
+            steps: 0,
+        }
+    }
+}
+
+impl TimeScale {
+    /// How fast simulated time is passing, as a multiple of real time.
+    pub fn scale(&self) -> f32 {
+        self.base * self.slow_motion
+    }
+
+    /// Is slow motion slowing time at all?
+    pub fn is_slowed(&self) -> bool {
+        self.slow_motion < 1.
+    }
+
+    /// Is the simulation frozen until it is stepped or resumed from the console?
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// How much simulated time passes each tick.
+///
+/// Systems in [`FixedUpdate`] read their time step from here rather than from [`FixedTime`], so
+/// that the [`TimeScale`] slows them all alike.
+#[derive(SystemParam)]
+pub struct SimulationTime<'w> {
+    /// The real time between ticks.
+    fixed_time: Res<'w, FixedTime>,
+    /// How fast simulated time is passing.
+    time_scale: Res<'w, TimeScale>,
+}
+
+impl SimulationTime<'_> {
+    /// How much simulated time passes each tick.
+    pub fn delta(&self) -> Duration {
+        self.fixed_time.period.mul_f32(self.time_scale.scale())
+    }
+
+    /// How much simulated time passes each tick, in seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.fixed_time.period.as_secs_f32() * self.time_scale.scale()
+    }
+}
+
+/// A ship's ability to slow time for a few seconds, for the big moments of a dogfight.
+///
+/// Its timings are in real seconds, so slow motion lasts as long as it feels like it does.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct SlowMotion {
+    /// How fast time passes while slowed, as a multiple of real time.
+    pub scale: f32,
+    /// How long time stays slowed each use, in seconds.
+    pub duration: f32,
+    /// How long after slow motion ends before it can be used again, in seconds.
+    pub cooldown_time: f32,
+    /// Has the pilot asked to slow time, or to end slow motion early?
+    pub requested: bool,
+    /// How long time has left to stay slowed, in seconds.
+    remaining: f32,
+    /// How long until slow motion can be used again, in seconds.
+    cooldown: f32,
+}
+
+impl Default for SlowMotion {
+    fn default() -> Self {
+        SlowMotion {
+            scale: 0.3,
+            duration: 4.,
+            cooldown_time: 20.,
+            requested: false,
+            remaining: 0.,
+            cooldown: 0.,
+        }
+    }
+}
+
+impl SlowMotion {
+    /// Is time being slowed?
+    pub fn is_engaged(&self) -> bool {
+        self.remaining > 0.
+    }
+
+    /// How long until slow motion can be used again, in seconds.
+    pub fn cooldown(&self) -> f32 {
+        self.cooldown
+    }
+
+    /// How much of the current use is left, between `0.0` and `1.0`.
+    pub fn remaining_fraction(&self) -> f32 {
+        if self.duration > 0. {
+            self.remaining / self.duration
+        } else {
+            0.
+        }
+    }
+}
+
+/// Slows time while a ship has asked for slow motion, then eases it back to normal and starts the
+/// cooldown.
+///
+/// Co-op games cannot be slowed, since the other players are still flying at full speed.
+fn engage_slow_motion(
+    fixed_time: Res<FixedTime>,
+    server: Option<Res<Server>>,
+    client: Option<Res<Client>>,
+    mut time_scale: ResMut<TimeScale>,
+    mut query: Query<&mut SlowMotion>,
+) {
+    let delta_time = fixed_time.period.as_secs_f32();
+    let multiplayer = server.is_some() || client.is_some();
+    let mut target = 1.;
+
+    for mut slow_motion in query.iter_mut() {
+        let requested = std::mem::take(&mut slow_motion.requested) && !multiplayer;
+
+        if slow_motion.is_engaged() {
+            slow_motion.remaining = if requested {
+                0.
+            } else {
+                (slow_motion.remaining - delta_time).max(0.)
+            };
+            if !slow_motion.is_engaged() {
+                slow_motion.cooldown = slow_motion.cooldown_time;
+            }
+        } else {
+            slow_motion.cooldown = (slow_motion.cooldown - delta_time).max(0.);
+            if requested && slow_motion.cooldown <= 0. {
+                slow_motion.remaining = slow_motion.duration;
+            }
+        }
+
+        if slow_motion.is_engaged() {
+            target = f32::min(target, slow_motion.scale);
+        }
+    }
+
+    let step = SLOW_MOTION_EASING * delta_time;
+    time_scale.slow_motion += (target - time_scale.slow_motion).clamp(-step, step);
+}
+
+/// Keeps the simulation frozen while it is paused from the console, running a single tick for
+/// each step asked for.
+///
+/// This runs before [`Time`] is updated, so that no real time leaks into the simulation while it
+/// is paused.
+fn step_paused_simulation(
+    mut time: ResMut<Time>,
+    mut fixed_time: ResMut<FixedTime>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    if !time_scale.paused {
+        return;
+    }
+    // Leaving photo mode unfreezes time, so freeze it again before the frame's time is measured
+    if !time.is_paused() {
+        time.pause();
+    }
+
+    if time_scale.steps > 0 {
+        time_scale.steps -= 1;
+        let period = fixed_time.period;
+        fixed_time.tick(period);
+    }
+}
+
+/// Console command that changes how fast time passes, or pauses and steps through the simulation.
+fn time_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    match *arguments {
+        ["scale", factor] => {
+            let factor = factor
+                .parse::<f32>()
+                .map_err(|_| format!("`{factor}` is not a number"))?;
+            let (min, max) = SCALE_RANGE;
+            if !(min..=max).contains(&factor) {
+                return Err(format!("the scale must be between {min} and {max}"));
+            }
+            world.resource_mut::<TimeScale>().base = factor;
+            Ok(format!("time passing at {factor}x"))
+        }
+        ["pause"] => {
+            world.resource_mut::<TimeScale>().paused = true;
+            world.resource_mut::<Time>().pause();
+            Ok("simulation paused".to_string())
+        }
+        ["resume"] => {
+            let mut time_scale = world.resource_mut::<TimeScale>();
+            time_scale.paused = false;
+            time_scale.steps = 0;
+            world.resource_mut::<Time>().unpause();
+            Ok("simulation resumed".to_string())
+        }
+        ["step", ref rest @ ..] => {
+            let ticks = match *rest {
+                [] => 1,
+                [ticks] => ticks
+                    .parse::<u32>()
+                    .map_err(|_| format!("`{ticks}` is not a whole number"))?,
+                _ => return Err("expected at most one tick count".to_string()),
+            };
+            let mut time_scale = world.resource_mut::<TimeScale>();
+            time_scale.paused = true;
+            time_scale.steps += ticks;
+            world.resource_mut::<Time>().pause();
+            Ok(format!("stepping {ticks} ticks"))
+        }
+        _ => Err("expected `scale <factor>`, `pause`, `resume` or `step [ticks]`".to_string()),
+    }
+}
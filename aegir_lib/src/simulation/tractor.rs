@@ -0,0 +1,146 @@
+//! Tractor beams, which grab small objects and hold them in front of a ship on a spring.
+
+use bevy::prelude::*;
+
+use super::energy::EnergySet;
+use super::flight::{FlightSet, Velocity};
+use super::geometry::{ray_sphere_distance, Collider};
+use super::time_control::SimulationTime;
+
+/// Tractor beam logic
+pub(super) struct TractorPlugin;
+
+impl Plugin for TractorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (release_or_grab, pull_held_objects)
+                .chain()
+                .after(EnergySet)
+                .before(FlightSet),
+        );
+    }
+}
+
+/// Marks objects light enough to be picked up by a [`TractorBeam`].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Grabbable {
+    /// How heavy the object is, which slows how quickly it is pulled, in tonnes.
+    pub mass: f32,
+}
+
+/// Marks objects currently held by a [`TractorBeam`], so they are not cleaned up mid-grab.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tethered {
+    /// The ship holding the object.
+    pub by: Entity,
+}
+
+/// A beam that grabs the [`Grabbable`] object a ship is aiming at and pulls it to a point ahead.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct TractorBeam {
+    /// How far away objects can be grabbed from, in meters.
+    pub range: f32,
+    /// How far in front of the ship held objects are pulled to, in meters.
+    pub hold_distance: f32,
+    /// How hard the spring pulls, in newtons per meter per tonne.
+    pub stiffness: f32,
+    /// How strongly the spring resists motion relative to the ship.
+    pub damping: f32,
+    /// The heaviest object the beam can lift, in tonnes.
+    pub max_mass: f32,
+    /// Is the pilot holding the beam on?
+    pub active: bool,
+    /// The object being held, if any.
+    held: Option<Entity>,
+}
+
+impl Default for TractorBeam {
+    fn default() -> Self {
+        TractorBeam {
+            range: 60.,
+            hold_distance: 8.,
+            stiffness: 12.,
+            damping: 5.,
+            max_mass: 5.,
+            active: false,
+            held: None,
+        }
+    }
+}
+
+impl TractorBeam {
+    /// The object being held, if any.
+    pub fn held(&self) -> Option<Entity> {
+        self.held
+    }
+}
+
+/// Lets go of objects when beams switch off, and grabs whatever active beams are aimed at.
+fn release_or_grab(
+    mut commands: Commands,
+    mut beams: Query<(Entity, &Transform, &mut TractorBeam)>,
+    objects: Query<(Entity, &Transform, &Collider, &Grabbable), Without<Tethered>>,
+    tethered: Query<(), With<Tethered>>,
+) {
+    for (ship, transform, mut beam) in beams.iter_mut() {
+        if let Some(held) = beam.held {
+            if !tethered.contains(held) {
+                // The object was destroyed or taken by another beam
+                beam.held = None;
+            } else if !beam.active {
+                // It keeps whatever velocity the spring gave it, so letting go throws it
+                commands.entity(held).remove::<Tethered>();
+                beam.held = None;
+            }
+            continue;
+        }
+        if !beam.active {
+            continue;
+        }
+
+        let origin = transform.translation;
+        let direction = transform.forward();
+        let target = objects
+            .iter()
+            .filter(|(.., grabbable)| grabbable.mass <= beam.max_mass)
+            .filter_map(|(entity, object_transform, collider, _)| {
+                ray_sphere_distance(
+                    origin,
+                    direction,
+                    object_transform.translation,
+                    collider.radius,
+                )
+                .filter(|&distance| distance <= beam.range)
+                .map(|distance| (entity, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((entity, _)) = target {
+            commands.entity(entity).insert(Tethered { by: ship });
+            beam.held = Some(entity);
+        }
+    }
+}
+
+/// Pulls held objects towards the hold point in front of their ship with a damped spring.
+fn pull_held_objects(
+    time: SimulationTime,
+    ships: Query<(&Transform, &Velocity, &TractorBeam)>,
+    mut objects: Query<(&Transform, &mut Velocity, &Grabbable, &Tethered), Without<TractorBeam>>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (transform, mut velocity, grabbable, tethered) in objects.iter_mut() {
+        let Ok((ship_transform, ship_velocity, beam)) = ships.get(tethered.by) else {
+            continue;
+        };
+
+        let hold_point = ship_transform.translation + ship_transform.forward() * beam.hold_distance;
+        let stretch = hold_point - transform.translation;
+        let relative_velocity = velocity.0 - ship_velocity.0;
+        let acceleration =
+            (stretch * beam.stiffness - relative_velocity * beam.damping) / grabbable.mass.max(1.);
+        velocity.0 += acceleration * delta_time;
+    }
+}
@@ -0,0 +1,400 @@
+//! Waves of hostile ships that hunt the player, read from a `.waves.ron` table.
+//!
+//! Each wave arrives from beyond sensor range once the last is cleared. The director watches how
+//! long the player takes to clear each wave and how much damage they take doing it, and raises or
+//! lowers an intensity that scales how many ships come next and how soon.
+
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::game_state::GameState;
+use crate::player::ship::PlayerShip;
+
+use super::ai::{spawn_ai_ship, AiGoal, AiPilot, Squadrons};
+use super::factions::Faction;
+use super::health::{Damaged, Destroyed, HealthSet};
+use super::random::RngStream;
+use super::ron_asset::RonAssetLoader;
+use super::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use super::time_control::SimulationTime;
+use super::weapons::{WeaponDefinition, WeaponLibrary};
+use super::WorldSeed;
+
+/// The wave table flown unless another is chosen.
+const DEFAULT_WAVE_TABLE: &str = "waves/default.waves.ron";
+
+/// How far apart neighbouring ships in a group spawn, in meters.
+const GROUP_SPACING: f32 = 25.;
+
+/// Wave spawning logic
+pub(super) struct WavesPlugin;
+
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<WaveTable>()
+            .add_asset_loader(RonAssetLoader::<WaveTable>::new(&["waves.ron"]))
+            .init_resource::<WaveDirector>()
+            .add_console_command("waves", "waves <on|off>", waves_command)
+            .add_systems(OnExit(GameState::Playing), reset_waves)
+            .add_systems(
+                FixedUpdate,
+                (track_waves, spawn_waves)
+                    .chain()
+                    .after(HealthSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The waves to send, and how to pace them, as loaded from a `.waves.ron` file.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "8f3b2c61-5d47-4e9a-a0c3-6e19d7b45f28"]
+pub struct WaveTable {
+    /// How long after waves are switched on the first one arrives, in seconds.
+    pub first_wave_delay: f32,
+    /// How long after a wave is cleared the next one arrives, in seconds, at an intensity of one.
+    pub interval: f32,
+    /// How far from the player waves spawn, in meters, which should be beyond sensor range.
+    pub spawn_distance: f32,
+    /// Each wave in turn. Once they run out the last is repeated, growing each time.
+    pub waves: Vec<WaveDefinition>,
+    /// How many ships each group of the last wave gains every time it is repeated.
+    #[serde(default)]
+    pub escalation: u32,
+    /// How the director adapts to the player.
+    pub pacing: Pacing,
+}
+
+/// The ships that make up one wave.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WaveDefinition {
+    /// Groups of ships, each spawning from its own direction.
+    pub groups: Vec<WaveGroup>,
+}
+
+/// Ships of one class that arrive together.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WaveGroup {
+    /// The name of the ship class, from the ship library.
+    pub ship: String,
+    /// How many ships there are at an intensity of one.
+    pub count: u32,
+    /// The name of the weapon fitted to every hardpoint, from the weapon library, or `None` for
+    /// the library's first.
+    #[serde(default)]
+    pub weapon: Option<String>,
+}
+
+/// How the director judges the player's performance, and how far it adapts to it.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Pacing {
+    /// How long a wave should take to clear, in seconds.
+    pub target_clear_time: f32,
+    /// How much damage the player should take clearing a wave.
+    pub target_damage_taken: f32,
+    /// How much intensity changes after each wave.
+    pub step: f32,
+    /// The lowest intensity the director drops to.
+    pub min_intensity: f32,
+    /// The highest intensity the director climbs to.
+    pub max_intensity: f32,
+}
+
+impl Pacing {
+    /// The intensity to play at after a wave at `intensity` took `clear_time` seconds to clear
+    /// and cost `damage_taken` health.
+    pub fn adapt(&self, intensity: f32, clear_time: f32, damage_taken: f32) -> f32 {
+        // Whichever measure is furthest over its target decides how hard the player found it
+        let strain =
+            (clear_time / self.target_clear_time).max(damage_taken / self.target_damage_taken);
+
+        let intensity = if strain < 0.75 {
+            intensity + self.step
+        } else if strain > 1.25 {
+            intensity - self.step
+        } else {
+            intensity
+        };
+        intensity.clamp(self.min_intensity, self.max_intensity)
+    }
+}
+
+/// Marks ships that belong to a wave, by the wave's number.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WaveMember(pub u32);
+
+/// A wave that has spawned and not yet been cleared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WaveInProgress {
+    /// How many of its ships are left.
+    remaining: u32,
+    /// How long ago it spawned, in seconds.
+    elapsed: f32,
+    /// How much damage the player has taken since it spawned.
+    damage_taken: f32,
+}
+
+/// Decides when waves spawn, and how big they are.
+#[derive(Resource, Debug, Clone)]
+pub struct WaveDirector {
+    /// Are waves being sent?
+    enabled: bool,
+    /// The table waves are read from.
+    table: Handle<WaveTable>,
+    /// How many waves have spawned since play began.
+    wave: u32,
+    /// How long until the next wave spawns, in seconds, once the last has been cleared.
+    countdown: Option<f32>,
+    /// The wave the player is fighting, if any.
+    in_progress: Option<WaveInProgress>,
+    /// How hard the director is pushing the player, scaling wave sizes and shortening the wait
+    /// between waves.
+    intensity: f32,
+}
+
+impl FromWorld for WaveDirector {
+    fn from_world(world: &mut World) -> Self {
+        let table = world.resource::<AssetServer>().load(DEFAULT_WAVE_TABLE);
+
+        WaveDirector {
+            enabled: false,
+            table,
+            wave: 0,
+            countdown: None,
+            in_progress: None,
+            intensity: 1.,
+        }
+    }
+}
+
+impl WaveDirector {
+    /// Are waves being sent?
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts or stops sending waves. Ships already sent keep fighting.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.countdown = None;
+    }
+
+    /// How many waves have spawned since play began.
+    pub fn wave(&self) -> u32 {
+        self.wave
+    }
+
+    /// How hard the director is pushing the player, where one plays the table as written.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Forgets every wave, ready for play to begin again.
+    fn reset(&mut self) {
+        self.wave = 0;
+        self.countdown = None;
+        self.in_progress = None;
+        self.intensity = 1.;
+    }
+}
+
+/// Forgets the waves of the game that just ended.
+fn reset_waves(mut director: ResMut<WaveDirector>) {
+    director.reset();
+}
+
+/// Counts down the current wave's ships and the damage they deal, adapting the pacing once it is
+/// cleared, and sends any ships left without a goal after the player.
+fn track_waves(
+    time: SimulationTime,
+    tables: Res<Assets<WaveTable>>,
+    mut director: ResMut<WaveDirector>,
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventReader<Destroyed>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    mut member_query: Query<&mut AiPilot, With<WaveMember>>,
+) {
+    let delta_time = time.delta_seconds();
+    let player = player_query.get_single().ok();
+
+    if let Some(player) = player {
+        for mut pilot in member_query.iter_mut() {
+            if pilot.goal == AiGoal::Hold {
+                pilot.goal = AiGoal::Attack(player);
+            }
+        }
+    }
+
+    let damage_taken: f32 = damaged
+        .iter()
+        .filter(|event| Some(event.target) == player)
+        .map(|event| event.amount)
+        .sum();
+    let lost = destroyed
+        .iter()
+        .filter(|event| member_query.contains(event.entity))
+        .count() as u32;
+
+    let intensity = director.intensity;
+    let Some(progress) = director.in_progress.as_mut() else {
+        return;
+    };
+    progress.elapsed += delta_time;
+    progress.damage_taken += damage_taken;
+    progress.remaining = progress.remaining.saturating_sub(lost);
+    // Ships can also leave without being destroyed, when the player jumps to another sector
+    progress.remaining = progress.remaining.min(member_query.iter().len() as u32);
+    if progress.remaining > 0 {
+        return;
+    }
+
+    let progress = *progress;
+    director.in_progress = None;
+    if let Some(table) = tables.get(&director.table) {
+        director.intensity = table
+            .pacing
+            .adapt(intensity, progress.elapsed, progress.damage_taken);
+        director.countdown = Some(table.interval / director.intensity);
+    }
+    info!(
+        "Wave {} cleared in {:.0}s, taking {:.0} damage; intensity is now {:.2}",
+        director.wave, progress.elapsed, progress.damage_taken, director.intensity
+    );
+}
+
+/// Spawns the next wave once its countdown runs out, out of sight of the player.
+#[allow(clippy::too_many_arguments)]
+fn spawn_waves(
+    mut commands: Commands,
+    time: SimulationTime,
+    world_seed: Res<WorldSeed>,
+    tables: Res<Assets<WaveTable>>,
+    ship_library: Res<ShipLibrary>,
+    ship_definitions: Res<Assets<ShipDefinition>>,
+    weapon_library: Res<WeaponLibrary>,
+    weapon_definitions: Res<Assets<WeaponDefinition>>,
+    mut squadrons: ResMut<Squadrons>,
+    mut director: ResMut<WaveDirector>,
+    player_query: Query<&Transform, With<PlayerShip>>,
+) {
+    if !director.enabled || director.in_progress.is_some() {
+        return;
+    }
+    let Some(table) = tables.get(&director.table) else {
+        return;
+    };
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let countdown = director.countdown.get_or_insert(table.first_wave_delay);
+    *countdown -= time.delta_seconds();
+    if *countdown > 0. {
+        return;
+    }
+    director.countdown = None;
+
+    let Some(last) = table.waves.len().checked_sub(1) else {
+        warn!("The wave table has no waves");
+        director.enabled = false;
+        return;
+    };
+    let index = director.wave as usize;
+    let wave = &table.waves[index.min(last)];
+    let repeats = index.saturating_sub(last) as u32;
+
+    let mut rng = world_seed.rng(RngStream::Wave(director.wave));
+    let mut spawned = 0;
+
+    for group in &wave.groups {
+        let Some((class, definition)) = ship_library
+            .ships()
+            .iter()
+            .filter_map(|handle| Some((handle, ship_definitions.get(handle)?)))
+            .find(|(_, definition)| definition.name == group.ship)
+        else {
+            warn!(
+                "The wave table names an unknown ship class `{}`",
+                group.ship
+            );
+            continue;
+        };
+        let weapon = match &group.weapon {
+            Some(name) => {
+                let weapon = weapon_library.weapons().iter().find(|handle| {
+                    weapon_definitions
+                        .get(handle)
+                        .is_some_and(|definition| &definition.name == name)
+                });
+                if weapon.is_none() {
+                    warn!("The wave table names an unknown weapon `{name}`");
+                }
+                weapon
+            }
+            None => weapon_library.weapons().first(),
+        };
+
+        let count = (group.count + repeats * table.escalation) as f32 * director.intensity;
+        let count = (count.round() as u32).max(1);
+
+        let direction = Vec3::new(
+            rng.gen_range(-1. ..1.),
+            rng.gen_range(-0.25..0.25),
+            rng.gen_range(-1. ..1.),
+        )
+        .try_normalize()
+        .unwrap_or(Vec3::Z);
+        let center = player_transform.translation + direction * table.spawn_distance;
+        let lateral = direction.cross(Vec3::Y).try_normalize().unwrap_or(Vec3::X);
+        let squadron = squadrons.allocate();
+
+        for index in 0..count {
+            let offset = (index as f32 - (count as f32 - 1.) / 2.) * GROUP_SPACING;
+            let transform = Transform::from_translation(center + lateral * offset)
+                .looking_at(player_transform.translation, Vec3::Y);
+
+            let ship = spawn_ai_ship(
+                &mut commands,
+                transform,
+                definition,
+                weapon,
+                Faction::Pirate,
+                squadron,
+                AiPilot::default(),
+            );
+            commands
+                .entity(ship)
+                .insert((ShipClass(class.clone()), WaveMember(director.wave + 1)));
+            spawned += 1;
+        }
+    }
+
+    director.wave += 1;
+    director.in_progress = Some(WaveInProgress {
+        remaining: spawned,
+        elapsed: 0.,
+        damage_taken: 0.,
+    });
+    info!("Wave {} incoming: {spawned} ships", director.wave);
+}
+
+/// Console command that starts or stops sending waves.
+fn waves_command(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let enabled = match *arguments {
+        ["on"] => true,
+        ["off"] => false,
+        _ => return Err("expected `on` or `off`".to_string()),
+    };
+
+    let mut director = world.resource_mut::<WaveDirector>();
+    director.set_enabled(enabled);
+
+    Ok(if enabled {
+        format!("waves on, from wave {}", director.wave() + 1)
+    } else {
+        "waves off".to_string()
+    })
+}
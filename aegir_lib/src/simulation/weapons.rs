@@ -0,0 +1,801 @@
+//! Weapons mounted on hardpoints, and the projectiles and beams they fire.
+//!
+//! Weapons are described by [`WeaponDefinition`] assets, loaded from `.weapon.ron` files in the
+//! `weapons` asset folder. Projectiles are drawn from an [`EntityPool`], and returned to it when
+//! they hit something or fizzle out. Beams have no entity of their own: each tick a firing beam
+//! weapon casts a ray from its hardpoint, damaging the first thing it touches.
+//!
+//! Before anything fires, each weapon is aimed: angled in from its hardpoint so that the ship's
+//! shots meet at the weapon's convergence range, then, if it is gimballed, turned as far as its
+//! gimbal allows towards the point its pilot is aiming at.
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use serde::Deserialize;
+
+use crate::game_state::InGame;
+use crate::pooling::EntityPool;
+
+use super::energy::{Energy, PowerDistribution, Subsystem};
+use super::flight::{FlightControls, FlightSet, Velocity};
+use super::geometry::{ray_sphere_distance, Collider};
+use super::health::{Damaged, HealthSet};
+use super::ron_asset::RonAssetLoader;
+use super::spatial::{SpatialIndex, SpatialSet};
+use super::time_control::SimulationTime;
+use super::SimulationAppExt;
+
+/// The weapons that can be fitted, in the order they are offered to the player.
+const WEAPON_PATHS: [&str; 5] = [
+    "weapons/pulse_laser.weapon.ron",
+    "weapons/mass_driver.weapon.ron",
+    "weapons/scatter_gun.weapon.ron",
+    "weapons/seeker_missile.weapon.ron",
+    "weapons/ion_beam.weapon.ron",
+];
+
+/// Weapon logic
+pub(super) struct WeaponsPlugin;
+
+impl Plugin for WeaponsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<WeaponDefinition>()
+            .add_simulation_event::<WeaponFired>()
+            .add_simulation_event::<WeaponOverheated>()
+            .add_simulation_event::<BeamHit>()
+            .add_asset_loader(RonAssetLoader::<WeaponDefinition>::new(&["weapon.ron"]))
+            .init_resource::<WeaponLibrary>()
+            .init_resource::<EntityPool<Projectile>>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (steer_seekers, detect_projectile_hits)
+                        .chain()
+                        .before(FlightSet),
+                    (
+                        cool_weapons,
+                        aim_weapons,
+                        fire_weapons,
+                        fire_beams,
+                        age_projectiles,
+                    )
+                        .chain()
+                        .after(SpatialSet)
+                        .before(HealthSet),
+                ),
+            );
+    }
+}
+
+/// How a weapon behaves, as loaded from a `.weapon.ron` file.
+///
+/// A weapon fires either projectiles or a continuous beam. Beams deal their damage, draw their
+/// energy and build up their heat every second they fire, rather than with every shot.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "8f0b6d2e-3c1a-4f57-9a43-5e2c1d7b9f10"]
+pub struct WeaponDefinition {
+    /// The name shown to the player.
+    pub name: String,
+    /// How much damage each projectile deals, or a beam deals each second.
+    pub damage: f32,
+    /// How many times the weapon fires each second, unless it fires a beam.
+    #[serde(default)]
+    pub rate_of_fire: f32,
+    /// How much energy each shot draws, or a beam draws each second, before power distribution.
+    pub energy_cost: f32,
+    /// How hard each shot kicks, from `0.0` for nothing to `1.0` for a violent jolt.
+    #[serde(default)]
+    pub recoil: f32,
+    /// How much [`Heat`] each shot, or each second of a beam, builds up, out of
+    /// [`Heat::THRESHOLD`].
+    #[serde(default)]
+    pub heat_per_shot: f32,
+    /// How much [`Heat`] is shed each second.
+    #[serde(default)]
+    pub heat_dissipation: f32,
+    /// How far the weapon can turn from its hardpoint to track what the pilot is aiming at, in
+    /// radians, or `0.0` if it is fixed.
+    #[serde(default)]
+    pub gimbal: f32,
+    /// How far ahead of the ship the weapon's shots cross its centerline, in meters, or `None` to
+    /// fire straight ahead of its hardpoint.
+    #[serde(default)]
+    pub convergence: Option<f32>,
+    /// The projectile the weapon fires, if it fires projectiles.
+    #[serde(default)]
+    pub projectile: Option<ProjectileDefinition>,
+    /// The beam the weapon fires, if it fires a beam.
+    #[serde(default)]
+    pub beam: Option<BeamDefinition>,
+}
+
+/// The projectile fired by a [`WeaponDefinition`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ProjectileDefinition {
+    /// How fast the projectile leaves the weapon, in meters per second.
+    pub speed: f32,
+    /// How long the projectile flies before it fizzles out, in seconds.
+    pub lifetime: f32,
+    /// How big the projectile is, in meters.
+    pub radius: f32,
+    /// The color the projectile is drawn in, as linear RGB.
+    pub color: [f32; 3],
+    /// How the projectile homes in on ships, if it does.
+    #[serde(default)]
+    pub seeker: Option<SeekerDefinition>,
+}
+
+/// The continuous beam fired by a [`WeaponDefinition`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct BeamDefinition {
+    /// How far the beam reaches, in meters.
+    pub range: f32,
+    /// How thick the beam is where it leaves the weapon, in meters; it tapers towards its end.
+    pub radius: f32,
+    /// The color the beam is drawn in, as linear RGB.
+    pub color: [f32; 3],
+}
+
+/// How a homing projectile finds and follows its target.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SeekerDefinition {
+    /// How quickly the projectile can turn, in radians per second.
+    pub turn_rate: f32,
+    /// How far off the projectile's nose its seeker can see, in radians.
+    pub cone: f32,
+    /// How far away its seeker can see, in meters.
+    pub range: f32,
+}
+
+/// Every weapon that can be fitted to a hardpoint.
+#[derive(Resource, Debug, Clone)]
+pub struct WeaponLibrary {
+    /// Handles to each weapon, in the order they are offered to the player.
+    weapons: Vec<Handle<WeaponDefinition>>,
+}
+
+impl WeaponLibrary {
+    /// Handles to each weapon, in the order they are offered to the player.
+    pub fn weapons(&self) -> &[Handle<WeaponDefinition>] {
+        &self.weapons
+    }
+}
+
+impl FromWorld for WeaponLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        WeaponLibrary {
+            weapons: WEAPON_PATHS
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+        }
+    }
+}
+
+/// A place on a ship where a weapon can be mounted.
+///
+/// Hardpoints are children of their ship, placed by their [`Transform`] and firing along its
+/// forward direction.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hardpoint {
+    /// Which of the ship's hardpoints this is, counting from zero.
+    pub slot: usize,
+}
+
+/// The weapon mounted on a [`Hardpoint`].
+#[derive(Component, Debug, Clone)]
+pub struct MountedWeapon {
+    /// What is mounted.
+    pub definition: Handle<WeaponDefinition>,
+    /// How long until the weapon can fire again, in seconds.
+    cooldown: f32,
+    /// Where the weapon's beam ends this tick, if it fires a beam and is firing.
+    beam_end: Option<Vec3>,
+    /// Which way the weapon is aimed this tick, in world space, or zero until it is first aimed.
+    direction: Vec3,
+}
+
+impl MountedWeapon {
+    /// Mounts a weapon that is ready to fire.
+    pub fn new(definition: Handle<WeaponDefinition>) -> Self {
+        MountedWeapon {
+            definition,
+            cooldown: 0.,
+            beam_end: None,
+            direction: Vec3::ZERO,
+        }
+    }
+
+    /// Which way the weapon is aimed this tick, in world space, once it has been aimed.
+    pub fn direction(&self) -> Option<Vec3> {
+        (self.direction != Vec3::ZERO).then_some(self.direction)
+    }
+
+    /// The weapon's `muzzle`, turned to face the way the weapon is aimed.
+    fn aim(&self, muzzle: Transform) -> Transform {
+        match self.direction() {
+            Some(direction) => muzzle.looking_at(muzzle.translation + direction, muzzle.up()),
+            None => muzzle,
+        }
+    }
+
+    /// Where the weapon's beam ends this tick, if it fires a beam and is firing.
+    pub fn beam_end(&self) -> Option<Vec3> {
+        self.beam_end
+    }
+}
+
+/// How hot a mounted weapon has run; past [`Heat::THRESHOLD`] it locks up until it has cooled.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Heat {
+    /// The heat built up, from zero upwards.
+    current: f32,
+    /// Has the weapon overheated and not yet cooled?
+    overheated: bool,
+}
+
+impl Heat {
+    /// The heat at which a weapon overheats.
+    pub const THRESHOLD: f32 = 100.;
+
+    /// The fraction of [`Heat::THRESHOLD`] an overheated weapon must cool to before it unlocks.
+    pub const RECOVERED: f32 = 0.25;
+
+    /// How close the weapon is to overheating, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f32 {
+        (self.current / Heat::THRESHOLD).clamp(0., 1.)
+    }
+
+    /// Has the weapon overheated and not yet cooled?
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Builds up `amount` of heat, returning whether this overheated the weapon.
+    pub fn add(&mut self, amount: f32) -> bool {
+        self.current += amount;
+        let overheating = !self.overheated && self.current >= Heat::THRESHOLD;
+        self.overheated |= overheating;
+        overheating
+    }
+
+    /// Sheds `amount` of heat, unlocking the weapon once it has cooled enough.
+    pub fn cool(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.);
+        if self.current <= Heat::THRESHOLD * Heat::RECOVERED {
+            self.overheated = false;
+        }
+    }
+}
+
+/// A weapon has overheated and locked up.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeaponOverheated {
+    /// The ship the weapon is mounted on.
+    pub ship: Entity,
+    /// The hardpoint the weapon is mounted on.
+    pub hardpoint: Entity,
+}
+
+/// Whether a ship's pilot is pulling the trigger on its weapons, and what they are aiming at.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeaponTrigger {
+    /// Is the trigger held?
+    pub firing: bool,
+    /// The point gimballed weapons should track, in world space, if any.
+    pub aim: Option<Vec3>,
+}
+
+/// A ship has fired one of its weapons, or switched on one of its beams.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct WeaponFired {
+    /// The ship that fired.
+    pub ship: Entity,
+    /// The weapon that was fired.
+    pub weapon: Handle<WeaponDefinition>,
+    /// Where the shot left the weapon, in world space.
+    pub muzzle: Vec3,
+}
+
+/// A beam has hit something this tick.
+///
+/// The hit has already been dealt as [`Damaged`]; this records the ray that made it, so that the
+/// hit can be checked again elsewhere.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct BeamHit {
+    /// The ship that fired the beam.
+    pub ship: Entity,
+    /// The hardpoint the beam was fired from.
+    pub hardpoint: Entity,
+    /// What the beam hit.
+    pub target: Entity,
+    /// Where the beam left the weapon, in world space.
+    pub origin: Vec3,
+    /// Which way the beam points, in world space.
+    pub direction: Vec3,
+    /// How far the beam reaches, in meters.
+    pub range: f32,
+    /// How much damage the hit dealt.
+    pub amount: f32,
+}
+
+/// A shot in flight.
+#[derive(Component, Debug, Clone)]
+pub struct Projectile {
+    /// The weapon that fired it.
+    pub weapon: Handle<WeaponDefinition>,
+    /// The ship that fired it, which it cannot hit.
+    pub source: Entity,
+    /// How much damage it deals.
+    pub damage: f32,
+    /// How big it is, in meters.
+    pub radius: f32,
+    /// How long it has left to fly, in seconds.
+    lifetime: f32,
+}
+
+/// Steers a [`Projectile`] towards the entity it is tracking.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Seeker {
+    /// What the projectile is tracking, if anything.
+    pub target: Option<Entity>,
+    /// How the seeker behaves.
+    pub definition: SeekerDefinition,
+}
+
+impl Seeker {
+    /// Can a seeker at `transform` see something at `position`?
+    pub fn sees(&self, transform: &Transform, position: Vec3) -> bool {
+        let offset = position - transform.translation;
+        offset.length() <= self.definition.range
+            && transform.forward().angle_between(offset) <= self.definition.cone
+    }
+}
+
+/// Sheds the heat built up by each weapon.
+fn cool_weapons(
+    time: SimulationTime,
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut query: Query<(&MountedWeapon, &mut Heat)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (weapon, mut heat) in query.iter_mut() {
+        if let Some(definition) = definitions.get(&weapon.definition) {
+            heat.cool(definition.heat_dissipation * delta_time);
+        }
+    }
+}
+
+/// Aims each mounted weapon, angling it in to its convergence range and then turning it as far as
+/// its gimbal allows towards the point its ship's pilot is aiming at.
+fn aim_weapons(
+    definitions: Res<Assets<WeaponDefinition>>,
+    ships: Query<(&Transform, &WeaponTrigger)>,
+    mut hardpoints: Query<(&Parent, &Transform, &mut MountedWeapon), With<Hardpoint>>,
+) {
+    for (parent, hardpoint_transform, mut weapon) in hardpoints.iter_mut() {
+        let Ok((ship_transform, trigger)) = ships.get(parent.get()) else {
+            continue;
+        };
+        let Some(definition) = definitions.get(&weapon.definition) else {
+            continue;
+        };
+
+        let muzzle = ship_transform.mul_transform(*hardpoint_transform);
+        let boresight = definition
+            .convergence
+            .and_then(|range| {
+                let meeting_point = ship_transform.translation + ship_transform.forward() * range;
+                (meeting_point - muzzle.translation).try_normalize()
+            })
+            .unwrap_or_else(|| muzzle.forward());
+        let tracking = trigger
+            .aim
+            .filter(|_| definition.gimbal > 0.)
+            .and_then(|aim| (aim - muzzle.translation).try_normalize());
+
+        weapon.direction = match tracking {
+            Some(desired) => {
+                let angle = boresight.angle_between(desired);
+                if angle <= definition.gimbal {
+                    desired
+                } else {
+                    // Stop at the edge of the gimbal's cone, as close to the aim as it reaches
+                    Quat::IDENTITY.slerp(
+                        Quat::from_rotation_arc(boresight, desired),
+                        definition.gimbal / angle,
+                    ) * boresight
+                }
+            }
+            None => boresight,
+        };
+    }
+}
+
+/// Fires the mounted projectile weapons of every ship whose trigger is held, paying for each shot
+/// in energy and heat.
+///
+/// Homing projectiles lock on to the ship their seeker sees closest to its nose.
+#[allow(clippy::too_many_arguments)]
+fn fire_weapons(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Projectile>>,
+    time: SimulationTime,
+    definitions: Res<Assets<WeaponDefinition>>,
+    index: Res<SpatialIndex>,
+    mut ships: Query<(
+        &Transform,
+        &Velocity,
+        &WeaponTrigger,
+        &mut Energy,
+        Option<&PowerDistribution>,
+    )>,
+    mut hardpoints: Query<
+        (
+            Entity,
+            &Parent,
+            &Transform,
+            &mut MountedWeapon,
+            Option<&mut Heat>,
+        ),
+        With<Hardpoint>,
+    >,
+    targets: Query<&Transform, With<FlightControls>>,
+    mut weapon_fired: EventWriter<WeaponFired>,
+    mut weapon_overheated: EventWriter<WeaponOverheated>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (hardpoint, parent, hardpoint_transform, mut weapon, heat) in hardpoints.iter_mut() {
+        weapon.cooldown = (weapon.cooldown - delta_time).max(0.);
+
+        let Ok((ship_transform, ship_velocity, trigger, mut energy, power)) =
+            ships.get_mut(parent.get())
+        else {
+            continue;
+        };
+        let Some(definition) = definitions.get(&weapon.definition) else {
+            continue;
+        };
+        let Some(projectile_definition) = &definition.projectile else {
+            continue;
+        };
+        if !trigger.firing || weapon.cooldown > 0. {
+            continue;
+        }
+        if heat.as_ref().is_some_and(|heat| heat.is_overheated()) {
+            continue;
+        }
+
+        let cost_multiplier = power.map_or(1., |power| power.cost_multiplier(Subsystem::Weapons));
+        if !energy.try_drain(definition.energy_cost * cost_multiplier) {
+            continue;
+        }
+        weapon.cooldown = 1. / definition.rate_of_fire;
+        if let Some(mut heat) = heat {
+            if heat.add(definition.heat_per_shot) {
+                weapon_overheated.send(WeaponOverheated {
+                    ship: parent.get(),
+                    hardpoint,
+                });
+            }
+        }
+
+        // Global transforms lag a frame behind the simulation, so work out the muzzle by hand
+        let muzzle = weapon.aim(ship_transform.mul_transform(*hardpoint_transform));
+        let velocity = ship_velocity.0 + muzzle.forward() * projectile_definition.speed;
+        let Some(mut projectile) = spawn_projectile(
+            &mut commands,
+            &mut pool,
+            &weapon.definition,
+            definition,
+            parent.get(),
+            muzzle,
+            velocity,
+        ) else {
+            continue;
+        };
+        if let Some(seeker_definition) = projectile_definition.seeker {
+            let mut seeker = Seeker {
+                target: None,
+                definition: seeker_definition,
+            };
+            seeker.target = index
+                .within(muzzle.translation, seeker_definition.range)
+                .filter_map(|(target, _)| Some((target, targets.get(target).ok()?)))
+                .filter(|&(target, transform)| {
+                    target != parent.get() && seeker.sees(&muzzle, transform.translation)
+                })
+                .map(|(target, transform)| {
+                    let angle = muzzle
+                        .forward()
+                        .angle_between(transform.translation - muzzle.translation);
+                    (target, angle)
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(target, _)| target);
+            projectile.insert(seeker);
+        }
+        weapon_fired.send(WeaponFired {
+            ship: parent.get(),
+            weapon: weapon.definition.clone(),
+            muzzle: muzzle.translation,
+        });
+    }
+}
+
+/// Fires a projectile from the weapon `definition` out of `muzzle` at `velocity`, on behalf of
+/// `source`, reusing a spent one if there is one.
+///
+/// Returns `None` without firing if the weapon fires beams rather than projectiles.
+pub fn spawn_projectile<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    pool: &mut EntityPool<Projectile>,
+    weapon: &Handle<WeaponDefinition>,
+    definition: &WeaponDefinition,
+    source: Entity,
+    muzzle: Transform,
+    velocity: Vec3,
+) -> Option<EntityCommands<'w, 's, 'a>> {
+    let projectile_definition = definition.projectile.as_ref()?;
+
+    Some(pool.spawn(
+        commands,
+        (
+            SpatialBundle::from_transform(muzzle.with_scale(Vec3::ONE)),
+            Velocity(velocity),
+            Projectile {
+                weapon: weapon.clone(),
+                source,
+                damage: definition.damage,
+                radius: projectile_definition.radius,
+                lifetime: projectile_definition.lifetime,
+            },
+            InGame,
+        ),
+    ))
+}
+
+/// Fires the mounted beam weapons of every ship whose trigger is held, paying for them in energy
+/// and heat for as long as they fire.
+///
+/// Each beam stops at the first thing in its path, which takes its damage for the tick.
+#[allow(clippy::too_many_arguments)]
+fn fire_beams(
+    time: SimulationTime,
+    definitions: Res<Assets<WeaponDefinition>>,
+    index: Res<SpatialIndex>,
+    mut ships: Query<(
+        &Transform,
+        &WeaponTrigger,
+        &mut Energy,
+        Option<&PowerDistribution>,
+    )>,
+    mut hardpoints: Query<
+        (
+            Entity,
+            &Parent,
+            &Transform,
+            &mut MountedWeapon,
+            Option<&mut Heat>,
+        ),
+        With<Hardpoint>,
+    >,
+    colliders: Query<(Entity, &Transform, &Collider)>,
+    mut damaged: EventWriter<Damaged>,
+    mut beam_hits: EventWriter<BeamHit>,
+    mut weapon_fired: EventWriter<WeaponFired>,
+    mut weapon_overheated: EventWriter<WeaponOverheated>,
+) {
+    let delta_time = time.delta_seconds();
+    // Anything a beam touches has its center within the beam's range plus its own radius
+    let max_radius = colliders
+        .iter()
+        .fold(0., |max_radius: f32, (.., collider)| {
+            max_radius.max(collider.radius)
+        });
+
+    for (hardpoint, parent, hardpoint_transform, mut weapon, heat) in hardpoints.iter_mut() {
+        let Some(definition) = definitions.get(&weapon.definition) else {
+            continue;
+        };
+        let Some(beam) = definition.beam else {
+            continue;
+        };
+        let was_firing = weapon.beam_end.take().is_some();
+
+        let Ok((ship_transform, trigger, mut energy, power)) = ships.get_mut(parent.get()) else {
+            continue;
+        };
+        if !trigger.firing || heat.as_ref().is_some_and(|heat| heat.is_overheated()) {
+            continue;
+        }
+
+        let cost_multiplier = power.map_or(1., |power| power.cost_multiplier(Subsystem::Weapons));
+        if !energy.try_drain(definition.energy_cost * cost_multiplier * delta_time) {
+            continue;
+        }
+        if let Some(mut heat) = heat {
+            if heat.add(definition.heat_per_shot * delta_time) {
+                weapon_overheated.send(WeaponOverheated {
+                    ship: parent.get(),
+                    hardpoint,
+                });
+            }
+        }
+
+        // Global transforms lag a frame behind the simulation, so work out the muzzle by hand
+        let muzzle = weapon.aim(ship_transform.mul_transform(*hardpoint_transform));
+        let origin = muzzle.translation;
+        let direction = muzzle.forward();
+        let hit = index
+            .within(origin, beam.range + max_radius)
+            .filter(|&(target, _)| target != parent.get())
+            .filter_map(|(target, _)| colliders.get(target).ok())
+            .filter_map(|(target, target_transform, collider)| {
+                ray_sphere_distance(
+                    origin,
+                    direction,
+                    target_transform.translation,
+                    collider.radius,
+                )
+                .filter(|&distance| distance <= beam.range)
+                .map(|distance| (target, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let end = origin + direction * hit.map_or(beam.range, |(_, distance)| distance);
+        weapon.beam_end = Some(end);
+        if let Some((target, _)) = hit {
+            let amount = definition.damage * delta_time;
+            damaged.send(Damaged {
+                target,
+                source: Some(parent.get()),
+                amount,
+                position: end,
+            });
+            beam_hits.send(BeamHit {
+                ship: parent.get(),
+                hardpoint,
+                target,
+                origin,
+                direction,
+                range: beam.range,
+                amount,
+            });
+        }
+        if !was_firing {
+            weapon_fired.send(WeaponFired {
+                ship: parent.get(),
+                weapon: weapon.definition.clone(),
+                muzzle: origin,
+            });
+        }
+    }
+}
+
+/// Returns a projectile that has finished flying to the pool, stopping it so it lies idle.
+fn retire_projectile(
+    commands: &mut Commands,
+    pool: &mut EntityPool<Projectile>,
+    projectile: Entity,
+) {
+    if let Some(mut entity_commands) = pool.release(commands, projectile) {
+        entity_commands.remove::<(Velocity, Seeker)>();
+    }
+}
+
+/// Fizzles out projectiles that have flown for too long.
+fn age_projectiles(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Projectile>>,
+    time: SimulationTime,
+    mut query: Query<(Entity, &mut Projectile)>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut projectile) in query.iter_mut() {
+        projectile.lifetime -= delta_time;
+        if projectile.lifetime <= 0. {
+            retire_projectile(&mut commands, &mut pool, entity);
+        }
+    }
+}
+
+/// Turns homing projectiles towards their targets, as quickly as their seekers allow.
+///
+/// Seekers whose targets have gone fly straight on.
+fn steer_seekers(
+    time: SimulationTime,
+    mut seekers: Query<(&mut Transform, &mut Velocity, &mut Seeker)>,
+    targets: Query<&Transform, Without<Seeker>>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut transform, mut velocity, mut seeker) in seekers.iter_mut() {
+        let Some(target) = seeker.target else {
+            continue;
+        };
+        let Ok(target_transform) = targets.get(target) else {
+            seeker.target = None;
+            continue;
+        };
+        let (Some(heading), Some(desired)) = (
+            velocity.0.try_normalize(),
+            (target_transform.translation - transform.translation).try_normalize(),
+        ) else {
+            continue;
+        };
+
+        let angle = heading.angle_between(desired);
+        let max_turn = seeker.definition.turn_rate * delta_time;
+        let heading = if angle <= max_turn {
+            desired
+        } else {
+            Quat::IDENTITY.slerp(Quat::from_rotation_arc(heading, desired), max_turn / angle)
+                * heading
+        };
+
+        velocity.0 = heading * velocity.0.length();
+        let position = transform.translation;
+        transform.look_at(position + heading, Vec3::Y);
+    }
+}
+
+/// Checks the path each projectile is about to fly this tick, damaging the first thing it hits.
+fn detect_projectile_hits(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Projectile>>,
+    time: SimulationTime,
+    index: Res<SpatialIndex>,
+    projectiles: Query<(Entity, &Transform, &Velocity, &Projectile)>,
+    colliders: Query<(Entity, &Transform, &Collider)>,
+    mut damaged: EventWriter<Damaged>,
+) {
+    let delta_time = time.delta_seconds();
+    // Anything a projectile hits has its center within its reach plus both radii
+    let max_radius = colliders
+        .iter()
+        .fold(0., |max_radius: f32, (.., collider)| {
+            max_radius.max(collider.radius)
+        });
+
+    for (entity, transform, velocity, projectile) in projectiles.iter() {
+        let origin = transform.translation;
+        let step = velocity.0 * delta_time;
+        let Some(direction) = step.try_normalize() else {
+            continue;
+        };
+        let reach = step.length();
+
+        let hit = index
+            .within(origin, reach + projectile.radius + max_radius)
+            .filter(|&(target, _)| target != projectile.source && target != entity)
+            .filter_map(|(target, _)| colliders.get(target).ok())
+            .filter_map(|(target, target_transform, collider)| {
+                ray_sphere_distance(
+                    origin,
+                    direction,
+                    target_transform.translation,
+                    collider.radius + projectile.radius,
+                )
+                .filter(|&distance| distance <= reach)
+                .map(|distance| (target, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((target, distance)) = hit {
+            damaged.send(Damaged {
+                target,
+                source: Some(projectile.source),
+                amount: projectile.damage,
+                position: origin + direction * distance,
+            });
+            retire_projectile(&mut commands, &mut pool, entity);
+        }
+    }
+}
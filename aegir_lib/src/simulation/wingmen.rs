@@ -0,0 +1,266 @@
+//! Friendly AI wingmen, who fly in formation on a leader and carry out its orders.
+//!
+//! A leader's [`WingCommander`] passes each [`WingOrder`] it is given to its [`Wingman`]s, who turn
+//! it into a goal for their [`AiPilot`] every tick, so that they fall back into formation once an
+//! order has been carried out.
+
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+
+use crate::debug::console::ConsoleAppExt;
+use crate::player::ship::PlayerShip;
+
+use super::ai::{nearest_enemy, spawn_ai_ship, AiGoal, AiPilot, AiSet, Squadrons};
+use super::factions::{Faction, Reputation};
+use super::health::Health;
+use super::navigation::NavigationSet;
+use super::ships::{ShipClass, ShipDefinition, ShipLibrary};
+use super::spatial::SpatialIndex;
+use super::weapons::WeaponLibrary;
+
+/// The slots wingmen take up around their leader, in meters to its right, above and behind it.
+///
+/// Wingmen beyond the last slot stack up behind it.
+const FORMATION_SLOTS: [Vec3; 4] = [
+    Vec3::new(-30., 0., 25.),
+    Vec3::new(30., 0., 25.),
+    Vec3::new(-60., 0., 50.),
+    Vec3::new(60., 0., 50.),
+];
+
+/// How far behind the last slot each extra wingman flies, in meters.
+const EXTRA_SLOT_SPACING: f32 = 30.;
+
+/// Wingmen logic
+pub(super) struct WingmenPlugin;
+
+impl Plugin for WingmenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command("spawn wingmen", "spawn wingmen [count]", spawn_wingmen)
+            .add_systems(
+                FixedUpdate,
+                (issue_wing_orders, direct_wingmen)
+                    .chain()
+                    .after(NavigationSet)
+                    .before(AiSet),
+            );
+    }
+}
+
+/// What a leader has told its wingmen to do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WingOrder {
+    /// Stay in formation, whatever is going on around them.
+    #[default]
+    FormUp,
+    /// Attack a particular ship, then form up once it is destroyed.
+    Attack(Entity),
+    /// Break formation and attack any enemies nearby, forming up when there are none.
+    Engage,
+    /// Stay in formation, but attack anything that attacks the leader.
+    Defend,
+}
+
+impl WingOrder {
+    /// A short description of the order, for the HUD.
+    pub fn name(self) -> &'static str {
+        match self {
+            WingOrder::FormUp => "Form up",
+            WingOrder::Attack(_) => "Attack my target",
+            WingOrder::Engage => "Break and engage",
+            WingOrder::Defend => "Defend me",
+        }
+    }
+}
+
+/// Lets a ship give orders to the wingmen flying with it.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WingCommander {
+    /// The order to give to the wingmen this tick, if any.
+    pub requested: Option<WingOrder>,
+    /// The last order given.
+    order: WingOrder,
+}
+
+impl WingCommander {
+    /// The last order given to the wingmen.
+    pub fn order(&self) -> WingOrder {
+        self.order
+    }
+}
+
+/// Flies in formation on a leader, carrying out the orders of its [`WingCommander`].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Wingman {
+    /// The ship being followed.
+    pub leader: Entity,
+    /// Where to fly relative to the leader, in meters to its right, above and behind it.
+    pub offset: Vec3,
+    /// What the leader has told the wingman to do.
+    pub order: WingOrder,
+}
+
+impl Wingman {
+    /// A wingman for `leader`, flying in the slot numbered `slot` from the front of the formation.
+    pub fn new(leader: Entity, slot: usize) -> Self {
+        let last = FORMATION_SLOTS.len() - 1;
+        let extra = slot.saturating_sub(last) as f32;
+        Wingman {
+            leader,
+            offset: FORMATION_SLOTS[slot.min(last)] + Vec3::Z * EXTRA_SLOT_SPACING * extra,
+            order: WingOrder::default(),
+        }
+    }
+}
+
+/// Passes the orders each commander has been given to its wingmen.
+fn issue_wing_orders(
+    mut commanders: Query<(Entity, &mut WingCommander)>,
+    mut wingmen: Query<&mut Wingman>,
+) {
+    for (leader, mut commander) in commanders.iter_mut() {
+        let Some(order) = commander.requested.take() else {
+            continue;
+        };
+        commander.order = order;
+
+        for mut wingman in wingmen.iter_mut() {
+            if wingman.leader == leader {
+                wingman.order = order;
+            }
+        }
+    }
+}
+
+/// Turns each wingman's order into a goal for its pilot.
+///
+/// Wingmen whose leader is gone are left to fend for themselves.
+fn direct_wingmen(
+    reputation: Res<Reputation>,
+    index: Res<SpatialIndex>,
+    mut commanders: Query<(&Transform, &mut WingCommander)>,
+    mut wingmen: Query<(&mut Wingman, &mut AiPilot, &Transform, &Faction)>,
+    attackers: Query<(Entity, &AiPilot, &Transform), Without<Wingman>>,
+    targets: Query<&Faction, With<Health>>,
+) {
+    for (mut wingman, mut pilot, transform, &faction) in wingmen.iter_mut() {
+        let Ok((leader_transform, mut commander)) = commanders.get_mut(wingman.leader) else {
+            if matches!(pilot.goal, AiGoal::FormOn { .. }) {
+                pilot.goal = AiGoal::Hold;
+            }
+            continue;
+        };
+        let formation = AiGoal::FormOn {
+            leader: wingman.leader,
+            offset: wingman.offset,
+        };
+
+        let goal = match wingman.order {
+            WingOrder::FormUp => formation,
+            WingOrder::Attack(target) => {
+                // Wingmen will not fire on their own side, and form up once the target is gone
+                if targets.get(target).is_ok_and(|&other| other != faction) {
+                    AiGoal::Attack(target)
+                } else {
+                    wingman.order = WingOrder::FormUp;
+                    if commander.order == WingOrder::Attack(target) {
+                        commander.order = WingOrder::FormUp;
+                    }
+                    formation
+                }
+            }
+            WingOrder::Engage => match pilot.goal {
+                AiGoal::Attack(target) if targets.contains(target) => pilot.goal,
+                _ => nearest_enemy(
+                    &index,
+                    &reputation,
+                    &targets,
+                    transform.translation,
+                    pilot.sensor_range,
+                    faction,
+                )
+                .map_or(formation, AiGoal::Attack),
+            },
+            WingOrder::Defend => attackers
+                .iter()
+                .filter(|(_, attacker, _)| attacker.goal == AiGoal::Attack(wingman.leader))
+                .map(|(attacker, _, attacker_transform)| {
+                    let distance = attacker_transform
+                        .translation
+                        .distance(leader_transform.translation);
+                    (attacker, distance)
+                })
+                .filter(|&(_, distance)| distance <= pilot.sensor_range)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map_or(formation, |(attacker, _)| AiGoal::Attack(attacker)),
+        };
+        if pilot.goal != goal {
+            pilot.goal = goal;
+        }
+    }
+}
+
+/// Console command that spawns wingmen in formation on the player's ship.
+fn spawn_wingmen(arguments: &[&str], world: &mut World) -> Result<String, String> {
+    let count = match *arguments {
+        [] => 2,
+        [count] => count
+            .parse::<usize>()
+            .map_err(|_| format!("`{count}` is not a whole number"))?,
+        _ => return Err("expected at most one count".to_string()),
+    };
+
+    let mut player_query =
+        world.query_filtered::<(Entity, &Transform, &Faction), With<PlayerShip>>();
+    let Ok((leader, &origin, &faction)) = player_query.get_single(world) else {
+        return Err("there is no player ship to fly with".to_string());
+    };
+    let mut wingmen_query = world.query::<&Wingman>();
+    let first_slot = wingmen_query
+        .iter(world)
+        .filter(|wingman| wingman.leader == leader)
+        .count();
+
+    let (class, definition) = {
+        let class = world.resource::<ShipLibrary>().ships().first().cloned();
+        let definitions = world.resource::<Assets<ShipDefinition>>();
+        let definition = class
+            .as_ref()
+            .and_then(|handle| definitions.get(handle))
+            .cloned()
+            .unwrap_or_default();
+        (class, definition)
+    };
+    let weapon = world.resource::<WeaponLibrary>().weapons().first().cloned();
+    let squadron = world.resource_mut::<Squadrons>().allocate();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    for slot in first_slot..first_slot + count {
+        let wingman = Wingman::new(leader, slot);
+        let transform = Transform {
+            translation: origin.transform_point(wingman.offset),
+            ..origin
+        };
+
+        let ship = spawn_ai_ship(
+            &mut commands,
+            transform,
+            &definition,
+            weapon.as_ref(),
+            faction,
+            squadron,
+            AiPilot::default(),
+        );
+        commands.entity(ship).insert(wingman);
+        if let Some(class) = &class {
+            commands.entity(ship).insert(ShipClass(class.clone()));
+        }
+    }
+    queue.apply(world);
+
+    Ok(format!(
+        "spawned {count} wingmen in squadron {}",
+        squadron.0
+    ))
+}
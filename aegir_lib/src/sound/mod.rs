@@ -0,0 +1,15 @@
+//! Sounds played in response to the simulation.
+use bevy::prelude::{App, Plugin};
+
+mod warnings;
+
+/// Adds the game's sound effects.
+///
+/// Like [`GraphicsPlugin`](crate::graphics::GraphicsPlugin), this only presents the simulation.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(warnings::WarningSoundPlugin);
+    }
+}
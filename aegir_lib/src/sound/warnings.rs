@@ -0,0 +1,95 @@
+//! Alarms that warn the player about the state of their ship.
+//!
+//! Each alarm also sends an [`AudioCaption`], for players who cannot hear it.
+
+use bevy::prelude::*;
+
+use crate::accessibility::AudioCaption;
+use crate::player::ship::PlayerShip;
+use crate::simulation::weapons::{Projectile, Seeker, WeaponOverheated};
+
+/// Warning sound logic
+pub(super) struct WarningSoundPlugin;
+
+impl Plugin for WarningSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WarningSounds>()
+            .add_systems(Update, (warn_of_overheating, warn_of_missile_locks));
+    }
+}
+
+/// Handles to every warning sound.
+#[derive(Resource, Debug)]
+struct WarningSounds {
+    /// Played when one of the player's weapons overheats.
+    overheat: Handle<AudioSource>,
+    /// Played on a loop while a hostile missile is tracking the player.
+    missile_lock: Handle<AudioSource>,
+}
+
+/// Marks the looping tone played while a hostile missile is tracking the player.
+#[derive(Component, Debug)]
+struct MissileLockTone;
+
+impl FromWorld for WarningSounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        WarningSounds {
+            overheat: asset_server.load("audio/overheat_warning.wav"),
+            missile_lock: asset_server.load("audio/missile_lock.wav"),
+        }
+    }
+}
+
+/// Sounds an alarm when one of the player's weapons overheats.
+fn warn_of_overheating(
+    mut commands: Commands,
+    sounds: Res<WarningSounds>,
+    mut events: EventReader<WeaponOverheated>,
+    mut captions: EventWriter<AudioCaption>,
+    query: Query<(), With<PlayerShip>>,
+) {
+    // Weapons overheating together only need one alarm
+    if events.iter().any(|event| query.contains(event.ship)) {
+        commands.spawn(AudioBundle {
+            source: sounds.overheat.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+        captions.send(AudioCaption("[Overheat alarm]"));
+    }
+}
+
+/// Sounds a tone for as long as a missile fired by someone else is tracking the player.
+fn warn_of_missile_locks(
+    mut commands: Commands,
+    sounds: Res<WarningSounds>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    missile_query: Query<(&Seeker, &Projectile)>,
+    tone_query: Query<Entity, With<MissileLockTone>>,
+    mut captions: EventWriter<AudioCaption>,
+) {
+    let locked = player_query.get_single().is_ok_and(|player| {
+        missile_query.iter().any(|(seeker, projectile)| {
+            seeker.target == Some(player) && projectile.source != player
+        })
+    });
+
+    if locked {
+        captions.send(AudioCaption("[Missile lock tone]"));
+    }
+
+    match (locked, tone_query.get_single()) {
+        (true, Err(_)) => {
+            commands.spawn((
+                AudioBundle {
+                    source: sounds.missile_lock.clone(),
+                    settings: PlaybackSettings::LOOP,
+                },
+                MissileLockTone,
+            ));
+        }
+        (false, Ok(tone)) => commands.entity(tone).despawn_recursive(),
+        _ => {}
+    }
+}
@@ -0,0 +1,280 @@
+//! Statistics about the player's flying, for this session and for every session they have flown.
+//!
+//! [`SessionStats`] count up from zero each time play begins. When play ends they are added to
+//! the [`LifetimeStats`], which are kept in [`LIFETIME_STATS_PATH`] between runs of the game.
+
+use std::fs;
+use std::path::Path;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::GameState;
+use crate::player::ship::PlayerShip;
+use crate::simulation::asteroids::Asteroid;
+use crate::simulation::factions::Faction;
+use crate::simulation::health::{Damaged, Destroyed, HealthSet};
+use crate::simulation::mining::OreMined;
+use crate::simulation::pickups::PickupCollected;
+use crate::simulation::time_control::SimulationTime;
+use crate::simulation::weapons::WeaponFired;
+use crate::simulation::SimulationAppExt;
+
+/// Where lifetime statistics are kept, relative to the working directory.
+pub const LIFETIME_STATS_PATH: &str = "aegir_stats.ron";
+
+/// Adds statistics tracking.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionStats>()
+            .add_simulation_event::<KillReported>()
+            .add_systems(Startup, load_lifetime_stats)
+            .add_systems(OnEnter(GameState::Playing), reset_session_stats)
+            .add_systems(OnExit(GameState::Playing), record_session)
+            .add_systems(
+                FixedUpdate,
+                (count_flight_time, count_combat, count_ore)
+                    .after(HealthSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Last, record_session_on_exit);
+    }
+}
+
+/// What the player has done since play began.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// How many ships and targets the player has destroyed.
+    pub kills: u32,
+    /// How many times the player's ship has been destroyed.
+    pub deaths: u32,
+    /// How much damage the player's shots have dealt.
+    pub damage_dealt: f32,
+    /// How many shots the player has fired.
+    pub shots_fired: u32,
+    /// How many of the player's shots have hit something.
+    pub shots_hit: u32,
+    /// How many units of ore the player has mined.
+    pub ore_mined: f32,
+    /// How long the player has spent flying, in seconds.
+    pub flight_time: f32,
+}
+
+impl SessionStats {
+    /// The fraction of shots fired that hit, or `0.0` before any have been fired.
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.
+        } else {
+            self.shots_hit as f32 / self.shots_fired as f32
+        }
+    }
+}
+
+/// What the player has done across every session, including the current one once it ends.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    /// How many sessions have been played.
+    pub sessions: u32,
+    /// The sum of every session's statistics.
+    pub totals: SessionStats,
+}
+
+impl LifetimeStats {
+    /// Adds a finished session to the totals.
+    pub fn add(&mut self, session: &SessionStats) {
+        self.sessions += 1;
+        self.totals.kills += session.kills;
+        self.totals.deaths += session.deaths;
+        self.totals.damage_dealt += session.damage_dealt;
+        self.totals.shots_fired += session.shots_fired;
+        self.totals.shots_hit += session.shots_hit;
+        self.totals.ore_mined += session.ore_mined;
+        self.totals.flight_time += session.flight_time;
+    }
+
+    /// Saves the statistics to the file at `path`, replacing it if it exists.
+    pub fn save(&self, path: &Path) -> Result<(), StatsError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Loads the statistics in the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, StatsError> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// Why lifetime statistics could not be saved or loaded.
+#[derive(Debug, Display, From)]
+pub enum StatsError {
+    /// The file could not be read or written.
+    #[display(fmt = "could not access the statistics file: {}", _0)]
+    Io(std::io::Error),
+    /// The statistics could not be written out.
+    #[display(fmt = "could not encode the statistics: {}", _0)]
+    Encoding(ron::Error),
+    /// The contents of the file are corrupt.
+    #[display(fmt = "the statistics file is corrupt: {}", _0)]
+    Decoding(ron::error::SpannedError),
+}
+
+impl std::error::Error for StatsError {}
+
+/// Something has been destroyed, described for the kill feed.
+///
+/// The entities involved are usually despawned by the time this is read, so they are described
+/// when the kill happens.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct KillReported {
+    /// Who destroyed it, if anyone.
+    pub killer: Option<String>,
+    /// What was destroyed.
+    pub victim: String,
+    /// Was it destroyed by the player?
+    pub by_player: bool,
+    /// Was it the player's ship?
+    pub of_player: bool,
+}
+
+/// Loads the lifetime statistics, starting afresh if there are none yet.
+fn load_lifetime_stats(mut commands: Commands) {
+    let path = Path::new(LIFETIME_STATS_PATH);
+    let stats = if path.exists() {
+        LifetimeStats::load(path).unwrap_or_else(|error| {
+            warn!("Starting lifetime statistics afresh: {error}");
+            LifetimeStats::default()
+        })
+    } else {
+        LifetimeStats::default()
+    };
+
+    commands.insert_resource(stats);
+}
+
+/// Starts counting the new session from zero.
+fn reset_session_stats(mut stats: ResMut<SessionStats>) {
+    *stats = SessionStats::default();
+}
+
+/// Adds the session that just ended to the lifetime statistics and saves them.
+fn record_session(session: Res<SessionStats>, mut lifetime: ResMut<LifetimeStats>) {
+    lifetime.add(&session);
+    if let Err(error) = lifetime.save(Path::new(LIFETIME_STATS_PATH)) {
+        error!("Could not save lifetime statistics: {error}");
+    }
+}
+
+/// Records the session in progress when the app closes mid-flight.
+fn record_session_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    state: Res<State<GameState>>,
+    session: Res<SessionStats>,
+    lifetime: ResMut<LifetimeStats>,
+) {
+    if exit_events.iter().next().is_none() || *state.get() != GameState::Playing {
+        return;
+    }
+
+    record_session(session, lifetime);
+}
+
+/// Counts the time spent flying.
+fn count_flight_time(
+    time: SimulationTime,
+    player_query: Query<(), With<PlayerShip>>,
+    mut stats: ResMut<SessionStats>,
+) {
+    if !player_query.is_empty() {
+        stats.flight_time += time.delta_seconds();
+    }
+}
+
+/// Names an entity for the kill feed.
+fn describe(entity: Entity, query: &Query<(Option<&Name>, Option<&Faction>)>) -> String {
+    match query.get(entity) {
+        Ok((Some(name), _)) => name.to_string(),
+        Ok((None, Some(faction))) => format!("{} ship", faction.name()),
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// Counts shots, hits, damage, kills and deaths, and reports each kill to the kill feed.
+///
+/// Breaking asteroids counts towards accuracy and damage, but not kills.
+#[allow(clippy::too_many_arguments)]
+fn count_combat(
+    mut fired: EventReader<WeaponFired>,
+    mut damaged: EventReader<Damaged>,
+    mut destroyed: EventReader<Destroyed>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    asteroid_query: Query<(), With<Asteroid>>,
+    description_query: Query<(Option<&Name>, Option<&Faction>)>,
+    mut stats: ResMut<SessionStats>,
+    mut kills: EventWriter<KillReported>,
+) {
+    let player = player_query.get_single().ok();
+    let is_player = |entity: Entity| Some(entity) == player;
+
+    stats.shots_fired += fired.iter().filter(|event| is_player(event.ship)).count() as u32;
+
+    for event in damaged.iter() {
+        if event.source.is_some_and(is_player) && !is_player(event.target) {
+            stats.shots_hit += 1;
+            stats.damage_dealt += event.amount;
+        }
+    }
+
+    for event in destroyed.iter() {
+        if asteroid_query.contains(event.entity) {
+            continue;
+        }
+        let by_player = event.killer.is_some_and(is_player);
+        let of_player = is_player(event.entity);
+        if by_player && !of_player {
+            stats.kills += 1;
+        }
+        if of_player {
+            stats.deaths += 1;
+        }
+
+        let describe_player_or = |entity: Entity| {
+            if is_player(entity) {
+                "You".to_string()
+            } else {
+                describe(entity, &description_query)
+            }
+        };
+        kills.send(KillReported {
+            killer: event.killer.map(describe_player_or),
+            victim: describe_player_or(event.entity),
+            by_player,
+            of_player,
+        });
+    }
+}
+
+/// Counts the ore mined or collected by the player.
+fn count_ore(
+    mut mined: EventReader<OreMined>,
+    mut collected: EventReader<PickupCollected>,
+    player_query: Query<Entity, With<PlayerShip>>,
+    mut stats: ResMut<SessionStats>,
+) {
+    let player = player_query.get_single().ok();
+    stats.ore_mined += mined
+        .iter()
+        .filter(|event| Some(event.miner) == player)
+        .map(|event| event.amount)
+        .sum::<f32>();
+    stats.ore_mined += collected
+        .iter()
+        .filter(|event| Some(event.ship) == player)
+        .map(|event| event.amount)
+        .sum::<f32>();
+}
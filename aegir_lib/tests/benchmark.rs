@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use aegir_lib::benchmark::{BenchmarkConfig, TimingStats, DEFAULT_FRAMES};
+
+#[test]
+fn benchmark_flags_are_read_from_the_command_line() {
+    let arguments = ["aegir_game", "--bench", "--headless", "--frames", "600"]
+        .into_iter()
+        .chain(["--bench-output", "bench.csv"])
+        .map(String::from);
+
+    let config = BenchmarkConfig::from_args(arguments);
+
+    assert!(config.enabled);
+    assert!(config.headless);
+    assert_eq!(config.frames, 600);
+    assert_eq!(config.output, Some(PathBuf::from("bench.csv")));
+}
+
+#[test]
+fn the_benchmark_is_off_unless_asked_for() {
+    let config = BenchmarkConfig::from_args(["aegir_game", "--frames", "lots"].map(String::from));
+
+    assert!(!config.enabled);
+    assert_eq!(config.frames, DEFAULT_FRAMES);
+}
+
+#[test]
+fn timing_stats_summarize_measurements() {
+    let stats = TimingStats::from_measurements((1..=20).map(f64::from)).unwrap();
+
+    assert_eq!(stats.samples, 20);
+    assert_eq!(stats.mean, 10.5);
+    assert_eq!(stats.min, 1.);
+    assert_eq!(stats.p95, 19.);
+    assert_eq!(stats.max, 20.);
+    assert!(TimingStats::from_measurements([]).is_none());
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use aegir_lib::game_state::GameState;
+use aegir_lib::simulation::TICK_RATE;
+use aegir_lib::HeadlessPlugins;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+#[test]
+fn the_simulation_runs_without_a_player() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), HeadlessPlugins))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1. / TICK_RATE,
+        )));
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::Playing
+    );
+}
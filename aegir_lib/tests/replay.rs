@@ -0,0 +1,33 @@
+use aegir_lib::player::input::{DockAction, FlightAction};
+use aegir_lib::player::loadout::Loadout;
+use aegir_lib::replay::{Replay, ReplayError, TickInput};
+use aegir_lib::simulation::missions::MissionSelection;
+use aegir_lib::simulation::WorldSeed;
+
+#[test]
+fn replays_survive_a_round_trip() {
+    let mut loadout = Loadout::default();
+    loadout.cycle_ship(3);
+    loadout.fit(1, 2);
+    let mut replay = Replay::new(WorldSeed(42), loadout, MissionSelection(Some(1)));
+    replay.ticks.push(TickInput::default());
+    replay.ticks.push(TickInput {
+        pressed: vec![FlightAction::ThrottleUp, FlightAction::RollLeft],
+        just_pressed: vec![FlightAction::RollLeft],
+        dock_just_pressed: vec![DockAction::Launch],
+        scroll: -1.5,
+        look: [0.25, -0.5],
+    });
+
+    let mut bytes = Vec::new();
+    replay.write(&mut bytes).unwrap();
+
+    assert_eq!(Replay::read(bytes.as_slice()).unwrap(), replay);
+}
+
+#[test]
+fn other_files_are_not_replays() {
+    let result = Replay::read(b"PNG\x0d\x0a\x1a\x0a".as_slice());
+
+    assert!(matches!(result, Err(ReplayError::NotAReplay)));
+}
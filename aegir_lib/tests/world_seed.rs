@@ -0,0 +1,45 @@
+use aegir_lib::simulation::random::{RngStream, WorldRng};
+use aegir_lib::simulation::WorldSeed;
+use rand::Rng;
+
+#[test]
+fn the_same_seed_draws_the_same_numbers() {
+    let first: Vec<u32> = WorldSeed(42)
+        .rng(RngStream::Asteroids)
+        .sample_iter(rand::distributions::Standard)
+        .take(8)
+        .collect();
+    let second: Vec<u32> = WorldSeed(42)
+        .rng(RngStream::Asteroids)
+        .sample_iter(rand::distributions::Standard)
+        .take(8)
+        .collect();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn streams_draw_different_numbers() {
+    let seed = WorldSeed(42);
+
+    assert_ne!(
+        seed.rng(RngStream::Asteroids).gen::<u64>(),
+        seed.rng(RngStream::Stations).gen::<u64>()
+    );
+    assert_ne!(
+        seed.rng(RngStream::Wave(0)).gen::<u64>(),
+        seed.rng(RngStream::Wave(1)).gen::<u64>()
+    );
+}
+
+#[test]
+fn drawing_from_one_stream_leaves_the_others_alone() {
+    let mut untouched = WorldRng::new(WorldSeed(7));
+    let mut drawn = WorldRng::new(WorldSeed(7));
+    drawn.stream(RngStream::Fragments).gen::<u64>();
+
+    assert_eq!(
+        untouched.stream(RngStream::Economy).gen::<u64>(),
+        drawn.stream(RngStream::Economy).gen::<u64>()
+    );
+}